@@ -0,0 +1,94 @@
+use crate::{
+    async_stream::AsyncStream,
+    shared::ack_gate::{AckGate, AckSlot},
+};
+use futures_lite::Stream;
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Acked
+///
+/// A result of a spawn group in ack mode, as delivered by an [`AckedStream`], which holds back another child task
+/// from starting until it's acknowledged with [`ack`](Self::ack)
+///
+/// It dereferences into the result. Dropping it without acknowledging it counts as acknowledging it, so a consumer
+/// that gives up on a result never keeps the spawn group from making progress. An acknowledgement once the spawn
+/// group was cancelled or reset since the result was produced does nothing, as the cancellation already let the
+/// child tasks start again.
+pub struct Acked<ValueType> {
+    value: ValueType,
+    // freed along with the result
+    slot: AckSlot,
+}
+
+impl<ValueType> Acked<ValueType> {
+    /// Acknowledges the result, letting another child task start, and hands it back
+    pub fn ack(self) -> ValueType {
+        let Acked { value, slot } = self;
+        drop(slot);
+        value
+    }
+}
+
+impl<ValueType> Deref for Acked<ValueType> {
+    type Target = ValueType;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<ValueType> DerefMut for Acked<ValueType> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<ValueType: fmt::Debug> fmt::Debug for Acked<ValueType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Acked").field(&self.value).finish()
+    }
+}
+
+/// Acked Stream
+///
+/// A stream of the results of a spawn group, each of them wrapped in an [`Acked`] to acknowledge once it's done with,
+/// as returned by [`SpawnGroup::acked_stream`](crate::SpawnGroup::acked_stream).
+///
+/// In ack mode, set with [`SpawnGroup::ack_mode`](crate::SpawnGroup::ack_mode), a child task only starts once fewer
+/// than the limit of started child tasks are either running or have their result left unacknowledged. Outside of ack
+/// mode, acknowledging a result does nothing.
+///
+/// The stream ends once the spawn group has no child task left and every buffered result was delivered.
+pub struct AckedStream<'a, ValueType: Send + 'static> {
+    stream: AsyncStream<ValueType>,
+    group: PhantomData<&'a mut ()>,
+}
+
+impl<ValueType: Send + 'static> AckedStream<'_, ValueType> {
+    pub(crate) fn new(stream: AsyncStream<ValueType>) -> Self {
+        Self {
+            stream,
+            group: PhantomData,
+        }
+    }
+}
+
+impl<ValueType: Send + 'static> Stream for AckedStream<'_, ValueType> {
+    type Item = Acked<ValueType>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let gate: &AckGate = self.stream.acks();
+        self.stream.poll_unacked(cx).map(|item| {
+            item.map(|(tag, value)| Acked {
+                value,
+                slot: AckSlot::kept(gate, tag.ack),
+            })
+        })
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    discarding_spawn_group::DiscardingSpawnGroup,
+    dyn_spawn_group::BoxFuture,
+    shared::{cancel_token::CancelToken, priority::Priority},
+};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    sync::Arc,
+    task::{Poll, Waker},
+};
+
+struct MailboxState<Msg> {
+    messages: VecDeque<Msg>,
+    addrs: usize,
+    closed: bool,
+    actor: Option<Waker>,
+    senders: Vec<Waker>,
+}
+
+struct Mailbox<Msg> {
+    state: Mutex<MailboxState<Msg>>,
+    capacity: usize,
+    cancelled: CancelToken,
+}
+
+impl<Msg> Mailbox<Msg> {
+    fn is_closed(&self, state: &MailboxState<Msg>) -> bool {
+        state.closed || self.cancelled.is_cancelled()
+    }
+
+    fn wake_senders(state: &mut MailboxState<Msg>) {
+        state.senders.drain(..).for_each(Waker::wake);
+    }
+}
+
+/// Error returned by [`Addr::send`] when the actor has already terminated, giving the message back
+pub struct SendError<Msg>(pub Msg);
+
+impl<Msg> Debug for SendError<Msg> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SendError(..)")
+    }
+}
+
+impl<Msg> Display for SendError<Msg> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("the actor has terminated")
+    }
+}
+
+impl<Msg> std::error::Error for SendError<Msg> {}
+
+/// Address of an actor spawned with [`spawn_actor`]
+///
+/// Cloning it gives another address of the same actor, which terminates once every address was dropped.
+pub struct Addr<Msg> {
+    mailbox: Arc<Mailbox<Msg>>,
+}
+
+impl<Msg> Addr<Msg> {
+    /// Sends a message to the actor, waiting while its mailbox is full
+    ///
+    /// # Parameters
+    ///
+    /// * `msg`: the message to send
+    ///
+    /// # Returns
+    /// - Ok: if the message was queued into the mailbox
+    /// - Err: the message itself if the actor has terminated
+    pub async fn send(&self, msg: Msg) -> Result<(), SendError<Msg>> {
+        let mut msg: Option<Msg> = Some(msg);
+        poll_fn(|cx| {
+            let mut state = self.mailbox.state.lock();
+            if self.mailbox.is_closed(&state) {
+                return Poll::Ready(Err(SendError(msg.take().expect("polled after completion"))));
+            }
+            if state.messages.len() >= self.mailbox.capacity {
+                state.senders.push(cx.waker().clone());
+                return Poll::Pending;
+            }
+            state
+                .messages
+                .push_back(msg.take().expect("polled after completion"));
+            if let Some(actor) = state.actor.take() {
+                actor.wake();
+            }
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+
+    /// A Boolean value that indicates whether the actor has terminated
+    pub fn is_closed(&self) -> bool {
+        self.mailbox.is_closed(&self.mailbox.state.lock())
+    }
+}
+
+impl<Msg> Clone for Addr<Msg> {
+    fn clone(&self) -> Self {
+        self.mailbox.state.lock().addrs += 1;
+        Self {
+            mailbox: self.mailbox.clone(),
+        }
+    }
+}
+
+impl<Msg> Drop for Addr<Msg> {
+    fn drop(&mut self) {
+        let mut state = self.mailbox.state.lock();
+        state.addrs -= 1;
+        if state.addrs == 0 {
+            if let Some(actor) = state.actor.take() {
+                actor.wake();
+            }
+        }
+    }
+}
+
+/// The actor's end of the mailbox, closing it whenever the actor terminates or is cancelled
+struct Inbox<Msg> {
+    mailbox: Arc<Mailbox<Msg>>,
+}
+
+impl<Msg> Inbox<Msg> {
+    async fn recv(&self) -> Option<Msg> {
+        poll_fn(|cx| {
+            let mut state = self.mailbox.state.lock();
+            if let Some(msg) = state.messages.pop_front() {
+                Mailbox::wake_senders(&mut state);
+                return Poll::Ready(Some(msg));
+            }
+            if state.addrs == 0 || self.mailbox.cancelled.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            state.actor = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<Msg> Drop for Inbox<Msg> {
+    fn drop(&mut self) {
+        let mut state = self.mailbox.state.lock();
+        state.closed = true;
+        state.messages.clear();
+        Mailbox::wake_senders(&mut state);
+    }
+}
+
+/// Spawns an actor as a child task of the spawn group and returns its address
+///
+/// The actor owns ``state`` and handles the messages sent to its address one at a time, in the order
+/// they were sent, by calling ``handler``. Its mailbox holds at most ``capacity`` messages, senders wait
+/// for room once it is full.
+///
+/// The actor terminates after handling the remaining messages once every address was dropped, or right away
+/// when the spawn group is cancelled, after which sending a message fails. Waiting for the spawn group waits
+/// for the actor too, so drop every address before waiting.
+///
+/// # Parameters
+///
+/// * `group`: the spawn group to spawn the actor into
+/// * `capacity`: the maximum number of messages its mailbox holds, ``0`` is treated as ``1``
+/// * `state`: the state owned by the actor
+/// * `handler`: an async closure that handles a message with mutable access to the state
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, spawn_actor, DiscardingSpawnGroup};
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// let total = Arc::new(AtomicUsize::new(0));
+/// let mut group = DiscardingSpawnGroup::new(2);
+/// let addr = spawn_actor(&mut group, 4, 0, {
+///     let total = total.clone();
+///     move |sum: &mut usize, value: usize| {
+///         let total = total.clone();
+///         Box::pin(async move {
+///             *sum += value;
+///             total.store(*sum, Ordering::SeqCst);
+///         })
+///     }
+/// });
+///
+/// block_on(async {
+///     for value in 1..=10 {
+///         addr.send(value).await.unwrap();
+///     }
+/// });
+/// drop(addr);
+/// block_on(group.wait_for_all());
+/// assert_eq!(total.load(Ordering::SeqCst), 55);
+///
+/// // cancelling the spawn group terminates the actor
+/// let addr = spawn_actor(&mut group, 1, (), |_: &mut (), _: usize| Box::pin(async {}));
+/// group.cancel_all();
+/// assert!(addr.is_closed());
+/// assert_eq!(block_on(addr.send(1)).unwrap_err().0, 1);
+/// ```
+pub fn spawn_actor<State, Msg, Handler>(
+    group: &mut DiscardingSpawnGroup,
+    capacity: usize,
+    state: State,
+    mut handler: Handler,
+) -> Addr<Msg>
+where
+    State: Send + 'static,
+    Msg: Send + 'static,
+    Handler: for<'a> FnMut(&'a mut State, Msg) -> BoxFuture<'a, ()> + Send + 'static,
+{
+    let mailbox: Arc<Mailbox<Msg>> = Arc::new(Mailbox {
+        state: Mutex::new(MailboxState {
+            messages: VecDeque::new(),
+            addrs: 1,
+            closed: false,
+            actor: None,
+            senders: vec![],
+        }),
+        capacity: capacity.max(1),
+        cancelled: group.cancel_token(),
+    });
+    let inbox: Inbox<Msg> = Inbox {
+        mailbox: mailbox.clone(),
+    };
+    group.spawn_task(Priority::default(), async move {
+        let mut state: State = state;
+        while let Some(msg) = inbox.recv().await {
+            handler(&mut state, msg).await;
+        }
+    });
+    Addr { mailbox }
+}
@@ -1,53 +1,125 @@
-use crate::{pin_future, threadpool_impl::ThreadPool};
+use crate::{
+    fault_injection::FaultInjector,
+    shared::{outstanding::OutstandingGuard, panic_isolation::PanicIsolation, priority::Priority},
+    threadpool_impl::{
+        default_factory, CpuQuota, ExecutionLog, QueuedHook, ShutdownMode, ThreadFactory,
+        ThreadPool, UniqueThread, WorkerStates,
+    },
+    wake_trace::WakeTracer,
+};
 
-use super::{notifier::Notifier, task::Task, task_queue::TaskQueue};
+use super::{lane::Lane, notifier::TaskWaker, task::Task, task_queue::TaskQueue};
 
-use cooked_waker::IntoWaker;
 use parking_lot::{lock_api::MutexGuard, Condvar, Mutex, RawMutex};
 
 use std::{
     future::Future,
+    io,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll, Waker},
+    time::Instant,
 };
 
 #[derive(Clone)]
-pub struct Executor {
-    cancel: Arc<AtomicBool>,
+pub(crate) struct Executor {
+    // bumped by every cancellation, which stops the threads running the executor that were started before it
+    cancelled: Arc<AtomicUsize>,
     lock_pair: Arc<(Mutex<bool>, Condvar)>,
     pool: Arc<ThreadPool>,
     queue: TaskQueue,
+    // the child tasks handed to the threadpool, in the order the worker threads poll them
+    ready: TaskQueue,
     started: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    factory: ThreadFactory,
+    tracer: WakeTracer,
+    compute: Option<ComputeLane>,
+    stalled: Arc<Mutex<Stalled>>,
+    // whether a spawn group holds the executor, which the pool cache never hands out while one does
+    held: Arc<AtomicBool>,
+}
+
+// the child tasks whose wakeup the fault injector dropped, left unscheduled until the spawn group is waited for
+#[derive(Default)]
+struct Stalled {
+    // the waits in progress, during which no wakeup is dropped so they see every child task through
+    waiting: usize,
+    tasks: Vec<Task>,
+}
+
+/// Keeps the wakeups of the child tasks from being dropped while a wait is in progress
+pub(crate) struct DrivingGuard(Arc<Mutex<Stalled>>);
+
+impl Drop for DrivingGuard {
+    fn drop(&mut self) {
+        self.0.lock().waiting -= 1;
+    }
+}
+
+// the worker threads of the compute lane, apart from the ones of the IO lane which the executor always has
+#[derive(Clone)]
+struct ComputeLane {
+    pool: Arc<ThreadPool>,
+    ready: TaskQueue,
 }
 
 impl Default for Executor {
     fn default() -> Self {
-        let result: Executor = Self {
-            cancel: Arc::new(AtomicBool::new(false)),
-            lock_pair: Arc::new((Mutex::new(false), Condvar::new())),
-            pool: Arc::new(ThreadPool::default()),
-            queue: TaskQueue::default(),
-            started: Arc::new(AtomicBool::new(false)),
-        };
-        result.start();
-        result
+        Self::with_pool(ThreadPool::default(), default_factory()).unwrap()
     }
 }
 
 impl Executor {
     pub(crate) fn new(count: usize) -> Self {
-        let result: Executor = Self {
-            cancel: Arc::new(AtomicBool::new(false)),
+        Self::with_factory(count, default_factory()).unwrap()
+    }
+
+    /// Creates the worker threads and the thread running the executor through the given thread factory
+    pub(crate) fn with_factory(count: usize, factory: ThreadFactory) -> io::Result<Self> {
+        Self::with_pool(ThreadPool::with_factory(count, &factory)?, factory)
+    }
+
+    /// Creates an executor polling every child task on the worker threads of its lane, with the given number of
+    /// worker threads for each lane
+    pub(crate) fn with_lanes(
+        io: usize,
+        compute: usize,
+        factory: ThreadFactory,
+    ) -> io::Result<Self> {
+        let compute: ComputeLane = ComputeLane {
+            pool: Arc::new(ThreadPool::with_factory(compute, &factory)?),
+            ready: TaskQueue::default(),
+        };
+        let mut result: Executor = Self::new_with(ThreadPool::with_factory(io, &factory)?, factory);
+        result.compute = Some(compute);
+        result.start()?;
+        Ok(result)
+    }
+
+    fn with_pool(pool: ThreadPool, factory: ThreadFactory) -> io::Result<Self> {
+        let result: Executor = Self::new_with(pool, factory);
+        result.start()?;
+        Ok(result)
+    }
+
+    fn new_with(pool: ThreadPool, factory: ThreadFactory) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicUsize::new(0)),
             lock_pair: Arc::new((Mutex::new(false), Condvar::new())),
-            pool: Arc::new(ThreadPool::new(count)),
+            pool: Arc::new(pool),
             queue: TaskQueue::default(),
+            ready: TaskQueue::default(),
             started: Arc::new(AtomicBool::new(false)),
-        };
-        result.start();
-        result
+            stopped: Arc::new(AtomicBool::new(false)),
+            factory,
+            tracer: WakeTracer::new(),
+            compute: None,
+            stalled: Arc::default(),
+            held: Arc::new(AtomicBool::new(true)),
+        }
     }
 }
 
@@ -59,6 +131,50 @@ impl Executor {
     fn update(&self, val: bool) {
         self.started.store(val, Ordering::Release);
     }
+
+    /// Whether a thread running the executor started before the given number of cancellations may go on
+    fn is_current(&self, cancelled: usize) -> bool {
+        self.cancelled.load(Ordering::Acquire) == cancelled && !self.stopped.load(Ordering::Acquire)
+    }
+
+    /// The lane the given child task is polled on next, always the IO lane without a compute lane
+    fn lane_of(&self, task: &Task) -> Lane {
+        match self.compute {
+            Some(_) => task.lane(),
+            None => Lane::Io,
+        }
+    }
+
+    /// The worker threads of the given lane, along with the child tasks handed to them
+    fn workers(&self, lane: Lane) -> (&Arc<ThreadPool>, &TaskQueue) {
+        match (&self.compute, lane) {
+            (Some(compute), Lane::Compute) => (&compute.pool, &compute.ready),
+            _ => (&self.pool, &self.ready),
+        }
+    }
+
+    /// Marks the executor as held by the spawn group that took it out of the pool cache
+    ///
+    /// # Panics
+    /// If a spawn group holds it already
+    pub(crate) fn hold(&self) {
+        let held: bool = self.held.swap(true, Ordering::AcqRel);
+        assert!(!held, "a cached threadpool is held by a live spawn group");
+    }
+
+    /// Marks the executor as let go of by its spawn group, before it's handed to the pool cache
+    ///
+    /// # Panics
+    /// If no spawn group holds it, as it was handed to the pool cache already
+    pub(crate) fn let_go(&self) {
+        let held: bool = self.held.swap(false, Ordering::AcqRel);
+        assert!(held, "a threadpool was handed to the pool cache twice");
+    }
+
+    /// Every threadpool of the executor
+    fn pools(&self) -> impl Iterator<Item = &Arc<ThreadPool>> {
+        std::iter::once(&self.pool).chain(self.compute.as_ref().map(|compute| &compute.pool))
+    }
 }
 
 impl Executor {
@@ -69,11 +185,59 @@ impl Executor {
         self.pool.submit(task);
     }
 
-    pub(crate) fn spawn<Fut>(&self, task: Fut) -> Task
+    pub(crate) fn submit_all<Task>(&self, tasks: impl IntoIterator<Item = Task>)
+    where
+        Task: FnOnce() + Send + 'static,
+    {
+        self.pool.submit_all(tasks);
+    }
+
+    /// Hands every job polling a child task to the worker threads of the lane of its child task, keeping their order
+    /// within each lane
+    ///
+    /// Every job is given the lane of the worker thread running it.
+    pub(crate) fn submit_all_on_lanes<Job>(&self, jobs: impl IntoIterator<Item = (Task, Job)>)
+    where
+        Job: FnOnce(Task, Lane) + Send + 'static,
+    {
+        let (compute, io): (Vec<_>, Vec<_>) = jobs
+            .into_iter()
+            .partition(|(task, _)| self.lane_of(task) == Lane::Compute);
+        for (lane, jobs) in [(Lane::Io, io), (Lane::Compute, compute)] {
+            if !jobs.is_empty() {
+                self.workers(lane).0.submit_all(
+                    jobs.into_iter()
+                        .map(move |(task, job)| move || job(task, lane)),
+                );
+            }
+        }
+    }
+
+    /// Whether the executor polls the child tasks on separate lanes
+    pub(crate) fn has_lanes(&self) -> bool {
+        self.compute.is_some()
+    }
+
+    pub(crate) fn spawn<Fut>(&self, priority: Priority, task: Fut) -> Task
+    where
+        Fut: Future<Output = ()> + 'static + Send,
+    {
+        self.spawn_with_id(priority, None, None, task)
+    }
+
+    /// Spawns a child task known to the registry of its spawn group by the given identifier, counted as outstanding
+    /// by its spawn group until it completes
+    pub(crate) fn spawn_with_id<Fut>(
+        &self,
+        priority: Priority,
+        id: Option<u64>,
+        outstanding: Option<OutstandingGuard>,
+        task: Fut,
+    ) -> Task
     where
         Fut: Future<Output = ()> + 'static + Send,
     {
-        let task: Task = Task::new(task);
+        let task: Task = Task::new(priority, id, self.tracer.clone(), outstanding, task);
         self.queue.push(&task);
 
         if !self.started() {
@@ -84,58 +248,177 @@ impl Executor {
 
     fn notify(&self) {
         self.update(true);
-        let pair2: Arc<(Mutex<bool>, Condvar)> = self.lock_pair.clone();
-        std::thread::spawn(move || {
-            let (lock, cvar) = &*pair2;
-            let mut started: MutexGuard<'_, RawMutex, bool> = lock.lock();
-            *started = true;
-            cvar.notify_one();
-        });
+        let (lock, cvar) = &*self.lock_pair;
+        let mut started: MutexGuard<'_, RawMutex, bool> = lock.lock();
+        *started = true;
+        // the threads left waiting by a cancellation are woken up along with the current one, only to stop
+        cvar.notify_all();
     }
 
     pub(crate) fn cancel(&self) {
-        self.cancel.store(true, Ordering::Release);
-        *self.lock_pair.0.lock() = false;
+        self.cancelled.fetch_add(1, Ordering::AcqRel);
+        let (lock, cvar) = &*self.lock_pair;
+        *lock.lock() = false;
+        cvar.notify_all();
         self.update(false);
         self.queue.drain_all();
-        self.cancel.store(false, Ordering::Release);
+        self.stalled.lock().tasks.clear();
+    }
+
+    /// Queues the child tasks whose wakeup was dropped again, and drops none of the wakeups until the returned guard
+    /// is dropped
+    pub(crate) fn drive_stalled(&self) -> DrivingGuard {
+        let mut stalled = self.stalled.lock();
+        stalled.waiting += 1;
+        stalled
+            .tasks
+            .drain(..)
+            .for_each(|task| self.queue.push(&task));
+        DrivingGuard(self.stalled.clone())
+    }
+
+    /// Stops every thread running the executor and every worker thread for good
+    ///
+    /// Draining waits for the worker threads to run the queued jobs and exit, abandoning drops the queued jobs and
+    /// leaves the worker threads to exit once done with their current one.
+    pub(crate) fn shutdown(&self, mode: ShutdownMode) {
+        let (lock, cvar) = &*self.lock_pair;
+        let started: MutexGuard<'_, RawMutex, bool> = lock.lock();
+        self.stopped.store(true, Ordering::Release);
+        cvar.notify_all();
+        drop(started);
+        for pool in self.pools() {
+            match mode {
+                ShutdownMode::Drain => pool.end(mode),
+                ShutdownMode::Abandon => drop(pool.stop(mode)),
+            }
+        }
     }
 
-    pub(crate) fn run(&self) {
-        while !self.cancel.load(Ordering::Acquire) {
+    fn run(&self, cancelled: usize) {
+        while self.is_current(cancelled) {
             self.queue.clone().for_each(|task| {
-                let queue: TaskQueue = self.queue.clone();
-                self.submit(move || {
-                    let waker: Waker = Arc::new(Notifier::default()).into_waker();
-                    pin_future!(task);
+                let lane: Lane = self.lane_of(&task);
+                let (pool, ready): (&Arc<ThreadPool>, &TaskQueue) = self.workers(lane);
+                ready.push_ready(&task);
+                let (queue, mut ready): (TaskQueue, TaskQueue) =
+                    (self.queue.clone(), ready.clone());
+                let faults: FaultInjector = self.faults().clone();
+                let stalled: Arc<Mutex<Stalled>> = self.stalled.clone();
+                let separate: bool = self.has_lanes();
+                pool.submit(move || {
+                    // whichever child task comes first once a worker thread is free is polled, not necessarily
+                    // the one handed over along with the job, so the ones woken in the meantime go first
+                    let Some(task) = ready.next() else {
+                        return;
+                    };
+                    task.clear_wake();
+                    let waker: Waker = Waker::from(Arc::new(TaskWaker::new(
+                        task.wake_flag(),
+                        ready,
+                        task.id,
+                        task.tracer().clone(),
+                    )));
                     let mut cx: Context<'_> = Context::from_waker(&waker);
-                    match task.as_mut().poll(&mut cx) {
+                    match task.poll_on(lane, separate, &mut cx) {
                         Poll::Ready(()) => (),
                         Poll::Pending => {
+                            if faults.should_drop_wakeup() {
+                                let mut stalled = stalled.lock();
+                                // a wait in progress would never see the child task otherwise
+                                if stalled.waiting == 0 {
+                                    stalled.tasks.push(task);
+                                    return;
+                                }
+                            }
                             queue.push(&task);
                         }
                     }
                 });
             });
         }
-        self.poll_all();
-        self.queue.drain_all();
+        // the queue is left alone, it already holds the child tasks spawned since the cancellation
+    }
+
+    pub(crate) fn num_threads(&self) -> usize {
+        self.pools().map(|pool| pool.count()).sum()
+    }
+
+    /// Whether the calling thread is a worker thread of any lane
+    pub(crate) fn is_worker_thread(&self) -> bool {
+        self.pools()
+            .any(|pool| pool.execution_log().is_current_worker())
+    }
+
+    pub(crate) fn faults(&self) -> &FaultInjector {
+        self.pool.faults()
+    }
+
+    pub(crate) fn worker_states(&self) -> &WorkerStates {
+        self.pool.worker_states()
+    }
+
+    pub(crate) fn execution_log(&self) -> &ExecutionLog {
+        self.pool.execution_log()
+    }
+
+    pub(crate) fn wake_tracer(&self) -> &WakeTracer {
+        &self.tracer
+    }
+
+    pub(crate) fn cpu_quota(&self) -> &CpuQuota {
+        self.pool.cpu_quota()
+    }
+
+    pub(crate) fn set_panic_isolation(&self, isolation: PanicIsolation) {
+        self.pools()
+            .for_each(|pool| pool.set_panic_isolation(isolation));
+    }
+
+    /// Sets the hook run whenever a job is queued on a lane without worker threads
+    pub(crate) fn set_on_queued(&self, hook: Option<QueuedHook>) {
+        self.pools()
+            .for_each(|pool| pool.set_on_queued(hook.clone()));
+    }
+
+    pub(crate) fn run_pending(&self) -> bool {
+        self.pools().any(|pool| pool.run_pending())
     }
 
     pub(crate) fn poll_all(&self) {
-        self.pool.wait_for_all();
+        self.pools().for_each(|pool| pool.wait_for_all());
+    }
+
+    /// Waits for the worker threads of every lane as ``poll_all`` does, giving up once the deadline passed
+    ///
+    /// # Returns
+    /// Whether the worker threads were done in time
+    pub(crate) fn poll_all_until(&self, deadline: Instant) -> bool {
+        self.pools().all(|pool| pool.wait_for_all_until(deadline))
+    }
+
+    /// Starts a thread running the given closure through the thread factory of the executor
+    pub(crate) fn spawn_thread<Task>(&self, name: String, task: Task) -> io::Result<()>
+    where
+        Task: FnOnce() + Send + 'static,
+    {
+        UniqueThread::new(&self.factory, name, task).map(drop)
     }
 
-    pub(crate) fn start(&self) {
+    /// Starts the thread running the executor through its thread factory
+    pub(crate) fn start(&self) -> io::Result<()> {
         let lock_pair: Arc<(Mutex<bool>, Condvar)> = self.lock_pair.clone();
         let executor: Executor = self.clone();
-        std::thread::spawn(move || {
+        let cancelled: usize = self.cancelled.load(Ordering::Acquire);
+        UniqueThread::new(&self.factory, "Executor".to_string(), move || {
             let (lock, cvar) = &*lock_pair;
             let mut started: MutexGuard<'_, RawMutex, bool> = lock.lock();
-            while !*started {
+            while !*started && executor.is_current(cancelled) {
                 cvar.wait(&mut started);
             }
-            executor.run();
-        });
+            drop(started);
+            executor.run(cancelled);
+        })?;
+        Ok(())
     }
 }
@@ -0,0 +1,132 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+/// Lane
+///
+/// The worker threads a child task is polled on, in a spawn group created with separate lanes through
+/// [`SpawnGroup::with_lanes`](crate::SpawnGroup::with_lanes).
+///
+/// A spawn group without separate lanes polls every child task on the same worker threads, as if they were all on
+/// the IO lane.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lane {
+    /// The worker threads for child tasks mostly waiting on IO, which every child task starts on unless spawned with
+    /// ``spawn_task_on``
+    #[default]
+    Io,
+    /// The worker threads for child tasks busy computing, kept apart so they never hold up the IO lane
+    Compute,
+}
+
+/// The lane a child task is to be polled on next, changed by the child task itself through [`move_to`]
+#[derive(Clone, Default)]
+pub(crate) struct LaneSlot(Arc<AtomicU8>);
+
+impl LaneSlot {
+    pub(crate) fn get(&self) -> Lane {
+        match self.0.load(Ordering::Acquire) {
+            0 => Lane::Io,
+            _ => Lane::Compute,
+        }
+    }
+
+    fn set(&self, lane: Lane) {
+        self.0.store(lane as u8, Ordering::Release);
+    }
+}
+
+struct Polling {
+    // the lane of the worker thread polling the child task
+    lane: Lane,
+    // whether the spawn group has a compute lane apart from its IO lane
+    separate: bool,
+    slot: LaneSlot,
+}
+
+thread_local! {
+    // the child task being polled on the calling thread, if any
+    static POLLING: RefCell<Option<Polling>> = const { RefCell::new(None) };
+}
+
+struct Restore(Option<Polling>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        POLLING.set(self.0.take());
+    }
+}
+
+/// Runs the given poll of a child task on a worker thread of the given lane, which the child task learns through
+/// [`move_to`]
+pub(crate) fn polling<T>(
+    lane: Lane,
+    separate: bool,
+    slot: &LaneSlot,
+    poll: impl FnOnce() -> T,
+) -> T {
+    let _restore: Restore = Restore(POLLING.replace(Some(Polling {
+        lane,
+        separate,
+        slot: slot.clone(),
+    })));
+    poll()
+}
+
+/// Moves the current child task over to the worker threads of the given lane, resuming it there
+///
+/// The child task suspends and is polled next by a worker thread of the lane, so everything after the await runs
+/// there until it moves again. It resolves right away when the child task is already on the lane, when its spawn
+/// group has no separate lanes and outside of a child task.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, move_to, Lane, Priority, SpawnGroup};
+/// use std::num::NonZeroUsize;
+///
+/// let threads = NonZeroUsize::new(2).unwrap();
+/// let mut group = SpawnGroup::<u64>::with_lanes(threads, threads).unwrap();
+/// group.spawn_task(Priority::default(), async {
+///     // fetched on the IO lane
+///     let input: Vec<u64> = (1..=100).collect();
+///     move_to(Lane::Compute).await;
+///     input.iter().map(|value| value * value).sum()
+/// });
+/// assert_eq!(block_on(group.first()), Some(338350));
+/// ```
+pub fn move_to(lane: Lane) -> MoveTo {
+    MoveTo { lane }
+}
+
+/// The future returned by [`move_to`]
+#[must_use = "futures do nothing unless awaited"]
+pub struct MoveTo {
+    lane: Lane,
+}
+
+impl Future for MoveTo {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let arrived: bool = POLLING.with_borrow(|polling| match polling {
+            Some(polling) if polling.separate && polling.lane != self.lane => {
+                polling.slot.set(self.lane);
+                false
+            }
+            _ => true,
+        });
+        if arrived {
+            return Poll::Ready(());
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -1,5 +1,7 @@
 pub(crate) mod executor;
+pub(crate) mod lane;
 pub(crate) mod notifier;
+mod pin_macro;
+pub(crate) mod pool_cache;
 pub(crate) mod task;
 mod task_queue;
-mod pin_macro;
\ No newline at end of file
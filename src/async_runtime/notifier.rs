@@ -1,14 +1,34 @@
-use cooked_waker::WakeRef;
-use std::sync::{Condvar, Mutex, MutexGuard};
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    task::Wake,
+    time::Duration,
+};
+
+use crate::wake_trace::WakeTracer;
+
+use super::task_queue::TaskQueue;
 
 #[derive(Default)]
-pub struct Notifier {
+pub(crate) struct Notifier {
     was_notified: Mutex<bool>,
     cv: Condvar,
+    // set once the future it woke has completed, so wakes coming after that are no-ops
+    defused: AtomicBool,
 }
 
-impl WakeRef for Notifier {
-    fn wake_by_ref(&self) {
+impl Wake for Notifier {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.defused.load(Ordering::Acquire) {
+            return;
+        }
         let was_notified: bool =
             { std::mem::replace(&mut self.was_notified.lock().unwrap(), true) };
         if !was_notified {
@@ -26,4 +46,75 @@ impl Notifier {
         }
         *was_notified = false;
     }
+
+    /// Waits for a wake for at most the given duration
+    ///
+    /// # Returns
+    /// Whether it was woken before the duration elapsed
+    pub(crate) fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (mut was_notified, _) = self
+            .cv
+            .wait_timeout_while(self.was_notified.lock().unwrap(), timeout, |was_notified| {
+                !*was_notified
+            })
+            .unwrap();
+        mem::replace(&mut *was_notified, false)
+    }
+
+    /// Turns every later wake into a no-op
+    pub(crate) fn defuse(&self) {
+        self.defused.store(true, Ordering::Release);
+    }
+
+    /// Forgets a wake that came in after the future completed, only sound once no waker is left but the caller's
+    pub(crate) fn reset(&self) {
+        *self.was_notified.lock().unwrap() = false;
+    }
+}
+
+/// Wakes a child task polled by the executor, from a worker thread or any thread outside of the threadpool alike
+///
+/// The wake moves the child task ahead of the ones with a lower priority, so whichever worker thread frees up next
+/// polls it rather than the background work queued before it.
+pub(crate) struct TaskWaker {
+    woken: Arc<AtomicBool>,
+    ready: TaskQueue,
+    id: Option<u64>,
+    tracer: WakeTracer,
+}
+
+impl TaskWaker {
+    pub(crate) fn new(
+        woken: Arc<AtomicBool>,
+        ready: TaskQueue,
+        id: Option<u64>,
+        tracer: WakeTracer,
+    ) -> Self {
+        Self {
+            woken,
+            ready,
+            id,
+            tracer,
+        }
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.tracer.woken(self.id);
+        if self.woken.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.ready.promote(&self.woken);
+    }
+}
+
+impl Drop for TaskWaker {
+    fn drop(&mut self) {
+        self.tracer.released(self.id);
+    }
 }
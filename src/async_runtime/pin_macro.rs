@@ -1,8 +1,27 @@
-/// Pins the variable implementing the ``std::future::Future`` trait onto the stack
+/// Pins the variables implementing the ``std::future::Future`` trait onto the stack
+///
+/// Each variable is moved into a new binding of the same name, a ``Pin<&mut _>`` shadowing it, so it can never be
+/// moved again once pinned. The pinned binding can be polled by hand, awaited by reference or handed to
+/// [`poll_once`](crate::poll_once) and [`now_or_never`](crate::now_or_never) without giving up the future.
+///
+/// Pinning neither polls nor drops the future, it's dropped along with its binding at the end of the scope.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, now_or_never, pin_future, sleep};
+/// use std::time::Duration;
+///
+/// let delay = sleep(Duration::from_millis(10));
+/// let ready = async { 42 };
+/// pin_future!(delay, ready);
+/// assert_eq!(now_or_never(delay.as_mut()), None);
+/// block_on(delay);
+/// assert_eq!(now_or_never(ready), Some(42));
+/// ```
 #[macro_export]
 macro_rules! pin_future {
-    ($x:ident) => {
-        let mut $x = $x;
-        let mut $x = unsafe { std::pin::Pin::new_unchecked(&mut $x) };
+    ($($x:ident),+ $(,)?) => {
+        $(let mut $x = std::pin::pin!($x);)+
     };
 }
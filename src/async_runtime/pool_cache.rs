@@ -0,0 +1,91 @@
+use super::executor::Executor;
+use crate::threadpool_impl::ShutdownMode;
+use std::cell::RefCell;
+
+/// Most threadpools kept warm by each thread
+const CAPACITY: usize = 4;
+
+/// Threadpools left behind by the scoped spawn groups that ended on this thread
+#[derive(Default)]
+struct Cached(Vec<Executor>);
+
+impl Drop for Cached {
+    fn drop(&mut self) {
+        self.0
+            .drain(..)
+            .for_each(|executor| executor.shutdown(ShutdownMode::Drain));
+    }
+}
+
+thread_local! {
+    static CACHED: RefCell<Cached> = RefCell::new(Cached::default());
+}
+
+/// Takes a threadpool left behind on this thread with the given number of worker threads, if any
+///
+/// A threadpool is only cached once its spawn group let go of it and leaves the cache held by the next one, so it
+/// belongs to a single spawn group at a time, even among scopes nested on the same thread, and cancelling one spawn
+/// group never reaches the child tasks of another one.
+///
+/// # Panics
+/// If a live spawn group holds the threadpool found
+pub(crate) fn take(count: usize) -> Option<Executor> {
+    let executor: Executor = CACHED.with_borrow_mut(|cached| {
+        let index: usize = cached
+            .0
+            .iter()
+            .position(|executor| executor.num_threads() == count)?;
+        Some(cached.0.swap_remove(index))
+    })?;
+    executor.hold();
+    Some(executor)
+}
+
+/// Keeps a threadpool with no child task left for the next scoped spawn group started on this thread
+///
+/// The oldest threadpool is torn down when this thread already keeps as many as it can.
+///
+/// # Panics
+/// If the threadpool isn't held by the spawn group releasing it, as it was released already
+pub(crate) fn release(executor: Executor) {
+    executor.let_go();
+    let evicted: Option<Executor> = CACHED.with_borrow_mut(|cached| {
+        cached.0.push(executor);
+        (cached.0.len() > CAPACITY).then(|| cached.0.remove(0))
+    });
+    if let Some(evicted) = evicted {
+        evicted.shutdown(ShutdownMode::Drain);
+    }
+}
+
+/// Tears down the threadpools kept warm by the current thread
+///
+/// The scoped spawn group functions like ``with_spawn_group`` hand their threadpool back to the thread they ended on,
+/// so a following call on the same thread starts right away on the same worker threads instead of creating new ones.
+/// Only threadpools that weren't customized by their spawn group are kept, and every thread keeps at most four of them
+/// until it exits or this function is called.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, flush_cached_pools, with_spawn_group, Priority};
+/// use futures_lite::StreamExt;
+///
+/// for round in 0..3 {
+///     // every round after the first one reuses the threadpool of the previous one
+///     let sum: u64 = block_on(with_spawn_group(move |mut group| async move {
+///         for value in 0..10 {
+///             group.spawn_task(Priority::default(), async move { value * round });
+///         }
+///         group.fold(0, |acc, value| acc + value).await
+///     }));
+///     assert_eq!(sum, 45 * round);
+/// }
+/// flush_cached_pools();
+/// ```
+pub fn flush_cached_pools() {
+    let flushed: Vec<Executor> = CACHED.with_borrow_mut(|cached| cached.0.drain(..).collect());
+    flushed
+        .into_iter()
+        .for_each(|executor| executor.shutdown(ShutdownMode::Drain));
+}
@@ -1,3 +1,8 @@
+use super::lane::{self, Lane, LaneSlot};
+use crate::{
+    shared::{outstanding::OutstandingGuard, priority::Priority},
+    wake_trace::WakeTracer,
+};
 use parking_lot::Mutex;
 use std::{
     future::Future,
@@ -6,25 +11,75 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    task::Poll,
+    task::{Context, Poll},
 };
 
 type LocalBoxedFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
 
 #[derive(Clone)]
-pub struct Task {
+pub(crate) struct Task {
     pub(crate) future: Arc<Mutex<LocalBoxedFuture>>,
     pub(crate) complete: Arc<AtomicBool>,
+    pub(crate) priority: Priority,
+    // set by its waker, cleared right before it's polled
+    woken: Arc<AtomicBool>,
+    // the identifier of the child task in the registry of its spawn group, if it has one
+    pub(crate) id: Option<u64>,
+    tracer: WakeTracer,
+    // keeps the child task counted as outstanding until its completion is recorded
+    outstanding: Arc<Mutex<Option<OutstandingGuard>>>,
+    lane: LaneSlot,
 }
 
 impl Task {
-    pub(crate) fn new<Fut: Future<Output = ()> + Send + 'static>(fut: Fut) -> Self {
+    pub(crate) fn new<Fut: Future<Output = ()> + Send + 'static>(
+        priority: Priority,
+        id: Option<u64>,
+        tracer: WakeTracer,
+        outstanding: Option<OutstandingGuard>,
+        fut: Fut,
+    ) -> Self {
         Self {
             future: Arc::new(Mutex::new(Box::pin(fut))),
             complete: Arc::new(AtomicBool::new(false)),
+            priority,
+            woken: Arc::new(AtomicBool::new(false)),
+            id,
+            tracer,
+            outstanding: Arc::new(Mutex::new(outstanding)),
+            lane: LaneSlot::default(),
         }
     }
 
+    pub(crate) fn tracer(&self) -> &WakeTracer {
+        &self.tracer
+    }
+
+    /// The flag its waker sets
+    pub(crate) fn wake_flag(&self) -> Arc<AtomicBool> {
+        self.woken.clone()
+    }
+
+    /// Whether it was woken since it was last polled
+    pub(crate) fn is_woken(&self) -> bool {
+        self.woken.load(Ordering::Acquire)
+    }
+
+    /// Whether the given flag is the one its waker sets
+    pub(crate) fn is_woken_through(&self, woken: &Arc<AtomicBool>) -> bool {
+        Arc::ptr_eq(&self.woken, woken)
+    }
+
+    /// Forgets the wake it received, right before it's polled so a wake during the poll isn't missed
+    pub(crate) fn clear_wake(&self) {
+        self.woken.store(false, Ordering::Release);
+    }
+
+    /// The lane it's to be polled on next
+    pub(crate) fn lane(&self) -> Lane {
+        self.lane.get()
+    }
+
     pub(crate) fn is_completed(&self) -> bool {
         self.complete.load(Ordering::Acquire)
     }
@@ -32,19 +87,40 @@ impl Task {
     fn complete(&self) {
         self.complete.store(true, Ordering::Release);
     }
+
+    /// Polls the future unless it has already completed
+    ///
+    /// A task may still be queued, or be woken, after it completed, so the completion is checked
+    /// under the lock of the future, and a completed future is never polled again.
+    pub(crate) fn poll_once(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut future = self.future.lock();
+        if self.is_completed() {
+            return Poll::Ready(());
+        }
+        self.tracer.resumed(self.id);
+        let result: Poll<()> = future.as_mut().poll(cx);
+        if result.is_ready() {
+            self.complete();
+            self.tracer.completed(self.id);
+            // only now is whoever waits for the spawn group told, so it sees the child task as completed
+            drop(self.outstanding.lock().take());
+        } else {
+            self.tracer.suspended(self.id);
+        }
+        result
+    }
+
+    /// Polls the future on a worker thread of the given lane, whether or not the spawn group has separate lanes
+    pub(crate) fn poll_on(&self, lane: Lane, separate: bool, cx: &mut Context<'_>) -> Poll<()> {
+        lane::polling(lane, separate, &self.lane, || self.poll_once(cx))
+    }
 }
 
 impl Future for Task {
     type Output = ();
-    fn poll(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Self::Output> {
-        match self.future.lock().as_mut().poll(cx) {
-            Poll::Ready(()) => {
-                self.complete();
-                Poll::Ready(())
-            }
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.poll_once(cx) {
+            Poll::Ready(()) => Poll::Ready(()),
             Poll::Pending => {
                 cx.waker().wake_by_ref();
                 Poll::Pending
@@ -1,9 +1,13 @@
 use super::task::Task;
 use parking_lot::Mutex;
-use std::{collections::VecDeque, iter::Iterator, sync::Arc};
+use std::{
+    collections::VecDeque,
+    iter::Iterator,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 #[derive(Clone, Default)]
-pub struct TaskQueue {
+pub(crate) struct TaskQueue {
     buffer: Arc<Mutex<VecDeque<Task>>>,
 }
 
@@ -14,6 +18,45 @@ impl TaskQueue {
 }
 
 impl TaskQueue {
+    /// Queues a woken task behind the tasks of the same or a higher priority, any other task at the back
+    pub(crate) fn push_ready(&self, task: &Task) {
+        let mut buffer = self.buffer.lock();
+        let index: usize = match task.is_woken() {
+            true => Self::woken_position(&buffer, task),
+            false => buffer.len(),
+        };
+        buffer.insert(index, task.clone());
+    }
+
+    /// Moves the task woken through the given flag to where it would have been queued once woken, if it's queued
+    ///
+    /// It's never moved back, so it keeps its place among the woken tasks of the same priority.
+    pub(crate) fn promote(&self, woken: &Arc<AtomicBool>) {
+        let mut buffer = self.buffer.lock();
+        let Some(from) = buffer
+            .iter()
+            .position(|queued: &Task| queued.is_woken_through(woken))
+        else {
+            return;
+        };
+        let Some(task) = buffer.remove(from) else {
+            return;
+        };
+        let to: usize = Self::woken_position(&buffer, &task).min(from);
+        buffer.insert(to, task);
+    }
+
+    /// Where a woken task goes, before the first task with a lower priority
+    ///
+    /// The tasks of the same or a higher priority stay ahead whether they were woken or not, so a task woken over and
+    /// over, like one yielding in a loop, never keeps one of them from being polled.
+    fn woken_position(buffer: &VecDeque<Task>, task: &Task) -> usize {
+        buffer
+            .iter()
+            .position(|queued: &Task| queued.priority < task.priority)
+            .unwrap_or(buffer.len())
+    }
+
     pub(crate) fn drain_all(&self) {
         self.buffer.lock().clear();
     }
@@ -22,13 +65,13 @@ impl TaskQueue {
 impl Iterator for TaskQueue {
     type Item = Task;
 
+    /// Skips the tasks that completed while they were queued
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(task) = self.buffer.lock().pop_front() else {
-            return None;
-        };
-        if !task.is_completed() {
-            return Some(task);
+        loop {
+            let task = self.buffer.lock().pop_front()?;
+            if !task.is_completed() {
+                return Some(task);
+            }
         }
-        None
     }
 }
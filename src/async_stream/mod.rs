@@ -1,50 +1,625 @@
 use std::{
     collections::VecDeque,
+    future::{poll_fn, Future},
+    iter, mem,
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Weak,
     },
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
-use async_mutex::{Mutex, MutexGuard};
 use futures_lite::{Stream, StreamExt};
+use parking_lot::{Mutex, Mutex as WakerSlot, MutexGuard};
 
-use crate::executors::block_on;
+use crate::shared::{
+    ack_gate::AckGate,
+    metrics::Metrics,
+    overflow_policy::OverflowPolicy,
+    teardown::{self, Teardown},
+};
+
+pub(crate) type Helper = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// How long a consumer may wait while results are buffered before it's woken regardless of the watermark
+const STARVATION_THRESHOLD: Duration = Duration::from_millis(5);
+
+#[derive(Default)]
+struct Waiters {
+    wakers: Vec<Waker>,
+    // when the oldest consumer still waiting started waiting
+    since: Option<Instant>,
+}
+
+/// Where an item came from, which places it when a consumer asks for the items in order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Tag {
+    /// The index its child task was spawned with, none for an item handed back by a consumer without its tag
+    pub(crate) index: Option<u64>,
+    /// The sequence number its child task was spawned with, if any
+    pub(crate) sequence: Option<u64>,
+    /// The epoch of the ack slot its child task kept until the item is acknowledged, none outside of ack mode and
+    /// once the item was consumed
+    pub(crate) ack: Option<u64>,
+}
+
+/// An item along with where it came from
+pub(crate) type Tagged<ItemType> = (Tag, ItemType);
+
+/// What the buffer holds, either an item or a marker placed in between the items
+pub(crate) enum Entry<ItemType> {
+    Item(Tagged<ItemType>),
+    Marker(u64),
+}
+
+/// The most the child tasks buffer before the overflow policy applies
+#[derive(Clone, Copy)]
+pub(crate) enum Bound {
+    /// A number of items
+    Items(usize),
+    /// A number of bytes as measured by the size function of the buffer, an item is always let into an empty buffer
+    /// though, whatever its size
+    Bytes(usize),
+}
+
+/// Measures the approximate number of bytes an item takes
+pub(crate) type SizeOf<ItemType> = fn(&ItemType) -> usize;
+
+fn size_of_type<ItemType>(_: &ItemType) -> usize {
+    mem::size_of::<ItemType>()
+}
+
+/// The buffered items, the ones still buffered once the last clone of the stream is dropped count as discarded
+///
+/// The markers go through the same buffer as the items so they keep their place among them, but they're never
+/// counted as items.
+struct Buffer<ItemType> {
+    entries: VecDeque<Entry<ItemType>>,
+    // the arrival of each entry, which never decreases from the front to the back
+    arrivals: VecDeque<u64>,
+    // the size each entry was measured with when buffered, zero for the markers
+    sizes: VecDeque<usize>,
+    // the sum of the sizes
+    bytes: usize,
+    size_of: SizeOf<ItemType>,
+    // the number of markers among the entries
+    markers: usize,
+    // the arrival of the next entry, an entry handed back to the front arrives before every other one
+    next_arrival: u64,
+    // the most inserted by the child tasks, along with what happens to the items beyond
+    capacity: Option<(Bound, OverflowPolicy)>,
+    metrics: Metrics,
+}
+
+impl<ItemType> Buffer<ItemType> {
+    /// The number of buffered items, leaving the markers out
+    fn len(&self) -> usize {
+        self.entries.len() - self.markers
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The overflow policy if the buffer has no room left for an item of the given size
+    fn overflow(&self, size: usize) -> Option<OverflowPolicy> {
+        self.capacity
+            .filter(|&(bound, _)| match bound {
+                Bound::Items(capacity) => self.len() >= capacity,
+                Bound::Bytes(capacity) => !self.is_empty() && self.bytes + size > capacity,
+            })
+            .map(|(_, policy)| policy)
+    }
+
+    /// Counts the bytes of an entry coming in, or of entries going out
+    fn add_bytes(&mut self, size: usize) {
+        self.bytes += size;
+        self.metrics.record_buffered_bytes(size);
+    }
+
+    fn remove_bytes(&mut self, size: usize) {
+        self.bytes -= size;
+        self.metrics.record_unbuffered_bytes(size);
+    }
+
+    /// Takes the oldest item out, leaving the markers in front of it in place
+    fn evict_oldest(&mut self) -> Option<Tagged<ItemType>> {
+        let position: usize = self
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, Entry::Item(_)))?;
+        self.remove_item(position)
+    }
 
-pub struct AsyncStream<ItemType> {
-    buffer: Arc<Mutex<VecDeque<ItemType>>>,
+    /// Takes the newest item out, leaving the markers behind it in place
+    fn pop_back_item(&mut self) -> Option<Tagged<ItemType>> {
+        let position: usize = self
+            .entries
+            .iter()
+            .rposition(|entry| matches!(entry, Entry::Item(_)))?;
+        self.remove_item(position)
+    }
+
+    fn remove_item(&mut self, position: usize) -> Option<Tagged<ItemType>> {
+        self.arrivals.remove(position);
+        let size: usize = self.sizes.remove(position).unwrap_or_default();
+        self.remove_bytes(size);
+        match self.entries.remove(position) {
+            Some(Entry::Item(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// Hands out the arrival of an entry arriving now
+    fn arrive(&mut self) -> u64 {
+        self.next_arrival += 1;
+        self.next_arrival
+    }
+
+    /// Buffers an item already measured
+    fn push_back(&mut self, item: Tagged<ItemType>, size: usize) {
+        let arrival: u64 = self.arrive();
+        self.arrivals.push_back(arrival);
+        self.sizes.push_back(size);
+        self.add_bytes(size);
+        self.entries.push_back(Entry::Item(item));
+    }
+
+    /// Buffers an item a consumer hands back ahead of every other entry
+    ///
+    /// The capacity and its overflow policy only bound what the child tasks insert, the item was let in once already
+    /// and dropping or holding it back would lose a result the consumer kept on purpose.
+    fn push_front(&mut self, item: Tagged<ItemType>) {
+        let size: usize = (self.size_of)(&item.1);
+        self.arrivals.push_front(0);
+        self.sizes.push_front(size);
+        self.add_bytes(size);
+        self.entries.push_front(Entry::Item(item));
+    }
+
+    /// Places an item behind the entries that arrived before it, and ahead of the ones that arrived after it
+    fn push_at(&mut self, arrival: u64, item: Tagged<ItemType>) {
+        let size: usize = (self.size_of)(&item.1);
+        let position: usize = self.arrivals.partition_point(|&other| other < arrival);
+        self.arrivals.insert(position, arrival);
+        self.sizes.insert(position, size);
+        self.add_bytes(size);
+        self.entries.insert(position, Entry::Item(item));
+    }
+
+    fn push_marker(&mut self, marker: u64) {
+        let arrival: u64 = self.arrive();
+        self.arrivals.push_back(arrival);
+        self.sizes.push_back(0);
+        self.entries.push_back(Entry::Marker(marker));
+        self.markers += 1;
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+        self.arrivals.reserve(additional);
+        self.sizes.reserve(additional);
+    }
+
+    /// Takes the next item out, dropping the markers in front of it, which only a marked consumer cares about
+    ///
+    /// The markers are left alone while no item follows them, so a marked consumer can still take them.
+    fn pop_item(&mut self) -> Option<Tagged<ItemType>> {
+        let position: usize = self
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, Entry::Item(_)))?;
+        self.markers -= position;
+        self.arrivals.drain(..=position);
+        let size: usize = self.sizes.drain(..=position).sum();
+        self.remove_bytes(size);
+        match self.entries.drain(..=position).last() {
+            Some(Entry::Item(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    fn pop_entry(&mut self) -> Option<Entry<ItemType>> {
+        let entry: Entry<ItemType> = self.entries.pop_front()?;
+        self.arrivals.pop_front();
+        let size: usize = self.sizes.pop_front().unwrap_or_default();
+        self.remove_bytes(size);
+        if let Entry::Marker(_) = entry {
+            self.markers -= 1;
+        }
+        Some(entry)
+    }
+
+    /// Takes every buffered item out at once, without their tags, dropping the markers
+    ///
+    /// The buffer keeps its capacity, so a preallocated one stays preallocated.
+    fn take_items(&mut self) -> VecDeque<ItemType> {
+        self.markers = 0;
+        self.arrivals.clear();
+        self.sizes.clear();
+        self.remove_bytes(self.bytes);
+        self.entries
+            .drain(..)
+            .filter_map(|entry| match entry {
+                Entry::Item((_, value)) => Some(value),
+                Entry::Marker(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl<ItemType> Drop for Buffer<ItemType> {
+    fn drop(&mut self) {
+        self.metrics.record_discarded(self.len());
+        self.remove_bytes(self.bytes);
+        // the last clone of the stream goes along with its spawn group, so the results are dropped as part of tearing
+        // it down
+        let _teardown: Teardown = teardown::enter();
+        self.entries.clear();
+    }
+}
+
+pub(crate) struct AsyncStream<ItemType> {
+    buffer: Arc<Mutex<Buffer<ItemType>>>,
     started: bool,
     counts: (Arc<AtomicUsize>, Arc<AtomicUsize>),
-    cancelled: bool,
+    // shared by every clone, so consumers holding a clone observe the teardown of the spawn group
+    cancelled: Arc<AtomicBool>,
+    helper: Option<Helper>,
+    waiters: Arc<WakerSlot<Waiters>>,
+    wake_after: Arc<AtomicUsize>,
+    wave: Arc<AtomicUsize>,
+    // the spawners that may still spawn child tasks, which keep the stream from ending
+    holds: Arc<AtomicUsize>,
+    // the spawners waiting for a child task to finish before spawning theirs
+    spawners: Arc<WakerSlot<Vec<Waker>>>,
+    // the child tasks waiting for room in the buffer to insert their item
+    producers: Arc<WakerSlot<Vec<Waker>>>,
+    acks: AckGate,
+}
+
+/// What became of an item a child task inserted
+enum Inserted<ItemType> {
+    /// Buffered along with the given number of items
+    Buffered(usize),
+    /// Left out since the stream was reset after the child task was spawned
+    Stale(ItemType),
+    /// Dropped for lack of room, either the inserted item or the oldest buffered ones
+    Overflowed(Vec<ItemType>),
 }
 
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) async fn insert_item(&mut self, value: ItemType) {
+    /// Buffers the result of a child task, applying the overflow policy once the buffer is full
+    ///
+    /// # Returns
+    /// The results dropped for lack of room, if any, which will never be consumed
+    pub(crate) async fn insert_item(
+        &mut self,
+        wave: usize,
+        tag: Tag,
+        value: ItemType,
+    ) -> Vec<ItemType> {
         if !self.started {
             self.started = true;
         }
-        self.buffer.lock().await.push_back(value);
+        let mut item: Option<Tagged<ItemType>> = Some((tag, value));
+        // only the value is moved while the buffer is locked, a stale one is dropped once it's released
+        // since dropping it may mean decrementing a reference count or freeing memory
+        match poll_fn(|cx| self.poll_insert(wave, &mut item, cx)).await {
+            Inserted::Buffered(buffered) => {
+                self.wake_if_ready(buffered);
+                Vec::new()
+            }
+            Inserted::Stale(_) => Vec::new(),
+            Inserted::Overflowed(values) => {
+                // the consumers may be waiting for the item that won't come anymore
+                self.wake();
+                values
+            }
+        }
+    }
+
+    fn poll_insert(
+        &self,
+        wave: usize,
+        item: &mut Option<Tagged<ItemType>>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Inserted<ItemType>> {
+        let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        let (mut tag, value) = item.take().expect("polled after completion");
+        // checked under the lock so a result never crosses a reset
+        if !self.is_current(wave) {
+            return Poll::Ready(Inserted::Stale(value));
+        }
+        let size: usize = (inner_lock.size_of)(&value);
+        let policy: Option<OverflowPolicy> = inner_lock.overflow(size);
+        // a cancelled spawn group may never consume the buffered items, so the child task gives up waiting then
+        if policy == Some(OverflowPolicy::Block) && !self.is_cancelled() {
+            // registered while the buffer is still locked, so the next consumed item can't go unnoticed
+            self.register_producer(cx.waker());
+            *item = Some((tag, value));
+            return Poll::Pending;
+        }
+        inner_lock.metrics.record_produced();
+        let overflowed: Vec<ItemType> = match policy {
+            None => {
+                inner_lock.push_back((tag, value), size);
+                return Poll::Ready(Inserted::Buffered(inner_lock.len()));
+            }
+            Some(OverflowPolicy::DropOldest) => {
+                // a bound in bytes may take more than one of the oldest items to make room
+                let mut evicted: Vec<ItemType> = Vec::new();
+                while inner_lock.overflow(size).is_some() {
+                    let (mut oldest_tag, oldest) = inner_lock
+                        .evict_oldest()
+                        .expect("a full buffer holds at least one item");
+                    self.acknowledge(&mut oldest_tag);
+                    evicted.push(oldest);
+                }
+                inner_lock.push_back((tag, value), size);
+                evicted
+            }
+            Some(OverflowPolicy::DropNewest) => {
+                self.acknowledge(&mut tag);
+                vec![value]
+            }
+            Some(OverflowPolicy::Block) => {
+                self.acknowledge(&mut tag);
+                vec![value]
+            }
+        };
+        // a blocked child task only drops its item once the spawn group was cancelled, which isn't an overflow
+        if policy != Some(OverflowPolicy::Block) {
+            inner_lock.metrics.record_overflowed(overflowed.len());
+        }
+        inner_lock.metrics.record_discarded(overflowed.len());
+        overflowed.iter().for_each(|_| self.consume_count());
+        Poll::Ready(Inserted::Overflowed(overflowed))
+    }
+
+    /// Measures the items buffered from now on with the given function, the ones already buffered keep their size
+    pub(crate) fn set_size_of(&self, size_of: SizeOf<ItemType>) {
+        self.buffer.lock().size_of = size_of;
+    }
+
+    /// Bounds the results the child tasks buffer, beyond which the overflow policy applies
+    pub(crate) fn set_capacity(&self, bound: Bound, policy: OverflowPolicy) {
+        self.buffer.lock().capacity = Some((bound, policy));
+        // a raised capacity may make room for the suspended child tasks
+        self.wake_producers();
+    }
+
+    /// Lets the child tasks buffer as many results as they produce again
+    pub(crate) fn clear_capacity(&self) {
+        self.buffer.lock().capacity = None;
+        self.wake_producers();
+    }
+
+    /// Wakes the given child task once a buffered item is taken out
+    fn register_producer(&self, waker: &Waker) {
+        let mut producers = self.producers.lock();
+        if !producers
+            .iter()
+            .any(|registered| registered.will_wake(waker))
+        {
+            producers.push(waker.clone());
+        }
+    }
+
+    /// Wakes every child task waiting for room in the buffer, each of them checks for room again once it polls
+    fn wake_producers(&self) {
+        let producers: Vec<Waker> = std::mem::take(&mut *self.producers.lock());
+        producers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    /// The arrival of an item finishing now, which places it among the buffered items once it's restored
+    pub(crate) fn arrival(&self) -> u64 {
+        self.buffer.lock().arrive()
+    }
+
+    /// Buffers the result of a child task that was first handed to its handle, which no longer counts as
+    /// an item to come since it was handed over
+    ///
+    /// The result takes the place it would have had if it went into the stream on arrival, ahead of the items that
+    /// arrived after it and are still buffered.
+    pub(crate) fn restore_item(&self, wave: usize, tag: Tag, value: ItemType, arrival: u64) {
+        let buffered: Result<usize, ItemType> = {
+            let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+            if self.is_current(wave) {
+                self.counts.0.fetch_add(1, Ordering::Release);
+                inner_lock.push_at(arrival, (tag, value));
+                inner_lock.metrics.record_produced();
+                Ok(inner_lock.len())
+            } else {
+                Err(value)
+            }
+        };
+        if let Ok(buffered) = buffered {
+            self.wake_if_ready(buffered);
+        }
     }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) async fn buffer_count(&self) -> usize {
-        self.buffer.lock().await.len()
+    pub(crate) fn requeue_item(&self, wave: usize, value: ItemType) {
+        self.requeue_tagged(wave, (Tag::default(), value));
+    }
+
+    /// Hands an item a consumer took back to the front of the buffer, keeping where it came from, unless the stream
+    /// started another wave since the item was taken
+    pub(crate) fn requeue_tagged(&self, wave: usize, item: Tagged<ItemType>) {
+        let buffered: Result<usize, Tagged<ItemType>> = {
+            let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+            if self.is_current(wave) {
+                self.counts.0.fetch_add(1, Ordering::Release);
+                inner_lock.push_front(item);
+                inner_lock.metrics.record_produced();
+                Ok(inner_lock.len())
+            } else {
+                Err(item)
+            }
+        };
+        if let Ok(buffered) = buffered {
+            self.wake_if_ready(buffered);
+        }
+    }
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    pub(crate) fn set_wake_after(&self, count: usize) {
+        self.wake_after.store(count.max(1), Ordering::Release);
+    }
+
+    /// Wakes the consumer only once the buffer fills up to the watermark, which is the
+    /// transition from empty to non-empty by default, or once every remaining item is buffered
+    /// so the last item always reaches a waiting consumer
+    fn wake_if_ready(&self, buffered: usize) {
+        if buffered == self.wake_after.load(Ordering::Acquire)
+            || buffered >= self.item_count()
+            || self.is_starved()
+        {
+            self.wake();
+        }
+    }
+
+    /// Wakes every waiting consumer, each of them takes an item only once it polls again, so
+    /// an item is never lost to a consumer that stopped waiting between its wake-up and its poll
+    fn wake(&self) {
+        let waiters: Waiters = std::mem::take(&mut *self.waiters.lock());
+        waiters.wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Wakes every waiting consumer as ``wake`` does, without keeping the stream alive
+    pub(crate) fn consumer_wakeup(&self) -> impl Fn() + Send + Sync + 'static {
+        let waiters: Weak<WakerSlot<Waiters>> = Arc::downgrade(&self.waiters);
+        move || {
+            if let Some(waiters) = waiters.upgrade() {
+                let waiters: Waiters = std::mem::take(&mut *waiters.lock());
+                waiters.wakers.into_iter().for_each(Waker::wake);
+            }
+        }
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut waiters = self.waiters.lock();
+        if !waiters
+            .wakers
+            .iter()
+            .any(|registered| registered.will_wake(waker))
+        {
+            waiters.wakers.push(waker.clone());
+        }
+        waiters.since.get_or_insert_with(Instant::now);
+    }
+
+    fn unregister(&self, waker: &Waker) {
+        let mut waiters = self.waiters.lock();
+        waiters
+            .wakers
+            .retain(|registered| !registered.will_wake(waker));
+        if waiters.wakers.is_empty() {
+            waiters.since = None;
+        }
+    }
+
+    fn is_starved(&self) -> bool {
+        self.waiters
+            .lock()
+            .since
+            .is_some_and(|since| since.elapsed() >= STARVATION_THRESHOLD)
+    }
+
+    /// Wakes the waiting consumers if they have been waiting for too long while results were buffered,
+    /// called by the worker threads before polling a child task so the consumer isn't starved by the
+    /// watermark while the worker threads keep running other child tasks
+    pub(crate) fn wake_if_starved(&self) {
+        // checked first so the buffer isn't locked on every poll of every child task while
+        // nobody is waiting, and a locked buffer is being used right now, the next poll checks again
+        if !self.is_starved() {
+            return;
+        }
+        let buffered: bool = self
+            .buffer
+            .try_lock()
+            .is_some_and(|buffer| !buffer.is_empty());
+        if buffered {
+            self.wake();
+        }
+    }
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    /// The number of items buffered, ready to be taken without waiting
+    pub(crate) fn buffered(&self) -> usize {
+        self.buffer.lock().len()
+    }
+
+    /// The number of entries the buffer holds before it reallocates
+    pub(crate) fn capacity(&self) -> usize {
+        self.buffer.lock().entries.capacity()
+    }
+
+    /// Whether polling the stream would end it, as it has no item buffered and no child task left to produce one
+    pub(crate) fn is_terminated(&self) -> bool {
+        let inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        inner_lock.is_empty() && (self.is_cancelled() || self.is_exhausted())
     }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
     pub(crate) fn increment(&self) {
-        self.counts.0.fetch_add(1, Ordering::Acquire);
-        self.counts.1.fetch_add(1, Ordering::Acquire);
+        self.increment_by(1);
+    }
+
+    /// Counts the given number of spawned child tasks at once
+    pub(crate) fn increment_by(&self, count: usize) {
+        self.counts.0.fetch_add(count, Ordering::Acquire);
+        self.counts.1.fetch_add(count, Ordering::Acquire);
+    }
+
+    /// Makes room in the buffer for the given number of items upfront
+    pub(crate) fn reserve(&self, additional: usize) {
+        self.buffer.lock().reserve(additional);
     }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) async fn first(&mut self) -> Option<ItemType> {
-        self.next().await
+    pub(crate) fn first(&mut self) -> First<'_, ItemType> {
+        First {
+            stream: self,
+            waker: None,
+        }
+    }
+}
+
+/// Future of the first item of the stream, which is safe to drop at any time
+///
+/// The item is taken from the buffer by the poll that returns it, never when the consumer
+/// is woken, so dropping the future after its wake-up leaves the item to the other consumers.
+pub(crate) struct First<'a, ItemType> {
+    stream: &'a mut AsyncStream<ItemType>,
+    waker: Option<Waker>,
+}
+
+impl<ItemType> Future for First<'_, ItemType> {
+    type Output = Option<ItemType>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result: Poll<Option<ItemType>> = self.stream.poll_next(cx);
+        self.waker = result.is_pending().then(|| cx.waker().clone());
+        result
+    }
+}
+
+impl<ItemType> Drop for First<'_, ItemType> {
+    fn drop(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            self.stream.unregister(&waker);
+        }
     }
 }
 
@@ -53,25 +628,205 @@ impl<ItemType> AsyncStream<ItemType> {
         self.counts.1.load(Ordering::Acquire)
     }
 
-    pub(crate) fn decrement_task_count(&self) {
-        if self.task_count() > 0 {
-            self.counts.1.fetch_sub(1, Ordering::Acquire);
+    pub(crate) fn decrement_task_count(&self, wave: usize) {
+        if self.is_current(wave) {
+            saturating_decrement(&self.counts.1);
+            self.wake_spawners();
         }
     }
 
+    /// Wakes the given spawner once a child task finishes
+    pub(crate) fn register_spawner(&self, waker: &Waker) {
+        let mut spawners = self.spawners.lock();
+        if !spawners
+            .iter()
+            .any(|registered| registered.will_wake(waker))
+        {
+            spawners.push(waker.clone());
+        }
+    }
+
+    /// Wakes every waiting spawner, each of them checks for room again once it polls
+    pub(crate) fn wake_spawners(&self) {
+        let spawners: Vec<Waker> = std::mem::take(&mut *self.spawners.lock());
+        spawners.into_iter().for_each(Waker::wake);
+    }
+
+    /// The number of items still to be consumed, counting the ones the running child tasks are yet to produce
     pub(crate) fn item_count(&self) -> usize {
         self.counts.0.load(Ordering::Acquire)
     }
 
-    pub(crate) fn decrement_count(&self) {
-        if self.item_count() > 0 {
-            self.counts.0.fetch_sub(1, Ordering::Acquire);
+    /// The number of items still to be consumed, the same as ``item_count`` until the stream is cancelled, from then
+    /// on only the buffered ones since the cancelled child tasks won't produce theirs
+    pub(crate) fn unconsumed_count(&self) -> usize {
+        if self.is_cancelled() {
+            return self.buffered();
+        }
+        self.item_count()
+    }
+
+    /// Whether every spawned child task has delivered or discarded its item and every buffered item was consumed
+    ///
+    /// An empty buffer alone never ends the stream, the items of the running child tasks are still counted, and
+    /// neither does a spawner holding it open.
+    fn is_exhausted(&self) -> bool {
+        self.item_count() == 0 && self.holds.load(Ordering::Acquire) == 0
+    }
+
+    /// Keeps the stream from ending until the returned guard is dropped, for a spawner that may still spawn child
+    /// tasks while the stream is consumed
+    pub(crate) fn hold_open(&self) -> HoldOpen<ItemType> {
+        self.holds.fetch_add(1, Ordering::AcqRel);
+        HoldOpen(self.clone())
+    }
+
+    pub(crate) fn decrement_count(&self, wave: usize) {
+        if !self.is_current(wave) {
+            return;
+        }
+        self.consume_count();
+        // a child task that won't produce an item may have been the last one the consumer waits for
+        self.wake();
+    }
+
+    fn consume_count(&self) {
+        saturating_decrement(&self.counts.0);
+    }
+
+    /// Takes the next item out of the locked buffer, the only way an item is ever consumed
+    ///
+    /// Every consumer, whether it polls the stream or awaits ``first()``, on any clone of the stream,
+    /// goes through here under the buffer lock, so each item is delivered to exactly one consumer and
+    /// the item count is decremented exactly once for it.
+    ///
+    /// An item consumed any other way than through ``take_unacked`` counts as acknowledged right away.
+    fn take_item(&self, buffer: &mut Buffer<ItemType>) -> Option<Tagged<ItemType>> {
+        let mut item: Tagged<ItemType> = self.take_unacked(buffer)?;
+        self.acknowledge(&mut item.0);
+        Some(item)
+    }
+
+    /// Takes the next item out of the locked buffer, leaving it to the consumer to acknowledge it
+    fn take_unacked(&self, buffer: &mut Buffer<ItemType>) -> Option<Tagged<ItemType>> {
+        let item: Tagged<ItemType> = buffer.pop_item()?;
+        buffer.metrics.record_consumed();
+        self.consume_count();
+        Some(item)
+    }
+
+    /// Takes the newest item out of the locked buffer rather than the oldest, consumed as the stream would
+    fn take_back_item(&self, buffer: &mut Buffer<ItemType>) -> Option<Tagged<ItemType>> {
+        let mut item: Tagged<ItemType> = buffer.pop_back_item()?;
+        buffer.metrics.record_consumed();
+        self.consume_count();
+        self.acknowledge(&mut item.0);
+        Some(item)
+    }
+
+    /// Takes the next entry out of the locked buffer, whether it's an item or a marker, for a marked consumer
+    fn take_entry(&self, buffer: &mut Buffer<ItemType>) -> Option<Entry<ItemType>> {
+        let mut entry: Entry<ItemType> = buffer.pop_entry()?;
+        if let Entry::Item((tag, _)) = &mut entry {
+            buffer.metrics.record_consumed();
+            self.consume_count();
+            self.acknowledge(tag);
+        }
+        Some(entry)
+    }
+
+    /// Frees the ack slot the item kept, so a consumer handing the item back doesn't free it again
+    fn acknowledge(&self, tag: &mut Tag) {
+        if let Some(epoch) = tag.ack.take() {
+            self.acks.release(epoch);
         }
     }
 
+    /// The ack gate of the spawn group, which the child tasks pass before they start
+    pub(crate) fn acks(&self) -> &AckGate {
+        &self.acks
+    }
+
+    /// Places a marker after the items buffered so far, which leaves every count untouched
+    pub(crate) fn insert_marker(&self, marker: u64) {
+        self.buffer.lock().push_marker(marker);
+        // a marked consumer may be waiting for it
+        self.wake();
+    }
+
+    pub(crate) fn set_helper(&mut self, helper: Option<Helper>) {
+        self.helper = helper;
+    }
+
+    pub(crate) fn wave(&self) -> usize {
+        self.wave.load(Ordering::Acquire)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    fn is_current(&self, wave: usize) -> bool {
+        self.wave() == wave
+    }
+
+    /// Starts a new wave, making the stream usable again
+    ///
+    /// # Returns
+    /// The items that were buffered, which will never be consumed, handed back rather than dropped
+    /// so they're dropped once the buffer is released
+    pub(crate) fn reset(&mut self) -> VecDeque<ItemType> {
+        let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        self.wave.fetch_add(1, Ordering::AcqRel);
+        let discarded: VecDeque<ItemType> = inner_lock.take_items();
+        self.counts.0.store(0, Ordering::Release);
+        self.counts.1.store(0, Ordering::Release);
+        self.cancelled.store(false, Ordering::Release);
+        drop(inner_lock);
+        self.wake();
+        self.wake_spawners();
+        self.wake_producers();
+        discarded
+    }
+
+    /// Takes every buffered item out of the stream at once
+    pub(crate) fn take_all(&self) -> VecDeque<ItemType> {
+        let items: VecDeque<ItemType> = self.buffer.lock().take_items();
+        self.wake_producers();
+        items
+    }
+
+    /// Takes the next item if one is buffered, consumed as the stream would, without ever waiting for one
+    pub(crate) fn try_take(&self) -> Option<ItemType> {
+        let item: Option<Tagged<ItemType>> = self.take_item(&mut self.buffer.lock());
+        if item.is_some() {
+            // the item taken makes room for a child task waiting to insert its own
+            self.wake_producers();
+        }
+        item.map(|(_, value)| value)
+    }
+
+    /// Takes every buffered item under a single lock of the buffer, each consumed as the stream would
+    pub(crate) fn take_ready(&self) -> Vec<ItemType> {
+        let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        let items: Vec<ItemType> = iter::from_fn(|| self.take_item(&mut inner_lock))
+            .map(|(_, value)| value)
+            .collect();
+        drop(inner_lock);
+        if !items.is_empty() {
+            self.wake_producers();
+        }
+        items
+    }
+
+    /// Ends the stream once its buffered items are consumed, waking every waiting consumer
+    /// so none of them is left pending after the spawn group was cancelled or torn down
     pub(crate) fn cancel_tasks(&mut self) {
-        self.cancelled = true;
+        self.cancelled.store(true, Ordering::Release);
         self.counts.1.store(0, Ordering::Release);
+        self.wake();
+        self.wake_spawners();
+        self.wake_producers();
     }
 }
 
@@ -81,19 +836,133 @@ impl<ItemType> Clone for AsyncStream<ItemType> {
             buffer: self.buffer.clone(),
             started: self.started,
             counts: self.counts.clone(),
-            cancelled: self.cancelled,
+            cancelled: self.cancelled.clone(),
+            helper: self.helper.clone(),
+            waiters: self.waiters.clone(),
+            wake_after: self.wake_after.clone(),
+            wave: self.wave.clone(),
+            holds: self.holds.clone(),
+            spawners: self.spawners.clone(),
+            producers: self.producers.clone(),
+            acks: self.acks.clone(),
         }
     }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) fn new() -> Self {
+    /// Creates a stream whose buffer has room for the given number of items before it reallocates
+    pub(crate) fn new(metrics: Metrics, capacity: usize) -> Self {
         AsyncStream::<ItemType> {
-            buffer: Arc::new(Mutex::new(VecDeque::new())),
+            buffer: Arc::new(Mutex::new(Buffer {
+                entries: VecDeque::with_capacity(capacity),
+                arrivals: VecDeque::with_capacity(capacity),
+                sizes: VecDeque::with_capacity(capacity),
+                bytes: 0,
+                size_of: size_of_type::<ItemType>,
+                markers: 0,
+                next_arrival: 0,
+                capacity: None,
+                metrics,
+            })),
             started: false,
             counts: (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0))),
-            cancelled: false,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            helper: None,
+            waiters: Arc::new(WakerSlot::new(Waiters::default())),
+            wake_after: Arc::new(AtomicUsize::new(1)),
+            wave: Arc::new(AtomicUsize::new(0)),
+            holds: Arc::new(AtomicUsize::new(0)),
+            spawners: Arc::new(WakerSlot::new(Vec::new())),
+            producers: Arc::new(WakerSlot::new(Vec::new())),
+            acks: AckGate::default(),
+        }
+    }
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    /// Waits for the next item like the stream does, along with the index its child task was spawned with
+    async fn next_tagged(&self) -> Option<Tagged<ItemType>> {
+        poll_fn(|cx| self.poll_tagged(cx)).await
+    }
+
+    /// Consumes every remaining item and returns them in the order their child tasks were spawned
+    ///
+    /// Items handed back by a consumer have no spawn index, so they come first in the order they were handed back.
+    pub(crate) async fn collect_ordered(&self) -> Vec<ItemType> {
+        let mut items: Vec<Tagged<ItemType>> = vec![];
+        while let Some(item) = self.next_tagged().await {
+            items.push(item);
+        }
+        items.sort_by_key(|(tag, _)| tag.index);
+        items.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Takes the next item, or registers the waker to be woken once there's one, under a single lock of the buffer
+    ///
+    /// A marked consumer takes the markers along with the items, including the ones left once the stream has ended.
+    fn take_or_register<T>(
+        &self,
+        waker: &Waker,
+        take: impl FnOnce(&Self, &mut Buffer<ItemType>) -> Option<T>,
+    ) -> Poll<Option<T>> {
+        let mut inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        if let Some(taken) = take(self, &mut inner_lock) {
+            drop(inner_lock);
+            // the item taken makes room for a child task waiting to insert its own
+            self.wake_producers();
+            return Poll::Ready(Some(taken));
+        }
+        if self.is_cancelled() || self.is_exhausted() {
+            return Poll::Ready(None);
+        }
+        // registered while the buffer is still locked, so the next inserted item can't go unnoticed
+        self.register(waker);
+        // a child task may have discarded its item, or the stream may have been ended, after the check above
+        if self.is_cancelled() || self.is_exhausted() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    }
+
+    pub(crate) fn poll_tagged(&self, cx: &mut Context<'_>) -> Poll<Option<Tagged<ItemType>>> {
+        self.poll_with(cx, Self::take_item)
+    }
+
+    /// Polls for the next item along with the epoch of the ack slot it kept, which the consumer frees once it
+    /// acknowledged the item
+    pub(crate) fn poll_unacked(&self, cx: &mut Context<'_>) -> Poll<Option<Tagged<ItemType>>> {
+        self.poll_with(cx, Self::take_unacked)
+    }
+
+    /// Polls for the newest item rather than the oldest, which ends along with the stream
+    pub(crate) fn poll_back(&self, cx: &mut Context<'_>) -> Poll<Option<ItemType>> {
+        self.poll_with(cx, Self::take_back_item)
+            .map(|item| item.map(|(_, value)| value))
+    }
+
+    /// Polls for the next entry, whether it's an item or a marker
+    pub(crate) fn poll_entry(&self, cx: &mut Context<'_>) -> Poll<Option<Entry<ItemType>>> {
+        self.poll_with(cx, Self::take_entry)
+    }
+
+    fn poll_with<T>(
+        &self,
+        cx: &mut Context<'_>,
+        take: impl FnOnce(&Self, &mut Buffer<ItemType>) -> Option<T>,
+    ) -> Poll<Option<T>> {
+        let helper: Option<Helper> = self.helper.clone();
+        let waker: &Waker = cx.waker();
+        let result: Poll<Option<T>> = self.take_or_register(waker, take);
+        // Helps the threadpool make progress by running at most one queued job
+        // per poll, so the consumer never turns into a permanent worker, and polls
+        // again once it ran one since it may be the only thread driving the threadpool,
+        // otherwise it's woken along with the other consumers once a job is queued
+        if let (Poll::Pending, Some(helper)) = (&result, helper) {
+            if helper() {
+                waker.wake_by_ref();
+            }
         }
+        result
     }
 }
 
@@ -101,17 +970,34 @@ impl<ItemType> Stream for AsyncStream<ItemType> {
     type Item = ItemType;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        block_on(async move {
-            let mut inner_lock: MutexGuard<'_, VecDeque<ItemType>> = self.buffer.lock().await;
-            if self.cancelled && inner_lock.is_empty() || self.item_count() == 0 {
-                return Poll::Ready(None);
-            }
-            let Some(value) = inner_lock.pop_front() else {
-                cx.waker().wake_by_ref();
-                return Poll::Pending;
-            };
-            self.decrement_count();
-            Poll::Ready(Some(value))
-        })
+        self.poll_tagged(cx)
+            .map(|item| item.map(|(_, value)| value))
+    }
+
+    /// At least the buffered items, and at most those along with the ones the pending child tasks are yet to produce
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let inner_lock: MutexGuard<'_, Buffer<ItemType>> = self.buffer.lock();
+        // a cancellation zeroes the pending child tasks, which won't produce their items anymore
+        let pending: usize = self.task_count();
+        (inner_lock.len(), Some(inner_lock.len() + pending))
     }
 }
+
+/// Keeps the stream from ending while it's alive, the consumers waiting for the stream to end are woken once it's
+/// dropped
+pub(crate) struct HoldOpen<ItemType>(AsyncStream<ItemType>);
+
+impl<ItemType> Drop for HoldOpen<ItemType> {
+    fn drop(&mut self) {
+        saturating_decrement(&self.0.holds);
+        self.0.wake();
+    }
+}
+
+/// Decrements the counter unless it's already zero, as a single atomic operation so concurrent
+/// decrements never wrap it around
+fn saturating_decrement(counter: &AtomicUsize) {
+    _ = counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+        count.checked_sub(1)
+    });
+}
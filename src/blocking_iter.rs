@@ -0,0 +1,220 @@
+use crate::{async_stream::AsyncStream, executors::block_on, spawn_group::SpawnGroup};
+use futures_lite::{future, StreamExt};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// Blocking Iter
+///
+/// An iterator blocking the current thread until the next result of a spawn group is available, as returned by
+/// [`SpawnGroup::blocking_iter`] and [`SpawnGroup::blocking_iter_with_prefetch`]. It ends like the stream of the
+/// spawn group does.
+///
+/// Without prefetching, each call to ``next`` blocks on the stream of the spawn group. With prefetching, a drain thread
+/// takes up to the given number of results off the stream ahead of the consumer, so ``next`` usually returns a result
+/// without parking when the child tasks produce many small results.
+///
+/// # Dropping
+/// Dropping the iterator stops its drain thread and hands the results it had prefetched back to the front of the spawn
+/// group's buffer in their original order, so stopping the iteration early never loses a result.
+pub struct BlockingIter<'a, ValueType: Send + 'static> {
+    source: Source<ValueType>,
+    group: PhantomData<&'a mut SpawnGroup<ValueType>>,
+}
+
+enum Source<ValueType> {
+    Direct(AsyncStream<ValueType>),
+    Prefetched {
+        stream: AsyncStream<ValueType>,
+        // the wave of the stream the items were prefetched in
+        wave: usize,
+        prefetch: Arc<Prefetch<ValueType>>,
+        drain: Option<thread::JoinHandle<Option<ValueType>>>,
+    },
+}
+
+struct Prefetched<ValueType> {
+    items: VecDeque<ValueType>,
+    // the stream ended, so no more items are coming
+    done: bool,
+}
+
+struct Prefetch<ValueType> {
+    capacity: usize,
+    state: Mutex<Prefetched<ValueType>>,
+    // signalled once an item is prefetched or the stream ended
+    ready: Condvar,
+    // signalled once an item is consumed or the drain thread must stop
+    room: Condvar,
+    stopped: AtomicBool,
+    stop_waker: Mutex<Option<Waker>>,
+}
+
+impl<ValueType: Send + 'static> BlockingIter<'_, ValueType> {
+    pub(crate) fn new(stream: AsyncStream<ValueType>, prefetch: usize) -> Self {
+        let source: Source<ValueType> = if prefetch == 0 {
+            Source::Direct(stream)
+        } else {
+            let prefetch: Arc<Prefetch<ValueType>> = Arc::new(Prefetch {
+                capacity: prefetch,
+                state: Mutex::new(Prefetched {
+                    items: VecDeque::with_capacity(prefetch),
+                    done: false,
+                }),
+                ready: Condvar::new(),
+                room: Condvar::new(),
+                stopped: AtomicBool::new(false),
+                stop_waker: Mutex::new(None),
+            });
+            let drain: thread::JoinHandle<Option<ValueType>> = thread::Builder::new()
+                .name("BlockingIter prefetch".to_string())
+                .spawn({
+                    let (stream, prefetch) = (stream.clone(), prefetch.clone());
+                    move || drain(stream, prefetch)
+                })
+                .unwrap();
+            Source::Prefetched {
+                wave: stream.wave(),
+                stream,
+                prefetch,
+                drain: Some(drain),
+            }
+        };
+        Self {
+            source,
+            group: PhantomData,
+        }
+    }
+}
+
+/// Keeps prefetching items off the stream until it ends or the iterator is dropped
+///
+/// # Returns
+/// - Some: an item taken off the stream after the iterator was dropped
+/// - None: if no item was left over
+fn drain<ValueType>(
+    mut stream: AsyncStream<ValueType>,
+    prefetch: Arc<Prefetch<ValueType>>,
+) -> Option<ValueType> {
+    loop {
+        {
+            let mut state = prefetch.state.lock();
+            while state.items.len() >= prefetch.capacity && !prefetch.is_stopped() {
+                prefetch.room.wait(&mut state);
+            }
+            if prefetch.is_stopped() {
+                return None;
+            }
+        }
+        // the future of the first item is cancel-safe, so losing the race never takes an item
+        let fetched: Option<Option<ValueType>> =
+            block_on(future::or(async { Some(stream.first().await) }, async {
+                Stopped {
+                    prefetch: &prefetch,
+                }
+                .await;
+                None
+            }));
+        let mut state = prefetch.state.lock();
+        match fetched {
+            None => return None,
+            Some(None) => {
+                state.done = true;
+                prefetch.ready.notify_all();
+                return None;
+            }
+            // checked under the lock, so the iterator either takes the item back or sees it prefetched
+            Some(Some(value)) if prefetch.is_stopped() => return Some(value),
+            Some(Some(value)) => {
+                state.items.push_back(value);
+                prefetch.ready.notify_one();
+            }
+        }
+    }
+}
+
+impl<ValueType> Prefetch<ValueType> {
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+}
+
+/// Future that completes once the iterator is dropped
+struct Stopped<'a, ValueType> {
+    prefetch: &'a Prefetch<ValueType>,
+}
+
+impl<ValueType> Future for Stopped<'_, ValueType> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // registered before checking, so the wake-up of the iterator can't go unnoticed
+        *self.prefetch.stop_waker.lock() = Some(cx.waker().clone());
+        if self.prefetch.is_stopped() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl<ValueType: Send + 'static> Iterator for BlockingIter<'_, ValueType> {
+    type Item = ValueType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            Source::Direct(stream) => block_on(stream.next()),
+            Source::Prefetched { prefetch, .. } => {
+                let mut state = prefetch.state.lock();
+                loop {
+                    if let Some(value) = state.items.pop_front() {
+                        prefetch.room.notify_one();
+                        return Some(value);
+                    }
+                    if state.done {
+                        return None;
+                    }
+                    prefetch.ready.wait(&mut state);
+                }
+            }
+        }
+    }
+}
+
+impl<ValueType: Send + 'static> Drop for BlockingIter<'_, ValueType> {
+    fn drop(&mut self) {
+        let Source::Prefetched {
+            stream,
+            wave,
+            prefetch,
+            drain,
+        } = &mut self.source
+        else {
+            return;
+        };
+        let items: VecDeque<ValueType> = {
+            let mut state = prefetch.state.lock();
+            prefetch.stopped.store(true, Ordering::Release);
+            prefetch.room.notify_all();
+            std::mem::take(&mut state.items)
+        };
+        if let Some(waker) = prefetch.stop_waker.lock().take() {
+            waker.wake();
+        }
+        let leftover: Option<ValueType> =
+            drain.take().and_then(|drain| drain.join().ok().flatten());
+        // each one is put back at the front, so the newest goes first to keep the original order
+        for value in items.into_iter().chain(leftover).rev() {
+            stream.requeue_item(*wave, value);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{executors::block_on, spawn_group::SpawnGroup};
+use futures_lite::{Stream, StreamExt};
+
+/// Blocking Results
+///
+/// An iterator owning a spawn group and blocking the current thread until its next result is available, as returned
+/// by [`SpawnGroup::into_blocking_iter`]. It ends like the stream of the spawn group does, once no child task is left
+/// to produce a result.
+///
+/// It lets a mostly synchronous program consume the results of a spawn group without an async context of its own.
+///
+/// # Dropping
+/// Dropping the iterator drops the spawn group along with it, so the child tasks still pending are waited for,
+/// cancelled or detached as its [`GroupDropPolicy`](crate::GroupDropPolicy) says.
+///
+/// # Panics
+/// Waiting for a result on one of the worker threads of the spawn group would keep that worker thread from ever
+/// polling the child task producing it, so ``next`` panics when called from one of them rather than deadlocking.
+pub struct BlockingResults<ValueType: Send + 'static> {
+    group: SpawnGroup<ValueType>,
+}
+
+impl<ValueType: Send + 'static> BlockingResults<ValueType> {
+    pub(crate) fn new(group: SpawnGroup<ValueType>) -> Self {
+        Self { group }
+    }
+}
+
+impl<ValueType: Send + 'static> Iterator for BlockingResults<ValueType> {
+    type Item = ValueType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.group.is_worker_thread() {
+            panic!("BlockingResults can't wait for a result on one of the worker threads of its own spawn group")
+        }
+        block_on(self.group.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.group.size_hint()
+    }
+}
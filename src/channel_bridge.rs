@@ -0,0 +1,62 @@
+use crate::{
+    async_stream::HoldOpen,
+    executors::block_on,
+    shared::{cancel_token::CancelToken, priority::Priority, runtime::RuntimeEngine},
+};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long the bridge waits for a message before checking again whether the spawn group was cancelled or closed
+const RECV_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Starts the thread spawning a child task for every message received, until the channel is closed or the spawn group
+/// refuses a child task, was cancelled or closed
+///
+/// # Returns
+/// The bridge thread, which returns the number of child tasks it spawned
+pub(crate) fn bridge<ValueType, Msg, Spawner, F>(
+    runtime: RuntimeEngine<ValueType>,
+    count: Arc<AtomicUsize>,
+    receiver: Receiver<Msg>,
+    priority: Priority,
+    mut spawner: Spawner,
+) -> thread::JoinHandle<usize>
+where
+    ValueType: Send + 'static,
+    Msg: Send + 'static,
+    Spawner: FnMut(Msg) -> F + Send + 'static,
+    F: Future<Output = ValueType> + Send + 'static,
+{
+    let token: CancelToken = runtime.cancel_token();
+    // held from now on, so the stream can't end in between the call and the bridge thread starting
+    let open: HoldOpen<ValueType> = runtime.stream().hold_open();
+    thread::Builder::new()
+        .name("SpawnGroup channel bridge".to_string())
+        .spawn(move || {
+            let _open: HoldOpen<ValueType> = open;
+            let mut spawned: usize = 0;
+            while !token.is_cancelled() && !runtime.is_closed() {
+                let msg: Msg = match receiver.recv_timeout(RECV_INTERVAL) {
+                    Ok(msg) => msg,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                // pauses while the pending child tasks fill the queue capacity of the spawn group
+                if block_on(runtime.write_task_when_ready(priority, spawner(msg))).is_err() {
+                    break;
+                }
+                count.fetch_add(1, Ordering::Acquire);
+                spawned += 1;
+            }
+            spawned
+        })
+        .unwrap()
+}
@@ -0,0 +1,105 @@
+use crate::spawn_group::SpawnGroup;
+use futures_lite::Stream;
+use std::{
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Checked Spawn Group
+///
+/// A spawn group handed to the body of [`with_checked_spawn_group`](crate::with_checked_spawn_group), which must end
+/// by calling [`finish`](Self::finish).
+///
+/// It dereferences into the [`SpawnGroup`] it wraps, so child tasks are spawned, awaited and cancelled just as with
+/// the spawn group itself. The only way to obtain the [`Finished`] token the body has to return is to consume the
+/// checked spawn group with ``finish``, so a body that forgets to wait for its child tasks doesn't compile rather than
+/// relying on the spawn group being dropped.
+pub struct CheckedSpawnGroup<ValueType: Send + 'static> {
+    group: SpawnGroup<ValueType>,
+}
+
+impl<ValueType: Send> CheckedSpawnGroup<ValueType> {
+    pub(crate) fn new(group: SpawnGroup<ValueType>) -> Self {
+        Self { group }
+    }
+
+    /// Waits for every child task to finish and returns their results, consuming the spawn group
+    ///
+    /// See [`SpawnGroup::finish`] for more.
+    ///
+    /// # Returns
+    /// The proof the spawn group was finished, holding the results in the order their child tasks finished
+    pub async fn finish(self) -> Finished<Vec<ValueType>> {
+        Finished {
+            value: self.group.finish().await,
+        }
+    }
+
+    /// Waits for every child task to finish and drops their results, consuming the spawn group
+    ///
+    /// See [`SpawnGroup::finish_discarding`] for more.
+    ///
+    /// # Returns
+    /// The proof the spawn group was finished
+    pub async fn finish_discarding(self) -> Finished<()> {
+        self.group.finish_discarding().await;
+        Finished { value: () }
+    }
+}
+
+impl<ValueType: Send> Deref for CheckedSpawnGroup<ValueType> {
+    type Target = SpawnGroup<ValueType>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.group
+    }
+}
+
+impl<ValueType: Send> DerefMut for CheckedSpawnGroup<ValueType> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.group
+    }
+}
+
+impl<ValueType: Send> Stream for CheckedSpawnGroup<ValueType> {
+    type Item = ValueType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().group).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.group.size_hint()
+    }
+}
+
+/// Finished
+///
+/// The proof a [`CheckedSpawnGroup`] was finished, as returned by its [`finish`](CheckedSpawnGroup::finish) methods,
+/// carrying a value along.
+///
+/// It can't be built any other way. The value is what [`with_checked_spawn_group`](crate::with_checked_spawn_group)
+/// returns, and [`map`](Self::map) turns the results of the child tasks into whatever the body means to return.
+#[derive(Debug)]
+#[must_use = "the body of a checked spawn group has to return the proof it was finished"]
+pub struct Finished<ValueType> {
+    value: ValueType,
+}
+
+impl<ValueType> Finished<ValueType> {
+    /// Maps the value carried by the proof
+    pub fn map<MappedType>(
+        self,
+        mapper: impl FnOnce(ValueType) -> MappedType,
+    ) -> Finished<MappedType> {
+        Finished {
+            value: mapper(self.value),
+        }
+    }
+
+    /// Returns the value carried by the proof
+    pub fn into_inner(self) -> ValueType {
+        self.value
+    }
+}
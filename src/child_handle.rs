@@ -0,0 +1,194 @@
+use crate::async_stream::{AsyncStream, Tag};
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+};
+
+struct SlotState<T> {
+    // the result along with its arrival in the stream of the spawn group, where it goes if the handle is dropped
+    output: Option<(T, u64)>,
+    finished: bool,
+    // the handle was dropped, so the result goes to the stream of the spawn group instead
+    detached: bool,
+    handle_waker: Option<Waker>,
+    task_waker: Option<Waker>,
+}
+
+/// Where a child task spawned with a handle leaves its result for the handle
+pub(crate) struct ChildSlot<T> {
+    state: Mutex<SlotState<T>>,
+    aborted: AtomicBool,
+}
+
+impl<T> ChildSlot<T> {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(SlotState {
+                output: None,
+                finished: false,
+                detached: false,
+                handle_waker: None,
+                task_waker: None,
+            }),
+            aborted: AtomicBool::new(false),
+        })
+    }
+
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Acquire)
+    }
+
+    /// Keeps the waker of the child task so aborting it wakes it up to stop
+    pub(crate) fn register_task(&self, waker: &Waker) {
+        let mut state = self.state.lock();
+        if !state
+            .task_waker
+            .as_ref()
+            .is_some_and(|registered| registered.will_wake(waker))
+        {
+            state.task_waker = Some(waker.clone());
+        }
+    }
+
+    /// Hands the result of the child task to its handle, along with the arrival it would have had in the stream of
+    /// the spawn group
+    ///
+    /// # Returns
+    /// The result back if the handle was dropped, so it goes to the stream of the spawn group instead
+    pub(crate) fn deliver(&self, value: T, arrival: u64) -> Option<T> {
+        let waker: Option<Waker> = {
+            let mut state = self.state.lock();
+            // an aborted child task that was being polled drops the result it produced anyway
+            if state.finished {
+                return None;
+            }
+            if state.detached {
+                return Some(value);
+            }
+            state.finished = true;
+            state.output = Some((value, arrival));
+            state.task_waker = None;
+            state.handle_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        None
+    }
+
+    /// Finishes the handle without a result, does nothing if it has already finished
+    pub(crate) fn finish(&self) {
+        let waker: Option<Waker> = {
+            let mut state = self.state.lock();
+            if state.finished {
+                return;
+            }
+            state.finished = true;
+            state.task_waker = None;
+            state.handle_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Finishes the handle without a result whenever the child task is dropped before delivering one
+pub(crate) struct SlotCompletion<T>(pub(crate) Arc<ChildSlot<T>>);
+
+impl<T> Drop for SlotCompletion<T> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+/// Child Handle
+///
+/// A handle to the result of a single child task spawned with
+/// [`SpawnGroup::spawn_task_with_handle`](crate::SpawnGroup::spawn_task_with_handle).
+///
+/// Awaiting it returns ``Some`` with the result of the child task, which then never goes into the stream of the spawn
+/// group, or ``None`` if the child task was aborted, cancelled along with its spawn group, panicked or refused by the
+/// spawn group.
+///
+/// Dropping a ``ChildHandle`` before taking the result hands the result to the stream of the spawn group like any
+/// other child task, whether the child task has finished yet or not. A result that was waiting in the handle takes the
+/// place it would have had in the stream had it gone there when its child task finished, ahead of the results that
+/// arrived after it. A result handed over once the stream of the spawn group has ended is buffered for its next
+/// consumer.
+pub struct ChildHandle<ValueType> {
+    slot: Arc<ChildSlot<ValueType>>,
+    // the stream of the spawn group, the wave and the tag the child task was spawned with
+    stream: Option<(AsyncStream<ValueType>, usize, Tag)>,
+}
+
+impl<ValueType> ChildHandle<ValueType> {
+    pub(crate) fn new(
+        slot: Arc<ChildSlot<ValueType>>,
+        stream: AsyncStream<ValueType>,
+        wave: usize,
+        tag: Tag,
+    ) -> Self {
+        Self {
+            slot,
+            stream: Some((stream, wave, tag)),
+        }
+    }
+
+    /// Returns a ``ChildHandle`` that has already finished without a result
+    pub(crate) fn refused() -> Self {
+        let slot: Arc<ChildSlot<ValueType>> = ChildSlot::new();
+        slot.finish();
+        Self { slot, stream: None }
+    }
+
+    /// Aborts the child task, which stops the next time it would be polled and never produces a result
+    ///
+    /// Awaiting the ``ChildHandle`` after aborting returns ``None`` unless the child task had already finished.
+    pub fn abort(&self) {
+        self.slot.aborted.store(true, Ordering::Release);
+        // woken up so it stops right away instead of whenever it would have been woken
+        let waker: Option<Waker> = self.slot.state.lock().task_waker.take();
+        self.slot.finish();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// A Boolean value that indicates whether the child task has finished, was aborted or cancelled
+    pub fn is_finished(&self) -> bool {
+        self.slot.state.lock().finished
+    }
+}
+
+impl<ValueType> Future for ChildHandle<ValueType> {
+    type Output = Option<ValueType>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock();
+        if state.finished {
+            return Poll::Ready(state.output.take().map(|(value, _)| value));
+        }
+        state.handle_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<ValueType> Drop for ChildHandle<ValueType> {
+    fn drop(&mut self) {
+        let output: Option<(ValueType, u64)> = {
+            let mut state = self.slot.state.lock();
+            state.detached = true;
+            state.output.take()
+        };
+        if let (Some((value, arrival)), Some((stream, wave, tag))) = (output, self.stream.take()) {
+            stream.restore_item(wave, tag, value, arrival);
+        }
+    }
+}
@@ -1,8 +1,27 @@
+use crate::async_runtime::lane::Lane;
+use crate::dyn_spawn_group::BoxFuture;
+use crate::join_handle::{finished, JoinHandle};
+use crate::shared::workload_hint::{recommended_threads, WorkloadHint};
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
+    adaptive::AdaptiveConfig, cancel_token::CancelToken, completion_counts::CompletionCounts,
+    drop_policy::GroupDropPolicy, initializible::Initializible, latency::QueueLatency,
+    metrics::Metrics, panic_isolation::PanicIsolation, panic_output::PanicOutput,
+    priority::Priority, priority_policy::PriorityPolicy, runtime::RuntimeEngine,
+    sharedfuncs::Shared, snapshot::GroupSnapshot, spawn_error::SpawnError, task_cap::TaskCapPolicy,
+    wait::Waitable,
 };
+use crate::threadpool_impl::{ExecutionRecord, ThreadSpec};
+use async_trait::async_trait;
 
-use std::future::Future;
+use std::{
+    fmt,
+    future::Future,
+    io,
+    num::NonZeroUsize,
+    sync::{atomic::AtomicBool, Arc},
+    thread,
+    time::Duration,
+};
 
 /// Discarding Spawn Group
 ///
@@ -20,35 +39,376 @@ use std::future::Future;
 ///
 pub struct DiscardingSpawnGroup {
     /// A field that indicates if the spawn group has been cancelled
+    #[deprecated(note = "use `DiscardingSpawnGroup::is_cancelled()` instead")]
     pub is_cancelled: bool,
+    // whether the spawn group was cancelled since it was created or last reset, which the deprecated field mirrors
+    cancelled: bool,
     runtime: RuntimeEngine<()>,
-    wait_at_drop: bool,
+    drop_policy: GroupDropPolicy,
+}
+
+impl DiscardingSpawnGroup {
+    /// Wraps the runtime of a spawn group that wasn't cancelled yet
+    #[allow(deprecated)]
+    fn from_runtime(runtime: RuntimeEngine<()>, drop_policy: GroupDropPolicy) -> Self {
+        Self {
+            is_cancelled: false,
+            cancelled: false,
+            runtime,
+            drop_policy,
+        }
+    }
+
+    /// Records whether the spawn group was cancelled, mirrored into the deprecated public field
+    #[allow(deprecated)]
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+        self.is_cancelled = cancelled;
+    }
 }
 
 impl DiscardingSpawnGroup {
     /// Don't implicity wait for spawned child tasks to finish before being dropped
     pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+    }
+
+    /// Sets what the spawn group does with its remaining child tasks when it's dropped, replacing the policy it was
+    /// created with
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    pub fn set_drop_policy(&mut self, policy: GroupDropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Bounds how long dropping the spawn group with ``GroupDropPolicy::WaitForAll`` waits for its child tasks
+    ///
+    /// Once the cap elapses, the ids of the child tasks still pending are reported to the panic output of the spawn
+    /// group, see [`PanicOutput`](crate::PanicOutput), and they're cancelled as by ``cancel_all``. A child task
+    /// blocking its worker thread keeps running there, detached from the spawn group.
+    ///
+    /// # Parameters
+    ///
+    /// * `cap`: the longest dropping the spawn group waits for its child tasks
+    pub fn drop_wait_cap(&mut self, cap: Duration) {
+        self.runtime.drop_wait_cap(cap);
+    }
+
+    /// Warns about the child tasks blocking their worker thread, whose single poll runs for longer than the threshold
+    /// without yielding
+    ///
+    /// See [`SpawnGroup::warn_on_blocking`](crate::SpawnGroup::warn_on_blocking) for more.
+    pub fn warn_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, false);
+    }
+
+    /// Warns about the child tasks blocking their worker thread, then makes them panic once their blocking poll
+    /// returns
+    ///
+    /// See [`SpawnGroup::panic_on_blocking`](crate::SpawnGroup::panic_on_blocking) for more.
+    pub fn panic_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, true);
+    }
+
+    /// Registers a hook that reports how long each child task waited in the queue
+    /// before a worker thread picked it up, replacing any previously registered hook
+    ///
+    /// The hook runs on the worker thread right before the child task is first polled,
+    /// so it should be cheap.
+    ///
+    /// # Parameters
+    ///
+    /// * `hook`: a closure that receives the ``QueueLatency`` of each child task
+    pub fn on_task_dequeued<Hook>(&mut self, hook: Hook)
+    where
+        Hook: Fn(QueueLatency) + Send + Sync + 'static,
+    {
+        self.runtime.on_task_dequeued(Arc::new(hook));
+    }
+
+    /// Sets where the reports of the child tasks panicking go, for the child tasks that start running from now on
+    ///
+    /// By default the report, including a backtrace, is printed to the standard error, which may garble the screen of
+    /// a terminal application or duplicate the panic reporting of the application. See [`PanicOutput`](crate::PanicOutput)
+    /// for the alternatives.
+    ///
+    /// # Parameters
+    ///
+    /// * `output`: where the reports go
+    pub fn panic_output(&mut self, output: PanicOutput) {
+        self.runtime.panic_output(output);
+    }
+
+    /// Sets what a worker thread does once a child task panicked on it, for the child tasks that panic from now on
+    ///
+    /// By default the worker thread goes on running child tasks. See [`PanicIsolation`](crate::PanicIsolation) for
+    /// restarting it instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `isolation`: what the worker thread does
+    pub fn panic_isolation(&mut self, isolation: PanicIsolation) {
+        self.runtime.panic_isolation(isolation);
+    }
+
+    /// Registers a middleware that wraps every child task spawned from now on
+    ///
+    /// A middleware receives the child task as a boxed future and returns the future run in its place, which lets
+    /// cross-cutting behavior like timeouts, tracing or metrics live in one place instead of at every spawn site.
+    /// Middlewares compose in registration order, the first registered one being the outermost.
+    ///
+    /// A middleware may extend the child task or complete without running it to completion, in which case the child
+    /// task produces no result, as if it was cancelled, and the counters of the spawn group stay correct either way.
+    /// Child tasks are only boxed once a middleware was registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `middleware`: a closure that wraps the future of each child task
+    pub fn task_middleware<Middleware>(&mut self, middleware: Middleware)
+    where
+        Middleware: Fn(BoxFuture<'static, ()>) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.runtime.task_middleware(Arc::new(middleware));
+    }
+
+    /// Sets which priorities the spawn group accepts for the child tasks spawned from now on
+    ///
+    /// See [`PriorityPolicy`](crate::PriorityPolicy) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: the priority policy to enforce
+    pub fn priority_policy(&mut self, policy: PriorityPolicy) {
+        self.runtime.priority_policy(policy);
+    }
+
+    /// Caps the number of child tasks the spawn group spawns over its lifetime, as a guard against runaway spawning
+    ///
+    /// The child tasks spawned before the cap was set count towards it, and so do the ones spawned before a reset.
+    /// Once the cap is reached, every further child task is handled as the policy says, see
+    /// [`TaskCapPolicy`](crate::TaskCapPolicy).
+    ///
+    /// # Parameters
+    ///
+    /// * `max_tasks`: the most child tasks the spawn group spawns
+    /// * `policy`: what happens to the child tasks beyond the cap
+    pub fn set_max_tasks(&mut self, max_tasks: usize, policy: TaskCapPolicy) {
+        self.runtime.set_max_tasks(max_tasks, policy);
+    }
+
+    /// Bounds the number of pending child tasks [`try_spawn_task`](Self::try_spawn_task) lets the spawn group have,
+    /// refusing the child tasks beyond it with ``SpawnError::QueueFull``
+    ///
+    /// Only ``try_spawn_task`` is bounded, every other way of spawning queues its child tasks regardless.
+    ///
+    /// # Parameters
+    ///
+    /// * `capacity`: the most pending child tasks, whether running or waiting for a worker thread
+    pub fn queue_capacity(&mut self, capacity: usize) {
+        self.runtime.queue_capacity(capacity);
+    }
+
+    /// Makes the number of child tasks allowed to run at once adapt to how long they take, between the bounds of the
+    /// given config
+    ///
+    /// A child task waits for room in the window before it starts, and the time it waits counts as queue latency.
+    /// The current width of the window is published as [`Metrics::concurrency_window`](crate::Metrics::concurrency_window).
+    /// See [`AdaptiveConfig`](crate::AdaptiveConfig) for how the window is adjusted.
+    ///
+    /// # Parameters
+    ///
+    /// * `config`: the bounds of the window and the latency the child tasks should finish within
+    ///
+    /// # Panics
+    /// If the minimum of `config` is zero or above its maximum
+    pub fn adaptive_concurrency(&mut self, config: AdaptiveConfig) {
+        self.runtime.adaptive_concurrency(config, None);
+    }
+
+    /// Declares that the spawn group spawns exactly ``size`` child tasks, trading its dynamic features for a lower
+    /// overhead per child task
+    ///
+    /// Room for the whole batch is made upfront, and the child tasks of the batch skip the registry behind
+    /// ``snapshot``, the dequeue hook, the task middlewares, the execution log and the metrics of their latency.
+    /// Their outcome is counted by counting the batch down rather than per child task.
+    ///
+    /// Once declared, spawning beyond the batch is refused with ``SpawnError::BatchComplete``, and spawning with a
+    /// feature the batch skips, such as a child handle, a sequence number or a subset, is refused with
+    /// ``SpawnError::BatchUnsupported``. Calling ``reset`` takes the spawn group out of batch mode.
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: the number of child tasks of the batch
+    ///
+    /// # Panics
+    /// If the spawn group already has child tasks or results, or batch mode was already declared
+    pub fn exclusive_batch(&mut self, size: usize) {
+        self.runtime.exclusive_batch(size);
+    }
+
+    /// Caps the CPU time the worker threads of the spawn group use collectively, measured in cores
+    ///
+    /// A quota of ``0.5`` lets the worker threads use half a core at most. Each worker thread measures how long
+    /// it's busy polling child tasks, and parks whenever the worker threads are over the quota, so the cap is
+    /// approximate and holds over windows of tens of milliseconds. The consumer helping the threadpool isn't capped.
+    ///
+    /// # Parameters
+    ///
+    /// * `cores`: the number of cores the worker threads may use, ``f64::INFINITY`` lifts the cap
+    ///
+    /// # Panics
+    /// If `cores` isn't a positive number
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, DiscardingSpawnGroup, Priority};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut group = DiscardingSpawnGroup::new(2);
+    /// // a background group sharing the machine, using a quarter of a core at most
+    /// group.cpu_quota(0.25);
+    /// let started = Instant::now();
+    /// for _ in 0..20 {
+    ///     group.spawn_task(Priority::BACKGROUND, async {
+    ///         let start = Instant::now();
+    ///         while start.elapsed() < Duration::from_millis(5) {}
+    ///     });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// // 100ms of busy work spread over roughly 400ms
+    /// assert!(started.elapsed() >= Duration::from_millis(250));
+    /// ```
+    pub fn cpu_quota(&mut self, cores: f64) {
+        assert!(
+            cores > 0.0,
+            "the CPU quota must be a positive number of cores"
+        );
+        self.runtime.cpu_quota(cores);
+    }
+
+    /// Registers how each worker thread of the spawn group creates its own state of type ``State``,
+    /// replacing any previously registered initializer of the same type
+    ///
+    /// Child tasks get the state of the worker thread polling them by calling the [`worker_state`](crate::worker_state)
+    /// function. Worker threads that already created their state keep it.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: a closure that creates the state, called at most once per worker thread
+    pub fn worker_state<State, Init>(&mut self, init: Init)
+    where
+        State: 'static,
+        Init: Fn() -> State + Send + Sync + 'static,
+    {
+        self.runtime.worker_state(init);
+    }
+
+    /// Injects the failures described by the fault plan into the underlying threadpool
+    ///
+    /// See [`FaultPlan`](crate::FaultPlan) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `plan`: the failures to inject
+    #[cfg(feature = "fault-injection")]
+    pub fn inject(&mut self, plan: crate::FaultPlan) {
+        self.runtime.inject(plan);
     }
 }
 
 impl DiscardingSpawnGroup {
     /// Instantiates `DiscardingSpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures
-    /// 
+    ///
     /// # Parameters
     ///
     /// * `num_of_threads`: number of threads to use
     pub fn new(num_of_threads: usize) -> Self {
-        Self {
-            is_cancelled: false,
-            runtime: RuntimeEngine::new(num_of_threads),
-            wait_at_drop: false,
-        }
+        Self::from_runtime(
+            RuntimeEngine::new(num_of_threads),
+            GroupDropPolicy::CancelAll,
+        )
+    }
+
+    /// Instantiates `DiscardingSpawnGroup` with as many threads as [`recommended_threads`](crate::recommended_threads) suggests
+    /// for the given workload
+    ///
+    /// See [`SpawnGroup::with_workload`](crate::SpawnGroup::with_workload) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `workload`: the child tasks the spawn group is going to run
+    pub fn with_workload(workload: WorkloadHint) -> Self {
+        Self::new(recommended_threads(workload))
+    }
+
+    /// Instantiates `DiscardingSpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures,
+    /// and what to do with the remaining child tasks when it's dropped
+    ///
+    /// Unlike ``new``, it can't be given zero threads by mistake. A threadpool without worker threads, only driven by
+    /// the consumer helping it, is still created with ``new(0)``.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    pub fn new_with(num_of_threads: NonZeroUsize, policy: GroupDropPolicy) -> Self {
+        let mut group: Self = Self::new(num_of_threads.get());
+        group.drop_policy = policy;
+        group
+    }
+
+    /// Instantiates `DiscardingSpawnGroup` with a specific number of threads, creating every thread it needs through the given thread factory
+    ///
+    /// Use it where threads must be created a particular way, for example to follow a naming convention, enter a security
+    /// context or go through an audit hook. The factory is kept and also creates the thread running the child tasks again
+    /// once the spawn group is used after being cancelled.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `factory`: a closure creating a thread from its [`ThreadSpec`](crate::ThreadSpec)
+    ///
+    /// # Returns
+    /// - Ok: the spawn group
+    /// - Err: the first error returned by the factory, once the threads it had already created are stopped
+    pub fn with_thread_factory<Factory>(num_of_threads: usize, factory: Factory) -> io::Result<Self>
+    where
+        Factory: Fn(ThreadSpec) -> io::Result<thread::JoinHandle<()>> + Send + Sync + 'static,
+    {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_factory(num_of_threads, Arc::new(factory))?,
+            GroupDropPolicy::CancelAll,
+        ))
+    }
+
+    /// Instantiates `DiscardingSpawnGroup` with separate IO and compute lanes, each with its own worker threads
+    ///
+    /// Child tasks start on the IO lane, or on the lane given to ``spawn_task_on``, and move between the lanes by
+    /// awaiting [`move_to`](crate::move_to), so the blocking-heavy and CPU-heavy phases of the child tasks never hold
+    /// up each other. Results, cancellation and waiting are still those of the one spawn group, whichever lane the
+    /// child tasks are on.
+    ///
+    /// # Parameters
+    ///
+    /// * `io_threads`: number of worker threads of the IO lane
+    /// * `compute_threads`: number of worker threads of the compute lane
+    ///
+    /// # Returns
+    /// The spawn group, or the error of the first worker thread that couldn't be spawned
+    pub fn with_lanes(io_threads: NonZeroUsize, compute_threads: NonZeroUsize) -> io::Result<Self> {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_lanes(io_threads, compute_threads)?,
+            GroupDropPolicy::CancelAll,
+        ))
     }
 }
 
 impl DiscardingSpawnGroup {
-    /// Spawns a new task into the spawn group
+    /// Spawns a new task into the spawn group, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
     ///
     /// # Parameters
     ///
@@ -61,6 +421,183 @@ impl DiscardingSpawnGroup {
         self.add_task(priority, closure);
     }
 
+    /// Spawns a new task into the spawn group under a name, does nothing if the spawn group was closed or its
+    /// priority policy refuses the priority
+    ///
+    /// See [`SpawnGroup::spawn_task_named`](crate::SpawnGroup::spawn_task_named) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: name of the child task, which doesn't need to be unique
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    pub fn spawn_task_named<F>(&mut self, name: impl Into<String>, priority: Priority, closure: F)
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        _ = self
+            .runtime
+            .write_named_task(Arc::from(name.into()), priority, closure);
+    }
+
+    /// Spawns a new task into the spawn group, polled on the worker threads of the given lane from the start, does
+    /// nothing if the spawn group was closed or its priority policy refuses the priority
+    ///
+    /// A spawn group without separate lanes polls it like any other child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `lane`: lane to start on
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``()``
+    pub fn spawn_task_on<F>(&mut self, lane: Lane, priority: Priority, closure: F)
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        _ = self.runtime.write_task_on(lane, priority, closure);
+    }
+
+    /// Spawns a new task into the spawn group with its default priority, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
+    ///
+    /// The default priority is ``Priority::default()`` unless changed with
+    /// [`set_default_priority`](Self::set_default_priority).
+    ///
+    /// # Parameters
+    ///
+    /// * `closure`: an async closure that doesn't return anything
+    pub fn spawn<F>(&mut self, closure: F)
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.spawn_task(self.runtime.spawn_priority(), closure);
+    }
+
+    /// Sets the priority of the child tasks spawned with [`spawn`](Self::spawn) from now on
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the priority to use when none is given
+    pub fn set_default_priority(&mut self, priority: Priority) {
+        self.runtime.set_default_priority(priority);
+    }
+
+    /// Spawns every task of the iterator into the spawn group with the same priority, does nothing if the spawn group
+    /// was closed or its priority policy refuses the priority
+    ///
+    /// The child tasks are the same as if each was spawned with [`spawn_task`](Self::spawn_task), but they're counted
+    /// and queued for the worker threads all at once, which is cheaper for many small child tasks.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use for every child task
+    /// * `closures`: the async closures that don't return anything
+    pub fn spawn_all<I, F>(&mut self, priority: Priority, closures: I)
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        _ = self.runtime.write_tasks(priority, closures);
+    }
+
+    /// Spawns a new task into the spawn group unless it was closed or its priority policy refuses the priority
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Closed`` if the spawn group was closed, ``SpawnError::Rejected``
+    ///   if its priority policy refused the priority, ``SpawnError::ThreadUnavailable``
+    ///   if its thread factory failed to create the thread running the child tasks again after a cancellation,
+    ///   or ``SpawnError::QueueFull`` if its pending child tasks fill the capacity set with ``queue_capacity``
+    pub fn try_spawn_task<F>(&mut self, priority: Priority, closure: F) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.write_bounded_task(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group once its pending child tasks no longer fill the capacity set with
+    /// [`queue_capacity`](Self::queue_capacity), suspending the caller until then
+    ///
+    /// The caller is woken as child tasks finish rather than polling in a loop. Without a queue capacity the child
+    /// task is spawned right away.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled while waiting, or any error
+    ///   ``try_spawn_task`` returns other than ``SpawnError::QueueFull``
+    pub async fn spawn_task_when_ready<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.write_task_when_ready(priority, closure).await
+    }
+
+    /// Runs a single future on the spawn group's threadpool and returns a
+    /// [`JoinHandle`](crate::JoinHandle) to await its output
+    ///
+    /// Unlike child tasks spawned with ``spawn_task``, its output doesn't go into the spawn group's
+    /// stream, but it's waited for and cancelled along with the spawn group. Dropping the
+    /// ``JoinHandle`` detaches the future. If the future can't be run, for example because the spawn group was
+    /// closed or is in exclusive batch mode, the returned ``JoinHandle`` finishes immediately without an output, and
+    /// [`try_run`](Self::try_run) tells why.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: the future to run
+    pub fn run<Fut>(&mut self, priority: Priority, future: Fut) -> JoinHandle<Fut::Output>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime
+            .run_task(priority, future)
+            .unwrap_or_else(|_| finished())
+    }
+
+    /// Runs a single future on the spawn group's threadpool as [`run`](Self::run) does, unless it can't be run
+    ///
+    /// See [`SpawnGroup::try_run`](crate::SpawnGroup::try_run) for more.
+    pub fn try_run<Fut>(
+        &mut self,
+        priority: Priority,
+        future: Fut,
+    ) -> Result<JoinHandle<Fut::Output>, SpawnError>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime.run_task(priority, future)
+    }
+
+    /// Spawns a new child task only if the spawn group wasn't cancelled since it was created or last reset
+    ///
+    /// See [`SpawnGroup::spawn_task_unless_cancelled`](crate::SpawnGroup::spawn_task_unless_cancelled) for more.
+    ///
+    /// # Returns
+    /// - true: if the child task was spawned
+    /// - false: if the spawn group was cancelled or closed, in which case the child task is dropped without running
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.add_task_unless_cancelled(priority, closure)
+    }
+
     /// Spawn a new task only if the group is not cancelled yet,
     /// otherwise does nothing
     ///
@@ -68,17 +605,272 @@ impl DiscardingSpawnGroup {
     ///
     /// * `priority`: priority to use
     /// * `closure`: an async closure that return doesn't return anything
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
     pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
     where
         F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
     {
-        self.add_task_unlessed_cancelled(priority, closure);
+        _ = self.spawn_task_unless_cancelled(priority, closure);
     }
 
     /// Cancels all running task in the spawn group
+    ///
+    /// Child tasks which haven't started running never run. A child task that a worker thread
+    /// was already polling finishes that poll, and delivers its result if it completed in it,
+    /// but it is never polled again. This method returns once those polls have finished.
     pub fn cancel_all(&mut self) {
         self.cancel_all_tasks();
     }
+
+    /// A Boolean value that indicates whether the spawn group was cancelled since it was created or last reset
+    // the deprecated field of the same name only mirrors the state
+    #[allow(clippy::misnamed_getters)]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Cancels every child task below the given priority that hasn't started running yet
+    ///
+    /// See [`SpawnGroup::shed_load`](crate::SpawnGroup::shed_load) for more.
+    ///
+    /// # Returns
+    /// The number of child tasks shed
+    pub fn shed_load(&mut self, keep: Priority) -> usize {
+        self.runtime.shed_load(keep)
+    }
+}
+
+impl DiscardingSpawnGroup {
+    /// Closes the spawn group so it no longer accepts new child tasks
+    ///
+    /// Once closed, ``spawn_task`` does nothing and ``try_spawn_task`` returns ``SpawnError::Closed``,
+    /// which makes waiting for all child tasks final. Closing an already closed spawn group does nothing.
+    pub fn close(&self) {
+        self.runtime.close();
+    }
+
+    /// A Boolean value that indicates whether the spawn group was closed
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Cancels every child task and starts a new generation, reopening the spawn group for reuse
+    ///
+    /// Buffered results are discarded, and so is any result of a child task spawned before the reset,
+    /// even one whose poll was already running, so no stale result crosses the generation boundary.
+    /// The spawn group is no longer cancelled nor closed afterwards.
+    ///
+    /// Child tasks still in flight are cancelled rather than waited for, so wait for all of them first to keep their
+    /// results. The worker threads are kept, so the next wave of child tasks runs on the same threadpool.
+    pub fn reset(&mut self) {
+        self.runtime.reset();
+        self.set_cancelled(false);
+    }
+
+    /// The generation of the spawn group, which starts at zero and increases with every ``reset()``
+    pub fn generation(&self) -> usize {
+        self.runtime.generation()
+    }
+
+    pub(crate) fn closed_flag(&self) -> Arc<AtomicBool> {
+        self.runtime.closed_flag()
+    }
+
+    pub(crate) fn cancel_token(&self) -> CancelToken {
+        self.runtime.cancel_token()
+    }
+}
+
+impl DiscardingSpawnGroup {
+    /// Waits for all remaining child tasks for finish.
+    ///
+    /// # Returns
+    /// Returns how the child tasks ended since the spawn group was created or last reset, which is
+    /// counted without keeping anything per child task
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, yield_now, CompletionCounts, DiscardingSpawnGroup, Priority};
+    ///
+    /// let mut group = DiscardingSpawnGroup::new(2);
+    /// for i in 0..10 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         if i == 3 {
+    ///             panic!("child task {i} failed");
+    ///         }
+    ///     });
+    /// }
+    /// let counts = block_on(group.wait_for_all());
+    /// assert_eq!(
+    ///     counts,
+    ///     CompletionCounts {
+    ///         completed: 9,
+    ///         cancelled: 0,
+    ///         panicked: 1
+    ///     }
+    /// );
+    ///
+    /// for _ in 0..5 {
+    ///     group.spawn_task(Priority::default(), async {
+    ///         loop {
+    ///             yield_now().await;
+    ///         }
+    ///     });
+    /// }
+    /// group.cancel_all();
+    /// let counts = block_on(group.wait_for_all());
+    /// assert_eq!(counts.cancelled, 5);
+    ///
+    /// group.reset();
+    /// assert_eq!(block_on(group.wait_for_all()), CompletionCounts::default());
+    /// ```
+    pub async fn wait_for_all(&mut self) -> CompletionCounts {
+        self.wait().await;
+        self.runtime.completion_counts()
+    }
+
+    /// Waits for all remaining child tasks for finish, blocking the current thread, and returns how they ended
+    ///
+    /// See [`SpawnGroup::wait_non_async`](crate::SpawnGroup::wait_non_async) for more.
+    pub fn wait_non_async(&mut self) -> CompletionCounts {
+        self.runtime.wait_for_all_tasks();
+        self.runtime.completion_counts()
+    }
+
+    /// Waits for every child task to finish and returns how they ended, consuming the spawn group
+    ///
+    /// The current task is suspended rather than the current thread blocked, and dropping the spawn group afterwards
+    /// never blocks, whatever its drop policy. See [`SpawnGroup::finish`](crate::SpawnGroup::finish) for more.
+    pub async fn finish(mut self) -> CompletionCounts {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        self.runtime.completion_counts()
+    }
+
+    /// Waits for all remaining child tasks to finish for at most the given duration, leaving the ones still running
+    /// once it elapses
+    ///
+    /// See [`SpawnGroup::wait_timeout`](crate::SpawnGroup::wait_timeout) for more.
+    ///
+    /// # Returns
+    /// Whether every child task finished within the duration
+    pub async fn wait_timeout(&mut self, duration: Duration) -> bool {
+        self.runtime.wait_until_idle_for(duration).await
+    }
+}
+
+impl DiscardingSpawnGroup {
+    /// Captures the state of the spawn group without stopping its child tasks
+    ///
+    /// See [`GroupSnapshot`](crate::GroupSnapshot) for more.
+    pub fn snapshot(&self) -> GroupSnapshot {
+        self.runtime.snapshot(self.cancelled)
+    }
+
+    /// Makes the child tasks spawned from now on leave breadcrumbs of where they suspend
+    ///
+    /// See [`SpawnGroup::trace_suspensions`](crate::SpawnGroup::trace_suspensions) for more.
+    pub fn trace_suspensions(&mut self, enabled: bool) {
+        self.runtime.trace_suspensions(enabled);
+    }
+
+    /// Returns the state of the spawn group as text, with a line for each child task that hasn't completed yet
+    ///
+    /// See [`SpawnGroup::dump_state`](crate::SpawnGroup::dump_state) for more.
+    pub fn dump_state(&self) -> String {
+        self.snapshot().to_string()
+    }
+
+    /// Returns the number of worker threads running the child tasks of the spawn group
+    pub fn num_threads(&self) -> usize {
+        self.runtime.num_threads()
+    }
+
+    /// Returns the number of child tasks that are yet to complete, whether running or waiting for a worker thread
+    pub fn pending_tasks(&self) -> usize {
+        self.runtime.pending_tasks()
+    }
+
+    /// Returns the counters of the child tasks completed by the spawn group
+    ///
+    /// See [`Metrics`](crate::Metrics) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, DiscardingSpawnGroup, Priority};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = DiscardingSpawnGroup::new(2);
+    /// let metrics = group.metrics();
+    /// for _ in 0..10 {
+    ///     group.spawn_task(Priority::default(), async {});
+    /// }
+    /// block_on(group.wait_for_all());
+    ///
+    /// assert_eq!(metrics.completed(), 10);
+    /// assert_eq!(metrics.window(Duration::from_secs(60)).completed, 10);
+    /// ```
+    pub fn metrics(&self) -> Metrics {
+        self.runtime.metrics()
+    }
+
+    /// Enables or disables recording the child tasks executed by each worker thread, disabled by default
+    ///
+    /// While disabled, recording costs a single atomic load per child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether to record the child tasks executed from now on
+    pub fn record_executions(&self, enabled: bool) {
+        self.runtime.record_executions(enabled);
+    }
+
+    /// Returns the last child tasks executed by the spawn group, ordered by the instant they ended
+    ///
+    /// Each worker thread keeps its last 256 child tasks, which answers what ran recently on
+    /// each of them when the spawn group hangs. Nothing is kept unless ``record_executions`` was enabled.
+    ///
+    /// See [`ExecutionRecord`](crate::ExecutionRecord) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, DiscardingSpawnGroup, ExecutionOutcome, Priority};
+    ///
+    /// let mut group = DiscardingSpawnGroup::new(2);
+    /// group.record_executions(true);
+    /// for _ in 0..10 {
+    ///     group.spawn_task(Priority::default(), async {});
+    /// }
+    /// group.spawn_task(Priority::HIGH, async { panic!("wedged") });
+    /// block_on(group.wait_for_all());
+    ///
+    /// let records = group.recent_executions();
+    /// assert_eq!(records.len(), 11);
+    /// assert!(records.iter().all(|record| record.worker.is_some_and(|worker| worker < 2)));
+    /// assert!(records.iter().all(|record| record.started_at <= record.ended_at));
+    /// let panicked = records
+    ///     .iter()
+    ///     .find(|record| record.outcome == ExecutionOutcome::Panicked)
+    ///     .unwrap();
+    /// assert_eq!(panicked.priority, Priority::HIGH);
+    /// ```
+    pub fn recent_executions(&self) -> Vec<ExecutionRecord> {
+        self.runtime.recent_executions()
+    }
+
+    /// Returns the last wakes, suspensions and resumptions of the child tasks of the spawn group, along with the
+    /// last release of each of their wakers
+    ///
+    /// Hangs almost always come down to a lost or misrouted wake, which
+    /// [`WakeTrace::stalled_tasks`](crate::WakeTrace::stalled_tasks) looks for. Only available with the
+    /// `wake-trace` feature, without which nothing is recorded.
+    #[cfg(feature = "wake-trace")]
+    pub fn wake_trace(&self) -> crate::WakeTrace {
+        self.runtime.wake_trace()
+    }
 }
 
 impl DiscardingSpawnGroup {
@@ -98,12 +890,20 @@ impl DiscardingSpawnGroup {
     }
 }
 
+impl fmt::Debug for DiscardingSpawnGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.runtime
+            .fmt_group(f, "DiscardingSpawnGroup", self.cancelled, self.drop_policy)
+    }
+}
+
 impl Drop for DiscardingSpawnGroup {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        match self.drop_policy {
+            GroupDropPolicy::WaitForAll => self.runtime.end_waiting(),
+            // cut off the same way as by cancel_all, without waiting for the polls in flight
+            GroupDropPolicy::CancelAll => self.runtime.end(false),
+            GroupDropPolicy::Detach => self.runtime.detach(),
         }
     }
 }
@@ -115,30 +915,31 @@ impl Shared for DiscardingSpawnGroup {
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        self.runtime.write_task(priority, closure);
+        _ = self.runtime.write_task(priority, closure);
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
-        }
+        !self.cancelled && self.runtime.write_task(priority, closure).is_ok()
     }
 
     fn cancel_all_tasks(&mut self) {
         self.runtime.cancel();
-        self.is_cancelled = true;
+        self.set_cancelled(true);
     }
 }
 
 impl Initializible for DiscardingSpawnGroup {
     fn init() -> Self {
-        DiscardingSpawnGroup {
-            is_cancelled: false,
-            runtime: RuntimeEngine::init(),
-            wait_at_drop: true,
-        }
+        Self::from_runtime(RuntimeEngine::init(), GroupDropPolicy::WaitForAll)
+    }
+}
+
+#[async_trait]
+impl Waitable for DiscardingSpawnGroup {
+    async fn wait(&self) {
+        self.runtime.wait_until_idle().await;
     }
 }
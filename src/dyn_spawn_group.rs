@@ -0,0 +1,245 @@
+use crate::{discarding_spawn_group::DiscardingSpawnGroup, shared::priority::Priority};
+use std::{any::Any, future::Future, pin::Pin};
+
+/// An owned, heap allocated and type erased future that can be sent across threads
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object safe interface of a spawn group whose child tasks return nothing
+///
+/// Code that doesn't know the concrete spawn group it is handed, such as dynamically loaded plugins,
+/// can spawn, cancel and wait for child tasks through a ``&mut dyn DynSpawner``
+pub trait DynSpawner: Send {
+    /// Spawns an already boxed future into the spawn group without boxing it again
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: a boxed future that doesn't return anything
+    fn spawn_boxed(&mut self, priority: Priority, future: BoxFuture<'static, ()>);
+
+    /// Cancels all running task in the spawn group
+    fn cancel_all(&mut self);
+
+    /// A Boolean value that indicates whether the spawn group has been cancelled
+    fn is_cancelled(&self) -> bool;
+
+    /// A Boolean value that indicates whether the group has any remaining tasks.
+    fn is_empty(&self) -> bool;
+
+    /// Closes the spawn group so it no longer accepts new child tasks
+    fn close(&self);
+
+    /// A Boolean value that indicates whether the spawn group was closed
+    fn is_closed(&self) -> bool;
+
+    /// Waits for all remaining child tasks for finish.
+    fn wait_for_all(&mut self) -> BoxFuture<'_, ()>;
+
+    /// Returns the spawn group as a reference to ``Any`` so it can be downcasted
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the spawn group as a mutable reference to ``Any`` so it can be downcasted
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Converts the boxed spawn group into a boxed ``Any`` so it can be downcasted
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+}
+
+impl DynSpawner for DiscardingSpawnGroup {
+    fn spawn_boxed(&mut self, priority: Priority, future: BoxFuture<'static, ()>) {
+        self.spawn_task(priority, future);
+    }
+
+    fn cancel_all(&mut self) {
+        DiscardingSpawnGroup::cancel_all(self);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        DiscardingSpawnGroup::is_cancelled(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        DiscardingSpawnGroup::is_empty(self)
+    }
+
+    fn close(&self) {
+        DiscardingSpawnGroup::close(self);
+    }
+
+    fn is_closed(&self) -> bool {
+        DiscardingSpawnGroup::is_closed(self)
+    }
+
+    fn wait_for_all(&mut self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            DiscardingSpawnGroup::wait_for_all(self).await;
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+}
+
+/// Dyn Spawn Group
+///
+/// A type erased spawn group that spawns asynchronous child tasks that returns nothing,
+/// without exposing the generic parameters or concrete type of the spawn group it wraps.
+///
+/// Child tasks are spawned by calling either ``spawn_task()`` or ``spawn_boxed()`` methods.
+///
+/// Running child tasks can be cancelled by calling ``cancel_all()`` method.
+///
+/// Cancelling, waiting and dropping behave exactly as they do on the wrapped spawn group.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, DiscardingSpawnGroup, DynSpawnGroup, DynSpawner, Priority};
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// // a plugin that knows nothing about the spawn group it is handed
+/// fn plugin(spawner: &mut dyn DynSpawner, counter: Arc<AtomicUsize>) {
+///     for _ in 0..5 {
+///         let counter = counter.clone();
+///         spawner.spawn_boxed(Priority::default(), Box::pin(async move {
+///             counter.fetch_add(1, Ordering::SeqCst);
+///         }));
+///     }
+/// }
+///
+/// let counter = Arc::new(AtomicUsize::new(0));
+/// let mut group = DynSpawnGroup::new(DiscardingSpawnGroup::new(2));
+/// plugin(group.as_dyn_mut(), counter.clone());
+/// block_on(group.wait_for_all());
+/// assert_eq!(counter.load(Ordering::SeqCst), 5);
+///
+/// // recover the concrete spawn group
+/// let group: DiscardingSpawnGroup = group.downcast().ok().unwrap();
+/// assert!(!group.is_cancelled());
+/// ```
+pub struct DynSpawnGroup {
+    inner: Box<dyn DynSpawner>,
+}
+
+impl DynSpawnGroup {
+    /// Erases the type of the given spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `group`: the spawn group to wrap
+    pub fn new<Group: DynSpawner + 'static>(group: Group) -> Self {
+        Self {
+            inner: Box::new(group),
+        }
+    }
+}
+
+impl DynSpawnGroup {
+    /// Spawns a new task into the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.inner.spawn_boxed(priority, Box::pin(closure));
+    }
+
+    /// Spawns an already boxed future into the spawn group without boxing it again
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: a boxed future that doesn't return anything
+    pub fn spawn_boxed(&mut self, priority: Priority, future: BoxFuture<'static, ()>) {
+        self.inner.spawn_boxed(priority, future);
+    }
+
+    /// Cancels all running task in the spawn group
+    pub fn cancel_all(&mut self) {
+        self.inner.cancel_all();
+    }
+
+    /// A Boolean value that indicates whether the spawn group has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// A Boolean value that indicates whether the group has any remaining tasks.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Closes the spawn group so it no longer accepts new child tasks
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    /// A Boolean value that indicates whether the spawn group was closed
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Waits for all remaining child tasks for finish.
+    pub async fn wait_for_all(&mut self) {
+        self.inner.wait_for_all().await;
+    }
+}
+
+impl DynSpawnGroup {
+    /// Returns the wrapped spawn group as a trait object
+    pub fn as_dyn(&self) -> &dyn DynSpawner {
+        self.inner.as_ref()
+    }
+
+    /// Returns the wrapped spawn group as a mutable trait object
+    pub fn as_dyn_mut(&mut self) -> &mut dyn DynSpawner {
+        self.inner.as_mut()
+    }
+
+    /// Returns a reference to the wrapped spawn group if it is of type ``Group``
+    pub fn downcast_ref<Group: DynSpawner + 'static>(&self) -> Option<&Group> {
+        self.inner.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to the wrapped spawn group if it is of type ``Group``
+    pub fn downcast_mut<Group: DynSpawner + 'static>(&mut self) -> Option<&mut Group> {
+        self.inner.as_any_mut().downcast_mut()
+    }
+
+    /// Converts back into the wrapped spawn group if it is of type ``Group``
+    ///
+    /// # Returns
+    /// - Ok: the wrapped spawn group
+    /// - Err: the type erased spawn group itself if the wrapped spawn group is of another type
+    pub fn downcast<Group: DynSpawner + 'static>(self) -> Result<Group, Self> {
+        if !self.inner.as_any().is::<Group>() {
+            return Err(self);
+        }
+        let Ok(group) = self.inner.into_any().downcast::<Group>() else {
+            unreachable!("type was checked above")
+        };
+        Ok(*group)
+    }
+}
+
+impl From<DiscardingSpawnGroup> for DynSpawnGroup {
+    fn from(group: DiscardingSpawnGroup) -> Self {
+        Self::new(group)
+    }
+}
@@ -1,17 +1,35 @@
+use crate::async_runtime::lane::Lane;
+use crate::async_stream::AsyncStream;
+use crate::dyn_spawn_group::BoxFuture;
+use crate::join_handle::{finished, JoinHandle};
+use crate::result_stream::ResultStream;
+use crate::rev_stream::RevStream;
+use crate::shared::result_size::{approx_result_size, ResultSize};
+use crate::shared::workload_hint::{recommended_threads, WorkloadHint};
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
-    wait::Waitable,
+    adaptive::AdaptiveConfig, drop_policy::GroupDropPolicy, initializible::Initializible,
+    latency::QueueLatency, metrics::Metrics, overflow_policy::OverflowPolicy,
+    panic_isolation::PanicIsolation, panic_output::PanicOutput, priority::Priority,
+    priority_policy::PriorityPolicy, runtime::RuntimeEngine, sharedfuncs::Shared,
+    snapshot::GroupSnapshot, spawn_error::SpawnError, task_cap::TaskCapPolicy, wait::Waitable,
 };
+use crate::threadpool_impl::{ExecutionRecord, ThreadSpec};
 use async_trait::async_trait;
+use futures_core::stream::FusedStream;
 use futures_lite::{Stream, StreamExt};
 use std::{
+    fmt,
     future::Future,
+    io,
+    num::NonZeroUsize,
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll},
+    thread,
+    time::Duration,
 };
 
 /// Err Spawn Group
@@ -31,56 +49,756 @@ use std::{
 /// FIFO order whenever it is being used
 pub struct ErrSpawnGroup<ValueType: Send + 'static, ErrorType: Send + 'static> {
     /// A field that indicates if the spawn group had been cancelled
+    #[deprecated(note = "use `ErrSpawnGroup::is_cancelled()` instead")]
     pub is_cancelled: bool,
+    // whether the spawn group was cancelled since it was created or last reset, which the deprecated field mirrors
+    cancelled: bool,
     count: Arc<AtomicUsize>,
     runtime: RuntimeEngine<Result<ValueType, ErrorType>>,
-    wait_at_drop: bool,
+    drop_policy: GroupDropPolicy,
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Wraps the runtime of a spawn group that wasn't cancelled yet
+    #[allow(deprecated)]
+    fn from_runtime(
+        runtime: RuntimeEngine<Result<ValueType, ErrorType>>,
+        drop_policy: GroupDropPolicy,
+    ) -> Self {
+        Self {
+            is_cancelled: false,
+            cancelled: false,
+            count: Arc::new(AtomicUsize::new(0)),
+            runtime,
+            drop_policy,
+        }
+    }
+
+    /// Records whether the spawn group was cancelled, mirrored into the deprecated public field
+    #[allow(deprecated)]
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+        self.is_cancelled = cancelled;
+    }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     /// Instantiates `ErrSpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures
-    /// 
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    pub fn new(num_of_threads: usize) -> Self {
+        Self::from_runtime(
+            RuntimeEngine::new(num_of_threads),
+            GroupDropPolicy::CancelAll,
+        )
+    }
+
+    /// Instantiates `ErrSpawnGroup` with as many threads as [`recommended_threads`](crate::recommended_threads) suggests
+    /// for the given workload
+    ///
+    /// See [`SpawnGroup::with_workload`](crate::SpawnGroup::with_workload) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `workload`: the child tasks the spawn group is going to run
+    pub fn with_workload(workload: WorkloadHint) -> Self {
+        Self::new(recommended_threads(workload))
+    }
+
+    /// Instantiates `ErrSpawnGroup` with a specific number of threads, and a result buffer preallocated with room
+    /// for the given number of results
+    ///
+    /// See [`SpawnGroup::with_capacity`](crate::SpawnGroup::with_capacity) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `result_capacity`: number of results the result buffer holds before it reallocates
+    pub fn with_capacity(num_of_threads: usize, result_capacity: usize) -> Self {
+        Self::from_runtime(
+            RuntimeEngine::with_capacity(num_of_threads, result_capacity),
+            GroupDropPolicy::CancelAll,
+        )
+    }
+
+    /// Instantiates `ErrSpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures,
+    /// and what to do with the remaining child tasks when it's dropped
+    ///
+    /// Unlike ``new``, it can't be given zero threads by mistake. A threadpool without worker threads, only driven by
+    /// the consumer helping it, is still created with ``new(0)``.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    pub fn new_with(num_of_threads: NonZeroUsize, policy: GroupDropPolicy) -> Self {
+        let mut group: Self = Self::new(num_of_threads.get());
+        group.drop_policy = policy;
+        group
+    }
+
+    /// Instantiates `ErrSpawnGroup` with a specific number of threads, creating every thread it needs through the given thread factory
+    ///
+    /// Use it where threads must be created a particular way, for example to follow a naming convention, enter a security
+    /// context or go through an audit hook. The factory is kept and also creates the thread running the child tasks again
+    /// once the spawn group is used after being cancelled.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `factory`: a closure creating a thread from its [`ThreadSpec`](crate::ThreadSpec)
+    ///
+    /// # Returns
+    /// - Ok: the spawn group
+    /// - Err: the first error returned by the factory, once the threads it had already created are stopped
+    pub fn with_thread_factory<Factory>(num_of_threads: usize, factory: Factory) -> io::Result<Self>
+    where
+        Factory: Fn(ThreadSpec) -> io::Result<thread::JoinHandle<()>> + Send + Sync + 'static,
+    {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_factory(num_of_threads, Arc::new(factory))?,
+            GroupDropPolicy::CancelAll,
+        ))
+    }
+
+    /// Instantiates `ErrSpawnGroup` with separate IO and compute lanes, each with its own worker threads
+    ///
+    /// Child tasks start on the IO lane, or on the lane given to ``spawn_task_on``, and move between the lanes by
+    /// awaiting [`move_to`](crate::move_to), so the blocking-heavy and CPU-heavy phases of the child tasks never hold
+    /// up each other. Results, cancellation and waiting are still those of the one spawn group, whichever lane the
+    /// child tasks are on.
+    ///
+    /// # Parameters
+    ///
+    /// * `io_threads`: number of worker threads of the IO lane
+    /// * `compute_threads`: number of worker threads of the compute lane
+    ///
+    /// # Returns
+    /// The spawn group, or the error of the first worker thread that couldn't be spawned
+    pub fn with_lanes(io_threads: NonZeroUsize, compute_threads: NonZeroUsize) -> io::Result<Self> {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_lanes(io_threads, compute_threads)?,
+            GroupDropPolicy::CancelAll,
+        ))
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Don't implicity wait for spawned child tasks to finish before being dropped
+    pub fn dont_wait_at_drop(&mut self) {
+        self.drop_policy = GroupDropPolicy::CancelAll;
+    }
+
+    /// Sets what the spawn group does with its remaining child tasks when it's dropped, replacing the policy it was
+    /// created with
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    pub fn set_drop_policy(&mut self, policy: GroupDropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Bounds how long dropping the spawn group with ``GroupDropPolicy::WaitForAll`` waits for its child tasks
+    ///
+    /// Once the cap elapses, the ids of the child tasks still pending are reported to the panic output of the spawn
+    /// group, see [`PanicOutput`](crate::PanicOutput), and they're cancelled as by ``cancel_all``. A child task
+    /// blocking its worker thread keeps running there, detached from the spawn group.
+    ///
+    /// # Parameters
+    ///
+    /// * `cap`: the longest dropping the spawn group waits for its child tasks
+    pub fn drop_wait_cap(&mut self, cap: Duration) {
+        self.runtime.drop_wait_cap(cap);
+    }
+
+    /// Warns about the child tasks blocking their worker thread, whose single poll runs for longer than the threshold
+    /// without yielding
+    ///
+    /// See [`SpawnGroup::warn_on_blocking`](crate::SpawnGroup::warn_on_blocking) for more.
+    pub fn warn_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, false);
+    }
+
+    /// Warns about the child tasks blocking their worker thread, then makes them panic once their blocking poll
+    /// returns
+    ///
+    /// See [`SpawnGroup::panic_on_blocking`](crate::SpawnGroup::panic_on_blocking) for more.
+    pub fn panic_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, true);
+    }
+
+    /// Registers a hook that reports how long each child task waited in the queue
+    /// before a worker thread picked it up, replacing any previously registered hook
+    ///
+    /// The hook runs on the worker thread right before the child task is first polled,
+    /// so it should be cheap.
+    ///
+    /// # Parameters
+    ///
+    /// * `hook`: a closure that receives the ``QueueLatency`` of each child task
+    pub fn on_task_dequeued<Hook>(&mut self, hook: Hook)
+    where
+        Hook: Fn(QueueLatency) + Send + Sync + 'static,
+    {
+        self.runtime.on_task_dequeued(Arc::new(hook));
+    }
+
+    /// Registers a hook that receives every result of the spawn group dropped without ever being consumed,
+    /// replacing any previously registered hook
+    ///
+    /// The results still buffered when the spawn group is reset or dropped are handed to the hook one at a time,
+    /// on the thread resetting or dropping it, so results owning external resources can be cleaned up. Once a hook
+    /// is registered, the results still buffered when the spawn group is dropped go to the hook rather than to the
+    /// clones of its stream. A panicking hook doesn't keep the remaining results from being handed to it.
+    ///
+    /// The number of discarded results is reported by [`Metrics::results_discarded`](crate::Metrics::results_discarded)
+    /// whether a hook is registered or not.
+    ///
+    /// # Parameters
+    ///
+    /// * `hook`: a closure that receives each discarded result
+    pub fn on_discarded<Hook>(&mut self, hook: Hook)
+    where
+        Hook: Fn(Result<ValueType, ErrorType>) + Send + Sync + 'static,
+    {
+        self.runtime.on_discarded(Arc::new(hook));
+    }
+
+    /// Sets where the reports of the child tasks panicking go, for the child tasks that start running from now on
+    ///
+    /// By default the report, including a backtrace, is printed to the standard error, which may garble the screen of
+    /// a terminal application or duplicate the panic reporting of the application. See [`PanicOutput`](crate::PanicOutput)
+    /// for the alternatives.
+    ///
+    /// # Parameters
+    ///
+    /// * `output`: where the reports go
+    pub fn panic_output(&mut self, output: PanicOutput) {
+        self.runtime.panic_output(output);
+    }
+
+    /// Sets what a worker thread does once a child task panicked on it, for the child tasks that panic from now on
+    ///
+    /// By default the worker thread goes on running child tasks. See [`PanicIsolation`](crate::PanicIsolation) for
+    /// restarting it instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `isolation`: what the worker thread does
+    pub fn panic_isolation(&mut self, isolation: PanicIsolation) {
+        self.runtime.panic_isolation(isolation);
+    }
+
+    /// Registers a middleware that wraps every child task spawned from now on
+    ///
+    /// A middleware receives the child task as a boxed future and returns the future run in its place, which lets
+    /// cross-cutting behavior like timeouts, tracing or metrics live in one place instead of at every spawn site.
+    /// Middlewares compose in registration order, the first registered one being the outermost.
+    ///
+    /// A middleware may extend the child task or complete without running it to completion, in which case the child
+    /// task produces no result, as if it was cancelled, and the counters of the spawn group stay correct either way.
+    /// Child tasks are only boxed once a middleware was registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `middleware`: a closure that wraps the future of each child task
+    pub fn task_middleware<Middleware>(&mut self, middleware: Middleware)
+    where
+        Middleware: Fn(BoxFuture<'static, ()>) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.runtime.task_middleware(Arc::new(middleware));
+    }
+
+    /// Sets which priorities the spawn group accepts for the child tasks spawned from now on
+    ///
+    /// See [`PriorityPolicy`](crate::PriorityPolicy) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: the priority policy to enforce
+    pub fn priority_policy(&mut self, policy: PriorityPolicy) {
+        self.runtime.priority_policy(policy);
+    }
+
+    /// Caps the number of child tasks the spawn group spawns over its lifetime, as a guard against runaway spawning
+    ///
+    /// The child tasks spawned before the cap was set count towards it, and so do the ones spawned before a reset.
+    /// Once the cap is reached, every further child task is handled as the policy says, see
+    /// [`TaskCapPolicy`](crate::TaskCapPolicy).
+    ///
+    /// # Parameters
+    ///
+    /// * `max_tasks`: the most child tasks the spawn group spawns
+    /// * `policy`: what happens to the child tasks beyond the cap
+    pub fn set_max_tasks(&mut self, max_tasks: usize, policy: TaskCapPolicy) {
+        self.runtime.set_max_tasks(max_tasks, policy);
+    }
+
+    /// Bounds the number of pending child tasks [`try_spawn_task`](Self::try_spawn_task) lets the spawn group have,
+    /// refusing the child tasks beyond it with ``SpawnError::QueueFull``
+    ///
+    /// Only ``try_spawn_task`` is bounded, every other way of spawning queues its child tasks regardless.
+    ///
+    /// # Parameters
+    ///
+    /// * `capacity`: the most pending child tasks, whether running or waiting for a worker thread
+    pub fn queue_capacity(&mut self, capacity: usize) {
+        self.runtime.queue_capacity(capacity);
+    }
+
+    /// Bounds the number of results the spawn group buffers until they're consumed, handling a result beyond it as the
+    /// given policy says
+    ///
+    /// Results handed back or restored, such as the result of a dropped child handle, are buffered regardless of the
+    /// capacity. With ``OverflowPolicy::Block``, a spawn group whose results nobody consumes never finishes its child
+    /// tasks, so waiting for them without consuming the results never returns. See
+    /// [`OverflowPolicy`](crate::OverflowPolicy) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `capacity`: the most results buffered at once
+    /// * `policy`: what happens to a result once the buffer is full
+    ///
+    /// # Panics
+    /// If `capacity` is zero
+    pub fn result_buffer(&mut self, capacity: usize, policy: OverflowPolicy) {
+        self.runtime.result_buffer(capacity, policy);
+    }
+
+    /// Bounds the number of bytes of the results the spawn group buffers until they're consumed, handling a result
+    /// beyond it as the given policy says
+    ///
+    /// See [`SpawnGroup::result_buffer_bytes`](crate::SpawnGroup::result_buffer_bytes) for more.
+    ///
+    /// # Panics
+    /// If `max_bytes` is zero
+    pub fn result_buffer_bytes(&mut self, max_bytes: usize, policy: OverflowPolicy) {
+        self.runtime.result_buffer_bytes(max_bytes, policy);
+    }
+
+    /// Keeps at most the newest result buffered, dropping the older unconsumed one whenever a child task produces
+    /// another
+    ///
+    /// See [`SpawnGroup::latest_only`](crate::SpawnGroup::latest_only) for more.
+    pub fn latest_only(&mut self, enabled: bool) {
+        self.runtime.latest_only(enabled);
+    }
+
+    /// Measures the results buffered from now on with the [`ResultSize`](crate::ResultSize) implementation of the
+    /// value or the error they hold rather than as ``size_of::<Result<ValueType, ErrorType>>()``
+    pub fn measure_results(&mut self)
+    where
+        ValueType: ResultSize,
+        ErrorType: ResultSize,
+    {
+        self.runtime.measure_results(approx_result_size);
+    }
+
+    /// Makes the number of child tasks allowed to run at once adapt to how long they take, between the bounds of the
+    /// given config
+    ///
+    /// A child task returning an error narrows the window, just as one running past the target latency.
+    ///
+    /// A child task waits for room in the window before it starts, and the time it waits counts as queue latency.
+    /// The current width of the window is published as [`Metrics::concurrency_window`](crate::Metrics::concurrency_window).
+    /// See [`AdaptiveConfig`](crate::AdaptiveConfig) for how the window is adjusted.
+    ///
+    /// # Parameters
+    ///
+    /// * `config`: the bounds of the window and the latency the child tasks should finish within
+    ///
+    /// # Panics
+    /// If the minimum of `config` is zero or above its maximum
+    pub fn adaptive_concurrency(&mut self, config: AdaptiveConfig) {
+        self.runtime.adaptive_concurrency(config, Some(Result::is_err));
+    }
+
+    /// Declares that the spawn group spawns exactly ``size`` child tasks, trading its dynamic features for a lower
+    /// overhead per child task
+    ///
+    /// Room for the whole batch is made upfront, and the child tasks of the batch skip the registry behind
+    /// ``snapshot``, the dequeue hook, the task middlewares, the execution log and the metrics of their latency.
+    /// Their outcome is counted by counting the batch down rather than per child task.
+    ///
+    /// Once declared, spawning beyond the batch is refused with ``SpawnError::BatchComplete``, and spawning with a
+    /// feature the batch skips, such as a child handle, a sequence number or a subset, is refused with
+    /// ``SpawnError::BatchUnsupported``. Calling ``reset`` takes the spawn group out of batch mode.
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: the number of child tasks of the batch
+    ///
+    /// # Panics
+    /// If the spawn group already has child tasks or results, or batch mode was already declared
+    pub fn exclusive_batch(&mut self, size: usize) {
+        self.runtime.exclusive_batch(size);
+    }
+
+    /// Caps the CPU time the worker threads of the spawn group use collectively, measured in cores
+    ///
+    /// A quota of ``0.5`` lets the worker threads use half a core at most. Each worker thread measures how long
+    /// it's busy polling child tasks, and parks whenever the worker threads are over the quota, so the cap is
+    /// approximate and holds over windows of tens of milliseconds. The consumer helping the threadpool isn't capped.
+    ///
+    /// # Parameters
+    ///
+    /// * `cores`: the number of cores the worker threads may use, ``f64::INFINITY`` lifts the cap
+    ///
+    /// # Panics
+    /// If `cores` isn't a positive number
+    pub fn cpu_quota(&mut self, cores: f64) {
+        assert!(
+            cores > 0.0,
+            "the CPU quota must be a positive number of cores"
+        );
+        self.runtime.cpu_quota(cores);
+    }
+
+    /// Registers how each worker thread of the spawn group creates its own state of type ``State``,
+    /// replacing any previously registered initializer of the same type
+    ///
+    /// Child tasks get the state of the worker thread polling them by calling the [`worker_state`](crate::worker_state)
+    /// function. Worker threads that already created their state keep it.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: a closure that creates the state, called at most once per worker thread
+    pub fn worker_state<State, Init>(&mut self, init: Init)
+    where
+        State: 'static,
+        Init: Fn() -> State + Send + Sync + 'static,
+    {
+        self.runtime.worker_state(init);
+    }
+
+    /// Injects the failures described by the fault plan into the underlying threadpool
+    ///
+    /// See [`FaultPlan`](crate::FaultPlan) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `plan`: the failures to inject
+    #[cfg(feature = "fault-injection")]
+    pub fn inject(&mut self, plan: crate::FaultPlan) {
+        self.runtime.inject(plan);
+    }
+
+    /// Lets the consumer of the spawn group's stream help run queued child tasks
+    ///
+    /// Whenever polling the stream finds no result ready, the polling thread runs at most one
+    /// queued job of the underlying threadpool before parking.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether the consumer should help or not
+    pub fn help_drive_pool(&mut self, enabled: bool) {
+        self.runtime.help_drive_pool(enabled);
+    }
+
+    /// Sets how many results must be buffered before a waiting consumer of the stream is woken
+    ///
+    /// By default the consumer is woken only when the buffer goes from empty to non-empty, instead of once per result.
+    /// A larger watermark trades latency for fewer wakeups in high-throughput spawn groups: a waiting consumer won't see a
+    /// result until ``count`` results are buffered, or until every remaining child task has finished, so the final
+    /// result always wakes the consumer and the stream terminates. A consumer kept waiting for a few milliseconds while
+    /// results are buffered is woken regardless of the watermark, so a long-running child task can't starve it.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of buffered results that wakes the consumer, ``0`` is treated as ``1``
+    pub fn wake_after_n(&mut self, count: usize) {
+        self.runtime.wake_after(count);
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Spawns a new task into the spawn group, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.add_task(priority, closure);
+    }
+
+    /// Spawns a new task into the spawn group under a name, does nothing if the spawn group was closed or its
+    /// priority policy refuses the priority
+    ///
+    /// See [`SpawnGroup::spawn_task_named`](crate::SpawnGroup::spawn_task_named) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: name of the child task, which doesn't need to be unique
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task_named<F>(&mut self, name: impl Into<String>, priority: Priority, closure: F)
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        let name: Arc<str> = Arc::from(name.into());
+        if self
+            .runtime
+            .write_named_task(name, priority, closure)
+            .is_ok()
+        {
+            self.increment_count();
+        }
+    }
+
+    /// Spawns a new task into the spawn group, polled on the worker threads of the given lane from the start, does
+    /// nothing if the spawn group was closed or its priority policy refuses the priority
+    ///
+    /// A spawn group without separate lanes polls it like any other child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `lane`: lane to start on
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task_on<F>(&mut self, lane: Lane, priority: Priority, closure: F)
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        if self.runtime.write_task_on(lane, priority, closure).is_ok() {
+            self.increment_count();
+        }
+    }
+
+    /// Spawns a new task into the spawn group with its default priority, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
+    ///
+    /// The default priority is ``Priority::default()`` unless changed with
+    /// [`set_default_priority`](Self::set_default_priority).
+    ///
+    /// # Parameters
+    ///
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn<F>(&mut self, closure: F)
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.spawn_task(self.runtime.spawn_priority(), closure);
+    }
+
+    /// Sets the priority of the child tasks spawned with [`spawn`](Self::spawn) from now on
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the priority to use when none is given
+    pub fn set_default_priority(&mut self, priority: Priority) {
+        self.runtime.set_default_priority(priority);
+    }
+
+    /// Spawns every task of the iterator into the spawn group with the same priority, does nothing if the spawn group
+    /// was closed or its priority policy refuses the priority
+    ///
+    /// The child tasks are the same as if each was spawned with [`spawn_task`](Self::spawn_task), but they're counted
+    /// and queued for the worker threads all at once, which is cheaper for many small child tasks.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use for every child task
+    /// * `closures`: the async closures that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_all<I, F>(&mut self, priority: Priority, closures: I)
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        if let Ok(count) = self.runtime.write_tasks(priority, closures) {
+            self.count.fetch_add(count, Ordering::Acquire);
+        }
+    }
+
+    /// Spawns a new task into the spawn group unless it was closed or its priority policy refuses the priority
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Closed`` if the spawn group was closed, ``SpawnError::Rejected``
+    ///   if its priority policy refused the priority, ``SpawnError::ThreadUnavailable``
+    ///   if its thread factory failed to create the thread running the child tasks again after a cancellation,
+    ///   or ``SpawnError::QueueFull`` if its pending child tasks fill the capacity set with ``queue_capacity``
+    ///
+    /// # Teardown
+    /// The results discarded along with a spawn group are dropped while it's torn down, and every child task spawned
+    /// from their destructors, into any spawn group, is refused with ``SpawnError::ShuttingDown``.
+    pub fn try_spawn_task<F>(&mut self, priority: Priority, closure: F) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.runtime.write_bounded_task(priority, closure)?;
+        self.increment_count();
+        Ok(())
+    }
+
+    /// Spawns a new task into the spawn group once its pending child tasks no longer fill the capacity set with
+    /// [`queue_capacity`](Self::queue_capacity), suspending the caller until then
+    ///
+    /// The caller is woken as child tasks finish rather than polling in a loop. Without a queue capacity the child
+    /// task is spawned right away.
+    ///
     /// # Parameters
     ///
-    /// * `num_of_threads`: number of threads to use
-    pub fn new(num_of_threads: usize) -> Self {
-        Self {
-            is_cancelled: false,
-            count: Arc::new(AtomicUsize::new(0)),
-            runtime: RuntimeEngine::new(num_of_threads),
-            wait_at_drop: false,
-        }
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled while waiting, or any error
+    ///   ``try_spawn_task`` returns other than ``SpawnError::QueueFull``
+    pub async fn spawn_task_when_ready<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.runtime
+            .write_task_when_ready(priority, closure)
+            .await?;
+        self.increment_count();
+        Ok(())
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Don't implicity wait for spawned child tasks to finish before being dropped
-    pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+    /// Runs a single future on the spawn group's threadpool and returns a
+    /// [`JoinHandle`](crate::JoinHandle) to await its output
+    ///
+    /// Unlike child tasks spawned with ``spawn_task``, its output doesn't go into the spawn group's
+    /// stream, but it's waited for and cancelled along with the spawn group. Dropping the
+    /// ``JoinHandle`` detaches the future. If the future can't be run, for example because the spawn group was
+    /// closed or is in exclusive batch mode, the returned ``JoinHandle`` finishes immediately without an output, and
+    /// [`try_run`](Self::try_run) tells why.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: the future to run
+    pub fn run<Fut>(&mut self, priority: Priority, future: Fut) -> JoinHandle<Fut::Output>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime
+            .run_task(priority, future)
+            .unwrap_or_else(|_| finished())
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Spawns a new task into the spawn group
+    /// Runs a single future on the spawn group's threadpool as [`run`](Self::run) does, unless it can't be run
+    ///
+    /// See [`SpawnGroup::try_run`](crate::SpawnGroup::try_run) for more.
+    pub fn try_run<Fut>(
+        &mut self,
+        priority: Priority,
+        future: Fut,
+    ) -> Result<JoinHandle<Fut::Output>, SpawnError>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime.run_task(priority, future)
+    }
+
+    /// Spawns a new task into the spawn group to retry the work of a result the consumer
+    /// found unsatisfactory
+    ///
+    /// This behaves exactly like ``spawn_task``
     ///
     /// # Parameters
     ///
     /// * `priority`: priority to use
     /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
-    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    pub fn respawn<F>(&mut self, priority: Priority, closure: F)
     where
         F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
             + Send
             + 'static,
     {
-        self.add_task(priority, closure);
+        self.spawn_task(priority, closure);
+    }
+
+    /// Pushes a result back into the front of the spawn group's stream so that the next
+    /// consumer receives it before any other result
+    ///
+    /// This is safe to call while child tasks are still producing results. The result was let into the result buffer
+    /// once already, so it's buffered regardless of the capacity set with ``result_buffer`` and its overflow policy.
+    ///
+    /// # Parameters
+    ///
+    /// * `value`: the result to requeue
+    pub fn requeue_result(&self, value: Result<ValueType, ErrorType>) {
+        let stream: AsyncStream<Result<ValueType, ErrorType>> = self.runtime.stream();
+        stream.requeue_item(stream.wave(), value);
     }
 
     /// Cancels all running task in the spawn group
+    ///
+    /// Child tasks which haven't started running never run. A child task that a worker thread
+    /// was already polling finishes that poll, and delivers its result if it completed in it,
+    /// but it is never polled again. This method returns once those polls have finished.
     pub fn cancel_all(&mut self) {
         self.cancel_all_tasks();
     }
 
+    /// A Boolean value that indicates whether the spawn group was cancelled since it was created or last reset
+    // the deprecated field of the same name only mirrors the state
+    #[allow(clippy::misnamed_getters)]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Cancels every child task below the given priority that hasn't started running yet
+    ///
+    /// See [`SpawnGroup::shed_load`](crate::SpawnGroup::shed_load) for more.
+    ///
+    /// # Returns
+    /// The number of child tasks shed
+    pub fn shed_load(&mut self, keep: Priority) -> usize {
+        self.runtime.shed_load(keep)
+    }
+
+    /// Spawns a new child task only if the spawn group wasn't cancelled since it was created or last reset
+    ///
+    /// See [`SpawnGroup::spawn_task_unless_cancelled`](crate::SpawnGroup::spawn_task_unless_cancelled) for more.
+    ///
+    /// # Returns
+    /// - true: if the child task was spawned
+    /// - false: if the spawn group was cancelled or closed, in which case the child task is dropped without running
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.add_task_unless_cancelled(priority, closure)
+    }
+
     /// Spawn a new task only if the group is not cancelled yet,
     /// otherwise does nothing
     ///
@@ -88,35 +806,384 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     ///
     /// * `priority`: priority to use
     /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
     pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
     where
         F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
             + Send
             + 'static,
     {
-        self.add_task_unlessed_cancelled(priority, closure);
+        _ = self.spawn_task_unless_cancelled(priority, closure);
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Closes the spawn group so it no longer accepts new child tasks
+    ///
+    /// Once closed, ``spawn_task`` does nothing and ``try_spawn_task`` returns ``SpawnError::Closed``,
+    /// which makes waiting for all child tasks final. Closing an already closed spawn group does nothing.
+    pub fn close(&self) {
+        self.runtime.close();
+    }
+
+    /// A Boolean value that indicates whether the spawn group was closed
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Runs the given future on the spawn group's threadpool and closes the spawn group once it completes, however
+    /// it completes
+    ///
+    /// See [`SpawnGroup::close_when`](crate::SpawnGroup::close_when) for more.
+    pub fn close_when<Fut>(&mut self, future: Fut) -> JoinHandle<Fut::Output>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime
+            .close_when(future)
+            .unwrap_or_else(|_| finished())
+    }
+
+    /// Cancels every child task and starts a new generation, reopening the spawn group for reuse
+    ///
+    /// Buffered results are discarded, and so is any result of a child task spawned before the reset,
+    /// even one whose poll was already running, so no stale result crosses the generation boundary.
+    /// The spawn group is no longer cancelled nor closed afterwards.
+    ///
+    /// Child tasks still in flight are cancelled rather than waited for, so wait for all of them first to keep their
+    /// results. The worker threads are kept, so the next wave of child tasks runs on the same threadpool.
+    pub fn reset(&mut self) {
+        self.runtime.reset();
+        self.set_cancelled(false);
+        self.decrement_count_to_zero();
+    }
+
+    /// The generation of the spawn group, which starts at zero and increases with every ``reset()``
+    pub fn generation(&self) -> usize {
+        self.runtime.generation()
+    }
+
+    pub(crate) fn closed_flag(&self) -> Arc<AtomicBool> {
+        self.runtime.closed_flag()
     }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     /// Returns the first element of the stream, or None if it is empty.
+    ///
+    /// It waits for the next result if none is buffered yet, and is cancel-safe: a result is
+    /// only taken once the returned future completes, so dropping it early never loses a result.
     pub async fn first(&self) -> Option<<ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result> {
         self.runtime.stream().first().await
     }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Returns an instance of the `Stream` trait.
-    pub fn stream(&self) -> impl Stream<Item = Result<ValueType, ErrorType>> {
-        self.runtime.stream()
+    /// Returns a stream of the results of the spawn group
+    ///
+    /// See [`SpawnGroup::stream`](crate::SpawnGroup::stream) for more.
+    pub fn stream(&self) -> ResultStream<Result<ValueType, ErrorType>> {
+        ResultStream::new(self.runtime.stream())
+    }
+
+    /// Returns a stream of the results of the spawn group that yields the newest buffered result first
+    ///
+    /// See [`RevStream`](crate::RevStream) for more.
+    pub fn rev_stream(&self) -> RevStream<Result<ValueType, ErrorType>> {
+        RevStream::new(self.runtime.stream())
+    }
+
+    /// Takes the next result if one is buffered, or returns None right away rather than waiting for one
+    ///
+    /// See [`SpawnGroup::try_next`](crate::SpawnGroup::try_next) for more.
+    pub fn try_next(&mut self) -> Option<Result<ValueType, ErrorType>> {
+        self.runtime.try_next()
+    }
+
+    /// Takes every result buffered at once, leaving the ones the pending child tasks are yet to produce to later calls
+    ///
+    /// See [`SpawnGroup::drain_ready`](crate::SpawnGroup::drain_ready) for more.
+    pub fn drain_ready(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        self.runtime.drain_ready()
+    }
+
+    /// Waits for up to `n` results and returns them as a vector, with fewer of them once the stream of the spawn group
+    /// ends
+    ///
+    /// See [`SpawnGroup::next_chunk`](crate::SpawnGroup::next_chunk) for more.
+    pub async fn next_chunk(&mut self, n: usize) -> Vec<Result<ValueType, ErrorType>> {
+        let mut results: Vec<Result<ValueType, ErrorType>> = Vec::with_capacity(n.min(self.len()));
+        while results.len() < n {
+            match self.next().await {
+                Some(result) => results.push(result),
+                None => break,
+            }
+        }
+        results
+    }
+
+    /// Waits for the newest result rather than the oldest one, or None once the stream of the spawn group ends
+    ///
+    /// See [`SpawnGroup::next_back`](crate::SpawnGroup::next_back) for more.
+    pub async fn next_back(&mut self) -> Option<Result<ValueType, ErrorType>> {
+        self.runtime.next_back().await
     }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     /// Waits for all remaining child tasks for finish.
+    ///
+    /// See [`SpawnGroup::wait_for_all`](crate::SpawnGroup::wait_for_all) for more.
     pub async fn wait_for_all(&mut self) {
         self.wait().await;
     }
+
+    /// Waits for all remaining child tasks for finish, blocking the current thread
+    ///
+    /// See [`SpawnGroup::wait_non_async`](crate::SpawnGroup::wait_non_async) for more.
+    pub fn wait_non_async(&mut self) {
+        self.runtime.wait_for_all_tasks();
+        self.decrement_count_to_zero();
+    }
+
+    /// Waits for all remaining child tasks to finish for at most the given duration, leaving the ones still running
+    /// once it elapses
+    ///
+    /// See [`SpawnGroup::wait_timeout`](crate::SpawnGroup::wait_timeout) for more.
+    ///
+    /// # Returns
+    /// Whether every child task finished within the duration
+    pub async fn wait_timeout(&mut self, duration: Duration) -> bool {
+        let finished: bool = self.runtime.wait_until_idle_for(duration).await;
+        if finished {
+            self.decrement_count_to_zero();
+        }
+        finished
+    }
+
+    /// Hands every result to the consumer as soon as it's delivered, until no child task remains and
+    /// no result is buffered
+    ///
+    /// See [`SpawnGroup::drain_to_completion`](crate::SpawnGroup::drain_to_completion) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `consumer`: a closure that receives the spawn group and each result
+    pub async fn drain_to_completion<Consumer>(&mut self, mut consumer: Consumer)
+    where
+        Consumer: FnMut(&mut Self, Result<ValueType, ErrorType>),
+    {
+        while let Some(result) = self.next().await {
+            consumer(self, result);
+        }
+        self.decrement_count_to_zero();
+    }
+
+    /// Waits for every child task to finish and splits their results into values and errors, each in the order
+    /// the child tasks finished
+    ///
+    /// Unlike [`try_collect_or_cancel`](Self::try_collect_or_cancel), an error never cuts the collection short,
+    /// so every child task gets to finish. The spawn group is left empty and can spawn child tasks again, whereas
+    /// a cancelled spawn group only returns the results delivered before its cancellation.
+    ///
+    /// # Returns
+    /// A tuple of the values of the successful child tasks and the errors of the failed ones
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, ErrSpawnGroup, Priority};
+    ///
+    /// let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    /// for i in 1..=6 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         if i % 3 == 0 {
+    ///             return Err(format!("{i} is divisible by three"));
+    ///         }
+    ///         Ok(i)
+    ///     });
+    /// }
+    /// let (mut values, mut errors) = block_on(group.try_collect_partial());
+    /// values.sort();
+    /// errors.sort();
+    /// assert_eq!(values, [1, 2, 4, 5]);
+    /// assert_eq!(errors, ["3 is divisible by three", "6 is divisible by three"]);
+    /// assert!(group.is_empty());
+    /// ```
+    pub async fn try_collect_partial(&mut self) -> (Vec<ValueType>, Vec<ErrorType>) {
+        let mut values: Vec<ValueType> = vec![];
+        let mut errors: Vec<ErrorType> = vec![];
+        while let Some(result) = self.next().await {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        self.decrement_count_to_zero();
+        (values, errors)
+    }
+
+    /// Collects the values of the child tasks in the order they finished until the first error, which cancels the
+    /// spawn group
+    ///
+    /// It fails fast without losing the work already done: the error comes back along with every value delivered
+    /// before it, including the ones still buffered at the time. The results the remaining child tasks deliver
+    /// afterwards, as well as the errors buffered behind the first one, are dropped, so the spawn group is left
+    /// cancelled and empty. Without any error it waits for every child task and leaves the spawn group empty, as
+    /// [`try_collect_partial`](Self::try_collect_partial) does.
+    ///
+    /// # Returns
+    /// The values of every child task, or the first error along with the values collected until then
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    /// group.spawn_task(Priority::default(), async { Ok(1) });
+    /// group.spawn_task(Priority::default(), async {
+    ///     sleep(Duration::from_millis(50)).await;
+    ///     Err("timed out".to_string())
+    /// });
+    /// group.spawn_task(Priority::default(), async {
+    ///     sleep(Duration::from_secs(3600)).await;
+    ///     Ok(3)
+    /// });
+    /// let (error, values) = block_on(group.try_collect_or_cancel()).unwrap_err();
+    /// assert_eq!(error, "timed out");
+    /// assert_eq!(values, [1]);
+    /// assert!(group.is_cancelled());
+    /// assert!(group.is_empty());
+    /// ```
+    pub async fn try_collect_or_cancel(
+        &mut self,
+    ) -> Result<Vec<ValueType>, (ErrorType, Vec<ValueType>)> {
+        let mut values: Vec<ValueType> = vec![];
+        while let Some(result) = self.next().await {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => {
+                    self.cancel_all();
+                    values.extend(self.drain_ready().into_iter().filter_map(Result::ok));
+                    return Err((error, values));
+                }
+            }
+        }
+        self.decrement_count_to_zero();
+        Ok(values)
+    }
+
+    /// Waits for all remaining child tasks to finish, then consumes the spawn group and splits their
+    /// results into values and errors, each in the order the child tasks finished
+    ///
+    /// Taking the spawn group by value means nothing else can spawn child tasks into it anymore, and
+    /// that dropping it afterwards doesn't wait again. It can be returned straight from the body of a
+    /// ``with_err_spawn_group`` function call to carry the results out of the scope.
+    ///
+    /// A cancelled spawn group only returns the results delivered before its cancellation.
+    ///
+    /// # Returns
+    /// A tuple of the values of the successful child tasks and the errors of the failed ones
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, with_err_spawn_group, ErrSpawnGroup, Priority};
+    ///
+    /// let (values, mut errors) = block_on(with_err_spawn_group(|mut group| async move {
+    ///     for i in 1..=6 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i % 3 == 0 {
+    ///                 return Err(format!("{i} is divisible by three"));
+    ///             }
+    ///             Ok(i)
+    ///         });
+    ///     }
+    ///     group.into_parts().await
+    /// }));
+    /// assert_eq!(values.iter().sum::<i32>(), 12);
+    /// errors.sort();
+    /// assert_eq!(errors, ["3 is divisible by three", "6 is divisible by three"]);
+    ///
+    /// // an empty spawn group
+    /// let group = ErrSpawnGroup::<i32, String>::new(1);
+    /// assert_eq!(block_on(group.into_parts()), (vec![], vec![]));
+    ///
+    /// // a cancelled spawn group
+    /// let mut group = ErrSpawnGroup::<i32, String>::new(1);
+    /// for i in 0..10 {
+    ///     group.spawn_task(Priority::default(), async move { Ok(i) });
+    /// }
+    /// group.cancel_all();
+    /// let (values, errors) = block_on(group.into_parts());
+    /// assert!(values.len() <= 10);
+    /// assert!(errors.is_empty());
+    /// ```
+    pub async fn into_parts(mut self) -> (Vec<ValueType>, Vec<ErrorType>)
+    where
+        ValueType: 'static,
+        ErrorType: 'static,
+    {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        let mut values: Vec<ValueType> = vec![];
+        let mut errors: Vec<ErrorType> = vec![];
+        while let Some(result) = self.next().await {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        (values, errors)
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order the child tasks were spawned
+    ///
+    /// Errors keep their place among the values. After a cancellation only the results of the child tasks that
+    /// completed are returned, still in spawn order.
+    ///
+    /// See [`SpawnGroup::collect_ordered`](crate::SpawnGroup::collect_ordered) for more.
+    ///
+    /// # Returns
+    /// A vector of the results in the order their child tasks were spawned
+    pub async fn collect_ordered(mut self) -> Vec<Result<ValueType, ErrorType>>
+    where
+        ValueType: 'static,
+        ErrorType: 'static,
+    {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        self.runtime.stream().collect_ordered().await
+    }
+
+    /// Waits for every child task to finish and returns their results, consuming the spawn group
+    ///
+    /// The current task is suspended rather than the current thread blocked, and dropping the spawn group afterwards
+    /// never blocks, whatever its drop policy. See [`SpawnGroup::finish`](crate::SpawnGroup::finish) for more.
+    ///
+    /// # Returns
+    /// A vector of the results, in the order their child tasks finished
+    pub async fn finish(mut self) -> Vec<Result<ValueType, ErrorType>>
+    where
+        ValueType: 'static,
+        ErrorType: 'static,
+    {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        (&mut self).collect().await
+    }
+
+    /// Waits for every child task to finish and drops their results, consuming the spawn group
+    ///
+    /// See [`finish`](Self::finish).
+    pub async fn finish_discarding(mut self) {
+        self.wait().await;
+        // the results left are dropped along with the spawn group
+        self.drop_policy = GroupDropPolicy::CancelAll;
+    }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
@@ -133,6 +1200,101 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     }
 }
 
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Captures the state of the spawn group without stopping its child tasks
+    ///
+    /// See [`GroupSnapshot`](crate::GroupSnapshot) for more.
+    pub fn snapshot(&self) -> GroupSnapshot {
+        self.runtime.snapshot(self.cancelled)
+    }
+
+    /// Makes the child tasks spawned from now on leave breadcrumbs of where they suspend
+    ///
+    /// See [`SpawnGroup::trace_suspensions`](crate::SpawnGroup::trace_suspensions) for more.
+    pub fn trace_suspensions(&mut self, enabled: bool) {
+        self.runtime.trace_suspensions(enabled);
+    }
+
+    /// Returns the state of the spawn group as text, with a line for each child task that hasn't completed yet
+    ///
+    /// See [`SpawnGroup::dump_state`](crate::SpawnGroup::dump_state) for more.
+    pub fn dump_state(&self) -> String {
+        self.snapshot().to_string()
+    }
+
+    /// Returns the number of worker threads running the child tasks of the spawn group
+    pub fn num_threads(&self) -> usize {
+        self.runtime.num_threads()
+    }
+
+    /// Returns the number of child tasks that are yet to complete, whether running or waiting for a worker thread
+    pub fn pending_tasks(&self) -> usize {
+        self.runtime.pending_tasks()
+    }
+
+    /// Returns the number of results produced by the child tasks that are yet to be consumed
+    pub fn buffered_results(&self) -> usize {
+        self.runtime.buffered_results()
+    }
+
+    /// Returns the number of results the result buffer holds before it reallocates
+    pub fn result_capacity(&self) -> usize {
+        self.runtime.result_capacity()
+    }
+
+    /// Returns the number of child tasks spawned whose result is yet to be consumed from the spawn group
+    ///
+    /// That's the pending child tasks along with the buffered results, apart from the child tasks cancelled with
+    /// ``cancel_all``, which will never produce one.
+    pub fn len(&self) -> usize {
+        self.runtime.len()
+    }
+
+    /// Returns the number of results ready to be consumed, the same as [`buffered_results`](Self::buffered_results)
+    pub fn completed_count(&self) -> usize {
+        self.runtime.buffered_results()
+    }
+
+    /// Returns the counters of the child tasks completed by the spawn group
+    ///
+    /// See [`Metrics`](crate::Metrics) for more.
+    pub fn metrics(&self) -> Metrics {
+        self.runtime.metrics()
+    }
+
+    /// Enables or disables recording the child tasks executed by each worker thread, disabled by default
+    ///
+    /// While disabled, recording costs a single atomic load per child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether to record the child tasks executed from now on
+    pub fn record_executions(&self, enabled: bool) {
+        self.runtime.record_executions(enabled);
+    }
+
+    /// Returns the last child tasks executed by the spawn group, ordered by the instant they ended
+    ///
+    /// Each worker thread keeps its last 256 child tasks, which answers what ran recently on
+    /// each of them when the spawn group hangs. Nothing is kept unless ``record_executions`` was enabled.
+    ///
+    /// See [`ExecutionRecord`](crate::ExecutionRecord) for more.
+    pub fn recent_executions(&self) -> Vec<ExecutionRecord> {
+        self.runtime.recent_executions()
+    }
+
+    /// Returns the last wakes, suspensions and resumptions of the child tasks of the spawn group, along with the
+    /// last release of each of their wakers
+    ///
+    /// Hangs almost always come down to a lost or misrouted wake, which
+    /// [`WakeTrace::stalled_tasks`](crate::WakeTrace::stalled_tasks) looks for. Only available with the
+    /// `wake-trace` feature, without which nothing is recorded.
+    #[cfg(feature = "wake-trace")]
+    pub fn wake_trace(&self) -> crate::WakeTrace {
+        self.runtime.wake_trace()
+    }
+}
+
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     /// A Boolean value that indicates whether the group has any remaining tasks.
     ///
@@ -163,32 +1325,16 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     ///
     /// # Returns
     /// Returns a vector of length `of_count` elements from the spawn group instance
-    #[deprecated(since = "2.0.0", note = "Buggy")]
+    #[deprecated(since = "2.0.0", note = "Use next_chunk instead, which never panics")]
     pub async fn get_chunks(&self, of_count: usize) -> Vec<Result<ValueType, ErrorType>> {
-        if of_count == 0 {
-            return vec![];
-        }
-        let buffer_count: usize = self.runtime.stream().buffer_count().await;
-        if buffer_count == of_count {
-            let mut count: usize = of_count;
-            let mut results: Vec<Result<ValueType, ErrorType>> = vec![];
-            while count != 0 {
-                if let Some(result) = self.runtime.stream().next().await {
-                    results.push(result);
-                    count -= 1;
-                }
-            }
-            return results;
-        }
         if of_count > self.count() {
             panic!("The argument supplied cannot be greater than the number of spawned child tasks")
         }
-        let mut count: usize = of_count;
-        let mut results: Vec<Result<ValueType, ErrorType>> = vec![];
-        while count != 0 {
-            if let Some(result) = self.runtime.stream().next().await {
-                results.push(result);
-                count -= 1;
+        let mut results: Vec<Result<ValueType, ErrorType>> = Vec::with_capacity(of_count);
+        while results.len() < of_count {
+            match self.runtime.stream().next().await {
+                Some(result) => results.push(result),
+                None => break,
             }
         }
         results
@@ -197,22 +1343,18 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
 
 impl<ValueType: Send, ErrorType: Send + 'static> Drop for ErrSpawnGroup<ValueType, ErrorType> {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        match self.drop_policy {
+            GroupDropPolicy::WaitForAll => self.runtime.end_waiting(),
+            // cut off the same way as by cancel_all, without waiting for the polls in flight
+            GroupDropPolicy::CancelAll => self.runtime.end(false),
+            GroupDropPolicy::Detach => self.runtime.detach(),
         }
     }
 }
 
 impl<ValueType: Send, ErrorType: Send> Initializible for ErrSpawnGroup<ValueType, ErrorType> {
     fn init() -> Self {
-        ErrSpawnGroup::<ValueType, ErrorType> {
-            count: Arc::new(AtomicUsize::new(0)),
-            is_cancelled: false,
-            runtime: RuntimeEngine::init(),
-            wait_at_drop: true,
-        }
+        Self::from_runtime(RuntimeEngine::init(), GroupDropPolicy::WaitForAll)
     }
 }
 
@@ -225,23 +1367,33 @@ impl<ValueType: Send + 'static, ErrorType: Send + 'static> Shared
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        self.increment_count();
-        self.runtime.write_task(priority, closure);
+        if self.runtime.write_task(priority, closure).is_ok() {
+            self.increment_count();
+        }
     }
 
     fn cancel_all_tasks(&mut self) {
         self.runtime.cancel();
-        self.is_cancelled = true;
+        self.set_cancelled(true);
         self.decrement_count_to_zero();
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
+        if self.cancelled || self.runtime.write_task(priority, closure).is_err() {
+            return false;
         }
+        self.increment_count();
+        true
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> fmt::Debug for ErrSpawnGroup<ValueType, ErrorType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.runtime
+            .fmt_group(f, "ErrSpawnGroup", self.cancelled, self.drop_policy)
     }
 }
 
@@ -251,6 +1403,20 @@ impl<ValueType: Send, ErrorType: Send> Stream for ErrSpawnGroup<ValueType, Error
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         self.runtime.stream().poll_next(cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.runtime.stream().size_hint()
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> FusedStream for ErrSpawnGroup<ValueType, ErrorType> {
+    /// Whether the spawn group has no result buffered and no child task left to produce one, either since every one
+    /// of them completed or since they were cancelled
+    ///
+    /// Spawning another child task makes the stream of the spawn group yield results again.
+    fn is_terminated(&self) -> bool {
+        self.runtime.stream().is_terminated()
+    }
 }
 
 #[async_trait]
@@ -258,7 +1424,7 @@ impl<ValueType: Send + 'static, ErrorType: Send + 'static> Waitable
     for ErrSpawnGroup<ValueType, ErrorType>
 {
     async fn wait(&self) {
-        self.runtime.wait_for_all_tasks();
+        self.runtime.wait_until_idle().await;
         self.decrement_count_to_zero();
     }
 }
@@ -1,24 +1,43 @@
 use std::{
+    cell::Cell,
     future::Future,
     sync::Arc,
-    task::{Context, Waker},
+    task::{Context, Poll, Waker},
 };
 
-use cooked_waker::IntoWaker;
-
 use crate::{async_runtime::notifier::Notifier, pin_future};
 
 thread_local! {
-    pub(crate) static WAKER_PAIR: (Arc<Notifier>, Waker) = {
-        let notifier = Arc::new(Notifier::default());
-        let waker = notifier.clone().into_waker();
-        (notifier, waker)
-    };
+    // the notifier of the last future blocked on by the thread, reused unless a clone of its waker outlived the future
+    static SUSPENDER: Cell<Option<Arc<Notifier>>> = const { Cell::new(None) };
+}
+
+/// Runs the body with a notifier suspending the current thread and its waker
+///
+/// A future may keep a clone of the waker and wake it after it completed, which would make the next
+/// future blocked on by the thread skip a suspension. So once the body returns, the notifier is
+/// defused if a clone of its waker is still around, and only reused otherwise.
+pub(crate) fn with_suspender<T>(body: impl FnOnce(&Notifier, &Waker) -> T) -> T {
+    let notifier: Arc<Notifier> = SUSPENDER
+        .try_with(Cell::take)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let waker: Waker = Waker::from(notifier.clone());
+    let output: T = body(&notifier, &waker);
+    drop(waker);
+    if Arc::strong_count(&notifier) == 1 {
+        notifier.reset();
+        _ = SUSPENDER.try_with(|cached| cached.set(Some(notifier)));
+    } else {
+        notifier.defuse();
+    }
+    output
 }
 
 pub(crate) fn block_future<Fut: Future>(
     future: Fut,
-    notifier: Arc<Notifier>,
+    notifier: &Notifier,
     waker: &Waker,
 ) -> Fut::Output {
     let mut context: Context<'_> = Context::from_waker(waker);
@@ -26,9 +45,16 @@ pub(crate) fn block_future<Fut: Future>(
     loop {
         match future.as_mut().poll(&mut context) {
             std::task::Poll::Ready(output) => return output,
-            std::task::Poll::Pending => {
-                notifier.wait()
-            }
+            std::task::Poll::Pending => notifier.wait(),
         }
     }
 }
+
+/// Polls the future once
+pub(crate) fn poll_future<Fut: Future>(future: Fut, waker: &Waker) -> Option<Fut::Output> {
+    pin_future!(future);
+    match future.as_mut().poll(&mut Context::from_waker(waker)) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
@@ -1,13 +1,14 @@
-use std::{future::Future, sync::Arc, task::Waker};
+use std::{future::Future, sync::OnceLock};
 
-use cooked_waker::IntoWaker;
+use crate::{
+    async_runtime::executor::Executor,
+    join_handle::{joinable, JoinHandle},
+    shared::priority::Priority,
+};
 
-use crate::async_runtime::{notifier::Notifier, task::Task};
-
-use self::{local_executor::block_future, task_executor::block_on_task};
+use self::local_executor::{block_future, poll_future, with_suspender};
 
 mod local_executor;
-mod task_executor;
 
 /// Blocks the current thread until the future is polled to finish.
 ///
@@ -20,30 +21,120 @@ mod task_executor;
 /// assert_eq!(result, 1);
 /// ```
 ///
+/// The waker handed to the future can be cloned, woken and dropped from any thread. Once every clone of it is dropped,
+/// the thread hands the same waker to the next future it blocks on.
+///
+/// ```rust
+/// use std::{future::poll_fn, task::Poll, thread};
+///
+/// let mut polls = 0;
+/// let mut data = std::ptr::null();
+/// spawn_groups::block_on(poll_fn(|cx| {
+///     polls += 1;
+///     data = cx.waker().data();
+///     if polls == 4 {
+///         return Poll::Ready(());
+///     }
+///     let handles: Vec<_> = (0..4)
+///         .map(|_| {
+///             let waker = cx.waker().clone();
+///             thread::spawn(move || {
+///                 for _ in 0..1000 {
+///                     waker.wake_by_ref();
+///                     waker.clone().wake();
+///                 }
+///             })
+///         })
+///         .collect();
+///     for handle in handles {
+///         handle.join().unwrap();
+///     }
+///     Poll::Pending
+/// }));
+/// assert_eq!(polls, 4);
+/// // no clone outlived the future, so the reference count of the waker went back to the one of the thread
+/// spawn_groups::block_on(poll_fn(|cx| {
+///     assert_eq!(cx.waker().data(), data);
+///     Poll::Ready(())
+/// }));
+///
+/// // a clone outliving the future keeps the waker from being handed to the next one
+/// let kept = spawn_groups::block_on(poll_fn(|cx| Poll::Ready(cx.waker().clone())));
+/// spawn_groups::block_on(poll_fn(|cx| {
+///     assert_ne!(cx.waker().data(), kept.data());
+///     Poll::Ready(())
+/// }));
+/// ```
+///
 pub fn block_on<Fut: Future>(future: Fut) -> Fut::Output {
-    let waker_pair: Result<(Arc<Notifier>, Waker), std::thread::AccessError> =
-        local_executor::WAKER_PAIR
-            .try_with(|waker_pair: &(Arc<Notifier>, Waker)| waker_pair.clone());
-    match waker_pair {
-        Ok((notifier, waker)) => block_future(future, notifier, &waker),
-        Err(_) => {
-            let notifier: Arc<Notifier> = Arc::new(Notifier::default());
-            let waker: Waker = notifier.clone().into_waker();
-            block_future(future, notifier, &waker)
-        }
-    }
+    with_suspender(|notifier, waker| block_future(future, notifier, waker))
+}
+
+/// Polls the future once on the current thread, returning its output if it was ready right away
+///
+/// The future is polled with the same kind of waker ``block_on`` suspends the thread with. A future keeping a clone of
+/// it and waking it later never makes a later ``block_on`` on the thread return early, and a future that's polled again
+/// later is handed the waker of whoever polls it then.
+///
+/// # Cancel safety
+/// A future still pending is dropped along with its progress, unless it's handed over by mutable reference, as a
+/// pinned binding from [`pin_future`](crate::pin_future) or any ``Unpin`` future, which can be polled again later.
+///
+/// Example
+/// ```rust
+/// use spawn_groups::{block_on, now_or_never, pin_future, sleep, Priority, SpawnGroup};
+/// use futures_lite::StreamExt;
+/// use std::time::Duration;
+///
+/// let mut group = SpawnGroup::<u64>::new(1);
+/// group.spawn_task(Priority::default(), async {
+///     sleep(Duration::from_millis(10)).await;
+///     7
+/// });
+/// let mut stream = group.stream();
+/// let next = stream.next();
+/// pin_future!(next);
+/// // the child task hasn't finished yet
+/// assert_eq!(now_or_never(next.as_mut()), None);
+/// assert_eq!(block_on(next), Some(7));
+/// assert_eq!(now_or_never(async { 1 }), Some(1));
+/// ```
+pub fn now_or_never<Fut: Future>(future: Fut) -> Option<Fut::Output> {
+    with_suspender(|_, waker| poll_future(future, waker))
 }
 
-pub(crate) fn block_task(task: Task) {
-    let waker_pair: Result<(Arc<Notifier>, Waker), std::thread::AccessError> =
-        local_executor::WAKER_PAIR
-            .try_with(|waker_pair: &(Arc<Notifier>, Waker)| waker_pair.clone());
-    match waker_pair {
-        Ok((notifier, waker)) => block_on_task(task, notifier, &waker),
-        Err(_) => {
-            let notifier: Arc<Notifier> = Arc::new(Notifier::default());
-            let waker: Waker = notifier.clone().into_waker();
-            block_on_task(task, notifier, &waker)
-        }
-    }
+/// Spawns a future onto a default threadpool shared by the whole program and returns a
+/// [`JoinHandle`](crate::JoinHandle) to await its output.
+///
+/// The default threadpool is created on the first call and uses as many threads as the default
+/// amount of parallelism a program can use on the system.
+///
+/// Dropping the returned ``JoinHandle`` detaches the future, which keeps running in the background.
+///
+/// Example
+/// ```rust
+/// use spawn_groups::{block_on, sleep, spawn};
+/// use std::time::Duration;
+///
+/// let handle = spawn(async { 21 * 2 });
+/// assert_eq!(block_on(handle), Some(42));
+///
+/// let handle = spawn(async {
+///     sleep(Duration::from_secs(60)).await;
+/// });
+/// handle.abort();
+/// assert!(handle.is_finished());
+/// assert_eq!(block_on(handle), None);
+/// ```
+pub fn spawn<Fut>(future: Fut) -> JoinHandle<Fut::Output>
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    static DEFAULT_EXECUTOR: OnceLock<Executor> = OnceLock::new();
+    let (task, handle) = joinable(future);
+    _ = DEFAULT_EXECUTOR
+        .get_or_init(Executor::default)
+        .spawn(Priority::default(), task);
+    handle
 }
@@ -0,0 +1,205 @@
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Fault Plan
+///
+/// Describes the failures to inject into the threadpool of a spawn group, which is useful for
+/// testing how resilient an application is to misbehaving child tasks.
+///
+/// The plan is consulted at well-defined points of the threadpool:
+/// * when a child task is spawned, to decide if it should panic
+/// * before a worker thread runs a job, to decide if it should be delayed
+/// * when a pending child task is rescheduled, to decide if its wakeup should be dropped
+///
+/// A dropped wakeup leaves the child task unscheduled until the spawn group is waited for, and no wakeup is dropped
+/// while it is.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{FaultPlan, Priority, SpawnGroup, block_on};
+/// use futures_lite::StreamExt;
+///
+/// let mut group = SpawnGroup::<usize>::new(2);
+/// group.inject(FaultPlan::new().panic_on_task(3));
+/// for i in 0..10 {
+///     group.spawn_task(Priority::default(), async move { i });
+/// }
+///
+/// let results: Vec<usize> = block_on(group.collect());
+/// assert_eq!(results.len(), 9);
+/// assert!(!results.contains(&3));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultPlan {
+    panic_on_tasks: Vec<usize>,
+    worker_delays: Vec<(usize, Duration)>,
+    drop_wakeups: f64,
+    seed: u64,
+}
+
+impl FaultPlan {
+    /// Instantiates an empty `FaultPlan` which injects no failures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the nth spawned child task panic instead of running, counting from zero
+    ///
+    /// # Parameters
+    ///
+    /// * `nth`: the spawn index of the child task
+    pub fn panic_on_task(mut self, nth: usize) -> Self {
+        self.panic_on_tasks.push(nth);
+        self
+    }
+
+    /// Delays the worker thread at the given index before every job it runs
+    ///
+    /// # Parameters
+    ///
+    /// * `index`: the index of the worker thread
+    /// * `duration`: how long to delay the worker thread
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, DiscardingSpawnGroup, FaultPlan, Priority};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut group = DiscardingSpawnGroup::new(1);
+    /// group.inject(FaultPlan::new().delay_worker(0, Duration::from_millis(50)));
+    /// let now = Instant::now();
+    /// group.spawn_task(Priority::default(), async {});
+    /// block_on(group.wait_for_all());
+    /// assert!(now.elapsed() >= Duration::from_millis(50));
+    /// ```
+    pub fn delay_worker(mut self, index: usize, duration: Duration) -> Self {
+        self.worker_delays.push((index, duration));
+        self
+    }
+
+    /// Drops the given fraction of the wakeups of pending child tasks
+    ///
+    /// # Parameters
+    ///
+    /// * `probability`: a value between 0.0 (never) and 1.0 (always)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, yield_now, FaultPlan, Priority, SpawnGroup};
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut group = SpawnGroup::<usize>::new(2);
+    /// group.inject(FaultPlan::new().drop_wakeups(1.0));
+    /// for i in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         yield_now().await;
+    ///         i
+    ///     });
+    /// }
+    ///
+    /// // waiting drives the child tasks whose wakeups were all dropped
+    /// block_on(group.wait_for_all());
+    /// let results: Vec<usize> = block_on(group.collect());
+    /// assert_eq!(results.len(), 5);
+    /// ```
+    pub fn drop_wakeups(mut self, probability: f64) -> Self {
+        self.drop_wakeups = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seeds the pseudo random generator used for dropping wakeups so runs are reproducible
+    ///
+    /// # Parameters
+    ///
+    /// * `seed`: the seed to use
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+#[derive(Default)]
+struct Injector {
+    plan: FaultPlan,
+    spawned: AtomicUsize,
+    state: AtomicU64,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct FaultInjector {
+    inner: Arc<Mutex<Option<Arc<Injector>>>>,
+}
+
+impl FaultInjector {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn inject(&self, plan: FaultPlan) {
+        let state = AtomicU64::new(plan.seed | 1);
+        *self.inner.lock() = Some(Arc::new(Injector {
+            plan,
+            spawned: AtomicUsize::new(0),
+            state,
+        }));
+    }
+
+    fn injector(&self) -> Option<Arc<Injector>> {
+        self.inner.lock().clone()
+    }
+
+    pub(crate) fn should_panic_task(&self) -> bool {
+        let Some(injector) = self.injector() else {
+            return false;
+        };
+        let nth: usize = injector.spawned.fetch_add(1, Ordering::AcqRel);
+        injector.plan.panic_on_tasks.contains(&nth)
+    }
+
+    pub(crate) fn delay_worker(&self, index: usize) {
+        let Some(injector) = self.injector() else {
+            return;
+        };
+        for (_, duration) in injector
+            .plan
+            .worker_delays
+            .iter()
+            .filter(|(worker, _)| *worker == index)
+        {
+            std::thread::sleep(*duration);
+        }
+    }
+
+    pub(crate) fn should_drop_wakeup(&self) -> bool {
+        let Some(injector) = self.injector() else {
+            return false;
+        };
+        if injector.plan.drop_wakeups <= 0.0 {
+            return false;
+        }
+        // xorshift64 keeps the dropped wakeups reproducible for a given seed, its state advanced in a single atomic
+        // step so concurrent worker threads never draw the same value or skip one of the sequence
+        let xorshift = |mut value: u64| {
+            value ^= value << 13;
+            value ^= value >> 7;
+            value ^= value << 17;
+            value
+        };
+        let previous: u64 = injector
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |value| {
+                Some(xorshift(value))
+            })
+            .unwrap_or_else(|value| value);
+        (xorshift(previous) as f64 / u64::MAX as f64) < injector.plan.drop_wakeups
+    }
+}
@@ -0,0 +1,33 @@
+#[cfg(feature = "fault-injection")]
+mod injector;
+
+#[cfg(feature = "fault-injection")]
+pub(crate) use injector::FaultInjector;
+#[cfg(feature = "fault-injection")]
+pub use injector::FaultPlan;
+
+/// Stands in for the fault injector when the `fault-injection` feature is disabled so every hook
+/// compiles down to nothing
+#[cfg(not(feature = "fault-injection"))]
+#[derive(Clone)]
+pub(crate) struct FaultInjector;
+
+#[cfg(not(feature = "fault-injection"))]
+impl FaultInjector {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub(crate) fn should_panic_task(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub(crate) fn delay_worker(&self, _index: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn should_drop_wakeup(&self) -> bool {
+        false
+    }
+}
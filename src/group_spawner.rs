@@ -0,0 +1,86 @@
+use crate::shared::{cancel_token::CancelToken, priority::Priority, runtime::RuntimeEngine};
+use std::{
+    future::Future,
+    sync::{atomic::AtomicUsize, Arc},
+};
+
+/// Spawns child tasks into the spawn group it was taken from, from anywhere, including its own child tasks
+///
+/// It's taken with ``SpawnGroup::spawner()`` and can be cloned and sent to other threads or moved into child tasks,
+/// which is what recursive workloads such as crawlers or tree traversals need. The child tasks it spawns are counted,
+/// awaited and streamed just like the ones spawned to the spawn group itself, even once the spawn group is waiting
+/// for all of them.
+///
+/// A spawner belongs to the generation of the spawn group it was taken in, so once the spawn group is cancelled or
+/// reset it no longer spawns anything, and neither does it once the spawn group is closed or dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{block_on, GroupSpawner, Priority, SpawnGroup};
+///
+/// fn visit(spawner: GroupSpawner<u64>, depth: u64) {
+///     let child = spawner.clone();
+///     spawner.spawn_task(Priority::default(), async move {
+///         if depth > 0 {
+///             visit(child.clone(), depth - 1);
+///             visit(child, depth - 1);
+///         }
+///         depth
+///     });
+/// }
+///
+/// let mut group = SpawnGroup::<u64>::new(4);
+/// visit(group.spawner(), 3);
+/// block_on(group.wait_for_all());
+/// let results: Vec<u64> = block_on((&mut group).collect());
+/// assert_eq!(results.len(), 15);
+/// ```
+pub struct GroupSpawner<ValueType: Send + 'static> {
+    runtime: RuntimeEngine<ValueType>,
+    count: Arc<AtomicUsize>,
+    token: CancelToken,
+}
+
+impl<ValueType: Send + 'static> GroupSpawner<ValueType> {
+    pub(crate) fn new(runtime: RuntimeEngine<ValueType>, count: Arc<AtomicUsize>) -> Self {
+        let token: CancelToken = runtime.cancel_token();
+        Self {
+            runtime,
+            count,
+            token,
+        }
+    }
+
+    /// Spawns a new task into the spawn group, does nothing if the spawn group was cancelled since the spawner was
+    /// taken, was closed or its priority policy refuses the priority
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task<F>(&self, priority: Priority, closure: F)
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        _ = self
+            .runtime
+            .write_task_in(&self.token, &self.count, priority, closure);
+    }
+
+    /// A Boolean value that indicates whether the spawn group was cancelled or reset since the spawner was taken
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl<ValueType: Send + 'static> Clone for GroupSpawner<ValueType> {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: self.runtime.clone(),
+            count: self.count.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use parking_lot::Mutex;
+use std::{
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+};
+
+struct JoinState<T> {
+    output: Option<T>,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+struct Shared<T> {
+    state: Mutex<JoinState<T>>,
+    aborted: AtomicBool,
+}
+
+impl<T> Shared<T> {
+    fn finish(&self, output: Option<T>) {
+        let waker: Option<Waker> = {
+            let mut state = self.state.lock();
+            if state.finished {
+                return;
+            }
+            state.finished = true;
+            state.output = output;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Join Handle
+///
+/// An owned permission to await the result of a single future spawned with either the
+/// [`spawn`](crate::spawn) function or the ``run`` method of any of the spawn groups.
+///
+/// Awaiting it returns ``Some`` with the future's output, or ``None`` if the future was aborted,
+/// cancelled along with its spawn group or panicked.
+///
+/// Dropping a ``JoinHandle`` detaches the future, which keeps running in the background, and the
+/// future's output is dropped once it is ready. Call ``abort()`` to stop it instead.
+#[must_use = "dropping a JoinHandle detaches its future, call abort() to stop it"]
+pub struct JoinHandle<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Aborts the future, which stops running the next time it would be polled
+    ///
+    /// Awaiting the ``JoinHandle`` after aborting returns ``None`` unless the future had already finished.
+    pub fn abort(&self) {
+        self.shared.aborted.store(true, Ordering::Release);
+        self.shared.finish(None);
+    }
+
+    /// A Boolean value that indicates whether the future has finished, was aborted or cancelled
+    pub fn is_finished(&self) -> bool {
+        self.shared.state.lock().finished
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock();
+        if state.finished {
+            return Poll::Ready(state.output.take());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Completes the ``JoinHandle`` with ``None`` whenever the future is dropped before finishing
+struct Completion<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        self.shared.finish(None);
+    }
+}
+
+/// Wraps the future into a task which reports its output to the returned ``JoinHandle``
+pub(crate) fn joinable<Fut>(future: Fut) -> (impl Future<Output = ()>, JoinHandle<Fut::Output>)
+where
+    Fut: Future,
+{
+    let shared: Arc<Shared<Fut::Output>> = Arc::new(Shared {
+        state: Mutex::new(JoinState {
+            output: None,
+            finished: false,
+            waker: None,
+        }),
+        aborted: AtomicBool::new(false),
+    });
+    let handle: JoinHandle<Fut::Output> = JoinHandle {
+        shared: shared.clone(),
+    };
+    let task = async move {
+        let completion: Completion<Fut::Output> = Completion { shared };
+        let mut future = pin!(future);
+        let output: Option<Fut::Output> = poll_fn(|cx| {
+            if completion.shared.aborted.load(Ordering::Acquire) {
+                return Poll::Ready(None);
+            }
+            future.as_mut().poll(cx).map(Some)
+        })
+        .await;
+        completion.shared.finish(output);
+    };
+    (task, handle)
+}
+
+/// Returns a ``JoinHandle`` that has already finished without an output
+pub(crate) fn finished<T>() -> JoinHandle<T> {
+    let (_, handle) = joinable(std::future::pending::<T>());
+    handle.abort();
+    handle
+}
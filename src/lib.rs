@@ -13,7 +13,7 @@
 //!
 //! ```ignore
 //! use async_std::stream::StreamExt;
-//! use spawn_groups::{with_err_spawn_group, GetType, Priority};
+//! use spawn_groups::{with_err_spawn_group, GetType};
 //! use std::time::Instant;
 //! use surf::{Error, Client, http::Mime, StatusCode};
 //!
@@ -47,7 +47,7 @@
 //!         let now = Instant::now();
 //!         for url in urls {
 //!             let client = client.clone();
-//!             group.spawn_task(Priority::default(), async move {
+//!             group.spawn(async move {
 //!                 let Some(mimetype) = get_mimetype(url, client).await else {
 //!                     return Err(Error::from_str(StatusCode::ExpectationFailed, format!("No content type found for {}", url)));
 //!                 }
@@ -74,28 +74,40 @@
 //!
 //! To properly use this crate
 //! * ``with_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value. See [`with_spawn_group`](self::with_spawn_group)
-//! for more information
+//!   for more information
 //!
 //! * ``with_type_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value by specifying the type explicitly. See [`with_type_spawn_group`](self::with_type_spawn_group)
-//! for more information
+//!   for more information
+//!
+//! * ``with_checked_spawn_group`` like ``with_spawn_group`` but failing to compile unless the spawn group is explicitly finished.
+//!   See [`with_checked_spawn_group`](self::with_checked_spawn_group) for more information
 //!
 //! * ``with_err_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value or an error.
-//! See [`with_err_spawn_group`](self::with_err_spawn_group)
-//! for more information
+//!   See [`with_err_spawn_group`](self::with_err_spawn_group)
+//!   for more information
 //!
 //! * ``with_err_type_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value or an error by specifiying the return type and the error type explicitly.
-//! See [`with_err_type_spawn_group`](self::with_err_type_spawn_group)
-//! for more information
+//!   See [`with_err_type_spawn_group`](self::with_err_type_spawn_group)
+//!   for more information
 //!
 //! * ``with_discarding_spawn_group`` for the creation of a dynamic number of asynchronous tasks that returns nothing.
-//! See [`with_discarding_spawn_group`](self::with_discarding_spawn_group)
-//! for more information
+//!   See [`with_discarding_spawn_group`](self::with_discarding_spawn_group)
+//!   for more information
 //!
 //! * ``sleep`` similar to ``std::thread::sleep`` but for sleeping in asynchronous environments. See [`sleep`](self::sleep)
-//! for more information
+//!   for more information
 //!
 //! * ``block_on`` polls future to finish. See [`block_on`](self::block_on)
-//! for more information
+//!   for more information
+//!
+//! * ``spawn`` runs a single future in the background on a default threadpool. See [`spawn`](self::spawn)
+//!   for more information
+//!
+//! * ``pin_future!``, ``poll_once`` and ``now_or_never`` help writing manual poll loops, such as over the stream of a
+//!   spawn group. See [`poll_once`](self::poll_once) and [`now_or_never`](self::now_or_never) for more information
+//!
+//! * ``sync::OnceCell`` initializes a value shared by child tasks once, without blocking the worker threads.
+//!   See [`OnceCell`](self::sync::OnceCell) for more information
 //!
 //! # Spawning Child Tasks
 //!
@@ -112,6 +124,14 @@
 //! By calling explicitly calling the ``cancel_all`` method on any of the spawn groups' instance, all running child tasks
 //! are immediately cancelled.
 //!
+//! Child tasks which haven't started running never run, while a child task that was already being polled finishes that poll
+//! and delivers its result only if it completed in it.
+//!
+//! # Closing
+//!
+//! By calling the ``close`` method on any of the spawn groups' instance, the spawn group stops accepting new child tasks
+//! so waiting for all child tasks and the end of its stream become final.
+//!
 //! # Waiting
 //!
 //! By calling explicitly calling the ``wait_for_all_tasks`` method on any of the spawn groups' instance, all child tasks
@@ -126,13 +146,12 @@
 //! ```rust
 //! use spawn_groups::with_spawn_group;
 //! use futures_lite::StreamExt;
-//! use spawn_groups::Priority;
 //! use spawn_groups::GetType;
 //!
 //! # spawn_groups::block_on(async move {
 //! with_spawn_group(|mut group| async move {
 //!      for i in 0..=10 {
-//!         group.spawn_task(Priority::default(), async move {
+//!         group.spawn(async move {
 //!           // simulate asynchronous operation
 //!              i
 //!          });
@@ -165,36 +184,99 @@
 //! * Avoid calling long, blocking, non asynchronous functions while using any of the spawn groups because it was built with asynchrony in mind.
 //! * Avoid spawning off an asynchronous function such as calling spawn methods from crate such as tokio, async_std, smol, etc.
 
+#![forbid(unsafe_code)]
+
+mod acked_stream;
+mod actor;
+mod blocking_iter;
+mod blocking_results;
+mod channel_bridge;
+mod checked_group;
+mod child_handle;
 mod discarding_spawn_group;
+mod dyn_spawn_group;
 mod err_spawn_group;
+mod group_spawner;
+mod join_handle;
+mod mapped_group;
+mod marked_stream;
+mod result_stream;
+mod rev_stream;
+mod sequenced_stream;
 mod spawn_group;
+mod subset;
+pub mod sync;
 
 mod async_runtime;
 mod async_stream;
 mod executors;
+mod fault_injection;
 mod meta_types;
+mod poll_once;
 mod shared;
 mod sleeper;
 mod threadpool_impl;
+mod wake_trace;
 mod yield_now;
 
+pub use acked_stream::{Acked, AckedStream};
+pub use actor::{spawn_actor, Addr, SendError};
+pub use async_runtime::lane::{move_to, Lane, MoveTo};
+pub use async_runtime::pool_cache::flush_cached_pools;
+pub use blocking_iter::BlockingIter;
+pub use blocking_results::BlockingResults;
+pub use checked_group::{CheckedSpawnGroup, Finished};
+pub use child_handle::ChildHandle;
 pub use discarding_spawn_group::DiscardingSpawnGroup;
+pub use dyn_spawn_group::{BoxFuture, DynSpawnGroup, DynSpawner};
 pub use err_spawn_group::ErrSpawnGroup;
-pub use executors::block_on;
+pub use executors::{block_on, now_or_never, spawn};
+#[cfg(feature = "fault-injection")]
+pub use fault_injection::FaultPlan;
+pub use group_spawner::GroupSpawner;
+pub use join_handle::JoinHandle;
+pub use mapped_group::MappedGroup;
+pub use marked_stream::{Item, MarkedStream};
+pub use result_stream::ResultStream;
+pub use rev_stream::RevStream;
 pub use meta_types::GetType;
+pub use poll_once::{poll_once, PollOnce};
+pub use sequenced_stream::{Sequenced, SequencedStream};
+pub use shared::adaptive::AdaptiveConfig;
+#[doc(hidden)]
+pub use shared::breadcrumbs::mark as __mark_trace_point;
+pub use shared::completion_counts::CompletionCounts;
+pub use shared::drop_policy::GroupDropPolicy;
 use shared::initializible::Initializible;
+pub use shared::latency::QueueLatency;
+pub use shared::metrics::{total_buffered_bytes, Metrics, WindowedMetrics};
+pub use shared::overflow_policy::OverflowPolicy;
+pub use shared::panic_isolation::PanicIsolation;
+pub use shared::panic_output::PanicOutput;
 pub use shared::priority::Priority;
-pub use sleeper::sleep;
+pub use shared::priority_policy::PriorityPolicy;
+pub use shared::result_size::ResultSize;
+pub use shared::snapshot::{GroupSnapshot, TaskSnapshot, TaskState};
+pub use shared::spawn_error::SpawnError;
+pub use shared::task_cap::TaskCapPolicy;
+pub use shared::workload_hint::{recommended_threads, WorkloadHint};
+pub use sleeper::{sleep, Delay};
 pub use spawn_group::SpawnGroup;
-pub use yield_now::yield_now;
+pub use subset::{Subset, SubsetWait};
+pub use threadpool_impl::{worker_state, ExecutionOutcome, ExecutionRecord, ThreadSpec};
+#[cfg(feature = "wake-trace")]
+pub use wake_trace::{WakeEvent, WakeEventKind, WakeTrace};
+pub use yield_now::{yield_now, Yielder};
 
 use std::future::Future;
 use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::atomic::Ordering;
 
 /// Starts a scoped closure that takes a mutable ``SpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the generic ``ResultType`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
-/// of the ``SpawnGroup`` struct.
+/// of the ``SpawnGroup`` struct. The spawn group is closed once the ``body`` returns.
 ///
 /// This function use a threadpool of the same number of threads as the number of active processor count that is default amount of parallelism a program can use on the system for polling the futures
 ///
@@ -246,13 +328,16 @@ where
 {
     _ = of_type;
     let task_group = spawn_group::SpawnGroup::<ResultType>::init();
-    body(task_group).await
+    let closed = task_group.closed_flag();
+    let result = body(task_group).await;
+    closed.store(true, Ordering::Release);
+    result
 }
 
 /// Starts a scoped closure that takes a mutable ``SpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the generic ``ResultType`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
-/// of the ``SpawnGroup`` struct.
+/// of the ``SpawnGroup`` struct. The spawn group is closed once the ``body`` returns.
 ///
 /// This function use a threadpool of the same number of threads as the number of active processor count that is default amount of parallelism a program can use on the system for polling the futures
 ///
@@ -299,14 +384,116 @@ where
     ResultType: Send + 'static,
 {
     let task_group = spawn_group::SpawnGroup::<ResultType>::init();
-    body(task_group).await
+    let closed = task_group.closed_flag();
+    let result = body(task_group).await;
+    closed.store(true, Ordering::Release);
+    result
+}
+
+/// Starts a scoped closure like [`with_spawn_group`], with a specific number of threads to use in the underlying
+/// threadpool when polling futures
+///
+/// See [`SpawnGroup`](spawn_group::SpawnGroup)
+/// for more.
+///
+/// # Parameters
+///
+/// * `num_of_threads`: number of threads to use
+/// * `body`: an async closure that takes a mutable instance of ``SpawnGroup`` as an argument
+///
+/// # Returns
+///
+/// Anything the ``body`` parameter returns
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{with_spawn_group_with_threads, Priority};
+/// use futures_lite::StreamExt;
+/// use std::num::NonZeroUsize;
+///
+/// # spawn_groups::block_on(async move {
+/// let threads = NonZeroUsize::new(2).unwrap();
+/// let final_result = with_spawn_group_with_threads(threads, |mut group| async move {
+///     assert_eq!(group.num_threads(), 2);
+///     for i in 0..=10 {
+///         group.spawn_task(Priority::default(), async move { i });
+///     }
+///     group.fold(0, |acc, x| acc + x).await
+/// })
+/// .await;
+///
+/// assert_eq!(final_result, 55);
+/// # });
+/// ```
+pub async fn with_spawn_group_with_threads<Closure, Fut, ResultType, ReturnType>(
+    num_of_threads: NonZeroUsize,
+    body: Closure,
+) -> ReturnType
+where
+    Closure: FnOnce(spawn_group::SpawnGroup<ResultType>) -> Fut + Send + 'static,
+    Fut: Future<Output = ReturnType> + Send + 'static,
+    ResultType: Send + 'static,
+{
+    let task_group = spawn_group::SpawnGroup::<ResultType>::scoped(num_of_threads.get());
+    let closed = task_group.closed_flag();
+    let result = body(task_group).await;
+    closed.store(true, Ordering::Release);
+    result
+}
+
+/// Starts a scoped closure like [`with_spawn_group`], whose ``body`` has to finish the spawn group explicitly
+///
+/// The ``body`` takes a [`CheckedSpawnGroup`] and must return the [`Finished`] token that only calling its ``finish``
+/// or ``finish_discarding`` method yields, so a ``body`` that would leave waiting for the child tasks to dropping the
+/// spawn group fails to compile.
+///
+/// # Parameters
+///
+/// * `body`: an async closure that takes an instance of ``CheckedSpawnGroup`` as an argument and returns the proof it
+///   was finished
+///
+/// # Returns
+///
+/// The value carried by the proof the ``body`` returns
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{with_checked_spawn_group, Priority};
+///
+/// # spawn_groups::block_on(async move {
+/// let final_result = with_checked_spawn_group(|mut group| async move {
+///     for i in 0..=10 {
+///         group.spawn_task(Priority::default(), async move { i });
+///     }
+///     group.finish().await.map(|results| results.into_iter().sum::<u64>())
+/// })
+/// .await;
+///
+/// assert_eq!(final_result, 55);
+/// # });
+/// ```
+pub async fn with_checked_spawn_group<Closure, Fut, ResultType, ReturnType>(
+    body: Closure,
+) -> ReturnType
+where
+    Closure: FnOnce(CheckedSpawnGroup<ResultType>) -> Fut + Send + 'static,
+    Fut: Future<Output = Finished<ReturnType>> + Send + 'static,
+    ResultType: Send + 'static,
+{
+    let task_group = spawn_group::SpawnGroup::<ResultType>::init();
+    let closed = task_group.closed_flag();
+    let finished = body(CheckedSpawnGroup::new(task_group)).await;
+    closed.store(true, Ordering::Release);
+    finished.into_inner()
 }
 
 /// Starts a scoped closure that takes a mutable ``ErrSpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the type ``Result<ResultType, ErrorType>``
 /// where ``ResultType`` can be of type and ``ErrorType`` which is any type that implements the standard ``Error`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling its ``wait_for_all()`` method
-/// of the ``ErrSpawnGroup`` struct
+/// of the ``ErrSpawnGroup`` struct. The spawn group is closed once the ``body`` returns.
 ///
 /// This function use a threadpool of the same number of threads as the number of active processor count that is default amount of parallelism a program can use on the system for polling the futures
 ///
@@ -398,20 +585,23 @@ pub async fn with_err_type_spawn_group<Closure, Fut, ResultType, ErrorType, Retu
 ) -> ReturnType
 where
     ErrorType: Send + 'static,
-    Fut: Future<Output = ReturnType>,
+    Fut: Future<Output = ReturnType> + Send + 'static,
     Closure: FnOnce(err_spawn_group::ErrSpawnGroup<ResultType, ErrorType>) -> Fut + Send + 'static,
     ResultType: Send + 'static,
 {
     _ = (of_type, error_type);
     let task_group = err_spawn_group::ErrSpawnGroup::<ResultType, ErrorType>::init();
-    body(task_group).await
+    let closed = task_group.closed_flag();
+    let result = body(task_group).await;
+    closed.store(true, Ordering::Release);
+    result
 }
 
 /// Starts a scoped closure that takes a mutable ``ErrSpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the type ``Result<ResultType, ErrorType>``
 /// where ``ResultType`` can be of type and ``ErrorType`` which is any type that implements the standard ``Error`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling its ``wait_for_all()`` method
-/// of the ``ErrSpawnGroup`` struct
+/// of the ``ErrSpawnGroup`` struct. The spawn group is closed once the ``body`` returns.
 ///
 /// This function use a threadpoolof the same number of threads as the number of active processor count that is default amount of parallelism a program can use on the system  for polling the futures
 ///
@@ -499,17 +689,20 @@ pub async fn with_err_spawn_group<Closure, Fut, ResultType, ErrorType, ReturnTyp
 ) -> ReturnType
 where
     ErrorType: Send + 'static,
-    Fut: Future<Output = ReturnType>,
+    Fut: Future<Output = ReturnType> + Send + 'static,
     Closure: FnOnce(err_spawn_group::ErrSpawnGroup<ResultType, ErrorType>) -> Fut + Send + 'static,
     ResultType: Send + 'static,
 {
     let task_group = err_spawn_group::ErrSpawnGroup::<ResultType, ErrorType>::init();
-    body(task_group).await
+    let closed = task_group.closed_flag();
+    let result = body(task_group).await;
+    closed.store(true, Ordering::Release);
+    result
 }
 
 /// Starts a scoped closure that takes a mutable ``DiscardingSpawnGroup`` instance as an argument which can execute any number of child tasks which return nothing.
 ///
-/// Ensures that before the function call ends, all spawned tasks are implicitly waited for
+/// Ensures that before the function call ends, all spawned tasks are implicitly waited for. The spawn group is closed once the ``body`` returns.
 ///
 /// This function use a threadpool of the same number of threads as the number of active processor count that is default amount of parallelism a program can use on the system for polling the futures
 ///
@@ -546,9 +739,12 @@ where
 /// ```
 pub async fn with_discarding_spawn_group<Closure, Fut, ReturnType>(body: Closure) -> ReturnType
 where
-    Fut: Future<Output = ReturnType>,
+    Fut: Future<Output = ReturnType> + Send + 'static,
     Closure: FnOnce(discarding_spawn_group::DiscardingSpawnGroup) -> Fut + Send + 'static,
 {
     let discarding_tg = discarding_spawn_group::DiscardingSpawnGroup::init();
-    body(discarding_tg).await
+    let closed = discarding_tg.closed_flag();
+    let result = body(discarding_tg).await;
+    closed.store(true, Ordering::Release);
+    result
 }
@@ -0,0 +1,83 @@
+use crate::{shared::snapshot::GroupSnapshot, spawn_group::SpawnGroup};
+use futures_lite::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Mapped Group
+///
+/// A view over a spawn group whose stream yields the results of its child tasks passed through a mapping function,
+/// as returned by [`SpawnGroup::map_view`].
+///
+/// Unlike mapping the stream, the view keeps the surface of the spawn group: waiting, cancelling and inspecting
+/// the view waits, cancels and inspects the spawn group itself, so both always agree. This lets a library spawn
+/// child tasks producing raw values while exposing a group of domain values.
+pub struct MappedGroup<'a, ValueType: Send + 'static, MappedType> {
+    group: &'a mut SpawnGroup<ValueType>,
+    mapper: Box<dyn FnMut(ValueType) -> MappedType + Send + 'a>,
+}
+
+impl<'a, ValueType: Send, MappedType> MappedGroup<'a, ValueType, MappedType> {
+    pub(crate) fn new<Mapper>(group: &'a mut SpawnGroup<ValueType>, mapper: Mapper) -> Self
+    where
+        Mapper: FnMut(ValueType) -> MappedType + Send + 'a,
+    {
+        Self {
+            group,
+            mapper: Box::new(mapper),
+        }
+    }
+}
+
+impl<ValueType: Send, MappedType> MappedGroup<'_, ValueType, MappedType> {
+    /// Returns the first mapped result of the stream, or None if it is empty
+    ///
+    /// See [`SpawnGroup::first`] for more.
+    pub async fn first(&mut self) -> Option<MappedType> {
+        let value: ValueType = self.group.first().await?;
+        Some((self.mapper)(value))
+    }
+
+    /// Waits for all remaining child tasks of the spawn group to finish
+    pub async fn wait_for_all(&self) {
+        self.group.wait_for_all().await;
+    }
+
+    /// Cancels all running child tasks of the spawn group
+    pub fn cancel_all(&mut self) {
+        self.group.cancel_all();
+    }
+
+    /// A Boolean value that indicates whether the spawn group had been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.group.is_cancelled()
+    }
+
+    /// A Boolean value that indicates whether the spawn group has any remaining tasks
+    pub fn is_empty(&self) -> bool {
+        self.group.is_empty()
+    }
+
+    /// Captures the state of the spawn group without stopping its child tasks
+    ///
+    /// See [`GroupSnapshot`](crate::GroupSnapshot) for more.
+    pub fn snapshot(&self) -> GroupSnapshot {
+        self.group.snapshot()
+    }
+}
+
+impl<ValueType: Send, MappedType> Stream for MappedGroup<'_, ValueType, MappedType> {
+    type Item = MappedType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        Pin::new(&mut *this.group)
+            .poll_next(cx)
+            .map(|value: Option<ValueType>| value.map(&mut this.mapper))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.group.size_hint()
+    }
+}
@@ -0,0 +1,57 @@
+use crate::async_stream::{AsyncStream, Entry};
+use futures_lite::Stream;
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Item
+///
+/// An item of a [`MarkedStream`], either the result of a child task or a marker inserted with ``insert_marker``
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item<ValueType> {
+    /// The result of a child task
+    Value(ValueType),
+    /// The tag of a marker, delivered after every result buffered before it was inserted
+    Marker(u64),
+}
+
+/// Marked Stream
+///
+/// A stream of the results of a spawn group along with the markers inserted in between them with
+/// [`SpawnGroup::insert_marker`](crate::SpawnGroup::insert_marker), as returned by
+/// [`SpawnGroup::marked_stream`](crate::SpawnGroup::marked_stream).
+///
+/// A marker goes through the same buffer as the results, so it comes after every result buffered before it was
+/// inserted and before every result buffered after it, which lets a consumer flush what it aggregated for a phase of
+/// child tasks without closing the spawn group. A marker is never counted as a result.
+///
+/// The stream ends once the spawn group has no child task left and every buffered result and marker was delivered.
+/// Consuming the results any other way drops the markers in front of them.
+pub struct MarkedStream<'a, ValueType: Send + 'static> {
+    stream: AsyncStream<ValueType>,
+    group: PhantomData<&'a mut ()>,
+}
+
+impl<ValueType: Send + 'static> MarkedStream<'_, ValueType> {
+    pub(crate) fn new(stream: AsyncStream<ValueType>) -> Self {
+        Self {
+            stream,
+            group: PhantomData,
+        }
+    }
+}
+
+impl<ValueType: Send + 'static> Stream for MarkedStream<'_, ValueType> {
+    type Item = Item<ValueType>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_entry(cx).map(|entry| {
+            entry.map(|entry| match entry {
+                Entry::Item((_, value)) => Item::Value(value),
+                Entry::Marker(marker) => Item::Marker(marker),
+            })
+        })
+    }
+}
@@ -1,8 +1,13 @@
+use std::marker::PhantomData;
+
 /// Type's metatype
 ///
 /// `GetType` provides a metatype that's a type of a type,
 /// it also enables a developer to pass a type as a value to specify a generic type of a parameter
 /// 
+/// `GetType` trait implements asssociated constant for every type and this associated constant provides a metatype value that's a type's type value
+/// of any type that is `?Sized`
+///
 /// # Examples
 /// ```
 /// use spawn_groups::GetType;
@@ -17,12 +22,6 @@
 /// 
 /// assert_eq!(string_result, String::from("32"));
 /// ```
-/// 
-
-use std::marker::PhantomData;
-
-/// `GetType` trait implements asssociated constant for every type and this associated constant provides a metatype value that's a type's type value
-/// of any type that is `?Sized`
 pub trait GetType {
     /// Acts as a metatype value
     const TYPE: PhantomData<Self> = PhantomData;
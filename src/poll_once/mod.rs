@@ -0,0 +1,32 @@
+mod poller;
+
+pub use poller::PollOnce;
+
+use std::future::Future;
+
+/// Polls the future once with the context of the calling task, resolving to the outcome of that poll
+///
+/// A wake registered by the future during the poll wakes the calling task, so a pending future is never left without
+/// a way to make progress.
+///
+/// # Cancel safety
+/// The future is owned by the returned one and dropped along with it, so a future still pending loses its progress
+/// unless it's handed over by mutable reference, as a pinned binding from [`pin_future`](crate::pin_future) or any
+/// ``Unpin`` future.
+///
+/// # Examples
+/// ```
+/// use spawn_groups::{block_on, pin_future, poll_once, sleep};
+/// use std::{future, task::Poll, time::Duration};
+///
+/// block_on(async {
+///     let delay = sleep(Duration::from_millis(10));
+///     pin_future!(delay);
+///     assert_eq!(poll_once(delay.as_mut()).await, Poll::Pending);
+///     delay.await;
+///     assert_eq!(poll_once(future::ready(1)).await, Poll::Ready(1));
+/// });
+/// ```
+pub fn poll_once<Fut: Future + Unpin>(future: Fut) -> PollOnce<Fut> {
+    PollOnce::new(future)
+}
@@ -0,0 +1,26 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Future returned by [`poll_once`](crate::poll_once), which is ready right away with the outcome of a single poll of
+/// the future it holds
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PollOnce<Fut> {
+    future: Fut,
+}
+
+impl<Fut> PollOnce<Fut> {
+    pub(crate) fn new(future: Fut) -> Self {
+        Self { future }
+    }
+}
+
+impl<Fut: Future + Unpin> Future for PollOnce<Fut> {
+    type Output = Poll<Fut::Output>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Poll::Ready(Pin::new(&mut self.future).poll(cx))
+    }
+}
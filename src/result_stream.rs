@@ -0,0 +1,82 @@
+use crate::async_stream::AsyncStream;
+use futures_core::stream::FusedStream;
+use futures_lite::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Result Stream
+///
+/// A stream of the results of a spawn group, as returned by [`SpawnGroup::stream`](crate::SpawnGroup::stream) and
+/// [`ErrSpawnGroup::stream`](crate::ErrSpawnGroup::stream).
+///
+/// Unlike the spawn group itself, it can be stored in a struct field or returned from a trait method without borrowing
+/// the spawn group. Its clones share the results with each other and with the spawn group, so each result is delivered
+/// to whichever of them takes it first.
+///
+/// Once the spawn group is cancelled or dropped, the stream ends after yielding the results already buffered, waking
+/// any consumer still waiting on it.
+pub struct ResultStream<ItemType> {
+    stream: AsyncStream<ItemType>,
+}
+
+impl<ItemType> ResultStream<ItemType> {
+    pub(crate) fn new(stream: AsyncStream<ItemType>) -> Self {
+        Self { stream }
+    }
+
+    /// The number of results buffered, which the stream yields without waiting
+    pub fn len(&self) -> usize {
+        self.stream.buffered()
+    }
+
+    /// Whether no result is buffered, so the next one has to be waited for if the stream isn't terminated
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the stream has ended, as it has no result buffered and the spawn group has no child task left to
+    /// produce one
+    ///
+    /// A terminated stream starts yielding results again once more child tasks are spawned into the spawn group.
+    pub fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+impl<ItemType> Clone for ResultStream<ItemType> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+        }
+    }
+}
+
+impl<ItemType> fmt::Debug for ResultStream<ItemType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultStream")
+            .field("len", &self.len())
+            .field("is_terminated", &self.is_terminated())
+            .finish()
+    }
+}
+
+impl<ItemType> Stream for ResultStream<ItemType> {
+    type Item = ItemType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().stream).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+impl<ItemType> FusedStream for ResultStream<ItemType> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
@@ -0,0 +1,72 @@
+use crate::async_stream::AsyncStream;
+use futures_core::stream::FusedStream;
+use futures_lite::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Rev Stream
+///
+/// A stream of the results of a spawn group that yields the newest buffered result first, as returned by
+/// [`SpawnGroup::rev_stream`](crate::SpawnGroup::rev_stream) and
+/// [`ErrSpawnGroup::rev_stream`](crate::ErrSpawnGroup::rev_stream).
+///
+/// It suits the consumers preferring the freshest result, such as the latest reading of a sensor. It shares the
+/// results with the spawn group and the other streams of it, so each result is delivered to whichever of them takes it
+/// first, and it ends along with the stream of the spawn group.
+pub struct RevStream<ItemType> {
+    stream: AsyncStream<ItemType>,
+}
+
+impl<ItemType> RevStream<ItemType> {
+    pub(crate) fn new(stream: AsyncStream<ItemType>) -> Self {
+        Self { stream }
+    }
+
+    /// The number of results buffered, which the stream yields without waiting
+    pub fn len(&self) -> usize {
+        self.stream.buffered()
+    }
+
+    /// Whether no result is buffered, so the next one has to be waited for if the stream isn't terminated
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<ItemType> Clone for RevStream<ItemType> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+        }
+    }
+}
+
+impl<ItemType> fmt::Debug for RevStream<ItemType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RevStream")
+            .field("len", &self.len())
+            .field("is_terminated", &self.is_terminated())
+            .finish()
+    }
+}
+
+impl<ItemType> Stream for RevStream<ItemType> {
+    type Item = ItemType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_back(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+impl<ItemType> FusedStream for RevStream<ItemType> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
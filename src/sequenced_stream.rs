@@ -0,0 +1,174 @@
+use crate::{
+    async_stream::{AsyncStream, Tagged},
+    sleeper::{sleep, Delay},
+    spawn_group::SpawnGroup,
+};
+use futures_lite::Stream;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The number of results a ``SequencedStream`` buffers ahead of a missing sequence number unless told otherwise
+const DEFAULT_MAX_BUFFERED: usize = 1024;
+
+/// Sequenced
+///
+/// An item of a [`SequencedStream`], either the result of a child task or the sequence number that was skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sequenced<ValueType> {
+    /// The result of the child task spawned with the sequence number
+    Item(u64, ValueType),
+    /// The sequence number whose result was given up on, so the results after it could be delivered
+    Gap(u64),
+}
+
+/// Sequenced Stream
+///
+/// A stream of the results of a spawn group in ascending order of the sequence numbers their child tasks were spawned
+/// with through [`SpawnGroup::spawn_task_sequenced`], starting at sequence number 0, as returned by
+/// [`SpawnGroup::sequenced_stream`].
+///
+/// Results arriving ahead of a missing sequence number are buffered until it arrives. The missing sequence number is
+/// given up on, and delivered as a [`Sequenced::Gap`], once
+/// - the spawn group has no child task left that could produce it
+/// - the gap timeout set with [`SequencedStream::gap_timeout`] elapsed since the first result after it arrived
+/// - the number of results buffered after it reached the cap set with [`SequencedStream::max_buffered`], 1024 by default
+///
+/// The stream ends once the spawn group has no child task left and every buffered result was delivered.
+///
+/// # Dropping
+/// The results the stream couldn't deliver in order, which are the ones still buffered, the ones of child tasks spawned
+/// without a sequence number, the ones arriving after their sequence number was given up on or already delivered, are
+/// handed back to the front of the spawn group's buffer when it's dropped, so none of them is lost.
+pub struct SequencedStream<'a, ValueType: Send + 'static> {
+    stream: AsyncStream<ValueType>,
+    // the wave of the stream the results were taken in
+    wave: usize,
+    // the sequence number delivered next
+    next: u64,
+    // the results that arrived ahead of the next sequence number
+    buffered: BTreeMap<u64, Tagged<ValueType>>,
+    // the results that can't be delivered in order
+    set_aside: Vec<Tagged<ValueType>>,
+    gap_timeout: Option<Duration>,
+    max_buffered: usize,
+    // started once a result arrived ahead of the missing next sequence number
+    stalled: Option<Delay>,
+    // the spawn group has no child task left
+    ended: bool,
+    group: PhantomData<&'a mut SpawnGroup<ValueType>>,
+}
+
+impl<ValueType: Send + 'static> SequencedStream<'_, ValueType> {
+    pub(crate) fn new(stream: AsyncStream<ValueType>) -> Self {
+        Self {
+            wave: stream.wave(),
+            stream,
+            next: 0,
+            buffered: BTreeMap::new(),
+            set_aside: vec![],
+            gap_timeout: None,
+            max_buffered: DEFAULT_MAX_BUFFERED,
+            stalled: None,
+            ended: false,
+            group: PhantomData,
+        }
+    }
+
+    /// Gives up on a missing sequence number once the given duration elapsed since the first result after it arrived
+    ///
+    /// # Parameters
+    ///
+    /// * `timeout`: how long to wait for a missing sequence number
+    pub fn gap_timeout(mut self, timeout: Duration) -> Self {
+        self.gap_timeout = Some(timeout);
+        self
+    }
+
+    /// Gives up on a missing sequence number once the given number of results after it are buffered
+    ///
+    /// # Parameters
+    ///
+    /// * `max_buffered`: the most results buffered ahead of a missing sequence number, at least 1
+    pub fn max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered.max(1);
+        self
+    }
+
+    /// Buffers a result arriving out of order, or sets it aside if it can't be delivered in order
+    fn buffer(&mut self, item: Tagged<ValueType>) {
+        match item.0.sequence {
+            Some(sequence) if sequence >= self.next && !self.buffered.contains_key(&sequence) => {
+                self.buffered.insert(sequence, item);
+            }
+            _ => self.set_aside.push(item),
+        }
+    }
+
+    /// Moves on to the next sequence number
+    fn advance(&mut self) -> u64 {
+        self.stalled = None;
+        self.next += 1;
+        self.next - 1
+    }
+
+    /// Whether the missing next sequence number was waited for long enough, starting the wait if need be
+    fn timed_out(&mut self, cx: &mut Context<'_>) -> bool {
+        let Some(timeout) = self.gap_timeout else {
+            return false;
+        };
+        let stalled: &mut Delay = self.stalled.get_or_insert_with(|| sleep(timeout));
+        Pin::new(stalled).poll(cx).is_ready()
+    }
+}
+
+// none of the fields is ever pinned, the results are only moved in and out of the buffer
+impl<ValueType: Send + 'static> Unpin for SequencedStream<'_, ValueType> {}
+
+impl<ValueType: Send + 'static> Stream for SequencedStream<'_, ValueType> {
+    type Item = Sequenced<ValueType>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        loop {
+            if let Some((_, value)) = this.buffered.remove(&this.next) {
+                return Poll::Ready(Some(Sequenced::Item(this.advance(), value)));
+            }
+            if !this.buffered.is_empty() && (this.ended || this.buffered.len() >= this.max_buffered)
+            {
+                return Poll::Ready(Some(Sequenced::Gap(this.advance())));
+            }
+            if this.ended {
+                return Poll::Ready(None);
+            }
+            match this.stream.poll_tagged(cx) {
+                Poll::Ready(Some(item)) => this.buffer(item),
+                Poll::Ready(None) => this.ended = true,
+                Poll::Pending => {
+                    if !this.buffered.is_empty() && this.timed_out(cx) {
+                        return Poll::Ready(Some(Sequenced::Gap(this.advance())));
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<ValueType: Send + 'static> Drop for SequencedStream<'_, ValueType> {
+    fn drop(&mut self) {
+        let items: Vec<Tagged<ValueType>> = std::mem::take(&mut self.buffered)
+            .into_values()
+            .chain(std::mem::take(&mut self.set_aside))
+            .collect();
+        // each one is put back at the front, so the last one goes first to keep their order
+        for item in items.into_iter().rev() {
+            self.stream.requeue_tagged(self.wave, item);
+        }
+    }
+}
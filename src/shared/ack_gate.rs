@@ -0,0 +1,110 @@
+use parking_lot::Mutex;
+use std::{
+    mem,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default)]
+struct State {
+    // none outside of ack mode
+    limit: Option<usize>,
+    // the child tasks started whose result, if any, wasn't acknowledged yet
+    unacked: usize,
+    epoch: u64,
+    waiters: Vec<Waker>,
+}
+
+/// Keeps child tasks from starting once the given number of started child tasks have results left unacknowledged,
+/// in the ack mode of a spawn group
+///
+/// Each child task takes a slot before it starts and keeps it until it's done without a result, or, if it produces
+/// one, until its result is acknowledged. A cancellation frees every slot at once, and the slots taken before it are
+/// never freed a second time.
+#[derive(Clone, Default)]
+pub(crate) struct AckGate {
+    state: Arc<Mutex<State>>,
+}
+
+impl AckGate {
+    pub(crate) fn set_limit(&self, limit: usize) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            state.limit = Some(limit);
+            mem::take(&mut state.waiters)
+        };
+        // a raised limit may let the waiting child tasks start
+        waiters.into_iter().for_each(Waker::wake);
+    }
+
+    /// Ready with a slot once fewer than the limit of started child tasks are unacknowledged, right away outside of
+    /// ack mode
+    pub(crate) fn poll_slot(&self, cx: &mut Context<'_>) -> Poll<AckSlot> {
+        let mut state = self.state.lock();
+        let Some(limit) = state.limit else {
+            return Poll::Ready(AckSlot(None));
+        };
+        if state.unacked < limit {
+            state.unacked += 1;
+            return Poll::Ready(AckSlot(Some((self.clone(), state.epoch))));
+        }
+        if !state
+            .waiters
+            .iter()
+            .any(|registered| registered.will_wake(cx.waker()))
+        {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Frees the slot taken during the given epoch, unless a cancellation freed it already
+    pub(crate) fn release(&self, epoch: u64) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            if state.epoch != epoch {
+                return;
+            }
+            state.unacked -= 1;
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+
+    /// Frees every slot taken so far, which a cancellation leaves unacknowledged for good
+    pub(crate) fn release_all(&self) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            state.epoch += 1;
+            state.unacked = 0;
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// The slot of a started child task, freed when it's dropped unless it's kept for the result of the child task
+pub(crate) struct AckSlot(Option<(AckGate, u64)>);
+
+impl AckSlot {
+    /// The slot kept for a result during the given epoch, freed once the result is acknowledged
+    pub(crate) fn kept(gate: &AckGate, epoch: Option<u64>) -> Self {
+        Self(epoch.map(|epoch| (gate.clone(), epoch)))
+    }
+
+    /// Keeps the slot taken until the result of the child task is acknowledged
+    ///
+    /// # Returns
+    /// The epoch the slot was taken during, none outside of ack mode
+    pub(crate) fn keep(mut self) -> Option<u64> {
+        self.0.take().map(|(_, epoch)| epoch)
+    }
+}
+
+impl Drop for AckSlot {
+    fn drop(&mut self) {
+        if let Some((gate, epoch)) = self.0.take() {
+            gate.release(epoch);
+        }
+    }
+}
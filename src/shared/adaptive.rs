@@ -0,0 +1,174 @@
+use crate::shared::metrics::Metrics;
+use parking_lot::Mutex;
+use std::{
+    mem,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// Adaptive Config
+///
+/// The bounds and the latency goal of the adaptive concurrency of a spawn group, as set with the
+/// ``adaptive_concurrency`` method of the spawn group
+///
+/// The spawn group keeps a window of child tasks allowed to run at once, starting at ``min``. Every child task
+/// finishing within ``target_latency`` widens the window by one over its width, so by one child task for each full
+/// window of them, up to ``max``. A child task taking longer than ``target_latency``, panicking, or failing in an
+/// ``ErrSpawnGroup``, halves the window down to ``min``. Only the child tasks started since the last halving can
+/// halve it again, so a burst of slow child tasks halves it once rather than once per child task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveConfig {
+    /// The fewest child tasks allowed to run at once, and where the window starts
+    pub min: usize,
+    /// The most child tasks allowed to run at once
+    pub max: usize,
+    /// How long a child task may take, from its first poll until it completes, before the window is narrowed
+    pub target_latency: Duration,
+}
+
+struct State {
+    // none unless the concurrency is adaptive
+    config: Option<AdaptiveConfig>,
+    window: f64,
+    // the child tasks started and not done yet
+    in_flight: usize,
+    // bumped on every halving, and on every new config
+    round: u64,
+    // bumped on every cancellation, which frees the slots taken before it
+    epoch: u64,
+    waiters: Vec<Waker>,
+}
+
+impl State {
+    fn limit(&self, config: &AdaptiveConfig) -> usize {
+        (self.window as usize).clamp(config.min, config.max)
+    }
+}
+
+/// Keeps child tasks from starting once the window of the adaptive concurrency is full, widening and narrowing it as
+/// the child tasks finish
+///
+/// Each child task takes a slot before it starts and frees it once it's done, feeding how long it ran and whether it
+/// failed to the controller unless it was cut off. The current width is published to the metrics of the spawn group.
+#[derive(Clone)]
+pub(crate) struct ConcurrencyWindow {
+    state: Arc<Mutex<State>>,
+    metrics: Metrics,
+}
+
+impl ConcurrencyWindow {
+    pub(crate) fn new(metrics: Metrics) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                config: None,
+                window: 0.0,
+                in_flight: 0,
+                round: 0,
+                epoch: 0,
+                waiters: vec![],
+            })),
+            metrics,
+        }
+    }
+
+    pub(crate) fn set(&self, config: AdaptiveConfig) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            state.config = Some(config);
+            state.window = config.min as f64;
+            state.round += 1;
+            self.metrics.record_window(config.min);
+            mem::take(&mut state.waiters)
+        };
+        // a wider window may let the waiting child tasks start
+        waiters.into_iter().for_each(Waker::wake);
+    }
+
+    /// Ready with a slot once the window has room for another child task, right away unless the concurrency is
+    /// adaptive
+    pub(crate) fn poll_slot(&self, cx: &mut Context<'_>) -> Poll<WindowSlot> {
+        let mut state = self.state.lock();
+        let Some(config) = state.config else {
+            return Poll::Ready(WindowSlot(None));
+        };
+        if state.in_flight < state.limit(&config) {
+            state.in_flight += 1;
+            return Poll::Ready(WindowSlot(Some(Taken {
+                window: self.clone(),
+                round: state.round,
+                epoch: state.epoch,
+            })));
+        }
+        if !state
+            .waiters
+            .iter()
+            .any(|registered| registered.will_wake(cx.waker()))
+        {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Frees the given slot unless a cancellation freed it already, adjusting the window with how the child task went
+    /// if it finished
+    fn release(&self, taken: &Taken, sample: Option<(Duration, bool)>) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            if state.epoch != taken.epoch {
+                return;
+            }
+            state.in_flight -= 1;
+            if let (Some(config), Some((ran_for, failed))) = (state.config, sample) {
+                if !failed && ran_for <= config.target_latency {
+                    state.window = (state.window + 1.0 / state.window).min(config.max as f64);
+                } else if taken.round == state.round {
+                    state.window = (state.window / 2.0).max(config.min as f64);
+                    state.round += 1;
+                }
+                self.metrics.record_window(state.limit(&config));
+            }
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+
+    /// Frees every slot taken so far, as the child tasks a cancellation cut off may never run again to free theirs
+    pub(crate) fn release_all(&self) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            state.epoch += 1;
+            state.in_flight = 0;
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+// a slot along with the round and the epoch it was taken during
+struct Taken {
+    window: ConcurrencyWindow,
+    round: u64,
+    epoch: u64,
+}
+
+/// The slot of a started child task, freed without adjusting the window when it's dropped before the child task
+/// finished
+pub(crate) struct WindowSlot(Option<Taken>);
+
+impl WindowSlot {
+    /// Frees the slot of a child task that finished after running for the given duration
+    pub(crate) fn finish(mut self, ran_for: Duration, failed: bool) {
+        if let Some(taken) = self.0.take() {
+            taken.window.release(&taken, Some((ran_for, failed)));
+        }
+    }
+}
+
+impl Drop for WindowSlot {
+    fn drop(&mut self) {
+        if let Some(taken) = self.0.take() {
+            taken.window.release(&taken, None);
+        }
+    }
+}
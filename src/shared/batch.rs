@@ -0,0 +1,86 @@
+use crate::shared::{completion_counts::CompletionCounts, spawn_error::SpawnError};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    OnceLock,
+};
+
+/// The exact number of child tasks a spawn group in exclusive batch mode spawns, as declared with its
+/// ``exclusive_batch`` method
+///
+/// Rather than registering, capping and counting the outcome of every child task, the spawn group counts the batch
+/// down twice, once as its child tasks are spawned and once as they complete.
+#[derive(Default)]
+pub(crate) struct Batch {
+    size: OnceLock<usize>,
+    unspawned: AtomicUsize,
+    unfinished: AtomicUsize,
+    // the child tasks that didn't complete are rare, so they're only counted on their own path
+    panicked: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Batch {
+    /// Declares the number of child tasks of the batch, once
+    ///
+    /// # Returns
+    /// Whether the batch wasn't declared before
+    pub(crate) fn declare(&self, size: usize) -> bool {
+        if self.size.set(size).is_err() {
+            return false;
+        }
+        self.unspawned.store(size, Ordering::Release);
+        self.unfinished.store(size, Ordering::Release);
+        true
+    }
+
+    /// The number of child tasks declared, none outside of batch mode
+    pub(crate) fn size(&self) -> Option<usize> {
+        self.size.get().copied()
+    }
+
+    /// Counts the given number of child tasks down as spawned, unless the batch doesn't have that many left
+    ///
+    /// # Returns
+    /// The place of the first of them in the batch
+    pub(crate) fn reserve(&self, size: usize, count: usize) -> Result<u64, SpawnError> {
+        self.unspawned
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |unspawned| {
+                unspawned.checked_sub(count)
+            })
+            .map(|unspawned| (size - unspawned) as u64)
+            .map_err(|_| SpawnError::BatchComplete(size))
+    }
+
+    pub(crate) fn complete(&self) {
+        self.unfinished.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn panic(&self) {
+        self.panicked.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Every child task spawned that hasn't completed or panicked by now counts as cancelled from now on
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn spawned(&self) -> usize {
+        let size: usize = self.size().unwrap_or_default();
+        size - self.unspawned.load(Ordering::Acquire).min(size)
+    }
+
+    pub(crate) fn counts(&self) -> CompletionCounts {
+        let size: usize = self.size().unwrap_or_default();
+        let completed: usize = size - self.unfinished.load(Ordering::Acquire).min(size);
+        let panicked: usize = self.panicked.load(Ordering::Acquire);
+        let cancelled: usize = match self.cancelled.load(Ordering::Acquire) {
+            true => self.spawned().saturating_sub(completed + panicked),
+            false => 0,
+        };
+        CompletionCounts {
+            completed: completed as u64,
+            cancelled: cancelled as u64,
+            panicked: panicked as u64,
+        }
+    }
+}
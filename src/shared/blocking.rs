@@ -0,0 +1,196 @@
+use crate::shared::panic_output::PanicOutput;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    future::{poll_fn, Future},
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    task::Poll,
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+/// The shortest pause of the watchdog between two sweeps, so a tiny threshold doesn't turn it into a busy loop
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_millis(1);
+
+/// What the watchdog of a spawn group does about the child tasks blocking their worker thread
+#[derive(Clone, Copy)]
+struct BlockingCheck {
+    // the longest a single poll may run before the child task counts as blocking the executor
+    threshold: Duration,
+    // whether the child task panics once its blocking poll returns, after the warning
+    panic: bool,
+}
+
+/// A poll of a child task in progress
+struct InFlight {
+    started: Instant,
+    name: Option<Arc<str>>,
+    thread: Thread,
+    // whether the warning about the poll was emitted already, so it's emitted once per poll
+    reported: bool,
+}
+
+impl InFlight {
+    /// Formats the warning about the poll unless it was reported already, which is emitted once the polls are
+    /// unlocked so a slow output doesn't hold up the other polls
+    fn report(&mut self, id: u64, threshold: Duration) -> Option<String> {
+        let elapsed: Duration = self.started.elapsed();
+        if self.reported || elapsed < threshold {
+            return None;
+        }
+        self.reported = true;
+        let name: String = self
+            .name
+            .as_deref()
+            .map_or_else(String::new, |name| format!(" '{name}'"));
+        Some(format!(
+            "warning: child task {id}{name} has been blocking the executor for {elapsed:?} without yielding, on \
+             thread '{}'",
+            self.thread.name().unwrap_or("<unnamed>")
+        ))
+    }
+}
+
+struct WatchState {
+    check: Mutex<Option<BlockingCheck>>,
+    // the polls in progress by the id of their child task, only kept while a check is set
+    polls: Mutex<HashMap<u64, InFlight>>,
+    // whether the watchdog thread was started
+    sweeping: AtomicBool,
+    output: Arc<Mutex<PanicOutput>>,
+}
+
+/// Watches the polls of the child tasks of a spawn group, flagging the ones that run for longer than a threshold
+/// without yielding, which block their worker thread and the child tasks queued behind them
+///
+/// A watchdog thread sweeps the polls in progress so a blocking child task is reported while it's still blocking,
+/// and it exits once the spawn group is gone.
+#[derive(Clone)]
+pub(crate) struct BlockingWatch {
+    state: Arc<WatchState>,
+}
+
+impl BlockingWatch {
+    /// Creates a disabled watch, whose warnings go to the given panic output once enabled
+    pub(crate) fn new(output: Arc<Mutex<PanicOutput>>) -> Self {
+        Self {
+            state: Arc::new(WatchState {
+                check: Mutex::new(None),
+                polls: Mutex::new(HashMap::new()),
+                sweeping: AtomicBool::new(false),
+                output,
+            }),
+        }
+    }
+
+    /// Sets the threshold past which a poll counts as blocking, and whether it panics after the warning
+    pub(crate) fn set(&self, threshold: Duration, panic: bool) {
+        *self.state.check.lock() = Some(BlockingCheck { threshold, panic });
+        if !self.state.sweeping.swap(true, Ordering::AcqRel) {
+            let state: Weak<WatchState> = Arc::downgrade(&self.state);
+            let spawned = thread::Builder::new()
+                .name("BlockingWatchdog".to_string())
+                .spawn(move || sweep(state));
+            // without a watchdog thread the blocking polls are still reported once they return
+            if spawned.is_err() {
+                self.state.sweeping.store(false, Ordering::Release);
+            }
+        }
+    }
+
+    /// Runs the future with each of its polls timed, once the check is enabled
+    pub(crate) async fn watch<F: Future>(
+        self,
+        id: u64,
+        name: Option<Arc<str>>,
+        future: F,
+    ) -> F::Output {
+        let mut future = pin!(future);
+        poll_fn(|cx| self.timed(id, &name, || future.as_mut().poll(cx))).await
+    }
+
+    /// Times the poll, which is reported once it returns if the watchdog didn't catch it while it was running, and
+    /// panics after it if the check says so
+    fn timed<T>(
+        &self,
+        id: u64,
+        name: &Option<Arc<str>>,
+        poll: impl FnOnce() -> Poll<T>,
+    ) -> Poll<T> {
+        if self.state.check.lock().is_none() {
+            return poll();
+        }
+        let started: Instant = Instant::now();
+        self.state.polls.lock().insert(
+            id,
+            InFlight {
+                started,
+                name: name.clone(),
+                thread: thread::current(),
+                reported: false,
+            },
+        );
+        // the entry goes away even if the poll unwinds
+        let _entry = EntryGuard(self, id);
+        let result: Poll<T> = poll();
+        let elapsed: Duration = started.elapsed();
+        let Some(check) = *self.state.check.lock() else {
+            return result;
+        };
+        if elapsed < check.threshold {
+            return result;
+        }
+        let report: Option<String> = self
+            .state
+            .polls
+            .lock()
+            .get_mut(&id)
+            .and_then(|in_flight| in_flight.report(id, check.threshold));
+        if let Some(report) = report {
+            self.state.output.lock().emit(&report);
+        }
+        if check.panic {
+            panic!(
+                "blocked the executor for {elapsed:?} without yielding, over the threshold of {:?}",
+                check.threshold
+            );
+        }
+        result
+    }
+}
+
+/// Removes the entry of a poll once it returns or unwinds
+struct EntryGuard<'a>(&'a BlockingWatch, u64);
+
+impl Drop for EntryGuard<'_> {
+    fn drop(&mut self) {
+        self.0.state.polls.lock().remove(&self.1);
+    }
+}
+
+/// The loop of the watchdog thread, reporting the polls running past the threshold
+fn sweep(state: Weak<WatchState>) {
+    loop {
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        let Some(check) = *state.check.lock() else {
+            return;
+        };
+        let reports: Vec<String> = state
+            .polls
+            .lock()
+            .iter_mut()
+            .filter_map(|(id, in_flight)| in_flight.report(*id, check.threshold))
+            .collect();
+        let output: PanicOutput = *state.output.lock();
+        reports.iter().for_each(|report| output.emit(report));
+        // the spawn group isn't kept alive while the watchdog sleeps
+        drop(state);
+        thread::sleep((check.threshold / 4).max(MIN_SWEEP_INTERVAL));
+    }
+}
@@ -0,0 +1,111 @@
+use parking_lot::Mutex;
+use std::{
+    cell::RefCell,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+/// Where a child task traced by its spawn group last went through, left by its polls and the trace points it passed
+#[derive(Default)]
+pub(crate) struct Breadcrumb {
+    // the last trace point the child task went through, along with when
+    label: Option<(&'static str, Instant)>,
+    polls: u64,
+    // when the last poll of the child task returned pending, none while it's being polled
+    suspended_at: Option<Instant>,
+}
+
+impl Breadcrumb {
+    pub(crate) fn trace_point(&self) -> Option<&'static str> {
+        self.label.map(|(label, _)| label)
+    }
+
+    pub(crate) fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// The time the suspended child task has been pending since its last trace point, or since its last poll
+    /// without one
+    pub(crate) fn suspended_for(&self) -> Option<Duration> {
+        let suspended_at: Instant = self.suspended_at?;
+        let since: Instant = self.label.map_or(suspended_at, |(_, at)| at);
+        Some(since.elapsed())
+    }
+}
+
+/// The breadcrumb of a single child task, shared by its registry record and the worker thread polling it
+pub(crate) type BreadcrumbSlot = Arc<Mutex<Breadcrumb>>;
+
+thread_local! {
+    static CURRENT: RefCell<Option<BreadcrumbSlot>> = const { RefCell::new(None) };
+}
+
+/// Restores the slot the thread was polling for before entering the scope, even when unwinding out of it
+struct Scope(Option<BreadcrumbSlot>);
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        CURRENT.set(self.0.take());
+    }
+}
+
+/// Polls a child task with the trace points it goes through left in its slot, counting the poll and noting whether
+/// the child task suspended
+pub(crate) fn polling<T>(slot: &BreadcrumbSlot, poll: impl FnOnce() -> Poll<T>) -> Poll<T> {
+    slot.lock().suspended_at = None;
+    let scope: Scope = Scope(CURRENT.replace(Some(slot.clone())));
+    let result: Poll<T> = poll();
+    drop(scope);
+    let mut breadcrumb = slot.lock();
+    breadcrumb.polls += 1;
+    if result.is_pending() {
+        breadcrumb.suspended_at = Some(Instant::now());
+    }
+    result
+}
+
+/// Leaves the label in the slot of the child task being polled on the calling thread, which ``trace_point!`` expands
+/// to
+///
+/// It does nothing outside of a child task, or for a child task its spawn group doesn't trace.
+#[doc(hidden)]
+pub fn mark(label: &'static str) {
+    CURRENT.with_borrow(|slot| {
+        if let Some(slot) = slot {
+            slot.lock().label = Some((label, Instant::now()));
+        }
+    });
+}
+
+/// Marks the point a child task reached, which the state dump of its spawn group reports while the child task is
+/// suspended after it
+///
+/// Placed right before an ``.await`` that may suspend for long, it tells where a hanging child task waits, as in
+/// "task 3 pending at 'db_query' for 42s". It only records anything for the child tasks spawned once the spawn group
+/// traces suspensions, see [`SpawnGroup::trace_suspensions`](crate::SpawnGroup::trace_suspensions), and costs a check
+/// of a thread-local otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{sleep, trace_point, Priority, SpawnGroup};
+/// use std::time::Duration;
+///
+/// let mut group = SpawnGroup::<u8>::new(1);
+/// group.trace_suspensions(true);
+/// group.spawn_task(Priority::default(), async {
+///     trace_point!("db_query");
+///     sleep(Duration::from_secs(3600)).await;
+///     0
+/// });
+/// std::thread::sleep(Duration::from_millis(50));
+/// assert!(group.dump_state().contains("pending at 'db_query'"));
+/// group.cancel_all();
+/// ```
+#[macro_export]
+macro_rules! trace_point {
+    ($label:expr) => {
+        $crate::__mark_trace_point($label)
+    };
+}
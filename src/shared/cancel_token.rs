@@ -0,0 +1,25 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Tells whether the spawn group it was taken from has been cancelled since
+#[derive(Clone)]
+pub(crate) struct CancelToken {
+    generation: Arc<AtomicUsize>,
+    taken_in: usize,
+}
+
+impl CancelToken {
+    pub(crate) fn new(generation: Arc<AtomicUsize>) -> Self {
+        let taken_in: usize = generation.load(Ordering::Acquire);
+        Self {
+            generation,
+            taken_in,
+        }
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.generation.load(Ordering::Acquire) != self.taken_in
+    }
+}
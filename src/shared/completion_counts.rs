@@ -0,0 +1,147 @@
+use crate::{
+    shared::{metrics::Metrics, registry::TaskRecord},
+    threadpool_impl::{ExecutionLog, ExecutionOutcome},
+};
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Completion Counts
+///
+/// How the child tasks of a spawn group ended since it was created or last reset, as returned by
+/// [`DiscardingSpawnGroup::wait_for_all`](crate::DiscardingSpawnGroup::wait_for_all)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompletionCounts {
+    /// Number of child tasks that ran to completion
+    pub completed: u64,
+    /// Number of child tasks that were cancelled, whether they had started or not
+    pub cancelled: u64,
+    /// Number of child tasks that panicked
+    pub panicked: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    completed: AtomicU64,
+    cancelled: AtomicU64,
+    panicked: AtomicU64,
+    // the epoch along with the number of its child tasks not counted yet, bumped by a reset or a cut
+    live: Mutex<(usize, u64)>,
+}
+
+/// Counters of how the child tasks of a spawn group ended, kept without storing anything per child task
+#[derive(Clone, Default)]
+pub(crate) struct CompletionCounters {
+    counters: Arc<Counters>,
+}
+
+impl CompletionCounters {
+    /// Returns the guard of a newly spawned child task, which counts it as cancelled unless told otherwise
+    ///
+    /// Besides the counters, a child task that panicked or was cancelled is counted in the metrics as well, while a
+    /// completed one is counted there along with its durations by whoever completed it. Once started, the child task
+    /// is recorded in the execution log however it ends, even if it's dropped while waiting to be polled again.
+    pub(crate) fn guard(&self, metrics: Metrics, record: Arc<TaskRecord>) -> CompletionGuard {
+        metrics.record_spawned();
+        let epoch: usize = {
+            let mut live = self.counters.live.lock();
+            live.1 += 1;
+            live.0
+        };
+        CompletionGuard {
+            counters: self.clone(),
+            epoch,
+            outcome: ExecutionOutcome::Cancelled,
+            metrics,
+            record,
+            started: None,
+        }
+    }
+
+    pub(crate) fn counts(&self) -> CompletionCounts {
+        CompletionCounts {
+            completed: self.counters.completed.load(Ordering::Acquire),
+            cancelled: self.counters.cancelled.load(Ordering::Acquire),
+            panicked: self.counters.panicked.load(Ordering::Acquire),
+        }
+    }
+
+    /// Zeroes the counters, child tasks spawned before aren't counted afterwards
+    pub(crate) fn reset(&self) {
+        let mut live = self.counters.live.lock();
+        *live = (live.0.wrapping_add(1), 0);
+        self.counters.completed.store(0, Ordering::Release);
+        self.counters.cancelled.store(0, Ordering::Release);
+        self.counters.panicked.store(0, Ordering::Release);
+    }
+
+    /// Counts the child tasks not counted yet as cancelled right away
+    ///
+    /// A cancellation doesn't wait for the child tasks it cuts off to be dropped, so they're counted here instead and
+    /// not again once dropped, even if a poll in flight during the cut ran them to completion.
+    pub(crate) fn cut(&self) {
+        let mut live = self.counters.live.lock();
+        self.counters.cancelled.fetch_add(live.1, Ordering::AcqRel);
+        *live = (live.0.wrapping_add(1), 0);
+    }
+}
+
+/// Counts the outcome of a child task once it's dropped, which happens even if it was never polled
+pub(crate) struct CompletionGuard {
+    counters: CompletionCounters,
+    epoch: usize,
+    outcome: ExecutionOutcome,
+    metrics: Metrics,
+    record: Arc<TaskRecord>,
+    started: Option<(ExecutionLog, Instant)>,
+}
+
+impl CompletionGuard {
+    pub(crate) fn set(&mut self, outcome: ExecutionOutcome) {
+        self.outcome = outcome;
+    }
+
+    /// Marks the child task as first polled at the given instant
+    pub(crate) fn start(&mut self, log: ExecutionLog, started_at: Instant) {
+        self.started = Some((log, started_at));
+    }
+}
+
+impl Drop for CompletionGuard {
+    fn drop(&mut self) {
+        if let Some((log, started_at)) = &self.started {
+            if log.is_enabled() {
+                log.record(
+                    self.record.id,
+                    self.record.priority,
+                    *started_at,
+                    self.outcome,
+                );
+            }
+        }
+        // the metrics have a reset of their own, so they count the child task whatever the epoch
+        match self.outcome {
+            ExecutionOutcome::Completed => {}
+            ExecutionOutcome::Panicked => self.metrics.record_panicked(),
+            ExecutionOutcome::Cancelled => self.metrics.record_cancelled(self.record.is_started()),
+        }
+        let counters: &Counters = &self.counters.counters;
+        // counted under the lock, so a reset or a cut can't slip between the epoch check and the count
+        let mut live = counters.live.lock();
+        if live.0 != self.epoch {
+            return;
+        }
+        live.1 -= 1;
+        let counter: &AtomicU64 = match self.outcome {
+            ExecutionOutcome::Completed => &counters.completed,
+            ExecutionOutcome::Cancelled => &counters.cancelled,
+            ExecutionOutcome::Panicked => &counters.panicked,
+        };
+        counter.fetch_add(1, Ordering::AcqRel);
+    }
+}
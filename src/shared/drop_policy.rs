@@ -0,0 +1,20 @@
+/// Group Drop Policy
+///
+/// What a spawn group does with its remaining child tasks when it's dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupDropPolicy {
+    /// Waits for every remaining child task to finish before the spawn group is torn down
+    ///
+    /// The wait is bounded by the drop wait cap of the spawn group, if one was set, past which the remaining child tasks
+    /// are cancelled instead.
+    WaitForAll,
+    /// Cancels the remaining child tasks right away as ``cancel_all`` does, without waiting for the ones being polled,
+    /// which is what ``new`` does
+    CancelAll,
+    /// Returns right away and leaves the remaining child tasks running in the background
+    ///
+    /// The threads of the spawn group outlive it until the last of its child tasks finished, and the results they
+    /// produce are dropped. No child task can be spawned into the spawn group anymore, not even through a spawner
+    /// outliving it.
+    Detach,
+}
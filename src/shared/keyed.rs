@@ -0,0 +1,30 @@
+use std::cmp::Ordering;
+
+/// A value ordered by its key alone, ties are broken by the order the values arrived in
+pub(crate) struct Keyed<Key, Value> {
+    pub(crate) key: Key,
+    pub(crate) arrival: usize,
+    pub(crate) value: Value,
+}
+
+impl<Key: Ord, Value> PartialEq for Keyed<Key, Value> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Key: Ord, Value> Eq for Keyed<Key, Value> {}
+
+impl<Key: Ord, Value> PartialOrd for Keyed<Key, Value> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Key: Ord, Value> Ord for Keyed<Key, Value> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then(self.arrival.cmp(&other.arrival))
+    }
+}
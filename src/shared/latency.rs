@@ -0,0 +1,15 @@
+use crate::shared::priority::Priority;
+use std::{sync::Arc, time::Duration};
+
+/// Queue Latency
+///
+/// How long a child task waited in the queue before a worker thread picked it up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueLatency {
+    /// The priority the child task was spawned with
+    pub priority: Priority,
+    /// The time between spawning the child task and a worker thread picking it up
+    pub queued_for: Duration,
+}
+
+pub(crate) type DequeueHook = Arc<dyn Fn(QueueLatency) + Send + Sync>;
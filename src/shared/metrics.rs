@@ -0,0 +1,442 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Number of per-second buckets kept, which is also the longest window that can be queried
+const BUCKETS: usize = 60;
+
+/// Number of power-of-two microsecond bins of the task duration histogram
+const BINS: usize = 40;
+
+/// Marks a bucket that doesn't hold any second yet
+const EMPTY: u64 = u64::MAX;
+
+/// The bytes of the results buffered by every spawn group of the process
+static TOTAL_BUFFERED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the approximate number of bytes of the results buffered by every spawn group of the process, waiting to be
+/// consumed
+///
+/// It's the sum of [`Metrics::buffered_bytes`] over every spawn group alive, see
+/// [`ResultSize`](crate::ResultSize) for how the results are measured.
+pub fn total_buffered_bytes() -> usize {
+    TOTAL_BUFFERED_BYTES.load(Ordering::Relaxed)
+}
+
+struct Bucket {
+    second: AtomicU64,
+    completed: AtomicU64,
+    queue_nanos: AtomicU64,
+    durations: [AtomicU64; BINS],
+}
+
+impl Bucket {
+    fn new() -> Self {
+        Self {
+            second: AtomicU64::new(EMPTY),
+            completed: AtomicU64::new(0),
+            queue_nanos: AtomicU64::new(0),
+            durations: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+    fn clear(&self) {
+        self.completed.store(0, Ordering::Relaxed);
+        self.queue_nanos.store(0, Ordering::Relaxed);
+        self.durations
+            .iter()
+            .for_each(|bin| bin.store(0, Ordering::Relaxed));
+    }
+}
+
+struct Counters {
+    epoch: Instant,
+    spawned: AtomicU64,
+    completed: AtomicU64,
+    panicked: AtomicU64,
+    cancelled_queued: AtomicU64,
+    cancelled_running: AtomicU64,
+    produced: AtomicU64,
+    consumed: AtomicU64,
+    discarded: AtomicU64,
+    over_cap: AtomicU64,
+    overflowed: AtomicU64,
+    // the width of the adaptive concurrency window, zero unless the concurrency is adaptive
+    window: AtomicU64,
+    buffered_bytes: AtomicUsize,
+    buckets: [Bucket; BUCKETS],
+}
+
+/// Index of the histogram bin of the given duration, bin ``i`` holds durations below ``2^i`` microseconds
+fn bin_of(duration: Duration) -> usize {
+    let micros: u64 = duration.as_micros().min(u64::MAX as u128) as u64;
+    (u64::BITS - micros.leading_zeros()).min(BINS as u32 - 1) as usize
+}
+
+/// Metrics
+///
+/// Counters of the child tasks completed by a spawn group, which grow monotonically until ``reset()`` is called.
+///
+/// Besides the totals, the completions of the trailing minute are kept in a ring of per-second buckets updated by the
+/// worker threads, from which ``window()`` computes windowed rates. Recording a completion only costs a few atomic operations.
+///
+/// Child tasks that panicked or were cancelled aren't counted as completed, nor in the windows, but have counters of
+/// their own: every child task spawned is counted once it ends as exactly one of completed, panicked, cancelled while
+/// queued or cancelled while running, so these add up to ``spawned()`` once the spawn group is idle. The child tasks of
+/// an exclusive batch and the futures handed to ``run`` aren't counted at all.
+///
+/// The results of the child tasks are accounted for as well: every result buffered by the spawn group is counted as
+/// produced, then either as consumed once taken from its stream, or as discarded once dropped without ever being
+/// consumed, like the results still buffered when the spawn group is reset or torn down. A result handed back with
+/// ``requeue_result`` is produced again.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::Metrics;
+/// use std::time::{Duration, Instant};
+///
+/// let metrics = Metrics::new();
+/// let start = Instant::now();
+///
+/// // a synthetic workload of 10 child tasks per second for 5 seconds
+/// for second in 0..5 {
+///     for _ in 0..10 {
+///         metrics.record_at(
+///             start + Duration::from_secs(second),
+///             Duration::from_millis(2),
+///             Duration::from_micros(512),
+///         );
+///     }
+/// }
+/// // a single slow child task in the last second
+/// metrics.record_at(start + Duration::from_secs(4), Duration::from_millis(2), Duration::from_secs(1));
+///
+/// let window = metrics.window_at(start + Duration::from_secs(4), Duration::from_secs(2));
+/// assert_eq!(window.completed, 21);
+/// assert_eq!(window.tasks_per_sec, 10.5);
+/// assert_eq!(window.average_queue_latency, Duration::from_millis(2));
+/// assert_eq!(window.p95_task_duration, Duration::from_micros(1024));
+/// assert_eq!(metrics.completed(), 51);
+///
+/// metrics.reset();
+/// assert_eq!(metrics.completed(), 0);
+/// assert_eq!(metrics.window(Duration::from_secs(60)).completed, 0);
+/// ```
+#[derive(Clone)]
+pub struct Metrics {
+    counters: Arc<Counters>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Creates an empty set of counters
+    pub fn new() -> Self {
+        Self {
+            counters: Arc::new(Counters {
+                epoch: Instant::now(),
+                spawned: AtomicU64::new(0),
+                completed: AtomicU64::new(0),
+                panicked: AtomicU64::new(0),
+                cancelled_queued: AtomicU64::new(0),
+                cancelled_running: AtomicU64::new(0),
+                produced: AtomicU64::new(0),
+                consumed: AtomicU64::new(0),
+                discarded: AtomicU64::new(0),
+                over_cap: AtomicU64::new(0),
+                overflowed: AtomicU64::new(0),
+                window: AtomicU64::new(0),
+                buffered_bytes: AtomicUsize::new(0),
+                buckets: std::array::from_fn(|_| Bucket::new()),
+            }),
+        }
+    }
+
+    /// Total number of child tasks spawned since creation or the last ``reset()``
+    pub fn spawned(&self) -> u64 {
+        self.counters.spawned.load(Ordering::Relaxed)
+    }
+
+    /// Total number of child tasks completed since creation or the last ``reset()``
+    pub fn completed(&self) -> u64 {
+        self.counters.completed.load(Ordering::Relaxed)
+    }
+
+    /// Total number of child tasks that panicked since creation or the last ``reset()``
+    pub fn panicked(&self) -> u64 {
+        self.counters.panicked.load(Ordering::Relaxed)
+    }
+
+    /// Total number of child tasks cancelled since creation or the last ``reset()``, whether they had started or not
+    pub fn cancelled(&self) -> u64 {
+        self.cancelled_queued() + self.cancelled_running()
+    }
+
+    /// Total number of child tasks cancelled since creation or the last ``reset()`` while still waiting to be polled
+    /// for the first time
+    pub fn cancelled_queued(&self) -> u64 {
+        self.counters.cancelled_queued.load(Ordering::Relaxed)
+    }
+
+    /// Total number of child tasks cancelled since creation or the last ``reset()`` after they were first polled,
+    /// including the ones a middleware completed without running them to completion
+    pub fn cancelled_running(&self) -> u64 {
+        self.counters.cancelled_running.load(Ordering::Relaxed)
+    }
+
+    /// Total number of results buffered since creation or the last ``reset()``
+    pub fn results_produced(&self) -> u64 {
+        self.counters.produced.load(Ordering::Relaxed)
+    }
+
+    /// Total number of results taken from the stream since creation or the last ``reset()``
+    pub fn results_consumed(&self) -> u64 {
+        self.counters.consumed.load(Ordering::Relaxed)
+    }
+
+    /// Total number of results dropped without ever being consumed since creation or the last ``reset()``
+    pub fn results_discarded(&self) -> u64 {
+        self.counters.discarded.load(Ordering::Relaxed)
+    }
+
+    /// Total number of results dropped since creation or the last ``reset()`` because the result buffer of the spawn
+    /// group was full, which are counted as discarded as well
+    pub fn results_overflowed(&self) -> u64 {
+        self.counters.overflowed.load(Ordering::Relaxed)
+    }
+
+    /// Total number of child tasks refused since creation or the last ``reset()`` because the spawn group had spawned
+    /// its maximum number of child tasks
+    pub fn spawns_over_cap(&self) -> u64 {
+        self.counters.over_cap.load(Ordering::Relaxed)
+    }
+
+    /// The number of child tasks the adaptive concurrency currently lets run at once, none unless the concurrency of
+    /// the spawn group is adaptive
+    ///
+    /// Unlike the counters, it's left as is by ``reset()``. See [`AdaptiveConfig`](crate::AdaptiveConfig) for more.
+    pub fn concurrency_window(&self) -> Option<usize> {
+        match self.counters.window.load(Ordering::Relaxed) {
+            0 => None,
+            window => Some(window as usize),
+        }
+    }
+
+    /// The approximate number of bytes of the results buffered by the spawn group, waiting to be consumed
+    ///
+    /// Unlike the counters, it's left as is by ``reset()``. See [`ResultSize`](crate::ResultSize) for how the results
+    /// are measured.
+    pub fn buffered_bytes(&self) -> usize {
+        self.counters.buffered_bytes.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_buffered_bytes(&self, bytes: usize) {
+        self.counters
+            .buffered_bytes
+            .fetch_add(bytes, Ordering::Relaxed);
+        TOTAL_BUFFERED_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_unbuffered_bytes(&self, bytes: usize) {
+        self.counters
+            .buffered_bytes
+            .fetch_sub(bytes, Ordering::Relaxed);
+        TOTAL_BUFFERED_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_window(&self, window: usize) {
+        self.counters.window.store(window as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_spawned(&self) {
+        self.counters.spawned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_panicked(&self) {
+        self.counters.panicked.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cancelled(&self, started: bool) {
+        let counter: &AtomicU64 = match started {
+            true => &self.counters.cancelled_running,
+            false => &self.counters.cancelled_queued,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_produced(&self) {
+        self.counters.produced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_consumed(&self) {
+        self.counters.consumed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_discarded(&self, count: usize) {
+        self.counters
+            .discarded
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_overflowed(&self, count: usize) {
+        self.counters
+            .overflowed
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_over_cap(&self, count: usize) {
+        self.counters
+            .over_cap
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Zeroes every counter and bucket, mostly useful for isolating tests
+    pub fn reset(&self) {
+        self.counters.spawned.store(0, Ordering::Relaxed);
+        self.counters.completed.store(0, Ordering::Relaxed);
+        self.counters.panicked.store(0, Ordering::Relaxed);
+        self.counters.cancelled_queued.store(0, Ordering::Relaxed);
+        self.counters.cancelled_running.store(0, Ordering::Relaxed);
+        self.counters.produced.store(0, Ordering::Relaxed);
+        self.counters.consumed.store(0, Ordering::Relaxed);
+        self.counters.discarded.store(0, Ordering::Relaxed);
+        self.counters.over_cap.store(0, Ordering::Relaxed);
+        self.counters.overflowed.store(0, Ordering::Relaxed);
+        for bucket in &self.counters.buckets {
+            bucket.second.store(EMPTY, Ordering::Release);
+            bucket.clear();
+        }
+    }
+
+    pub(crate) fn record(&self, queued_for: Duration, ran_for: Duration) {
+        self.record_at(Instant::now(), queued_for, ran_for);
+    }
+
+    /// Records a child task completed at the given instant
+    ///
+    /// The worker threads record every completed child task by themselves, this is meant for
+    /// feeding a synthetic workload.
+    ///
+    /// # Parameters
+    ///
+    /// * `at`: the instant the child task completed
+    /// * `queued_for`: how long the child task waited in the queue
+    /// * `ran_for`: how long the child task took from its first poll until it completed
+    pub fn record_at(&self, at: Instant, queued_for: Duration, ran_for: Duration) {
+        let second: u64 = self.second_of(at);
+        let bucket: &Bucket = &self.counters.buckets[second as usize % BUCKETS];
+        let held: u64 = bucket.second.load(Ordering::Acquire);
+        if held != second
+            && bucket
+                .second
+                .compare_exchange(held, second, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            // the bucket held a second that fell out of the ring, a completion racing
+            // with this may be lost but the bucket never mixes two different seconds for long
+            bucket.clear();
+        }
+        bucket.completed.fetch_add(1, Ordering::Relaxed);
+        bucket.queue_nanos.fetch_add(
+            queued_for.as_nanos().min(u64::MAX as u128) as u64,
+            Ordering::Relaxed,
+        );
+        bucket.durations[bin_of(ran_for)].fetch_add(1, Ordering::Relaxed);
+        self.counters.completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Computes the rates of the trailing window ending now
+    ///
+    /// # Parameters
+    ///
+    /// * `window`: length of the trailing window, rounded up to whole seconds and capped at a minute
+    pub fn window(&self, window: Duration) -> WindowedMetrics {
+        self.window_at(Instant::now(), window)
+    }
+
+    /// Computes the rates of the trailing window ending at the given instant
+    ///
+    /// # Parameters
+    ///
+    /// * `now`: the instant the window ends at, its second is included in the window
+    /// * `window`: length of the trailing window, rounded up to whole seconds and capped at a minute
+    pub fn window_at(&self, now: Instant, window: Duration) -> WindowedMetrics {
+        let seconds: u64 = window
+            .as_secs()
+            .saturating_add(u64::from(window.subsec_nanos() > 0))
+            .clamp(1, BUCKETS as u64);
+        let last: u64 = self.second_of(now);
+        let first: u64 = last.saturating_sub(seconds - 1);
+
+        let mut completed: u64 = 0;
+        let mut queue_nanos: u64 = 0;
+        let mut durations: [u64; BINS] = [0; BINS];
+        for bucket in &self.counters.buckets {
+            let second: u64 = bucket.second.load(Ordering::Acquire);
+            if second == EMPTY || second < first || second > last {
+                continue;
+            }
+            completed += bucket.completed.load(Ordering::Relaxed);
+            queue_nanos += bucket.queue_nanos.load(Ordering::Relaxed);
+            for (total, bin) in durations.iter_mut().zip(&bucket.durations) {
+                *total += bin.load(Ordering::Relaxed);
+            }
+        }
+
+        WindowedMetrics {
+            window: Duration::from_secs(seconds),
+            completed,
+            tasks_per_sec: completed as f64 / seconds as f64,
+            average_queue_latency: queue_nanos
+                .checked_div(completed)
+                .map_or(Duration::ZERO, Duration::from_nanos),
+            p95_task_duration: percentile(&durations, completed, 95),
+        }
+    }
+
+    fn second_of(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.counters.epoch).as_secs()
+    }
+}
+
+/// Upper bound of the histogram bin holding the given percentile
+fn percentile(durations: &[u64; BINS], total: u64, percent: u64) -> Duration {
+    if total == 0 {
+        return Duration::ZERO;
+    }
+    let rank: u64 = (total * percent).div_ceil(100);
+    let mut seen: u64 = 0;
+    for (bin, count) in durations.iter().enumerate() {
+        seen += count;
+        if seen >= rank {
+            return Duration::from_micros(1 << bin);
+        }
+    }
+    Duration::from_micros(1 << (BINS - 1))
+}
+
+/// Windowed Metrics
+///
+/// Rates of the child tasks completed during a trailing window, as computed by [`Metrics::window`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowedMetrics {
+    /// The length of the window in whole seconds
+    pub window: Duration,
+    /// The number of child tasks completed during the window
+    pub completed: u64,
+    /// The average number of child tasks completed per second
+    pub tasks_per_sec: f64,
+    /// The average time the completed child tasks waited in the queue
+    pub average_queue_latency: Duration,
+    /// The 95th percentile of how long the completed child tasks ran, rounded up to the next power of two microseconds
+    pub p95_task_duration: Duration,
+}
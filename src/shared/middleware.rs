@@ -0,0 +1,60 @@
+use crate::dyn_spawn_group::BoxFuture;
+use parking_lot::Mutex;
+use std::{future::Future, sync::Arc};
+
+pub(crate) type Middleware =
+    Arc<dyn Fn(BoxFuture<'static, ()>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// The middlewares of a spawn group, in registration order
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares {
+    chain: Arc<Mutex<Option<Arc<[Middleware]>>>>,
+}
+
+impl Middlewares {
+    pub(crate) fn register(&self, middleware: Middleware) {
+        let mut chain = self.chain.lock();
+        let mut middlewares: Vec<Middleware> = chain.as_deref().unwrap_or_default().to_vec();
+        middlewares.push(middleware);
+        *chain = Some(middlewares.into());
+    }
+
+    /// The middlewares registered so far, which apply to the child tasks spawned from now on
+    pub(crate) fn chain(&self) -> Option<Arc<[Middleware]>> {
+        self.chain.lock().clone()
+    }
+}
+
+/// Runs a child task through the middlewares, the first registered one being the outermost
+///
+/// The child task is only boxed when there's a middleware to run it through.
+///
+/// # Returns
+/// - Some: the output of the child task
+/// - None: if a middleware completed without running the child task to completion
+pub(crate) async fn run_through<Fut>(
+    chain: Option<Arc<[Middleware]>>,
+    task: Fut,
+) -> Option<Fut::Output>
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let Some(chain) = chain else {
+        return Some(task.await);
+    };
+    let slot: Arc<Mutex<Option<Fut::Output>>> = Arc::new(Mutex::new(None));
+    let mut wrapped: BoxFuture<'static, ()> = Box::pin({
+        let slot = slot.clone();
+        async move {
+            let output = task.await;
+            *slot.lock() = Some(output);
+        }
+    });
+    for middleware in chain.iter().rev() {
+        wrapped = middleware(wrapped);
+    }
+    wrapped.await;
+    let output: Option<Fut::Output> = slot.lock().take();
+    output
+}
@@ -1,5 +1,30 @@
+pub(crate) mod ack_gate;
+pub(crate) mod adaptive;
+pub(crate) mod batch;
+pub(crate) mod blocking;
+pub(crate) mod breadcrumbs;
+pub(crate) mod cancel_token;
+pub(crate) mod completion_counts;
+pub(crate) mod drop_policy;
 pub(crate) mod initializible;
+pub(crate) mod keyed;
+pub(crate) mod latency;
+pub(crate) mod metrics;
+pub(crate) mod middleware;
+pub(crate) mod overflow_policy;
+pub(crate) mod outstanding;
+pub(crate) mod panic_isolation;
+pub(crate) mod panic_output;
 pub(crate) mod priority;
+pub(crate) mod priority_policy;
+pub(crate) mod registry;
+pub(crate) mod result_size;
 pub(crate) mod runtime;
 pub(crate) mod sharedfuncs;
+pub(crate) mod snapshot;
+pub(crate) mod spawn_error;
+pub(crate) mod subsets;
+pub(crate) mod task_cap;
+pub(crate) mod teardown;
 pub(crate) mod wait;
+pub(crate) mod workload_hint;
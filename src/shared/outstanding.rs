@@ -0,0 +1,86 @@
+use parking_lot::Mutex;
+use std::{
+    mem,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default)]
+struct State {
+    count: usize,
+    epoch: usize,
+    waiters: Vec<Waker>,
+}
+
+/// The child tasks of a spawn group that haven't finished yet, whose waiters are woken once none of them is left
+///
+/// Every child task holds a guard from the moment it's spawned until it's done with, whether it completed, was cut
+/// by a cancellation or was dropped without ever running, so the waiters are never left waiting on a child task that
+/// is gone.
+#[derive(Clone, Default)]
+pub(crate) struct Outstanding {
+    state: Arc<Mutex<State>>,
+}
+
+impl Outstanding {
+    /// Counts a newly spawned child task until the returned guard is dropped
+    pub(crate) fn guard(&self) -> OutstandingGuard {
+        let mut state = self.state.lock();
+        state.count += 1;
+        OutstandingGuard {
+            outstanding: self.clone(),
+            epoch: state.epoch,
+        }
+    }
+
+    /// Ready once no child task is left, otherwise the waker is woken once that's the case
+    pub(crate) fn poll_idle(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock();
+        if state.count == 0 {
+            return Poll::Ready(());
+        }
+        if !state
+            .waiters
+            .iter()
+            .any(|registered| registered.will_wake(cx.waker()))
+        {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Stops counting every child task spawned so far, which a cancellation leaves to finish on their own
+    pub(crate) fn release_all(&self) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.state.lock();
+            state.epoch += 1;
+            state.count = 0;
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Keeps its child task counted as outstanding while it's alive
+pub(crate) struct OutstandingGuard {
+    outstanding: Outstanding,
+    epoch: usize,
+}
+
+impl Drop for OutstandingGuard {
+    fn drop(&mut self) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.outstanding.state.lock();
+            // a child task spawned before the last cancellation is no longer counted
+            if state.epoch != self.epoch {
+                return;
+            }
+            state.count -= 1;
+            if state.count > 0 {
+                return;
+            }
+            mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
@@ -0,0 +1,21 @@
+/// Overflow Policy
+///
+/// What a spawn group does with a result once its result buffer is full, as set with the ``result_buffer`` or
+/// ``result_buffer_bytes`` method of the spawn group
+///
+/// Either way a dropped result is handed to the discard hook of the spawn group, if any, and counted by
+/// [`Metrics::results_overflowed`](crate::Metrics::results_overflowed) as well as
+/// [`Metrics::results_discarded`](crate::Metrics::results_discarded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// The oldest buffered results are dropped to make room for the new one, the default
+    #[default]
+    DropOldest,
+    /// The new result is dropped, keeping the buffered ones
+    DropNewest,
+    /// The child task producing the result is suspended until a result is consumed and makes room for it
+    ///
+    /// The worker thread moves on to other child tasks meanwhile. Once the spawn group is cancelled, the suspended
+    /// child tasks give up and drop their result instead.
+    Block,
+}
@@ -0,0 +1,32 @@
+use std::cell::Cell;
+
+/// Panic Isolation
+///
+/// What a worker thread does once a child task panicked on it
+///
+/// Either way the panic is caught, the child task counts as panicked and produces no result, and the queued child tasks
+/// are left for the worker threads to run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PanicIsolation {
+    /// The worker thread goes on running child tasks, the default
+    #[default]
+    Task,
+    /// The worker thread exits and the threadpool starts a fresh one in its place, so whatever the panic left behind
+    /// in the thread-local state of the worker thread, including its [`worker_state`](crate::worker_state), is
+    /// dropped along with it
+    RestartWorker,
+}
+
+thread_local! {
+    static PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Records that a child task panicked on the calling thread
+pub(crate) fn note_panic() {
+    PANICKED.set(true);
+}
+
+/// Whether a child task panicked on the calling thread since the last call
+pub(crate) fn take_panicked() -> bool {
+    PANICKED.take()
+}
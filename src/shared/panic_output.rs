@@ -0,0 +1,79 @@
+use std::{
+    cell::{Cell, RefCell},
+    sync::Arc,
+};
+
+/// Panic Output
+///
+/// Where the report of a child task panicking goes, which includes the name of the panicking thread, the name of the
+/// child task if it was spawned with one, the location of the panic, its message and a backtrace
+///
+/// The panic itself is caught either way, so the child task counts as panicked and produces no result. The child tasks
+/// a spawn group cuts off once its drop wait cap elapsed, and the ones found blocking the executor, are reported there
+/// as well.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum PanicOutput {
+    /// The report is dropped without ever being formatted
+    Silent,
+    /// The report is printed to the standard error, the default
+    #[default]
+    Stderr,
+    /// The report is handed to the function, on the thread the child task panicked on
+    Handler(fn(&str)),
+}
+
+impl PanicOutput {
+    /// Hands the report to the output, on the calling thread
+    pub(crate) fn emit(self, report: &str) {
+        match self {
+            PanicOutput::Silent => (),
+            PanicOutput::Stderr => eprintln!("{report}"),
+            PanicOutput::Handler(handler) => handler(report),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<PanicOutput> = const { Cell::new(PanicOutput::Stderr) };
+    static TASK_NAME: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Restores the output the thread used before entering the scope, even when unwinding out of it
+struct Scope(PanicOutput);
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
+
+/// Runs the body with the panics on the calling thread reported to the given output
+pub(crate) fn scoped<T>(output: PanicOutput, body: impl FnOnce() -> T) -> T {
+    let _scope: Scope = Scope(CURRENT.replace(output));
+    body()
+}
+
+/// The output the panics on the calling thread are reported to
+pub(crate) fn current() -> PanicOutput {
+    CURRENT.get()
+}
+
+/// Restores the name of the child task the thread was polling before entering the scope, even when unwinding out of it
+struct NameScope(Option<Arc<str>>);
+
+impl Drop for NameScope {
+    fn drop(&mut self) {
+        TASK_NAME.set(self.0.take());
+    }
+}
+
+/// Runs the body with the panics on the calling thread reported as coming from the child task of the given name
+pub(crate) fn named<T>(name: Option<Arc<str>>, body: impl FnOnce() -> T) -> T {
+    let _scope: NameScope = NameScope(TASK_NAME.replace(name));
+    body()
+}
+
+/// The name of the child task the calling thread is polling, if it was spawned with one
+pub(crate) fn task_name() -> Option<Arc<str>> {
+    TASK_NAME.with_borrow(Option::clone)
+}
@@ -1,7 +1,8 @@
 /// Task Priority
 ///
 /// Spawn groups uses it to rank the importance of their spawned tasks and order of returned values only when waited for.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Priority {
     BACKGROUND = 0,
     LOW,
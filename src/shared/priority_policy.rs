@@ -0,0 +1,29 @@
+use crate::shared::{priority::Priority, spawn_error::SpawnError};
+use std::ops::RangeInclusive;
+
+/// Priority Policy
+///
+/// Decides which priorities a spawn group accepts for its child tasks, which is checked every time a child task is spawned.
+///
+/// A spawn group shared by several parts of a program can, for example, reserve the top priorities for its own child tasks.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PriorityPolicy {
+    /// Every priority is accepted as is
+    #[default]
+    AllowAll,
+    /// Priorities outside the range are moved to its nearest bound
+    Clamp(RangeInclusive<Priority>),
+    /// Child tasks with a priority outside the range are refused
+    Reject(RangeInclusive<Priority>),
+}
+
+impl PriorityPolicy {
+    pub(crate) fn admit(&self, priority: Priority) -> Result<Priority, SpawnError> {
+        match self {
+            PriorityPolicy::AllowAll => Ok(priority),
+            PriorityPolicy::Clamp(range) => Ok(priority.clamp(*range.start(), *range.end())),
+            PriorityPolicy::Reject(range) if range.contains(&priority) => Ok(priority),
+            PriorityPolicy::Reject(_) => Err(SpawnError::Rejected(priority)),
+        }
+    }
+}
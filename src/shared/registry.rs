@@ -0,0 +1,187 @@
+use crate::shared::{breadcrumbs::BreadcrumbSlot, priority::Priority, snapshot::TaskState};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Number of cancelled child tasks kept in their terminal state, the oldest ones are let go of beyond it
+const CANCELLED_KEPT: usize = 256;
+
+pub(crate) struct TaskRecord {
+    pub(crate) id: u64,
+    pub(crate) priority: Priority,
+    pub(crate) spawned_at: Instant,
+    // the label the child task was spawned with, if any
+    pub(crate) name: Option<Arc<str>>,
+    // where the child task last suspended, if its spawn group traces suspensions
+    pub(crate) breadcrumb: Option<BreadcrumbSlot>,
+    started: AtomicBool,
+    cancelled: AtomicBool,
+    // taken by whichever comes first of the child task starting and it being shed, so a shed child task never starts
+    claimed: AtomicBool,
+    shed: AtomicBool,
+}
+
+impl TaskRecord {
+    /// Marks the child task as running
+    ///
+    /// # Returns
+    /// - true: if the child task may run
+    /// - false: if it was shed before starting, in which case it must never run
+    pub(crate) fn start(&self) -> bool {
+        if self.claimed.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.started.store(true, Ordering::Release);
+        true
+    }
+
+    /// Whether the child task was shed before starting
+    pub(crate) fn is_shed(&self) -> bool {
+        self.shed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn state(&self) -> TaskState {
+        if self.is_cancelled() {
+            TaskState::Cancelled
+        } else if self.is_started() {
+            TaskState::Running
+        } else {
+            TaskState::Queued
+        }
+    }
+}
+
+/// Keeps track of the child tasks of a spawn group which haven't completed yet, and of the ones last cancelled
+#[derive(Clone, Default)]
+pub(crate) struct Registry {
+    next_id: Arc<AtomicU64>,
+    tasks: Arc<Mutex<HashMap<u64, Arc<TaskRecord>>>>,
+    // always locked after the pending child tasks, never before
+    cancelled: Arc<Mutex<VecDeque<Arc<TaskRecord>>>>,
+}
+
+impl Registry {
+    pub(crate) fn register(
+        &self,
+        priority: Priority,
+        name: Option<Arc<str>>,
+        traced: bool,
+    ) -> Arc<TaskRecord> {
+        let record: Arc<TaskRecord> = Arc::new(TaskRecord {
+            id: self.next_id.fetch_add(1, Ordering::AcqRel),
+            priority,
+            spawned_at: Instant::now(),
+            name,
+            breadcrumb: traced.then(BreadcrumbSlot::default),
+            started: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            claimed: AtomicBool::new(false),
+            shed: AtomicBool::new(false),
+        });
+        self.tasks.lock().insert(record.id, record.clone());
+        record
+    }
+
+    /// Lets go of a child task that ended without being cancelled
+    ///
+    /// A child task whose last poll was already running when it was cancelled may still complete, it's no longer
+    /// reported as cancelled then.
+    pub(crate) fn remove(&self, record: &TaskRecord) {
+        let mut tasks = self.tasks.lock();
+        if tasks.remove(&record.id).is_none() && record.is_cancelled() {
+            self.cancelled
+                .lock()
+                .retain(|cancelled| cancelled.id != record.id);
+        }
+    }
+
+    /// Moves a child task to its terminal cancelled state
+    pub(crate) fn cancel(&self, id: u64) {
+        let mut tasks = self.tasks.lock();
+        if let Some(record) = tasks.remove(&id) {
+            self.retire(vec![record]);
+        }
+    }
+
+    /// Moves every child task which hasn't completed yet to its terminal cancelled state
+    pub(crate) fn cancel_all(&self) {
+        let mut tasks = self.tasks.lock();
+        let mut records: Vec<Arc<TaskRecord>> = tasks.drain().map(|(_, record)| record).collect();
+        records.sort_by_key(|record| record.id);
+        self.retire(records);
+    }
+
+    /// Moves every child task below the given priority that hasn't started yet to its terminal cancelled state
+    ///
+    /// # Returns
+    /// The number of child tasks shed, which will never start
+    pub(crate) fn shed(&self, keep: Priority) -> usize {
+        let mut tasks = self.tasks.lock();
+        let mut records: Vec<Arc<TaskRecord>> = tasks
+            .values()
+            .filter(|record| record.priority < keep && !record.claimed.swap(true, Ordering::AcqRel))
+            .cloned()
+            .collect();
+        records.sort_by_key(|record| record.id);
+        for record in &records {
+            record.shed.store(true, Ordering::Release);
+            tasks.remove(&record.id);
+        }
+        let shed: usize = records.len();
+        self.retire(records);
+        shed
+    }
+
+    // called with the pending child tasks locked, so a child task is never seen both pending and cancelled
+    fn retire(&self, records: Vec<Arc<TaskRecord>>) {
+        let mut cancelled = self.cancelled.lock();
+        for record in records {
+            record.cancelled.store(true, Ordering::Release);
+            if cancelled.len() == CANCELLED_KEPT {
+                cancelled.pop_front();
+            }
+            cancelled.push_back(record);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut tasks = self.tasks.lock();
+        tasks.clear();
+        self.cancelled.lock().clear();
+    }
+
+    pub(crate) fn spawned(&self) -> u64 {
+        self.next_id.load(Ordering::Acquire)
+    }
+
+    /// The child tasks which haven't completed yet along with the ones last cancelled, ordered by identifier
+    pub(crate) fn records(&self) -> Vec<Arc<TaskRecord>> {
+        let tasks = self.tasks.lock();
+        let mut records: Vec<Arc<TaskRecord>> = tasks.values().cloned().collect();
+        records.extend(self.cancelled.lock().iter().cloned());
+        drop(tasks);
+        records.sort_by_key(|record| record.id);
+        records
+    }
+
+    /// The child tasks which haven't completed yet ordered by identifier
+    pub(crate) fn pending(&self) -> Vec<Arc<TaskRecord>> {
+        let mut records: Vec<Arc<TaskRecord>> = self.tasks.lock().values().cloned().collect();
+        records.sort_by_key(|record| record.id);
+        records
+    }
+}
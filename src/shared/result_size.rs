@@ -0,0 +1,65 @@
+use std::mem;
+
+/// Result Size
+///
+/// How many bytes a result of a spawn group approximately takes, which the byte counters of the spawn group add up
+/// while the result is buffered, as reported by [`Metrics::buffered_bytes`](crate::Metrics::buffered_bytes) and
+/// [`total_buffered_bytes`](crate::total_buffered_bytes).
+///
+/// A spawn group counts each of its results as ``size_of::<ValueType>()``, unless it was told to measure them with
+/// this trait through its ``measure_results`` method. A result is measured once when it's buffered, and counts as
+/// that many bytes until it's consumed or dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, Priority, ResultSize, SpawnGroup};
+///
+/// struct Page {
+///     body: Vec<u8>,
+/// }
+///
+/// impl ResultSize for Page {
+///     fn approx_size(&self) -> usize {
+///         std::mem::size_of::<Self>() + self.body.capacity()
+///     }
+/// }
+///
+/// let mut group = SpawnGroup::<Page>::new(2);
+/// group.measure_results();
+/// group.spawn_task(Priority::default(), async { Page { body: vec![0; 1024] } });
+/// block_on(group.wait_for_all());
+/// assert_eq!(group.metrics().buffered_bytes(), std::mem::size_of::<Page>() + 1024);
+/// ```
+pub trait ResultSize {
+    /// The approximate number of bytes the result takes, including what it owns, ``size_of::<Self>()`` unless
+    /// overridden
+    fn approx_size(&self) -> usize
+    where
+        Self: Sized,
+    {
+        mem::size_of::<Self>()
+    }
+}
+
+impl ResultSize for String {
+    fn approx_size(&self) -> usize {
+        mem::size_of::<Self>() + self.capacity()
+    }
+}
+
+impl<T> ResultSize for Vec<T> {
+    fn approx_size(&self) -> usize {
+        mem::size_of::<Self>() + self.capacity() * mem::size_of::<T>()
+    }
+}
+
+/// Measures the result of a fallible child task by whichever of the value or the error it holds
+pub(crate) fn approx_result_size<ValueType: ResultSize, ErrorType: ResultSize>(
+    result: &Result<ValueType, ErrorType>,
+) -> usize {
+    match result {
+        Ok(value) => value.approx_size(),
+        Err(error) => error.approx_size(),
+    }
+}
@@ -1,82 +1,899 @@
 use crate::{
-    async_runtime::{executor::Executor, task::Task},
-    async_stream::AsyncStream,
-    executors::block_task,
-    shared::{initializible::Initializible, priority::Priority},
+    async_runtime::{
+        executor::{DrivingGuard, Executor},
+        lane::{move_to, Lane},
+        notifier::Notifier,
+        pool_cache,
+        task::Task,
+    },
+    async_stream::{AsyncStream, Bound, HoldOpen, SizeOf, Tag},
+    child_handle::{ChildHandle, ChildSlot, SlotCompletion},
+    executors::block_on,
+    join_handle::{joinable, JoinHandle},
+    shared::{
+        ack_gate::{AckGate, AckSlot},
+        adaptive::{AdaptiveConfig, ConcurrencyWindow, WindowSlot},
+        batch::Batch,
+        blocking::BlockingWatch,
+        breadcrumbs::{self, Breadcrumb},
+        cancel_token::CancelToken,
+        completion_counts::{CompletionCounters, CompletionCounts, CompletionGuard},
+        drop_policy::GroupDropPolicy,
+        initializible::Initializible,
+        latency::{DequeueHook, QueueLatency},
+        metrics::Metrics,
+        middleware::{self, Middleware, Middlewares},
+        overflow_policy::OverflowPolicy,
+        outstanding::{Outstanding, OutstandingGuard},
+        panic_isolation::{self, PanicIsolation},
+        panic_output::{self, PanicOutput},
+        priority::Priority,
+        priority_policy::PriorityPolicy,
+        registry::{Registry, TaskRecord},
+        snapshot::{GroupSnapshot, TaskSnapshot},
+        spawn_error::SpawnError,
+        subsets::{SubsetGuard, Subsets},
+        task_cap::{TaskCap, TaskCapPolicy},
+        teardown::{self, Teardown},
+    },
+    sleeper::sleep,
+    threadpool_impl::{
+        default_count, default_factory, ExecutionLog, ExecutionOutcome, ExecutionRecord,
+        ShutdownMode, ThreadFactory,
+    },
+    wake_trace,
 };
-use parking_lot::Mutex;
+use futures_lite::FutureExt;
+use parking_lot::{Mutex, MutexGuard};
 use std::{
-    future::Future,
+    collections::VecDeque,
+    fmt,
+    future::{poll_fn, Future},
+    io, mem,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    pin::pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
 type TaskQueue = Arc<Mutex<Vec<(Priority, Task)>>>;
 
-pub struct RuntimeEngine<ItemType> {
+pub(crate) type DiscardHook<ItemType> = Arc<dyn Fn(ItemType) + Send + Sync>;
+
+/// Tells whether a result stands for a failed child task, which narrows the adaptive concurrency window
+pub(crate) type FailureCheck<ItemType> = fn(&ItemType) -> bool;
+
+/// Hands the results that will never be consumed to the discard hook, if any, one at a time on the current thread
+///
+/// A panicking hook doesn't keep the remaining results from being handed to it.
+fn hand_to_hook<ItemType>(
+    hook: &Mutex<Option<DiscardHook<ItemType>>>,
+    results: impl IntoIterator<Item = ItemType>,
+) {
+    let hook: Option<DiscardHook<ItemType>> = hook.lock().clone();
+    let Some(hook) = hook else {
+        return;
+    };
+    for result in results {
+        _ = panic::catch_unwind(AssertUnwindSafe(|| hook(result)));
+    }
+}
+
+/// Closes the spawn group once dropped, unless it was reset since, and keeps its stream from ending until then
+struct Closer<ItemType> {
+    closed: Arc<AtomicBool>,
+    stream: AsyncStream<ItemType>,
+    wave: usize,
+    // dropped after the spawn group is closed, so the consumers it wakes see the spawn group closed
+    _open: HoldOpen<ItemType>,
+}
+
+impl<ItemType> Drop for Closer<ItemType> {
+    fn drop(&mut self) {
+        if self.stream.wave() == self.wave {
+            self.closed.store(true, Ordering::Release);
+            self.stream.wake_spawners();
+        }
+    }
+}
+
+/// The closer of a future started with ``close_when``, dropped by whichever comes first of the future ending and the
+/// spawn group being cancelled, since a cancelled future may only be dropped once its worker thread lets go of it
+type CloserSlot<ItemType> = Arc<Mutex<Option<Closer<ItemType>>>>;
+
+/// Drops the closer left in the slot, outside of the lock of the slot
+fn close_now<ItemType>(slot: &CloserSlot<ItemType>) {
+    let closer: Option<Closer<ItemType>> = slot.lock().take();
+    drop(closer);
+}
+
+/// Closes the spawn group through its slot once the future holding it ends
+struct CloseOnDrop<ItemType>(CloserSlot<ItemType>);
+
+impl<ItemType> Drop for CloseOnDrop<ItemType> {
+    fn drop(&mut self) {
+        close_now(&self.0);
+    }
+}
+
+pub(crate) struct RuntimeEngine<ItemType> {
     tasks: TaskQueue,
     runtime: Executor,
     stream: AsyncStream<ItemType>,
     wait_flag: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    on_dequeued: Arc<Mutex<Option<DequeueHook>>>,
+    on_discarded: Arc<Mutex<Option<DiscardHook<ItemType>>>>,
+    panic_output: Arc<Mutex<PanicOutput>>,
+    registry: Registry,
+    generation: Arc<AtomicUsize>,
+    metrics: Metrics,
+    policy: Arc<Mutex<PriorityPolicy>>,
+    // the priority of the child tasks spawned without one
+    default_priority: Arc<Mutex<Priority>>,
+    completions: CompletionCounters,
+    middlewares: Middlewares,
+    subsets: Subsets,
+    task_cap: Arc<TaskCap>,
+    // the most pending child tasks ``try_spawn_task`` lets the spawn group have
+    queue_capacity: Arc<Mutex<Option<usize>>>,
+    // held while a bounded spawner checks for room and spawns, so concurrent ones never go beyond the capacity, and
+    // while a spawner checks its generation and spawns, which a cut waits for before moving on to the next generation
+    admission: Arc<Mutex<()>>,
+    // whether the threadpool goes back to the cache of the thread the spawn group ends on
+    recyclable: Arc<AtomicBool>,
+    // the child tasks declared upfront in exclusive batch mode
+    batch: Arc<Batch>,
+    // set while the spawn group waits for all of its child tasks, woken by every child task spawned meanwhile
+    waiter: Arc<Mutex<Option<Waker>>>,
+    // the child tasks that haven't finished yet, which the asynchronous wait suspends on
+    outstanding: Outstanding,
+    // the child tasks allowed to run at once under adaptive concurrency
+    window: ConcurrencyWindow,
+    is_failure: Arc<Mutex<Option<FailureCheck<ItemType>>>>,
+    // the longest a spawn group dropped while waiting for all of its child tasks waits for them
+    drop_wait_cap: Arc<Mutex<Option<Duration>>>,
+    // the closers of the futures started with ``close_when`` that may still be running
+    closers: Arc<Mutex<Vec<CloserSlot<ItemType>>>>,
+    // whether the child tasks spawned from now on leave breadcrumbs of where they suspend
+    traced: Arc<AtomicBool>,
+    // reports the child tasks blocking their worker thread, once enabled
+    blocking: BlockingWatch,
 }
 
-impl<ItemType> Initializible for RuntimeEngine<ItemType> {
-    fn init() -> Self {
+// every clone shares the same child tasks, so the spawners handed out by the spawn group feed it
+impl<ItemType> Clone for RuntimeEngine<ItemType> {
+    fn clone(&self) -> Self {
         Self {
-            tasks: Arc::new(Mutex::new(vec![])),
-            stream: AsyncStream::new(),
-            runtime: Executor::default(),
-            wait_flag: Arc::new(AtomicBool::new(false)),
+            tasks: self.tasks.clone(),
+            runtime: self.runtime.clone(),
+            stream: self.stream.clone(),
+            wait_flag: self.wait_flag.clone(),
+            closed: self.closed.clone(),
+            on_dequeued: self.on_dequeued.clone(),
+            on_discarded: self.on_discarded.clone(),
+            panic_output: self.panic_output.clone(),
+            registry: self.registry.clone(),
+            generation: self.generation.clone(),
+            metrics: self.metrics.clone(),
+            policy: self.policy.clone(),
+            default_priority: self.default_priority.clone(),
+            completions: self.completions.clone(),
+            middlewares: self.middlewares.clone(),
+            subsets: self.subsets.clone(),
+            task_cap: self.task_cap.clone(),
+            queue_capacity: self.queue_capacity.clone(),
+            admission: self.admission.clone(),
+            recyclable: self.recyclable.clone(),
+            batch: self.batch.clone(),
+            waiter: self.waiter.clone(),
+            outstanding: self.outstanding.clone(),
+            window: self.window.clone(),
+            is_failure: self.is_failure.clone(),
+            drop_wait_cap: self.drop_wait_cap.clone(),
+            closers: self.closers.clone(),
+            traced: self.traced.clone(),
+            blocking: self.blocking.clone(),
         }
     }
 }
 
+impl<ItemType> Initializible for RuntimeEngine<ItemType> {
+    fn init() -> Self {
+        Self::scoped(default_count())
+    }
+}
+
 impl<ItemType> RuntimeEngine<ItemType> {
     pub(crate) fn new(count: usize) -> Self {
+        Self::with_executor(Executor::new(count))
+    }
+
+    /// Creates a runtime whose result buffer has room for the given number of results before it reallocates
+    pub(crate) fn with_capacity(count: usize, result_capacity: usize) -> Self {
+        Self::with_executor_and_capacity(Executor::new(count), result_capacity)
+    }
+
+    /// Creates the runtime of a scoped spawn group, whose threadpool is taken from and handed back to the cache of the
+    /// current thread
+    pub(crate) fn scoped(count: usize) -> Self {
+        let engine: Self = match pool_cache::take(count) {
+            Some(runtime) => {
+                let engine: Self = Self::with_executor(runtime);
+                // the thread running the executor stopped when the previous spawn group ended
+                engine.store(true);
+                engine
+            }
+            None => Self::with_executor(Executor::new(count)),
+        };
+        engine.recyclable.store(true, Ordering::Release);
+        engine
+    }
+
+    /// Creates every thread of the spawn group through the given thread factory
+    pub(crate) fn with_factory(count: usize, factory: ThreadFactory) -> io::Result<Self> {
+        Ok(Self::with_executor(Executor::with_factory(count, factory)?))
+    }
+
+    /// Creates the runtime of a spawn group with separate IO and compute lanes, each with its own worker threads
+    pub(crate) fn with_lanes(
+        io_threads: NonZeroUsize,
+        compute_threads: NonZeroUsize,
+    ) -> io::Result<Self> {
+        let runtime: Executor =
+            Executor::with_lanes(io_threads.get(), compute_threads.get(), default_factory())?;
+        Ok(Self::with_executor(runtime))
+    }
+
+    fn with_executor(runtime: Executor) -> Self {
+        Self::with_executor_and_capacity(runtime, 0)
+    }
+
+    fn with_executor_and_capacity(runtime: Executor, result_capacity: usize) -> Self {
+        let metrics: Metrics = Metrics::new();
+        let panic_output: Arc<Mutex<PanicOutput>> = Arc::new(Mutex::new(PanicOutput::default()));
         Self {
             tasks: Arc::new(Mutex::new(vec![])),
-            stream: AsyncStream::new(),
-            runtime: Executor::new(count),
+            stream: AsyncStream::new(metrics.clone(), result_capacity),
+            runtime,
             wait_flag: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            on_dequeued: Arc::new(Mutex::new(None)),
+            on_discarded: Arc::new(Mutex::new(None)),
+            blocking: BlockingWatch::new(panic_output.clone()),
+            panic_output,
+            registry: Registry::default(),
+            generation: Arc::new(AtomicUsize::new(0)),
+            metrics: metrics.clone(),
+            policy: Arc::new(Mutex::new(PriorityPolicy::default())),
+            default_priority: Arc::new(Mutex::new(Priority::default())),
+            completions: CompletionCounters::default(),
+            middlewares: Middlewares::default(),
+            subsets: Subsets::default(),
+            task_cap: Arc::default(),
+            queue_capacity: Arc::new(Mutex::new(None)),
+            admission: Arc::new(Mutex::new(())),
+            recyclable: Arc::new(AtomicBool::new(false)),
+            batch: Arc::default(),
+            waiter: Arc::default(),
+            outstanding: Outstanding::default(),
+            window: ConcurrencyWindow::new(metrics),
+            is_failure: Arc::new(Mutex::new(None)),
+            drop_wait_cap: Arc::new(Mutex::new(None)),
+            closers: Arc::default(),
+            traced: Arc::new(AtomicBool::new(false)),
         }
     }
 }
 
 impl<ItemType> RuntimeEngine<ItemType> {
     pub(crate) fn cancel(&mut self) {
+        self.cut();
+        self.poll();
+        // polls that were in flight above may have queued their child task again,
+        // drop those as well so no cancelled child task outlives the cancellation
+        self.runtime.cancel();
+        self.tasks.lock().clear();
+    }
+
+    /// Cuts every child task spawned so far off, without waiting for the polls in flight
+    fn cut(&mut self) {
+        // child tasks polled after this cut-line never produce a result
+        {
+            let _admission = self.admission.lock();
+            self.generation.fetch_add(1, Ordering::AcqRel);
+        }
+        self.outstanding.release_all();
+        // the wait released above returns before the child tasks cut off are dropped, so they're counted now
+        self.completions.cut();
         self.store(true);
         self.runtime.cancel();
         self.tasks.lock().clear();
+        // the child tasks cut off stay listed as cancelled rather than vanishing
+        self.registry.cancel_all();
+        // wakes the consumers waiting for a result, which then see the cancellation and end their streams
         self.stream.cancel_tasks();
-        self.poll();
+        self.subsets.cancel_all();
+        self.batch.cancel();
+        self.stream.acks().release_all();
+        self.window.release_all();
+        // the futures started with close_when are cut off as well, which closes the spawn group
+        let closers: Vec<CloserSlot<ItemType>> = mem::take(&mut *self.closers.lock());
+        closers.iter().for_each(close_now);
     }
 }
 
 impl<ItemType> RuntimeEngine<ItemType> {
+    pub(crate) fn reset(&mut self) {
+        self.cancel();
+        self.registry.clear();
+        self.completions.reset();
+        // a reset spawn group is out of exclusive batch mode
+        self.batch = Arc::default();
+        let buffered: VecDeque<ItemType> = self.stream.reset();
+        self.discard(buffered);
+        self.closed.store(false, Ordering::Release);
+    }
+
+    /// Hands the results that will never be consumed to the discard hook one at a time, on the current thread
+    ///
+    /// A panicking hook doesn't keep the remaining results from being handed to it.
+    fn discard(&self, results: VecDeque<ItemType>) {
+        self.metrics.record_discarded(results.len());
+        hand_to_hook(&self.on_discarded, results);
+    }
+
+    pub(crate) fn generation(&self) -> usize {
+        self.stream.wave()
+    }
+
     pub(crate) fn stream(&self) -> AsyncStream<ItemType> {
         self.stream.clone()
     }
 
-    pub(crate) fn end(&mut self) {
+    pub(crate) fn insert_marker(&self, marker: u64) {
+        self.stream.insert_marker(marker);
+    }
+
+    pub(crate) fn help_drive_pool(&mut self, enabled: bool) {
+        if !enabled {
+            self.stream.set_helper(None);
+            self.runtime.set_on_queued(None);
+            return;
+        }
+        // the hook is only ever set on the threadpool of this spawn group
+        self.customize();
+        let runtime: Executor = self.runtime.clone();
+        self.stream
+            .set_helper(Some(Arc::new(move || runtime.run_pending())));
+        self.runtime
+            .set_on_queued(Some(Arc::new(self.stream.consumer_wakeup())));
+    }
+
+    pub(crate) fn worker_state<State, Init>(&self, init: Init)
+    where
+        State: 'static,
+        Init: Fn() -> State + Send + Sync + 'static,
+    {
+        self.customize();
+        self.runtime.worker_states().register(init);
+    }
+
+    pub(crate) fn wake_after(&self, count: usize) {
+        self.stream.set_wake_after(count);
+    }
+
+    #[cfg(feature = "fault-injection")]
+    pub(crate) fn inject(&self, plan: crate::FaultPlan) {
+        self.customize();
+        self.runtime.faults().inject(plan);
+    }
+
+    pub(crate) fn trace_suspensions(&self, enabled: bool) {
+        self.traced.store(enabled, Ordering::Release);
+    }
+
+    pub(crate) fn detect_blocking(&self, threshold: Duration, panic: bool) {
+        self.blocking.set(threshold, panic);
+    }
+
+    pub(crate) fn on_task_dequeued(&self, hook: DequeueHook) {
+        *self.on_dequeued.lock() = Some(hook);
+    }
+
+    pub(crate) fn on_discarded(&self, hook: DiscardHook<ItemType>) {
+        *self.on_discarded.lock() = Some(hook);
+    }
+
+    pub(crate) fn panic_output(&self, output: PanicOutput) {
+        *self.panic_output.lock() = output;
+    }
+
+    pub(crate) fn panic_isolation(&self, isolation: PanicIsolation) {
+        self.customize();
+        self.runtime.set_panic_isolation(isolation);
+    }
+
+    pub(crate) fn task_middleware(&self, middleware: Middleware) {
+        self.middlewares.register(middleware);
+    }
+
+    pub(crate) fn cancel_token(&self) -> CancelToken {
+        CancelToken::new(self.generation.clone())
+    }
+
+    pub(crate) fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    pub(crate) fn subsets(&self) -> &Subsets {
+        &self.subsets
+    }
+
+    pub(crate) fn completion_counts(&self) -> CompletionCounts {
+        match self.batch.size() {
+            Some(_) => self.batch.counts(),
+            None => self.completions.counts(),
+        }
+    }
+
+    pub(crate) fn cpu_quota(&self, cores: f64) {
+        self.customize();
+        self.runtime.cpu_quota().set(cores);
+    }
+
+    pub(crate) fn record_executions(&self, enabled: bool) {
+        self.customize();
+        self.runtime.execution_log().set_enabled(enabled);
+    }
+
+    pub(crate) fn recent_executions(&self) -> Vec<ExecutionRecord> {
+        self.runtime.execution_log().records()
+    }
+
+    #[cfg(feature = "wake-trace")]
+    pub(crate) fn wake_trace(&self) -> crate::WakeTrace {
+        self.runtime.wake_tracer().trace()
+    }
+
+    /// Tears the spawn group down, shutting its threadpool down unless it goes back to the cache of the current thread
+    ///
+    /// # Parameters
+    ///
+    /// * `drain`: whether the jobs still queued on the threadpool run before it shuts down, or are dropped
+    pub(crate) fn end(&mut self, drain: bool) {
+        let _teardown: Teardown = teardown::enter();
+        // the spawners outliving the spawn group are refused from now on
+        self.close();
+        // the child tasks left are cut off as by a cancellation, and every consumer still waiting on a clone of the
+        // stream gets its terminal None
+        self.cut();
+        // with a discard hook, the results still buffered are handed to it rather than left to the clones of the stream
+        if self.on_discarded.lock().is_some() {
+            let buffered: VecDeque<ItemType> = self.stream.take_all();
+            self.discard(buffered);
+        }
+        if self.recyclable.load(Ordering::Acquire) {
+            self.recycle();
+            return;
+        }
+        self.runtime.shutdown(if drain {
+            ShutdownMode::Drain
+        } else {
+            ShutdownMode::Abandon
+        });
+    }
+
+    /// A threadpool customized by its spawn group is never handed to another one
+    fn customize(&self) {
+        self.recyclable.store(false, Ordering::Release);
+    }
+
+    /// Hands the threadpool over to the cache of the current thread once no child task is left on it
+    fn recycle(&mut self) {
+        // child tasks left behind are cut the next time they are polled, so none of them runs for the next spawn group
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.poll();
         self.runtime.cancel();
-        self.tasks.lock().clear();
+        self.runtime.wake_tracer().clear();
+        pool_cache::release(self.runtime.clone());
+    }
+
+    pub(crate) fn num_threads(&self) -> usize {
+        self.runtime.num_threads()
+    }
+
+    pub(crate) fn is_worker_thread(&self) -> bool {
+        self.runtime.is_worker_thread()
+    }
+
+    pub(crate) fn pending_tasks(&self) -> usize {
+        self.stream.task_count()
+    }
+
+    /// The child tasks spawned whose result is yet to be consumed, whether they're still pending or not
+    pub(crate) fn len(&self) -> usize {
+        self.stream.unconsumed_count()
+    }
+
+    /// Waits for the newest result rather than the oldest one
+    pub(crate) async fn next_back(&self) -> Option<ItemType> {
+        poll_fn(|cx| self.stream.poll_back(cx)).await
+    }
+
+    pub(crate) fn try_next(&self) -> Option<ItemType> {
+        self.stream.try_take()
+    }
+
+    pub(crate) fn drain_ready(&self) -> Vec<ItemType> {
+        self.stream.take_ready()
+    }
+
+    /// The results buffered are the items still to be consumed that no child task is still going to produce
+    pub(crate) fn buffered_results(&self) -> usize {
+        let pending: usize = self.stream.task_count();
+        self.len().saturating_sub(pending)
+    }
+
+    /// Cancels every child task below the given priority that hasn't started yet, uncounting them right away
+    ///
+    /// # Returns
+    /// The number of child tasks shed
+    pub(crate) fn shed_load(&self, keep: Priority) -> usize {
+        let shed: usize = self.registry.shed(keep);
+        let wave: usize = self.stream.wave();
+        for _ in 0..shed {
+            self.stream.decrement_count(wave);
+            self.stream.decrement_task_count(wave);
+        }
+        shed
+    }
+
+    /// The names of the child tasks which haven't completed yet, in spawn order, leaving out the unnamed ones
+    fn in_flight_names(&self) -> Vec<Arc<str>> {
+        self.registry
+            .pending()
+            .into_iter()
+            .filter_map(|record: Arc<TaskRecord>| record.name.clone())
+            .collect()
+    }
+
+    /// Formats the state of the spawn group owning the runtime, leaving out its buffered results since they may not
+    /// implement ``Debug``
+    pub(crate) fn fmt_group(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        cancelled: bool,
+        drop_policy: GroupDropPolicy,
+    ) -> fmt::Result {
+        f.debug_struct(name)
+            .field("cancelled", &cancelled)
+            .field("closed", &self.is_closed())
+            .field("spawned", &self.metrics.spawned())
+            .field("completed", &self.metrics.completed())
+            .field("pending", &self.pending_tasks())
+            .field("buffered", &self.buffered_results())
+            .field("threads", &self.num_threads())
+            .field("drop_policy", &drop_policy)
+            .field("in_flight", &self.in_flight_names())
+            .finish()
+    }
+
+    pub(crate) fn snapshot(&self, cancelled: bool) -> GroupSnapshot {
+        let pending: usize = self.stream.task_count();
+        let tasks: Vec<TaskSnapshot> = self
+            .registry
+            .records()
+            .into_iter()
+            .map(|record: Arc<TaskRecord>| {
+                let breadcrumb = record.breadcrumb.as_ref().map(|slot| slot.lock());
+                let breadcrumb: Option<&Breadcrumb> = breadcrumb.as_deref();
+                TaskSnapshot {
+                    id: record.id,
+                    priority: record.priority,
+                    state: record.state(),
+                    age: record.spawned_at.elapsed(),
+                    name: record.name.as_deref().map(String::from),
+                    trace_point: breadcrumb.and_then(Breadcrumb::trace_point),
+                    polls: breadcrumb.map_or(0, Breadcrumb::polls),
+                    suspended_for: breadcrumb.and_then(Breadcrumb::suspended_for),
+                }
+            })
+            .collect();
+        // the child tasks of a batch are never registered
+        let spawned: u64 = match self.batch.size() {
+            Some(_) => self.batch.spawned() as u64,
+            None => self.registry.spawned(),
+        };
+        GroupSnapshot {
+            spawned,
+            pending,
+            buffered: self.buffered_results(),
+            cancelled,
+            closed: self.is_closed(),
+            tasks,
+        }
     }
 }
 
 impl<ValueType: Send + 'static> RuntimeEngine<ValueType> {
+    /// Waits for every child task to finish without blocking the current thread
+    ///
+    /// The executor keeps running the child tasks meanwhile, and the waiting task is woken by the last child task to
+    /// finish, so it may be awaited from any executor, or from one of the child tasks of another spawn group.
+    pub(crate) async fn wait_until_idle(&self) {
+        let _driving: DrivingGuard = self.runtime.drive_stalled();
+        poll_fn(|cx| self.outstanding.poll_idle(cx)).await;
+        // the finished child tasks are let go of, rather than piling up over the life of the spawn group
+        self.tasks.lock().retain(|(_, task)| !task.is_completed());
+    }
+
+    /// Leaves the child tasks running once the spawn group is dropped, tearing the spawn group down on a thread of its
+    /// own once the last of them finished
+    ///
+    /// The thread holds onto the runtime, and so onto the threadpool, until then. Without a thread to do so, the
+    /// spawn group waits for its child tasks before it's torn down instead.
+    pub(crate) fn detach(&mut self) {
+        // the spawners outliving the spawn group are refused from now on
+        self.close();
+        // the threadpool is shut down along with the spawn group rather than handed to the cache of another thread
+        self.customize();
+        let mut runtime: Self = self.clone();
+        let detached = self.runtime.spawn_thread("Detached".to_string(), move || {
+            block_on(runtime.wait_until_idle());
+            runtime.end(true);
+        });
+        if detached.is_err() {
+            self.wait_for_all_tasks();
+            self.end(true);
+        }
+    }
+
+    /// Waits for every child task to finish for at most the given duration, leaving them running once it elapses
+    ///
+    /// # Returns
+    /// Whether every child task finished in time
+    pub(crate) async fn wait_until_idle_for(&self, duration: Duration) -> bool {
+        let idle = async {
+            self.wait_until_idle().await;
+            true
+        };
+        let timeout = async {
+            sleep(duration).await;
+            false
+        };
+        idle.or(timeout).await
+    }
+
+    /// Ends the runtime once every child task finished, as a spawn group dropped while waiting for all of its child
+    /// tasks does
+    ///
+    /// Past the drop wait cap, the child tasks still pending are reported to the panic output and cut off instead.
+    pub(crate) fn end_waiting(&mut self) {
+        let cap: Option<Duration> = *self.drop_wait_cap.lock();
+        if self.wait_for_all_tasks_until(cap.map(|cap| Instant::now() + cap)) {
+            return self.end(true);
+        }
+        let pending: Vec<u64> = self
+            .registry
+            .pending()
+            .iter()
+            .map(|record| record.id)
+            .collect();
+        self.panic_output.lock().emit(&format!(
+            "spawn group dropped with {} child tasks still pending after waiting {:?} for them, cancelling the child \
+             tasks with the ids {:?}",
+            self.stream.task_count(),
+            cap.unwrap_or_default(),
+            pending
+        ));
+        self.end(false);
+    }
+
+    /// Polls the child tasks in rounds, highest priority first, until every one of them has completed
+    ///
+    /// A worker thread never blocks on a single child task, which may be waiting on the buffer lock for a child
+    /// task queued behind it, so each round polls every pending child task once and the next round only starts once
+    /// one of them was woken.
     pub(crate) fn wait_for_all_tasks(&self) {
-        self.poll();
+        self.wait_for_all_tasks_until(None);
+    }
+
+    /// Polls the child tasks as ``wait_for_all_tasks`` does, giving up once the deadline passed
+    ///
+    /// The child tasks left pending once it gives up are dropped, and the ones being polled at that point are left to
+    /// the worker threads, so the spawn group has to be cut off afterwards.
+    ///
+    /// # Returns
+    /// Whether every child task completed before the deadline
+    fn wait_for_all_tasks_until(&self, deadline: Option<Instant>) -> bool {
+        if !self.poll_until(deadline) {
+            return false;
+        }
         self.runtime.cancel();
-        self.tasks.lock().sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
         self.store(true);
-        while let Some((_, handle)) = self.tasks.lock().pop() {
-            self.runtime.submit(move || {
-                block_task(handle);
-            });
+        let notifier: Arc<Notifier> = Arc::new(Notifier::default());
+        let waker: Waker = Waker::from(notifier.clone());
+        *self.waiter.lock() = Some(waker.clone());
+        // the child tasks still pending after a round, kept apart from the ones spawned meanwhile
+        let pending: TaskQueue = Arc::default();
+        loop {
+            let mut round: Vec<(Priority, Task)> = mem::take(&mut *self.tasks.lock());
+            // with no child task spawned since the last round, the next one only starts once one of them was woken
+            if round.is_empty() && !pending.lock().is_empty() {
+                match deadline {
+                    Some(deadline) => {
+                        // an elapsed deadline is noticed once the child tasks left are known
+                        notifier.wait_timeout(deadline.saturating_duration_since(Instant::now()));
+                    }
+                    None => notifier.wait(),
+                }
+                round = mem::take(&mut *self.tasks.lock());
+            }
+            round.append(&mut pending.lock());
+            round.retain(|(_, task)| !task.is_completed());
+            if round.is_empty() {
+                *self.waiter.lock() = None;
+                // the child tasks whose spawner got in before the spawn group stopped waiting are left to the executor
+                let polled: bool = self.poll_until(deadline);
+                if !self.tasks.lock().is_empty() {
+                    // a thread that can't be started now is started again by the next child task spawned
+                    drop(self.restart());
+                }
+                return polled;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                *self.waiter.lock() = None;
+                return false;
+            }
+            round.sort_by_key(|(priority, _)| *priority);
+            let separate: bool = self.runtime.has_lanes();
+            self.runtime
+                .submit_all_on_lanes(round.into_iter().rev().map(|(priority, task)| {
+                    let (pending, waker): (TaskQueue, Waker) = (
+                        pending.clone(),
+                        wake_trace::traced(&waker, task.id, task.tracer()),
+                    );
+                    (task, move |task: Task, lane| {
+                        if task
+                            .poll_on(lane, separate, &mut Context::from_waker(&waker))
+                            .is_pending()
+                        {
+                            pending.lock().push((priority, task));
+                        }
+                    })
+                }));
+            if !self.poll_until(deadline) {
+                *self.waiter.lock() = None;
+                return false;
+            }
         }
-        self.poll();
+    }
+}
+
+impl<ItemType> RuntimeEngine<ItemType> {
+    pub(crate) fn priority_policy(&self, policy: PriorityPolicy) {
+        *self.policy.lock() = policy;
+    }
+
+    pub(crate) fn set_default_priority(&self, priority: Priority) {
+        *self.default_priority.lock() = priority;
+    }
+
+    /// The priority of the child tasks spawned without one
+    pub(crate) fn spawn_priority(&self) -> Priority {
+        *self.default_priority.lock()
+    }
+
+    pub(crate) fn admit(&self, priority: Priority) -> Result<Priority, SpawnError> {
+        if teardown::is_tearing_down() {
+            return Err(SpawnError::ShuttingDown);
+        }
+        if self.is_closed() {
+            return Err(SpawnError::Closed);
+        }
+        self.policy.lock().admit(priority)
+    }
+
+    /// Counts the given number of child tasks against the cap of the spawn group
+    fn reserve(&self, count: usize) -> Result<(), SpawnError> {
+        let reserved: Result<(), SpawnError> = self.task_cap.reserve(count);
+        if reserved.is_err() {
+            self.metrics.record_over_cap(count);
+        }
+        reserved
+    }
+
+    pub(crate) fn set_max_tasks(&self, max_tasks: usize, policy: TaskCapPolicy) {
+        self.task_cap.set(max_tasks, policy);
+    }
+
+    pub(crate) fn queue_capacity(&self, capacity: usize) {
+        *self.queue_capacity.lock() = Some(capacity);
+    }
+
+    pub(crate) fn drop_wait_cap(&self, cap: Duration) {
+        *self.drop_wait_cap.lock() = Some(cap);
+    }
+
+    pub(crate) fn exclusive_batch(&self, size: usize) {
+        assert!(
+            self.stream.item_count() == 0,
+            "exclusive batch mode must be declared before spawning any child task"
+        );
+        assert!(
+            self.batch.declare(size),
+            "exclusive batch mode was already declared"
+        );
+        self.stream.reserve(size);
+        self.tasks.lock().reserve(size);
+    }
+
+    /// Keeps child tasks from starting once the given number of started child tasks have results left
+    /// unacknowledged
+    pub(crate) fn ack_mode(&self, max_unacked: usize) {
+        self.stream.acks().set_limit(max_unacked);
+    }
+
+    pub(crate) fn result_buffer(&self, capacity: usize, policy: OverflowPolicy) {
+        assert!(capacity > 0, "the result buffer needs room for at least one result");
+        self.stream.set_capacity(Bound::Items(capacity), policy);
+    }
+
+    pub(crate) fn result_buffer_bytes(&self, max_bytes: usize, policy: OverflowPolicy) {
+        assert!(max_bytes > 0, "the result buffer needs room for at least one byte");
+        self.stream.set_capacity(Bound::Bytes(max_bytes), policy);
+    }
+
+    /// Keeps only the newest result buffered, or lets the child tasks buffer every result again
+    pub(crate) fn latest_only(&self, enabled: bool) {
+        if enabled {
+            self.stream
+                .set_capacity(Bound::Items(1), OverflowPolicy::DropOldest);
+        } else {
+            self.stream.clear_capacity();
+        }
+    }
+
+    pub(crate) fn result_capacity(&self) -> usize {
+        self.stream.capacity()
+    }
+
+    pub(crate) fn measure_results(&self, size_of: SizeOf<ItemType>) {
+        self.stream.set_size_of(size_of);
+    }
+
+    pub(crate) fn adaptive_concurrency(
+        &self,
+        config: AdaptiveConfig,
+        is_failure: Option<FailureCheck<ItemType>>,
+    ) {
+        assert!(
+            config.min > 0 && config.min <= config.max,
+            "the adaptive concurrency window needs a minimum of at least one child task, not above its maximum"
+        );
+        *self.is_failure.lock() = is_failure;
+        self.window.set(config);
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        // the spawners waiting for room are refused rather than left waiting
+        self.stream.wake_spawners();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn closed_flag(&self) -> Arc<AtomicBool> {
+        self.closed.clone()
     }
 }
 
@@ -88,30 +905,575 @@ impl<ItemType> RuntimeEngine<ItemType> {
     pub(crate) fn store(&self, val: bool) {
         self.wait_flag.store(val, Ordering::Release);
     }
+
+    /// Starts the thread running the executor again if the spawn group was cancelled since it last ran
+    ///
+    /// While the spawn group waits for all of its child tasks, it polls the ones spawned meanwhile itself, so the
+    /// executor starts again only once it's done waiting.
+    ///
+    /// # Returns
+    /// The lock on the waiting spawn group, held by the spawner until its child tasks are queued so the spawn group
+    /// can't stop waiting in between
+    fn restart(&self) -> Result<MutexGuard<'_, Option<Waker>>, SpawnError> {
+        let waiter: MutexGuard<'_, Option<Waker>> = self.waiter.lock();
+        if self.load() && waiter.is_none() {
+            self.runtime
+                .start()
+                .map_err(|error| SpawnError::ThreadUnavailable(error.kind()))?;
+            self.store(false);
+        }
+        Ok(waiter)
+    }
 }
 
 impl<ItemType: Send + 'static> RuntimeEngine<ItemType> {
-    pub(crate) fn write_task<F>(&self, priority: Priority, task: F)
+    pub(crate) fn write_task<F>(&self, priority: Priority, task: F) -> Result<(), SpawnError>
     where
         F: Future<Output = ItemType> + Send + 'static,
     {
-        if self.load() {
-            self.runtime.start();
-            self.store(false);
+        self.write_tracked_task(priority, task, None, None, None, None)
+            .map(|_| ())
+    }
+
+    /// Spawns a child task unless the spawn group was cancelled or reset since the token was taken, counting it
+    /// with the given count once spawned
+    ///
+    /// The generation is checked and the child task counted under the admission, which a cancellation takes to move
+    /// on to the next generation, so the child task is either spawned and counted before the cut or refused.
+    pub(crate) fn write_task_in<F>(
+        &self,
+        token: &CancelToken,
+        count: &AtomicUsize,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let _admission = self.admission.lock();
+        if token.is_cancelled() {
+            return Err(SpawnError::Cancelled);
         }
+        self.write_task(priority, task)?;
+        count.fetch_add(1, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Spawns a child task polled on the worker threads of the given lane from the start
+    pub(crate) fn write_task_on<F>(
+        &self,
+        lane: Lane,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.write_task(priority, async move {
+            move_to(lane).await;
+            task.await
+        })
+    }
+
+    /// Spawns a child task unless the pending ones already fill the queue capacity of the spawn group
+    pub(crate) fn write_bounded_task<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let _admission = self.admission.lock();
+        if let Some(capacity) = self.full_capacity() {
+            return Err(SpawnError::QueueFull(capacity));
+        }
+        self.write_task(priority, task)
+    }
+
+    /// Waits until the pending child tasks no longer fill the queue capacity of the spawn group, then spawns the
+    /// child task
+    ///
+    /// The spawner is woken every time a child task finishes rather than polling, and a cancellation of the spawn
+    /// group while it waits refuses the child task.
+    pub(crate) async fn write_task_when_ready<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let token: CancelToken = self.cancel_token();
+        let mut task: Option<F> = Some(task);
+        poll_fn(|cx| {
+            if token.is_cancelled() {
+                return Poll::Ready(Err(SpawnError::Cancelled));
+            }
+            let _admission = self.admission.lock();
+            if self.full_capacity().is_some() {
+                // registered before checking again, so a child task finishing in between isn't missed
+                self.stream.register_spawner(cx.waker());
+                if self.full_capacity().is_some() {
+                    return Poll::Pending;
+                }
+            }
+            let Some(task) = task.take() else {
+                return Poll::Ready(Ok(()));
+            };
+            Poll::Ready(self.write_task(priority, task))
+        })
+        .await
+    }
+
+    /// The queue capacity of the spawn group if its pending child tasks fill it
+    ///
+    /// A closed spawn group is never full, so its spawners report it being closed rather than full.
+    fn full_capacity(&self) -> Option<usize> {
+        let capacity: Option<usize> = *self.queue_capacity.lock();
+        capacity.filter(|&capacity| self.pending_tasks() >= capacity && !self.is_closed())
+    }
+
+    /// Spawns a child task whose result carries the given sequence number
+    pub(crate) fn write_sequenced_task<F>(
+        &self,
+        sequence: u64,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.write_tracked_task(priority, task, None, None, Some(sequence), None)
+            .map(|_| ())
+    }
+
+    /// Spawns a child task whose result goes to the returned handle, unless the handle is dropped first
+    pub(crate) fn write_task_with_handle<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<ChildHandle<ItemType>, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let slot: Arc<ChildSlot<ItemType>> = ChildSlot::new();
+        let wave: usize = self.stream.wave();
+        let tag: Tag =
+            self.write_tracked_task(priority, task, None, Some(slot.clone()), None, None)?;
+        Ok(ChildHandle::new(slot, self.stream(), wave, tag))
+    }
+
+    /// Spawns a child task listed and reported under the given name
+    pub(crate) fn write_named_task<F>(
+        &self,
+        name: Arc<str>,
+        priority: Priority,
+        task: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.write_tracked_task(priority, task, None, None, None, Some(name))
+            .map(|_| ())
+    }
+
+    /// Spawns a child task that a subset keeps track of until its result, if any, is delivered,
+    /// whose result goes to the handle owning the slot if there's one, carries the sequence number if there's one and
+    /// is listed and reported under the name if there's one
+    ///
+    /// # Returns
+    /// The tag of the result of the child task, whose index increases with every child task spawned to the spawn group
+    pub(crate) fn write_tracked_task<F>(
+        &self,
+        priority: Priority,
+        task: F,
+        subset: Option<SubsetGuard>,
+        child: Option<Arc<ChildSlot<ItemType>>>,
+        sequence: Option<u64>,
+        name: Option<Arc<str>>,
+    ) -> Result<Tag, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let priority: Priority = self.admit(priority)?;
+        if let Some(size) = self.batch.size() {
+            if subset.is_some() || child.is_some() || sequence.is_some() || name.is_some() {
+                return Err(SpawnError::BatchUnsupported);
+            }
+            let index: u64 = self.batch.reserve(size, 1)?;
+            let _waiter = self.restart()?;
+            self.stream.increment();
+            let (tag, job) = self.batch_task_job(priority, index, task);
+            self.runtime.submit(job);
+            return Ok(tag);
+        }
+        self.reserve(1)?;
+        let _waiter = self.restart()?;
         self.stream.increment();
+        let (tag, job) = self.child_task_job(priority, task, subset, child, sequence, name);
+        self.runtime.submit(job);
+        Ok(tag)
+    }
+
+    /// Spawns every child task with the same priority, counting them and queueing them all at once
+    ///
+    /// # Returns
+    /// The number of child tasks spawned
+    pub(crate) fn write_tasks<F>(
+        &self,
+        priority: Priority,
+        tasks: impl IntoIterator<Item = F>,
+    ) -> Result<usize, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let priority: Priority = self.admit(priority)?;
+        let tasks: Vec<F> = tasks.into_iter().collect();
+        if let Some(size) = self.batch.size() {
+            // either the batch has every child task left or none of them is spawned
+            let first: u64 = self.batch.reserve(size, tasks.len())?;
+            let _waiter = self.restart()?;
+            let jobs: Vec<_> = (first..)
+                .zip(tasks)
+                .map(|(index, task)| self.batch_task_job(priority, index, task).1)
+                .collect();
+            self.stream.increment_by(jobs.len());
+            let count: usize = jobs.len();
+            self.runtime.submit_all(jobs);
+            return Ok(count);
+        }
+        // either every child task fits under the cap or none of them is spawned
+        self.reserve(tasks.len())?;
+        let _waiter = self.restart()?;
+        let jobs: Vec<_> = tasks
+            .into_iter()
+            .map(|task| {
+                self.child_task_job(priority, task, None, None, None, None)
+                    .1
+            })
+            .collect();
+        // counted before any of them is queued, so none of them can finish before it's counted
+        self.stream.increment_by(jobs.len());
+        let count: usize = jobs.len();
+        self.runtime.submit_all(jobs);
+        Ok(count)
+    }
+
+    /// Builds the job spawning a child task on the executor once a worker thread runs it, without counting the
+    /// child task
+    fn child_task_job<F>(
+        &self,
+        priority: Priority,
+        task: F,
+        subset: Option<SubsetGuard>,
+        child: Option<Arc<ChildSlot<ItemType>>>,
+        sequence: Option<u64>,
+        name: Option<Arc<str>>,
+    ) -> (Tag, impl FnOnce() + Send + 'static)
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
         let mut stream: AsyncStream<ItemType> = self.stream();
+        let wave: usize = stream.wave();
         let runtime = self.runtime.clone();
         let tasks: Arc<Mutex<Vec<(Priority, Task)>>> = self.tasks.clone();
-        self.runtime.submit(move || {
+        let waiter: Arc<Mutex<Option<Waker>>> = self.waiter.clone();
+        let inject_panic: bool = self.runtime.faults().should_panic_task();
+        let on_dequeued: Arc<Mutex<Option<DequeueHook>>> = self.on_dequeued.clone();
+        let on_discarded: Arc<Mutex<Option<DiscardHook<ItemType>>>> = self.on_discarded.clone();
+        let output: Arc<Mutex<PanicOutput>> = self.panic_output.clone();
+        let queued_at: Instant = Instant::now();
+        let registry: Registry = self.registry.clone();
+        let traced: bool = self.traced.load(Ordering::Acquire);
+        let record: Arc<TaskRecord> = registry.register(priority, name, traced);
+        let tag: Tag = Tag {
+            index: Some(record.id),
+            sequence,
+            ack: None,
+        };
+        let token: CancelToken = self.cancel_token();
+        let metrics: Metrics = self.metrics.clone();
+        let log: ExecutionLog = self.runtime.execution_log().clone();
+        // counts the child task as cancelled if it's dropped without ever running
+        let mut guard: CompletionGuard = self.completions.guard(metrics.clone(), record.clone());
+        let chain: Option<Arc<[Middleware]>> = self.middlewares.chain();
+        let outstanding: OutstandingGuard = self.outstanding.guard();
+        let window: ConcurrencyWindow = self.window.clone();
+        let is_failure: Option<FailureCheck<ItemType>> = *self.is_failure.lock();
+        let blocking: BlockingWatch = self.blocking.clone();
+        let job = move || {
             tasks.lock().push((
                 priority,
-                runtime.spawn(async move {
-                    stream.insert_item(task.await).await;
-                    stream.decrement_task_count();
+                runtime.spawn_with_id(priority, Some(record.id), Some(outstanding), async move {
+                    // finishes the handle without a result unless the result is delivered to it
+                    let child: Option<SlotCompletion<ItemType>> = child.map(SlotCompletion);
+                    let is_cut = || {
+                        token.is_cancelled()
+                            || record.is_shed()
+                            || child.as_ref().is_some_and(|child| child.0.is_aborted())
+                    };
+                    let cut = || {
+                        // a shed child task was already let go of and uncounted when it was shed
+                        if record.is_shed() {
+                            return;
+                        }
+                        registry.cancel(record.id);
+                        stream.decrement_count(wave);
+                    };
+                    if is_cut() {
+                        return cut();
+                    }
+                    // in ack mode, the child task only starts once few enough results are left unacknowledged
+                    let acks: AckGate = stream.acks().clone();
+                    let slot: Option<AckSlot> = poll_fn(|cx| {
+                        if is_cut() {
+                            return Poll::Ready(None);
+                        }
+                        acks.poll_slot(cx).map(Some)
+                    })
+                    .await;
+                    let Some(slot) = slot else {
+                        return cut();
+                    };
+                    // under adaptive concurrency, the child task only starts once the window has room for it
+                    let window_slot: Option<WindowSlot> = poll_fn(|cx| {
+                        if is_cut() {
+                            return Poll::Ready(None);
+                        }
+                        window.poll_slot(cx).map(Some)
+                    })
+                    .await;
+                    let Some(window_slot) = window_slot else {
+                        return cut();
+                    };
+                    if !record.start() {
+                        return;
+                    }
+                    let started_at: Instant = Instant::now();
+                    guard.start(log, started_at);
+                    let queued_for: Duration = started_at.duration_since(queued_at);
+                    let hook: Option<DequeueHook> = on_dequeued.lock().clone();
+                    if let Some(hook) = hook {
+                        hook(QueueLatency {
+                            priority,
+                            queued_for,
+                        });
+                    }
+                    let (id, name): (u64, Option<Arc<str>>) = (record.id, record.name.clone());
+                    let task = async move {
+                        if inject_panic {
+                            panic!("Injected fault: child task panicked");
+                        }
+                        blocking
+                            .watch(id, name, middleware::run_through(chain, task))
+                            .await
+                    };
+                    let mut task = pin!(AssertUnwindSafe(task).catch_unwind());
+                    let output: PanicOutput = *output.lock();
+                    // a poll that began before the cancellation finishes and delivers its result,
+                    // but the child task is never polled again after it
+                    let output = poll_fn(|cx| {
+                        if is_cut() {
+                            return Poll::Ready(None);
+                        }
+                        stream.wake_if_starved();
+                        if let Some(child) = &child {
+                            child.0.register_task(cx.waker());
+                        }
+                        panic_output::scoped(output, || {
+                            panic_output::named(record.name.clone(), || match &record.breadcrumb {
+                                Some(slot) => breadcrumbs::polling(slot, || task.as_mut().poll(cx)),
+                                None => task.as_mut().poll(cx),
+                            })
+                        })
+                        .map(Some)
+                    })
+                    .await;
+                    // a child task whose middleware completed without running it to completion counts as cancelled
+                    let outcome: ExecutionOutcome = match output {
+                        Some(Ok(Some(_))) => ExecutionOutcome::Completed,
+                        Some(Err(_)) => {
+                            panic_isolation::note_panic();
+                            ExecutionOutcome::Panicked
+                        }
+                        Some(Ok(None)) | None => ExecutionOutcome::Cancelled,
+                    };
+                    guard.set(outcome);
+                    drop(guard);
+                    // a cancelled child task tells nothing about the latency
+                    match &output {
+                        Some(Ok(Some(value))) => window_slot.finish(
+                            started_at.elapsed(),
+                            is_failure.is_some_and(|is_failure| is_failure(value)),
+                        ),
+                        Some(Err(_)) => window_slot.finish(started_at.elapsed(), true),
+                        Some(Ok(None)) | None => drop(window_slot),
+                    }
+                    // a panicking or cancelled child task never produces a result
+                    match output {
+                        Some(Ok(Some(value))) => {
+                            metrics.record(queued_for, started_at.elapsed());
+                            // a result taken by the handle never goes into the stream
+                            let value: Option<ItemType> = match &child {
+                                Some(child) => child.0.deliver(value, stream.arrival()),
+                                None => Some(value),
+                            };
+                            match value {
+                                Some(value) => {
+                                    let tag: Tag = Tag {
+                                        ack: slot.keep(),
+                                        ..tag
+                                    };
+                                    let overflowed: Vec<ItemType> =
+                                        stream.insert_item(wave, tag, value).await;
+                                    hand_to_hook(&on_discarded, overflowed);
+                                }
+                                None => stream.decrement_count(wave),
+                            }
+                        }
+                        Some(Ok(None)) | Some(Err(_)) | None => stream.decrement_count(wave),
+                    }
+                    match outcome {
+                        ExecutionOutcome::Cancelled => registry.cancel(record.id),
+                        ExecutionOutcome::Completed | ExecutionOutcome::Panicked => {
+                            registry.remove(&record)
+                        }
+                    }
+                    stream.decrement_task_count(wave);
+                    drop(subset);
                 }),
             ));
+            // a spawn group waiting for all of its child tasks polls this one as well
+            if let Some(waiter) = &*waiter.lock() {
+                waiter.wake_by_ref();
+            }
+        };
+        (tag, job)
+    }
+
+    /// Builds the job spawning a child task of the batch, which skips the registry, the hooks, the middlewares and
+    /// the execution log, and counts the batch down once it completes
+    fn batch_task_job<F>(
+        &self,
+        priority: Priority,
+        index: u64,
+        task: F,
+    ) -> (Tag, impl FnOnce() + Send + 'static)
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let mut stream: AsyncStream<ItemType> = self.stream();
+        let wave: usize = stream.wave();
+        let runtime = self.runtime.clone();
+        let tasks: TaskQueue = self.tasks.clone();
+        let waiter: Arc<Mutex<Option<Waker>>> = self.waiter.clone();
+        let token: CancelToken = self.cancel_token();
+        let batch: Arc<Batch> = self.batch.clone();
+        let output: PanicOutput = *self.panic_output.lock();
+        let on_discarded: Arc<Mutex<Option<DiscardHook<ItemType>>>> = self.on_discarded.clone();
+        let tag: Tag = Tag {
+            index: Some(index),
+            sequence: None,
+            ack: None,
+        };
+        let outstanding: OutstandingGuard = self.outstanding.guard();
+        let job = move || {
+            tasks.lock().push((
+                priority,
+                runtime.spawn_with_id(priority, Some(index), Some(outstanding), async move {
+                    if token.is_cancelled() {
+                        stream.decrement_count(wave);
+                        return;
+                    }
+                    let mut task = pin!(AssertUnwindSafe(task).catch_unwind());
+                    let output = poll_fn(|cx| {
+                        if token.is_cancelled() {
+                            return Poll::Ready(None);
+                        }
+                        stream.wake_if_starved();
+                        panic_output::scoped(output, || task.as_mut().poll(cx)).map(Some)
+                    })
+                    .await;
+                    match output {
+                        Some(Ok(value)) => {
+                            batch.complete();
+                            let overflowed: Vec<ItemType> =
+                                stream.insert_item(wave, tag, value).await;
+                            hand_to_hook(&on_discarded, overflowed);
+                        }
+                        Some(Err(_)) => {
+                            panic_isolation::note_panic();
+                            batch.panic();
+                            stream.decrement_count(wave);
+                        }
+                        None => stream.decrement_count(wave),
+                    }
+                    stream.decrement_task_count(wave);
+                }),
+            ));
+            // a spawn group waiting for all of its child tasks polls this one as well
+            if let Some(waiter) = &*waiter.lock() {
+                waiter.wake_by_ref();
+            }
+        };
+        (tag, job)
+    }
+}
+
+impl<ItemType> RuntimeEngine<ItemType> {
+    pub(crate) fn run_task<Fut>(
+        &self,
+        priority: Priority,
+        future: Fut,
+    ) -> Result<JoinHandle<Fut::Output>, SpawnError>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let priority: Priority = self.admit(priority)?;
+        // the futures run alongside the child tasks are never listed, which an exclusive batch relies on
+        if self.batch.size().is_some() {
+            return Err(SpawnError::BatchUnsupported);
+        }
+        let _waiter = self.restart()?;
+        let (task, handle) = joinable(future);
+        let runtime: Executor = self.runtime.clone();
+        let tasks: TaskQueue = self.tasks.clone();
+        let outstanding: OutstandingGuard = self.outstanding.guard();
+        self.runtime.submit(move || {
+            let task: Task = runtime.spawn_with_id(priority, None, Some(outstanding), task);
+            tasks.lock().push((priority, task));
         });
+        Ok(handle)
+    }
+
+    /// Runs the given future as ``run_task`` does, closing the spawn group once it completes or is cancelled
+    ///
+    /// The stream doesn't end in the meantime, even with no child task pending. A future that can't be run is dropped
+    /// right away, which closes the spawn group at once.
+    pub(crate) fn close_when<Fut>(&self, future: Fut) -> Result<JoinHandle<Fut::Output>, SpawnError>
+    where
+        ItemType: Send + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let slot: CloserSlot<ItemType> = Arc::new(Mutex::new(Some(Closer {
+            closed: self.closed.clone(),
+            stream: self.stream.clone(),
+            wave: self.stream.wave(),
+            _open: self.stream.hold_open(),
+        })));
+        {
+            let mut closers = self.closers.lock();
+            closers.retain(|slot| slot.lock().is_some());
+            closers.push(slot.clone());
+        }
+        let close_on_drop: CloseOnDrop<ItemType> = CloseOnDrop(slot);
+        self.run_task(self.spawn_priority(), async move {
+            let _close_on_drop: CloseOnDrop<ItemType> = close_on_drop;
+            future.await
+        })
     }
 }
 
@@ -119,4 +1481,18 @@ impl<ItemType> RuntimeEngine<ItemType> {
     pub(crate) fn poll(&self) {
         self.runtime.poll_all();
     }
+
+    /// Waits for the polls in flight as ``poll`` does, giving up once the deadline passed if there's one
+    ///
+    /// # Returns
+    /// Whether the polls were done in time
+    fn poll_until(&self, deadline: Option<Instant>) -> bool {
+        match deadline {
+            Some(deadline) => self.runtime.poll_all_until(deadline),
+            None => {
+                self.poll();
+                true
+            }
+        }
+    }
 }
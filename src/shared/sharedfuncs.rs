@@ -12,9 +12,8 @@ pub trait Shared {
         F: Future<Output = Self::Result> + Send + 'static;
     /// Cancels all running tasks in the engine
     fn cancel_all_tasks(&mut self);
-    /// Add a new task only if the engine is not cancelled yet,
-    /// otherwise does nothing
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    /// Add a new task only if the engine is not cancelled yet, returning whether it was added
+    fn add_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static;
 }
@@ -0,0 +1,106 @@
+use crate::shared::priority::Priority;
+use std::{fmt, time::Duration};
+
+/// The state of a child task which hasn't completed yet, or of one that was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TaskState {
+    /// The child task is waiting for a worker thread to pick it up
+    Queued,
+    /// The child task has been polled at least once
+    Running,
+    /// The child task was cancelled before it completed, whether it had started or not, which is its terminal state
+    Cancelled,
+}
+
+/// A child task which hasn't completed yet or was cancelled at the time the snapshot was taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaskSnapshot {
+    /// Identifier of the child task, unique within its spawn group and increasing in spawn order
+    pub id: u64,
+    /// The priority the child task was spawned with
+    pub priority: Priority,
+    /// Whether the child task is queued, running or cancelled
+    pub state: TaskState,
+    /// The time since the child task was spawned
+    pub age: Duration,
+    /// The name the child task was spawned with through ``spawn_task_named``, if any
+    pub name: Option<String>,
+    /// The last label the child task went through with ``trace_point!``, if its spawn group traced suspensions when
+    /// it was spawned
+    pub trace_point: Option<&'static str>,
+    /// Number of times the child task was polled, which is only counted if its spawn group traced suspensions when it
+    /// was spawned
+    pub polls: u64,
+    /// The time the child task has been suspended since its last trace point, or since its last poll without one,
+    /// which is only known if its spawn group traced suspensions when it was spawned
+    pub suspended_for: Option<Duration>,
+}
+
+impl fmt::Display for TaskSnapshot {
+    /// Formats the child task on a single line, such as "task 3 pending at 'db_query' for 42s"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task {}", self.id)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        match (self.state, self.suspended_for, self.trace_point) {
+            (TaskState::Queued, ..) => write!(f, " queued for {}s", self.age.as_secs()),
+            (TaskState::Cancelled, ..) => write!(f, " cancelled"),
+            (TaskState::Running, Some(suspended_for), Some(label)) => {
+                write!(f, " pending at '{label}' for {}s", suspended_for.as_secs())
+            }
+            (TaskState::Running, Some(suspended_for), None) => write!(
+                f,
+                " pending for {}s after {} polls",
+                suspended_for.as_secs(),
+                self.polls
+            ),
+            (TaskState::Running, None, _) => write!(f, " running for {}s", self.age.as_secs()),
+        }
+    }
+}
+
+/// Group Snapshot
+///
+/// The state of a spawn group captured without stopping its child tasks, which answers
+/// what was in flight at the time it was taken. With the `serde` feature enabled, it implements
+/// ``serde::Serialize`` so it can be attached to crash reports.
+///
+/// Since child tasks keep running while the snapshot is taken, the counters and the
+/// child tasks listed might be off by the few child tasks that completed meanwhile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupSnapshot {
+    /// Number of child tasks spawned since the spawn group was created
+    pub spawned: u64,
+    /// Number of child tasks which haven't completed yet
+    pub pending: usize,
+    /// Number of results waiting to be consumed from the spawn group's stream
+    pub buffered: usize,
+    /// Whether the spawn group was cancelled
+    pub cancelled: bool,
+    /// Whether the spawn group was closed
+    pub closed: bool,
+    /// The child tasks which haven't completed yet along with the last cancelled ones, ordered by their identifiers
+    ///
+    /// A cancelled child task is kept in the [`Cancelled`](TaskState::Cancelled) state until the spawn group is
+    /// reset or the 256 child tasks cancelled after it take its place.
+    pub tasks: Vec<TaskSnapshot>,
+}
+
+impl fmt::Display for GroupSnapshot {
+    /// Formats the counters on a first line, followed by a line for each child task listed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "spawned: {}, pending: {}, buffered: {}, cancelled: {}, closed: {}",
+            self.spawned, self.pending, self.buffered, self.cancelled, self.closed
+        )?;
+        for task in &self.tasks {
+            write!(f, "\n{task}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::shared::priority::Priority;
+use std::{error::Error, fmt::Display, io};
+
+/// Spawn Error
+///
+/// The reason a spawn group refused to spawn a child task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SpawnError {
+    /// The spawn group was closed and no longer accepts new child tasks
+    Closed,
+    /// The priority policy of the spawn group refused the priority of the child task
+    Rejected(Priority),
+    /// The thread factory of the spawn group failed to create the thread it needed to run the child task
+    ThreadUnavailable(io::ErrorKind),
+    /// The spawn group already spawned the maximum number of child tasks it was given
+    TaskCapReached(usize),
+    /// The child task was spawned while a spawn group was being torn down on the same thread, typically from the
+    /// destructor of one of its discarded results
+    ShuttingDown,
+    /// The pending child tasks of the spawn group already fill the queue capacity it was given
+    QueueFull(usize),
+    /// The spawn group in exclusive batch mode already spawned every child task of the batch it declared
+    BatchComplete(usize),
+    /// The child task needs a feature the spawn group skips in exclusive batch mode, such as a child handle, a
+    /// sequence number or a subset
+    BatchUnsupported,
+    /// The spawn group was cancelled while the child task waited for room in its queue
+    Cancelled,
+}
+
+impl Display for SpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::Closed => f.write_str("The spawn group was closed"),
+            SpawnError::Rejected(priority) => {
+                write!(f, "The spawn group refused the priority {:?}", priority)
+            }
+            SpawnError::ThreadUnavailable(kind) => {
+                write!(f, "The spawn group failed to create a thread: {}", kind)
+            }
+            SpawnError::TaskCapReached(max_tasks) => {
+                write!(
+                    f,
+                    "The spawn group reached its maximum of {} child tasks",
+                    max_tasks
+                )
+            }
+            SpawnError::QueueFull(capacity) => {
+                write!(
+                    f,
+                    "The spawn group already has {} pending child tasks",
+                    capacity
+                )
+            }
+            SpawnError::BatchComplete(size) => {
+                write!(
+                    f,
+                    "The spawn group already spawned the {} child tasks of its batch",
+                    size
+                )
+            }
+            SpawnError::BatchUnsupported => {
+                f.write_str("The child task isn't supported in exclusive batch mode")
+            }
+            SpawnError::Cancelled => {
+                f.write_str("The spawn group was cancelled while the child task waited")
+            }
+            SpawnError::ShuttingDown => {
+                f.write_str("The child task was spawned while a spawn group was torn down")
+            }
+        }
+    }
+}
+
+impl Error for SpawnError {}
@@ -0,0 +1,102 @@
+use parking_lot::Mutex;
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    task::{Context, Poll, Waker},
+};
+
+/// Counter of the child tasks spawned through a subset that haven't finished yet
+#[derive(Default)]
+pub(crate) struct SubsetState {
+    remaining: AtomicUsize,
+    // the spawn group was cancelled, so the child tasks of the subset never finish
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl SubsetState {
+    /// Tracks a newly spawned child task until the returned guard is dropped
+    pub(crate) fn track(self: &Arc<Self>) -> SubsetGuard {
+        self.remaining.fetch_add(1, Ordering::AcqRel);
+        SubsetGuard {
+            state: self.clone(),
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn poll_done(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.poll_below(1, cx)
+    }
+
+    /// Ready once fewer than ``limit`` child tasks of the subset are left, or once the spawn group was cancelled
+    pub(crate) fn poll_below(&self, limit: usize, cx: &mut Context<'_>) -> Poll<()> {
+        {
+            // registered before checking, so the child task finishing can't go unnoticed
+            let mut wakers = self.wakers.lock();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        if self.remaining() < limit || self.is_cancelled() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        let wakers: Vec<Waker> = mem::take(&mut *self.wakers.lock());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Counts a child task of a subset as finished once it's dropped, which happens even if it was never polled
+pub(crate) struct SubsetGuard {
+    state: Arc<SubsetState>,
+}
+
+impl Drop for SubsetGuard {
+    fn drop(&mut self) {
+        self.state.remaining.fetch_sub(1, Ordering::AcqRel);
+        // any child task finishing may be the one a bounded spawner waits for, not only the last one
+        self.state.wake();
+    }
+}
+
+/// The subsets of a spawn group, completed all at once when it's cancelled
+#[derive(Clone, Default)]
+pub(crate) struct Subsets {
+    states: Arc<Mutex<Vec<Weak<SubsetState>>>>,
+}
+
+impl Subsets {
+    pub(crate) fn create(&self) -> Arc<SubsetState> {
+        let state: Arc<SubsetState> = Arc::default();
+        let mut states = self.states.lock();
+        states.retain(|state| state.strong_count() > 0);
+        states.push(Arc::downgrade(&state));
+        state
+    }
+
+    pub(crate) fn cancel_all(&self) {
+        let states: Vec<Weak<SubsetState>> = mem::take(&mut *self.states.lock());
+        states
+            .iter()
+            .filter_map(Weak::upgrade)
+            .for_each(|state| state.cancel());
+    }
+}
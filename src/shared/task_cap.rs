@@ -0,0 +1,57 @@
+use crate::shared::spawn_error::SpawnError;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Task Cap Policy
+///
+/// What a spawn group does with a child task spawned once it has spawned its maximum number of child tasks, as set
+/// with the ``set_max_tasks`` method of the spawn group
+///
+/// Either way the child task never runs, and it's counted by [`Metrics::spawns_over_cap`](crate::Metrics::spawns_over_cap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskCapPolicy {
+    /// The child task is refused, ``try_spawn_task`` returns ``SpawnError::TaskCapReached`` while ``spawn_task``
+    /// drops it silently, the default
+    #[default]
+    Refuse,
+    /// Spawning the child task panics, on the thread spawning it
+    Panic,
+}
+
+/// The maximum number of child tasks a spawn group spawns over its lifetime, along with how many it spawned
+#[derive(Default)]
+pub(crate) struct TaskCap {
+    limit: Mutex<Option<(usize, TaskCapPolicy)>>,
+    spawned: AtomicUsize,
+}
+
+impl TaskCap {
+    pub(crate) fn set(&self, max_tasks: usize, policy: TaskCapPolicy) {
+        *self.limit.lock() = Some((max_tasks, policy));
+    }
+
+    /// Counts the given number of child tasks as spawned, unless that would go beyond the cap
+    ///
+    /// The child tasks spawned before a cap was set count towards it as well.
+    pub(crate) fn reserve(&self, count: usize) -> Result<(), SpawnError> {
+        let limit: Option<(usize, TaskCapPolicy)> = *self.limit.lock();
+        let Some((max_tasks, policy)) = limit else {
+            self.spawned.fetch_add(count, Ordering::AcqRel);
+            return Ok(());
+        };
+        let reserved = self
+            .spawned
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |spawned| {
+                spawned
+                    .checked_add(count)
+                    .filter(|&spawned| spawned <= max_tasks)
+            });
+        match (reserved, policy) {
+            (Ok(_), _) => Ok(()),
+            (Err(_), TaskCapPolicy::Refuse) => Err(SpawnError::TaskCapReached(max_tasks)),
+            (Err(_), TaskCapPolicy::Panic) => {
+                panic!("The spawn group reached its maximum of {max_tasks} child tasks")
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::cell::Cell;
+
+thread_local! {
+    // the number of spawn groups being torn down on the calling thread, nested when a result dropped by one of them
+    // tears down a spawn group of its own
+    static TEARING_DOWN: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks the calling thread as tearing down a spawn group until it's dropped
+pub(crate) struct Teardown(());
+
+impl Drop for Teardown {
+    fn drop(&mut self) {
+        TEARING_DOWN.set(TEARING_DOWN.get() - 1);
+    }
+}
+
+/// Refuses every child task spawned on the calling thread, into any spawn group, until the returned guard is dropped
+///
+/// The results discarded while a spawn group is torn down are dropped on the thread tearing it down, which may be in
+/// the middle of shutting the threadpool down, so a destructor spawning from there would be lost or hang.
+pub(crate) fn enter() -> Teardown {
+    TEARING_DOWN.set(TEARING_DOWN.get() + 1);
+    Teardown(())
+}
+
+/// Whether a spawn group is being torn down on the calling thread
+pub(crate) fn is_tearing_down() -> bool {
+    TEARING_DOWN.get() > 0
+}
@@ -0,0 +1,82 @@
+use std::thread;
+
+/// Largest share of time a child task is assumed to spend waiting, which caps the recommendation at ten threads per core
+const MAX_IO_BOUND_FRACTION: f64 = 0.9;
+
+/// Workload Hint
+///
+/// Describes the child tasks a spawn group is going to run, from which [`recommended_threads`](crate::recommended_threads)
+/// suggests how many threads its threadpool should use, and which
+/// [`SpawnGroup::with_workload`](crate::SpawnGroup::with_workload) creates the spawn group with.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WorkloadHint {
+    /// Share of its time a child task spends waiting rather than computing, from 0.0 for CPU-bound child tasks to 1.0
+    pub io_bound_fraction: f64,
+    /// Number of child tasks expected to run at once, if known
+    pub expected_task_count: Option<usize>,
+    /// Whether the child tasks wait by blocking their thread, like synchronous file or network calls do,
+    /// rather than by awaiting
+    pub blocking: bool,
+}
+
+impl WorkloadHint {
+    /// Suggests a number of threads for a machine with the given number of cores
+    ///
+    /// The heuristics are:
+    /// * Child tasks that await while waiting don't hold a thread, so one thread per core keeps the cores busy however
+    ///   I/O bound they are.
+    /// * Child tasks that block while waiting hold a thread, so the cores stay busy with
+    ///   ``cores / (1 - io_bound_fraction)`` threads. The fraction is capped at 0.9, which caps this at ten threads per core.
+    /// * There is no point in more threads than child tasks expected to run at once.
+    /// * The suggestion is never below one thread.
+    ///
+    /// # Parameters
+    ///
+    /// * `cores`: number of cores of the machine
+    pub fn threads_for(&self, cores: usize) -> usize {
+        let cores: usize = cores.max(1);
+        let threads: usize = if self.blocking {
+            let io_bound_fraction: f64 = if self.io_bound_fraction.is_nan() {
+                0.0
+            } else {
+                self.io_bound_fraction.clamp(0.0, MAX_IO_BOUND_FRACTION)
+            };
+            (cores as f64 / (1.0 - io_bound_fraction)).round() as usize
+        } else {
+            cores
+        };
+        match self.expected_task_count {
+            Some(tasks) => threads.min(tasks),
+            None => threads,
+        }
+        .max(1)
+    }
+}
+
+/// Suggests the number of threads a spawn group should use for the given workload on this machine
+///
+/// See [`WorkloadHint::threads_for`] for the heuristics, which are applied to the default amount of parallelism
+/// the program can use.
+///
+/// # Parameters
+///
+/// * `workload`: the child tasks the spawn group is going to run
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{recommended_threads, SpawnGroup, WorkloadHint};
+///
+/// // child tasks making synchronous database calls, waiting about 80% of the time
+/// let hint = WorkloadHint {
+///     io_bound_fraction: 0.8,
+///     expected_task_count: Some(1_000),
+///     blocking: true,
+/// };
+/// let group = SpawnGroup::<()>::new(recommended_threads(hint));
+/// assert_eq!(hint.threads_for(4), 20);
+/// ```
+pub fn recommended_threads(workload: WorkloadHint) -> usize {
+    let cores: usize = thread::available_parallelism().map_or(1, |cores| cores.get());
+    workload.threads_for(cores)
+}
@@ -5,6 +5,7 @@ use std::{
     time::{Duration, Instant},
 };
 
+/// Future returned by [`sleep`](crate::sleep), which is ready once its duration has elapsed
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Delay {
@@ -2,7 +2,7 @@ mod delay;
 
 use std::time::Duration;
 
-use self::delay::Delay;
+pub use self::delay::Delay;
 
 /// Sleeps for the specified amount of time.
 ///
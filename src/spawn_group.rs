@@ -1,17 +1,48 @@
+use crate::async_runtime::lane::Lane;
+use crate::acked_stream::AckedStream;
+use crate::async_stream::AsyncStream;
+use crate::blocking_iter::BlockingIter;
+use crate::blocking_results::BlockingResults;
+use crate::channel_bridge;
+use crate::child_handle::ChildHandle;
+use crate::dyn_spawn_group::BoxFuture;
+use crate::group_spawner::GroupSpawner;
+use crate::join_handle::{finished, JoinHandle};
+use crate::mapped_group::MappedGroup;
+use crate::marked_stream::MarkedStream;
+use crate::result_stream::ResultStream;
+use crate::rev_stream::RevStream;
+use crate::sequenced_stream::SequencedStream;
+use crate::shared::workload_hint::{recommended_threads, WorkloadHint};
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
-    wait::Waitable,
+    adaptive::AdaptiveConfig, drop_policy::GroupDropPolicy, initializible::Initializible,
+    keyed::Keyed, latency::QueueLatency, metrics::Metrics, overflow_policy::OverflowPolicy,
+    panic_isolation::PanicIsolation, panic_output::PanicOutput, priority::Priority,
+    priority_policy::PriorityPolicy, result_size::ResultSize, runtime::RuntimeEngine,
+    sharedfuncs::Shared, snapshot::GroupSnapshot, spawn_error::SpawnError, subsets::SubsetGuard,
+    task_cap::TaskCapPolicy, wait::Waitable,
 };
+use crate::subset::Subset;
+use crate::threadpool_impl::{ExecutionRecord, ThreadSpec};
 use async_trait::async_trait;
+use futures_core::stream::FusedStream;
 use futures_lite::{Stream, StreamExt};
 use std::{
-    future::Future,
-    pin::Pin,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    future::{poll_fn, Future},
+    hash::Hash,
+    io,
+    num::NonZeroUsize,
+    pin::{pin, Pin},
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::Receiver,
         Arc,
     },
     task::{Context, Poll},
+    thread,
+    time::Duration,
 };
 
 /// Spawn Group
@@ -29,96 +60,2218 @@ use std::{
 ///
 /// It dereferences into a ``futures`` crate ``Stream`` type where the results of each finished child task is stored and it pops out the result in First-In First-Out
 /// FIFO order whenever it is being used
-
 pub struct SpawnGroup<ValueType: Send + 'static> {
     /// A field that indicates if the spawn group had been cancelled
+    #[deprecated(note = "use `SpawnGroup::is_cancelled()` instead")]
     pub is_cancelled: bool,
-    wait_at_drop: bool,
+    // whether the spawn group was cancelled since it was created or last reset, which the deprecated field mirrors
+    cancelled: bool,
+    drop_policy: GroupDropPolicy,
     count: Arc<AtomicUsize>,
     runtime: RuntimeEngine<ValueType>,
 }
 
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Wraps the runtime of a spawn group that wasn't cancelled yet
+    #[allow(deprecated)]
+    fn from_runtime(runtime: RuntimeEngine<ValueType>, drop_policy: GroupDropPolicy) -> Self {
+        Self {
+            is_cancelled: false,
+            cancelled: false,
+            count: Arc::new(AtomicUsize::new(0)),
+            runtime,
+            drop_policy,
+        }
+    }
+
+    /// Records whether the spawn group was cancelled, mirrored into the deprecated public field
+    #[allow(deprecated)]
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+        self.is_cancelled = cancelled;
+    }
+}
+
 impl<ValueType: Send> SpawnGroup<ValueType> {
     /// Instantiates `SpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures
-    /// 
+    ///
     /// # Parameters
     ///
     /// * `num_of_threads`: number of threads to use
     pub fn new(num_of_threads: usize) -> Self {
-        Self {
-            is_cancelled: false,
-            count: Arc::new(AtomicUsize::new(0)),
-            runtime: RuntimeEngine::new(num_of_threads),
-            wait_at_drop: false,
-        }
+        Self::from_runtime(
+            RuntimeEngine::new(num_of_threads),
+            GroupDropPolicy::CancelAll,
+        )
+    }
+
+    /// Instantiates `SpawnGroup` with as many threads as [`recommended_threads`](crate::recommended_threads) suggests
+    /// for the given workload
+    ///
+    /// The number of threads is worked out once, from the amount of parallelism the program can use when the spawn
+    /// group is created. It behaves like one created with ``new`` otherwise.
+    ///
+    /// # Parameters
+    ///
+    /// * `workload`: the child tasks the spawn group is going to run
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{recommended_threads, SpawnGroup, WorkloadHint};
+    ///
+    /// // child tasks making synchronous database calls, waiting about 80% of the time
+    /// let hint = WorkloadHint {
+    ///     io_bound_fraction: 0.8,
+    ///     expected_task_count: Some(1_000),
+    ///     blocking: true,
+    /// };
+    /// let group = SpawnGroup::<()>::with_workload(hint);
+    /// assert_eq!(group.num_threads(), recommended_threads(hint));
+    /// ```
+    pub fn with_workload(workload: WorkloadHint) -> Self {
+        Self::new(recommended_threads(workload))
+    }
+
+    /// Instantiates `SpawnGroup` with a specific number of threads, and a result buffer preallocated with room for
+    /// the given number of results
+    ///
+    /// A spawn group producing many small results then doesn't reallocate its result buffer until more results than
+    /// that are left unconsumed at once. It behaves like one created with ``new`` otherwise.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `result_capacity`: number of results the result buffer holds before it reallocates
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::with_capacity(2, 1024);
+    /// let capacity = group.result_capacity();
+    /// assert!(capacity >= 1024);
+    /// for value in 0..1024 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(group.result_capacity(), capacity);
+    /// ```
+    pub fn with_capacity(num_of_threads: usize, result_capacity: usize) -> Self {
+        Self::from_runtime(
+            RuntimeEngine::with_capacity(num_of_threads, result_capacity),
+            GroupDropPolicy::CancelAll,
+        )
+    }
+
+    /// Instantiates `SpawnGroup` with a specific number of threads to use in the underlying threadpool when polling futures,
+    /// and what to do with the remaining child tasks when it's dropped
+    ///
+    /// Unlike ``new``, it can't be given zero threads by mistake. A threadpool without worker threads, only driven by
+    /// the consumer helping it, is still created with ``new(0)``.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, GroupDropPolicy, Priority, SpawnGroup};
+    /// use std::{
+    ///     num::NonZeroUsize,
+    ///     sync::{
+    ///         atomic::{AtomicBool, Ordering},
+    ///         Arc,
+    ///     },
+    ///     time::Duration,
+    /// };
+    ///
+    /// let flushed = Arc::new(AtomicBool::new(false));
+    /// let threads = NonZeroUsize::new(2).unwrap();
+    /// let mut group = SpawnGroup::<()>::new_with(threads, GroupDropPolicy::WaitForAll);
+    /// group.spawn_task(Priority::default(), {
+    ///     let flushed = flushed.clone();
+    ///     async move {
+    ///         sleep(Duration::from_millis(20)).await;
+    ///         flushed.store(true, Ordering::SeqCst);
+    ///     }
+    /// });
+    /// drop(group);
+    /// assert!(flushed.load(Ordering::SeqCst));
+    /// ```
+    pub fn new_with(num_of_threads: NonZeroUsize, policy: GroupDropPolicy) -> Self {
+        let mut group: Self = Self::new(num_of_threads.get());
+        group.drop_policy = policy;
+        group
+    }
+
+    /// Instantiates the `SpawnGroup` of a scoped closure, which waits for its child tasks when dropped
+    pub(crate) fn scoped(num_of_threads: usize) -> Self {
+        Self::from_runtime(
+            RuntimeEngine::scoped(num_of_threads),
+            GroupDropPolicy::WaitForAll,
+        )
+    }
+
+    /// Instantiates `SpawnGroup` with a specific number of threads, creating every thread it needs through the given thread factory
+    ///
+    /// Use it where threads must be created a particular way, for example to follow a naming convention, enter a security
+    /// context or go through an audit hook. The factory is kept and also creates the thread running the child tasks again
+    /// once the spawn group is used after being cancelled.
+    ///
+    /// # Parameters
+    ///
+    /// * `num_of_threads`: number of threads to use
+    /// * `factory`: a closure creating a thread from its [`ThreadSpec`](crate::ThreadSpec)
+    ///
+    /// # Returns
+    /// - Ok: the spawn group
+    /// - Err: the first error returned by the factory, once the threads it had already created are stopped
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup, ThreadSpec};
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let created = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroup::<u64>::with_thread_factory(2, {
+    ///     let created = created.clone();
+    ///     move |mut spec: ThreadSpec| {
+    ///         created.fetch_add(1, Ordering::Relaxed);
+    ///         spec.name = format!("billing-{}", spec.name);
+    ///         spec.spawn()
+    ///     }
+    /// })
+    /// .unwrap();
+    ///
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// assert_eq!(block_on(group.first()), Some(1));
+    /// // both worker threads and the thread running the child tasks
+    /// assert_eq!(created.load(Ordering::Relaxed), 3);
+    /// ```
+    pub fn with_thread_factory<Factory>(num_of_threads: usize, factory: Factory) -> io::Result<Self>
+    where
+        Factory: Fn(ThreadSpec) -> io::Result<thread::JoinHandle<()>> + Send + Sync + 'static,
+    {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_factory(num_of_threads, Arc::new(factory))?,
+            GroupDropPolicy::CancelAll,
+        ))
+    }
+
+    /// Instantiates `SpawnGroup` with separate IO and compute lanes, each with its own worker threads
+    ///
+    /// Child tasks start on the IO lane, or on the lane given to ``spawn_task_on``, and move between the lanes by
+    /// awaiting [`move_to`](crate::move_to), so the blocking-heavy and CPU-heavy phases of the child tasks never hold
+    /// up each other. Results, cancellation and waiting are still those of the one spawn group, whichever lane the
+    /// child tasks are on.
+    ///
+    /// # Parameters
+    ///
+    /// * `io_threads`: number of worker threads of the IO lane
+    /// * `compute_threads`: number of worker threads of the compute lane
+    ///
+    /// # Returns
+    /// The spawn group, or the error of the first worker thread that couldn't be spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, move_to, Lane, Priority, SpawnGroup};
+    /// use futures_lite::StreamExt;
+    /// use std::num::NonZeroUsize;
+    ///
+    /// let (io, compute) = (NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(2).unwrap());
+    /// let mut group = SpawnGroup::<u64>::with_lanes(io, compute).unwrap();
+    /// for value in 1..=10 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         // waits on IO on the IO lane, then crunches the numbers on the compute lane
+    ///         move_to(Lane::Compute).await;
+    ///         (1..=value).product()
+    ///     });
+    /// }
+    /// group.spawn_task_on(Lane::Compute, Priority::default(), async { 0 });
+    /// let results: Vec<u64> = block_on(group.collect());
+    /// assert_eq!(results.len(), 11);
+    /// assert_eq!(results.iter().max(), Some(&3628800));
+    /// ```
+    pub fn with_lanes(io_threads: NonZeroUsize, compute_threads: NonZeroUsize) -> io::Result<Self> {
+        Ok(Self::from_runtime(
+            RuntimeEngine::with_lanes(io_threads, compute_threads)?,
+            GroupDropPolicy::CancelAll,
+        ))
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Don't implicity wait for spawned child tasks to finish before being dropped
+    pub fn dont_wait_at_drop(&mut self) {
+        self.drop_policy = GroupDropPolicy::CancelAll;
+    }
+
+    /// Sets what the spawn group does with its remaining child tasks when it's dropped, replacing the policy it was
+    /// created with
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: what to do with the remaining child tasks when the spawn group is dropped
+    pub fn set_drop_policy(&mut self, policy: GroupDropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Bounds how long dropping the spawn group with ``GroupDropPolicy::WaitForAll`` waits for its child tasks
+    ///
+    /// Once the cap elapses, the ids of the child tasks still pending are reported to the panic output of the spawn
+    /// group, see [`PanicOutput`](crate::PanicOutput), and they're cancelled as by ``cancel_all``. A child task
+    /// blocking its worker thread keeps running there, detached from the spawn group.
+    ///
+    /// # Parameters
+    ///
+    /// * `cap`: the longest dropping the spawn group waits for its child tasks
+    pub fn drop_wait_cap(&mut self, cap: Duration) {
+        self.runtime.drop_wait_cap(cap);
+    }
+
+    /// Warns about the child tasks blocking their worker thread, whose single poll runs for longer than the threshold
+    /// without yielding
+    ///
+    /// Blocking calls such as ``std::thread::sleep`` or synchronous IO in a child task hold up its worker thread along
+    /// with every child task queued behind it, which silently degrades the threadpool. Once enabled, a watchdog thread
+    /// times the polls in progress and reports the ones past the threshold while they're still blocking, with the id
+    /// and the name of the child task, to the panic output of the spawn group, see [`PanicOutput`](crate::PanicOutput).
+    /// Each poll is reported at most once. It replaces the threshold set before, as well as
+    /// [`panic_on_blocking`](Self::panic_on_blocking).
+    ///
+    /// # Parameters
+    ///
+    /// * `threshold`: the longest a single poll of a child task may run
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, PanicOutput, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(1);
+    /// group.panic_output(PanicOutput::Handler(|report| {
+    ///     assert!(report.contains("child task 0 'parse' has been blocking the executor"));
+    /// }));
+    /// group.warn_on_blocking(Duration::from_millis(20));
+    /// group.spawn_task_named("parse", Priority::default(), async {
+    ///     std::thread::sleep(Duration::from_millis(100));
+    ///     1
+    /// });
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(group.buffered_results(), 1);
+    /// ```
+    pub fn warn_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, false);
+    }
+
+    /// Warns about the child tasks blocking their worker thread as [`warn_on_blocking`](Self::warn_on_blocking) does,
+    /// then makes them panic once their blocking poll returns
+    ///
+    /// It's meant for tests, where a blocking call should fail loudly. The panic is handled as any other panic of a
+    /// child task, which produces no result and counts as panicked.
+    ///
+    /// # Parameters
+    ///
+    /// * `threshold`: the longest a single poll of a child task may run
+    pub fn panic_on_blocking(&mut self, threshold: Duration) {
+        self.runtime.detect_blocking(threshold, true);
+    }
+
+    /// Registers a hook that reports how long each child task waited in the queue
+    /// before a worker thread picked it up, replacing any previously registered hook
+    ///
+    /// The hook runs on the worker thread right before the child task is first polled,
+    /// so it should be cheap.
+    ///
+    /// # Parameters
+    ///
+    /// * `hook`: a closure that receives the ``QueueLatency`` of each child task
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, QueueLatency, SpawnGroup};
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let reported = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroup::<()>::new(2);
+    /// let counter = reported.clone();
+    /// group.on_task_dequeued(move |latency: QueueLatency| {
+    ///     assert_eq!(latency.priority, Priority::HIGH);
+    ///     counter.fetch_add(1, Ordering::SeqCst);
+    /// });
+    /// for _ in 0..5 {
+    ///     group.spawn_task(Priority::HIGH, async {});
+    /// }
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(reported.load(Ordering::SeqCst), 5);
+    /// ```
+    pub fn on_task_dequeued<Hook>(&mut self, hook: Hook)
+    where
+        Hook: Fn(QueueLatency) + Send + Sync + 'static,
+    {
+        self.runtime.on_task_dequeued(Arc::new(hook));
+    }
+
+    /// Registers a hook that receives every result of the spawn group dropped without ever being consumed,
+    /// replacing any previously registered hook
+    ///
+    /// The results still buffered when the spawn group is reset or dropped are handed to the hook one at a time,
+    /// on the thread resetting or dropping it, so results owning external resources can be cleaned up. Once a hook
+    /// is registered, the results still buffered when the spawn group is dropped go to the hook rather than to the
+    /// clones of its stream. A panicking hook doesn't keep the remaining results from being handed to it.
+    ///
+    /// The number of discarded results is reported by [`Metrics::results_discarded`](crate::Metrics::results_discarded)
+    /// whether a hook is registered or not.
+    ///
+    /// # Parameters
+    ///
+    /// * `hook`: a closure that receives each discarded result
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let cleaned_up = Arc::new(Mutex::new(vec![]));
+    /// let mut group = SpawnGroup::<String>::new(2);
+    /// let metrics = group.metrics();
+    /// group.on_discarded({
+    ///     let cleaned_up = cleaned_up.clone();
+    ///     move |path: String| cleaned_up.lock().unwrap().push(path)
+    /// });
+    /// for job in 0..3 {
+    ///     group.spawn_task(Priority::default(), async move { format!("/tmp/job-{job}") });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// let consumed = block_on(group.first());
+    /// assert!(consumed.is_some());
+    /// drop(group);
+    ///
+    /// assert_eq!(cleaned_up.lock().unwrap().len(), 2);
+    /// assert_eq!(metrics.results_produced(), 3);
+    /// assert_eq!(metrics.results_consumed(), 1);
+    /// assert_eq!(metrics.results_discarded(), 2);
+    /// ```
+    pub fn on_discarded<Hook>(&mut self, hook: Hook)
+    where
+        Hook: Fn(ValueType) + Send + Sync + 'static,
+    {
+        self.runtime.on_discarded(Arc::new(hook));
+    }
+
+    /// Sets where the reports of the child tasks panicking go, for the child tasks that start running from now on
+    ///
+    /// By default the report, including a backtrace, is printed to the standard error, which may garble the screen of
+    /// a terminal application or duplicate the panic reporting of the application. See [`PanicOutput`](crate::PanicOutput)
+    /// for the alternatives.
+    ///
+    /// # Parameters
+    ///
+    /// * `output`: where the reports go
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, PanicOutput, Priority, SpawnGroup};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static REPORTED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// group.panic_output(PanicOutput::Handler(|report| {
+    ///     assert!(report.contains("no config file"));
+    ///     REPORTED.fetch_add(1, Ordering::SeqCst);
+    /// }));
+    /// group.spawn_task(Priority::default(), async { panic!("no config file") });
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [1]);
+    /// assert_eq!(REPORTED.load(Ordering::SeqCst), 1);
+    /// ```
+    pub fn panic_output(&mut self, output: PanicOutput) {
+        self.runtime.panic_output(output);
+    }
+
+    /// Sets what a worker thread does once a child task panicked on it, for the child tasks that panic from now on
+    ///
+    /// By default the worker thread goes on running child tasks. See [`PanicIsolation`](crate::PanicIsolation) for
+    /// restarting it instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `isolation`: what the worker thread does
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, PanicIsolation, PanicOutput, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// group.panic_output(PanicOutput::Silent);
+    /// group.panic_isolation(PanicIsolation::RestartWorker);
+    /// group.spawn_task(Priority::default(), async { panic!("corrupted the thread-local cache") });
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [1]);
+    /// ```
+    pub fn panic_isolation(&mut self, isolation: PanicIsolation) {
+        self.runtime.panic_isolation(isolation);
+    }
+
+    /// Registers a middleware that wraps every child task spawned from now on
+    ///
+    /// A middleware receives the child task as a boxed future and returns the future run in its place, which lets
+    /// cross-cutting behavior like timeouts, tracing or metrics live in one place instead of at every spawn site.
+    /// Middlewares compose in registration order, the first registered one being the outermost.
+    ///
+    /// A middleware may extend the child task or complete without running it to completion, in which case the child
+    /// task produces no result, as if it was cancelled, and the counters of the spawn group stay correct either way.
+    /// Child tasks are only boxed once a middleware was registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `middleware`: a closure that wraps the future of each child task
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, BoxFuture, Priority, SpawnGroup};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(vec![]));
+    /// let layer = |name: &'static str| {
+    ///     let log = log.clone();
+    ///     move |task: BoxFuture<'static, ()>| -> BoxFuture<'static, ()> {
+    ///         let log = log.clone();
+    ///         Box::pin(async move {
+    ///             log.lock().unwrap().push(format!("{name} before"));
+    ///             task.await;
+    ///             log.lock().unwrap().push(format!("{name} after"));
+    ///         })
+    ///     }
+    /// };
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(1);
+    /// group.task_middleware(layer("outer"));
+    /// group.task_middleware(layer("inner"));
+    /// group.spawn_task(Priority::default(), async { 7 });
+    /// block_on(group.wait_for_all());
+    ///
+    /// assert_eq!(block_on(group.collect::<Vec<_>>()), [7]);
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     ["outer before", "inner before", "inner after", "outer after"]
+    /// );
+    /// ```
+    pub fn task_middleware<Middleware>(&mut self, middleware: Middleware)
+    where
+        Middleware: Fn(BoxFuture<'static, ()>) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.runtime.task_middleware(Arc::new(middleware));
+    }
+
+    /// Sets which priorities the spawn group accepts for the child tasks spawned from now on
+    ///
+    /// See [`PriorityPolicy`](crate::PriorityPolicy) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `policy`: the priority policy to enforce
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, PriorityPolicy, SpawnError, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<Priority>::new(1);
+    ///
+    /// // the top priorities are reserved
+    /// group.priority_policy(PriorityPolicy::Reject(Priority::BACKGROUND..=Priority::MEDIUM));
+    /// assert_eq!(
+    ///     group.try_spawn_task(Priority::HIGH, async { Priority::HIGH }),
+    ///     Err(SpawnError::Rejected(Priority::HIGH))
+    /// );
+    /// assert!(group.try_spawn_task(Priority::LOW, async { Priority::LOW }).is_ok());
+    /// // spawn_task silently drops the refused child task
+    /// group.spawn_task(Priority::USERINITIATED, async { Priority::USERINITIATED });
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [Priority::LOW]);
+    ///
+    /// // the top priorities are lowered instead
+    /// group.priority_policy(PriorityPolicy::Clamp(Priority::BACKGROUND..=Priority::MEDIUM));
+    /// assert!(group.try_spawn_task(Priority::HIGH, async { Priority::HIGH }).is_ok());
+    /// assert_eq!(group.snapshot().tasks[0].priority, Priority::MEDIUM);
+    ///
+    /// group.priority_policy(PriorityPolicy::AllowAll);
+    /// assert!(group.try_spawn_task(Priority::HIGH, async { Priority::HIGH }).is_ok());
+    /// block_on(group.wait_for_all());
+    /// ```
+    pub fn priority_policy(&mut self, policy: PriorityPolicy) {
+        self.runtime.priority_policy(policy);
+    }
+
+    /// Caps the number of child tasks the spawn group spawns over its lifetime, as a guard against runaway spawning
+    ///
+    /// The child tasks spawned before the cap was set count towards it, and so do the ones spawned before a reset.
+    /// Once the cap is reached, every further child task is handled as the policy says, see
+    /// [`TaskCapPolicy`](crate::TaskCapPolicy).
+    ///
+    /// # Parameters
+    ///
+    /// * `max_tasks`: the most child tasks the spawn group spawns
+    /// * `policy`: what happens to the child tasks beyond the cap
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnError, SpawnGroup, TaskCapPolicy};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.set_max_tasks(3, TaskCapPolicy::Refuse);
+    /// for value in 0..3 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// assert_eq!(
+    ///     group.try_spawn_task(Priority::default(), async { 3 }),
+    ///     Err(SpawnError::TaskCapReached(3))
+    /// );
+    /// // spawn_task silently drops the refused child task
+    /// group.spawn_task(Priority::default(), async { 4 });
+    /// assert_eq!(group.metrics().spawns_over_cap(), 2);
+    /// block_on(group.wait_for_all());
+    /// ```
+    pub fn set_max_tasks(&mut self, max_tasks: usize, policy: TaskCapPolicy) {
+        self.runtime.set_max_tasks(max_tasks, policy);
+    }
+
+    /// Bounds the number of pending child tasks [`try_spawn_task`](Self::try_spawn_task) lets the spawn group have,
+    /// refusing the child tasks beyond it with ``SpawnError::QueueFull``
+    ///
+    /// Only ``try_spawn_task`` is bounded, every other way of spawning queues its child tasks regardless.
+    ///
+    /// # Parameters
+    ///
+    /// * `capacity`: the most pending child tasks, whether running or waiting for a worker thread
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, Priority, SpawnError, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.queue_capacity(2);
+    /// for value in 0..2 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_secs(3600)).await;
+    ///         value
+    ///     });
+    /// }
+    /// assert_eq!(
+    ///     group.try_spawn_task(Priority::default(), async { 2 }),
+    ///     Err(SpawnError::QueueFull(2))
+    /// );
+    /// group.cancel_all();
+    /// ```
+    pub fn queue_capacity(&mut self, capacity: usize) {
+        self.runtime.queue_capacity(capacity);
+    }
+
+    /// Bounds the number of results the spawn group buffers until they're consumed, handling a result beyond it as the
+    /// given policy says
+    ///
+    /// Results handed back or restored, such as the result of a dropped child handle, are buffered regardless of the
+    /// capacity. With ``OverflowPolicy::Block``, a spawn group whose results nobody consumes never finishes its child
+    /// tasks, so waiting for them without consuming the results never returns. See
+    /// [`OverflowPolicy`](crate::OverflowPolicy) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `capacity`: the most results buffered at once
+    /// * `policy`: what happens to a result once the buffer is full
+    ///
+    /// # Panics
+    /// If `capacity` is zero
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, OverflowPolicy, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(1);
+    /// group.result_buffer(2, OverflowPolicy::DropOldest);
+    /// for value in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(1)).await;
+    ///         value
+    ///     });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// // only the two newest results were kept
+    /// assert_eq!(group.metrics().results_overflowed(), 3);
+    /// assert_eq!(block_on(group.finish()).len(), 2);
+    /// ```
+    pub fn result_buffer(&mut self, capacity: usize, policy: OverflowPolicy) {
+        self.runtime.result_buffer(capacity, policy);
+    }
+
+    /// Bounds the number of bytes of the results the spawn group buffers until they're consumed, handling a result
+    /// beyond it as the given policy says
+    ///
+    /// It's the same as [`result_buffer`](Self::result_buffer), with the results measured as described in
+    /// [`ResultSize`](crate::ResultSize) rather than counted. A result is always let into an empty buffer whatever its
+    /// size, and with ``OverflowPolicy::DropOldest``, as many of the oldest results as needed are dropped to make
+    /// room for the new one.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_bytes`: the most bytes of results buffered at once
+    /// * `policy`: what happens to a result once the buffer has no room for it
+    ///
+    /// # Panics
+    /// If `max_bytes` is zero
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, OverflowPolicy, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<String>::new(1);
+    /// group.measure_results();
+    /// group.result_buffer_bytes(200, OverflowPolicy::DropNewest);
+    /// for length in [100, 40, 100] {
+    ///     group.spawn_task(Priority::default(), async move { "x".repeat(length) });
+    ///     block_on(group.wait_for_all());
+    /// }
+    /// // the last result didn't fit next to the first two
+    /// assert_eq!(group.metrics().results_overflowed(), 1);
+    /// assert_eq!(group.buffered_results(), 2);
+    /// ```
+    pub fn result_buffer_bytes(&mut self, max_bytes: usize, policy: OverflowPolicy) {
+        self.runtime.result_buffer_bytes(max_bytes, policy);
+    }
+
+    /// Keeps at most the newest result buffered, dropping the older unconsumed one whenever a child task produces
+    /// another, for the consumers where the latest result wins
+    ///
+    /// It's the same as ``result_buffer(1, OverflowPolicy::DropOldest)``, so each dropped result is counted as
+    /// overflowed and no longer counts as a result to come, and disabling it lifts any bound on the result buffer.
+    /// The child tasks keep running either way, and waiting for all of them still waits for every one of them.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether only the newest result is kept
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(1);
+    /// group.latest_only(true);
+    /// for reading in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { reading });
+    ///     block_on(group.wait_for_all());
+    /// }
+    /// assert_eq!(group.len(), 1);
+    /// assert_eq!(block_on(group.collect::<Vec<_>>()), [4]);
+    /// ```
+    pub fn latest_only(&mut self, enabled: bool) {
+        self.runtime.latest_only(enabled);
+    }
+
+    /// Measures the results buffered from now on with their [`ResultSize`](crate::ResultSize) implementation rather
+    /// than as ``size_of::<ValueType>()``
+    ///
+    /// See [`ResultSize`](crate::ResultSize) for more.
+    pub fn measure_results(&mut self)
+    where
+        ValueType: ResultSize,
+    {
+        self.runtime.measure_results(ValueType::approx_size);
+    }
+
+    /// Makes the number of child tasks allowed to run at once adapt to how long they take, between the bounds of the
+    /// given config
+    ///
+    /// A child task waits for room in the window before it starts, and the time it waits counts as queue latency.
+    /// The current width of the window is published as [`Metrics::concurrency_window`](crate::Metrics::concurrency_window).
+    /// See [`AdaptiveConfig`](crate::AdaptiveConfig) for how the window is adjusted.
+    ///
+    /// # Parameters
+    ///
+    /// * `config`: the bounds of the window and the latency the child tasks should finish within
+    ///
+    /// # Panics
+    /// If the minimum of `config` is zero or above its maximum
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, AdaptiveConfig, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(4);
+    /// group.adaptive_concurrency(AdaptiveConfig {
+    ///     min: 1,
+    ///     max: 8,
+    ///     target_latency: Duration::from_millis(50),
+    /// });
+    /// assert_eq!(group.metrics().concurrency_window(), Some(1));
+    /// for value in 0..20 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(1)).await;
+    ///         value
+    ///     });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// // every child task finished well within the target, widening the window
+    /// assert!(group.metrics().concurrency_window() > Some(1));
+    /// ```
+    pub fn adaptive_concurrency(&mut self, config: AdaptiveConfig) {
+        self.runtime.adaptive_concurrency(config, None);
+    }
+
+    /// Declares that the spawn group spawns exactly ``size`` child tasks, trading its dynamic features for a lower
+    /// overhead per child task
+    ///
+    /// Room for the whole batch is made upfront, and the child tasks of the batch skip the registry behind
+    /// ``snapshot``, the dequeue hook, the task middlewares, the execution log and the metrics of their latency.
+    /// Their outcome is counted by counting the batch down rather than per child task.
+    ///
+    /// Once declared, spawning beyond the batch is refused with ``SpawnError::BatchComplete``, and spawning with a
+    /// feature the batch skips, such as a child handle, a sequence number, a subset or a future run with ``try_run``,
+    /// is refused with ``SpawnError::BatchUnsupported``. Calling ``reset`` takes the spawn group out of batch mode.
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: the number of child tasks of the batch
+    ///
+    /// # Panics
+    /// If the spawn group already has child tasks or results, or batch mode was already declared
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnError, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.exclusive_batch(1000);
+    /// group.spawn_all(Priority::default(), (0..1000).map(|value| async move { value }));
+    /// assert_eq!(
+    ///     group.try_spawn_task(Priority::default(), async { 1000 }),
+    ///     Err(SpawnError::BatchComplete(1000))
+    /// );
+    /// assert_eq!(block_on((&mut group).count()), 1000);
+    /// ```
+    pub fn exclusive_batch(&mut self, size: usize) {
+        self.runtime.exclusive_batch(size);
+    }
+
+    /// Caps the CPU time the worker threads of the spawn group use collectively, measured in cores
+    ///
+    /// A quota of ``0.5`` lets the worker threads use half a core at most. Each worker thread measures how long
+    /// it's busy polling child tasks, and parks whenever the worker threads are over the quota, so the cap is
+    /// approximate and holds over windows of tens of milliseconds. The consumer helping the threadpool isn't capped.
+    ///
+    /// # Parameters
+    ///
+    /// * `cores`: the number of cores the worker threads may use, ``f64::INFINITY`` lifts the cap
+    ///
+    /// # Panics
+    /// If `cores` isn't a positive number
+    pub fn cpu_quota(&mut self, cores: f64) {
+        assert!(
+            cores > 0.0,
+            "the CPU quota must be a positive number of cores"
+        );
+        self.runtime.cpu_quota(cores);
+    }
+
+    /// Registers how each worker thread of the spawn group creates its own state of type ``State``,
+    /// replacing any previously registered initializer of the same type
+    ///
+    /// Child tasks get the state of the worker thread polling them by calling the [`worker_state`](crate::worker_state)
+    /// function. Worker threads that already created their state keep it.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: a closure that creates the state, called at most once per worker thread
+    pub fn worker_state<State, Init>(&mut self, init: Init)
+    where
+        State: 'static,
+        Init: Fn() -> State + Send + Sync + 'static,
+    {
+        self.runtime.worker_state(init);
+    }
+
+    /// Injects the failures described by the fault plan into the underlying threadpool
+    ///
+    /// See [`FaultPlan`](crate::FaultPlan) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `plan`: the failures to inject
+    #[cfg(feature = "fault-injection")]
+    pub fn inject(&mut self, plan: crate::FaultPlan) {
+        self.runtime.inject(plan);
+    }
+
+    /// Lets the consumer of the spawn group's stream help run queued child tasks
+    ///
+    /// Whenever polling the stream finds no result ready, the polling thread runs at most one
+    /// queued job of the underlying threadpool before parking. This keeps a saturated small
+    /// threadpool, or one with no worker threads at all, making progress.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether the consumer should help or not
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// // a threadpool without worker threads, every task runs on the consumer
+    /// let mut group = SpawnGroup::<usize>::new(0);
+    /// group.help_drive_pool(true);
+    /// for i in 0..10 {
+    ///     group.spawn_task(Priority::default(), async move { i });
+    /// }
+    ///
+    /// let sum = block_on(async move {
+    ///     let mut sum = 0;
+    ///     while let Some(value) = group.next().await {
+    ///         sum += value;
+    ///     }
+    ///     sum
+    /// });
+    /// assert_eq!(sum, 45);
+    /// ```
+    pub fn help_drive_pool(&mut self, enabled: bool) {
+        self.runtime.help_drive_pool(enabled);
+    }
+
+    /// Sets how many results must be buffered before a waiting consumer of the stream is woken
+    ///
+    /// By default the consumer is woken only when the buffer goes from empty to non-empty, instead of once per result.
+    /// A larger watermark trades latency for fewer wakeups in high-throughput spawn groups: a waiting consumer won't see a
+    /// result until ``count`` results are buffered, or until every remaining child task has finished, so the final
+    /// result always wakes the consumer and the stream terminates. A consumer kept waiting for a few milliseconds while
+    /// results are buffered is woken regardless of the watermark, so a long-running child task can't starve it.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of buffered results that wakes the consumer, ``0`` is treated as ``1``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::{Stream, StreamExt};
+    /// use spawn_groups::{block_on, yield_now, Priority, SpawnGroup};
+    /// use std::{
+    ///     pin::Pin,
+    ///     sync::{
+    ///         atomic::{AtomicBool, AtomicUsize, Ordering},
+    ///         Arc,
+    ///     },
+    ///     task::{Context, Wake, Waker},
+    ///     time::Duration,
+    /// };
+    ///
+    /// #[derive(Default)]
+    /// struct CountingWaker(AtomicUsize);
+    ///
+    /// impl Wake for CountingWaker {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// let mut group = SpawnGroup::<usize>::new(2);
+    /// // only the final result wakes the consumer
+    /// group.wake_after_n(usize::MAX);
+    /// let go = Arc::new(AtomicBool::new(false));
+    /// for i in 0..10 {
+    ///     let go = go.clone();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         while !go.load(Ordering::SeqCst) {
+    ///             yield_now().await;
+    ///         }
+    ///         i
+    ///     });
+    /// }
+    ///
+    /// let counter = Arc::new(CountingWaker::default());
+    /// let waker = Waker::from(counter.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert!(Pin::new(&mut group).poll_next(&mut cx).is_pending());
+    ///
+    /// go.store(true, Ordering::SeqCst);
+    /// while counter.0.load(Ordering::SeqCst) == 0 {
+    ///     std::thread::sleep(Duration::from_millis(1));
+    /// }
+    /// assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    /// assert_eq!(block_on(group.collect::<Vec<_>>()).len(), 10);
+    /// ```
+    pub fn wake_after_n(&mut self, count: usize) {
+        self.runtime.wake_after(count);
+    }
+}
+
+impl<ValueType: Send + 'static> SpawnGroup<ValueType> {
+    /// Spawns a new task into the spawn group, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.add_task(priority, closure);
+    }
+
+    /// Spawns a new task into the spawn group under a name, does nothing if the spawn group was closed or its
+    /// priority policy refuses the priority
+    ///
+    /// The name tells the child task apart from the others wherever the spawn group lists or reports it: in the panic
+    /// report if it panics, in the [`snapshot`](Self::snapshot) of the spawn group and in its ``Debug`` output while
+    /// it's in flight. A spawn group in exclusive batch mode refuses it, as its child tasks are never listed.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: name of the child task, which doesn't need to be unique
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// group.spawn_task_named("fetch config", Priority::default(), async {
+    ///     sleep(Duration::from_secs(3600)).await;
+    ///     0
+    /// });
+    /// assert_eq!(group.snapshot().tasks[0].name.as_deref(), Some("fetch config"));
+    /// assert!(format!("{group:?}").contains("\"fetch config\""));
+    /// group.cancel_all();
+    /// ```
+    pub fn spawn_task_named<F>(&mut self, name: impl Into<String>, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        let name: Arc<str> = Arc::from(name.into());
+        if self
+            .runtime
+            .write_named_task(name, priority, closure)
+            .is_ok()
+        {
+            self.increment_count();
+        }
+    }
+
+    /// Spawns a new task into the spawn group, polled on the worker threads of the given lane from the start, does
+    /// nothing if the spawn group was closed or its priority policy refuses the priority
+    ///
+    /// A spawn group without separate lanes polls it like any other child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `lane`: lane to start on
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task_on<F>(&mut self, lane: Lane, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        if self.runtime.write_task_on(lane, priority, closure).is_ok() {
+            self.increment_count();
+        }
+    }
+
+    /// Spawns a new task into the spawn group with its default priority, does nothing if the spawn group was closed
+    /// or its priority policy refuses the priority
+    ///
+    /// The default priority is ``Priority::default()`` unless changed with
+    /// [`set_default_priority`](Self::set_default_priority).
+    ///
+    /// # Parameters
+    ///
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// group.spawn(async { 0 });
+    /// group.set_default_priority(Priority::HIGH);
+    /// group.spawn(async {
+    ///     sleep(Duration::from_secs(3600)).await;
+    ///     1
+    /// });
+    /// let snapshot = group.snapshot();
+    /// assert!(snapshot.tasks.iter().any(|task| task.priority == Priority::HIGH));
+    /// group.cancel_all();
+    /// ```
+    pub fn spawn<F>(&mut self, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.spawn_task(self.runtime.spawn_priority(), closure);
+    }
+
+    /// Sets the priority of the child tasks spawned with [`spawn`](Self::spawn) from now on
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the priority to use when none is given
+    pub fn set_default_priority(&mut self, priority: Priority) {
+        self.runtime.set_default_priority(priority);
+    }
+
+    /// Spawns every task of the iterator into the spawn group with the same priority, does nothing if the spawn group
+    /// was closed or its priority policy refuses the priority
+    ///
+    /// The child tasks are the same as if each was spawned with [`spawn_task`](Self::spawn_task), but they're counted
+    /// and queued for the worker threads all at once, which is cheaper for many small child tasks.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use for every child task
+    /// * `closures`: the async closures that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.spawn_all(Priority::default(), (0..1000).map(|value| async move { value * 2 }));
+    /// let mut results: Vec<u64> = block_on((&mut group).collect());
+    /// results.sort();
+    /// assert_eq!(results, (0..1000).map(|value| value * 2).collect::<Vec<_>>());
+    /// ```
+    pub fn spawn_all<I, F>(&mut self, priority: Priority, closures: I)
+    where
+        I: IntoIterator<Item = F>,
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        if let Ok(count) = self.runtime.write_tasks(priority, closures) {
+            self.count.fetch_add(count, Ordering::Acquire);
+        }
+    }
+
+    /// Spawns a new task into the spawn group unless it was closed or its priority policy refuses the priority
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Closed`` if the spawn group was closed, ``SpawnError::Rejected``
+    ///   if its priority policy refused the priority, ``SpawnError::ThreadUnavailable``
+    ///   if its thread factory failed to create the thread running the child tasks again after a cancellation,
+    ///   or ``SpawnError::QueueFull`` if its pending child tasks fill the capacity set with ``queue_capacity``
+    ///
+    /// # Teardown
+    /// The results discarded along with a spawn group are dropped while it's torn down, and every child task spawned
+    /// from their destructors, into any spawn group, is refused with ``SpawnError::ShuttingDown``.
+    pub fn try_spawn_task<F>(&mut self, priority: Priority, closure: F) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.write_bounded_task(priority, closure)?;
+        self.increment_count();
+        Ok(())
+    }
+
+    /// Spawns a new task into the spawn group once its pending child tasks no longer fill the capacity set with
+    /// [`queue_capacity`](Self::queue_capacity), suspending the caller until then
+    ///
+    /// The caller is woken as child tasks finish rather than polling in a loop. Without a queue capacity the child
+    /// task is spawned right away.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// - Ok: if the child task was spawned
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled while waiting, or any error
+    ///   ``try_spawn_task`` returns other than ``SpawnError::QueueFull``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.queue_capacity(2);
+    /// block_on(async {
+    ///     for value in 0..10 {
+    ///         group
+    ///             .spawn_task_when_ready(Priority::default(), async move {
+    ///                 sleep(Duration::from_millis(10)).await;
+    ///                 value
+    ///             })
+    ///             .await
+    ///             .unwrap();
+    ///         assert!(group.pending_tasks() <= 2);
+    ///     }
+    /// });
+    /// assert_eq!(block_on((&mut group).count()), 10);
+    /// ```
+    pub async fn spawn_task_when_ready<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.runtime
+            .write_task_when_ready(priority, closure)
+            .await?;
+        self.increment_count();
+        Ok(())
+    }
+
+    /// Spawns a new task into the spawn group whose result carries the given sequence number
+    ///
+    /// The result goes into the stream of the spawn group like any other, but
+    /// [`sequenced_stream`](Self::sequenced_stream) delivers it in the order of its sequence number rather than
+    /// in the order it arrived.
+    ///
+    /// # Parameters
+    ///
+    /// * `sequence`: the place of the result in the sequenced stream
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task_sequenced<F>(&mut self, sequence: u64, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        if self
+            .runtime
+            .write_sequenced_task(sequence, priority, closure)
+            .is_ok()
+        {
+            self.increment_count();
+        }
+    }
+
+    /// Spawns a new task into the spawn group and returns a [`ChildHandle`](crate::ChildHandle) to await its result
+    ///
+    /// The child task is an ordinary child task of the spawn group, except that its result goes to the handle rather
+    /// than into the stream of the spawn group, so it's never observed twice. If the handle is dropped without taking
+    /// the result, the result goes into the stream instead. If the spawn group refuses the child task, the returned
+    /// handle finishes immediately without a result.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<&str>::new(2);
+    /// group.spawn_task(Priority::default(), async { "thumbnail" });
+    /// let manifest = group.spawn_task_with_handle(Priority::HIGH, async { "manifest" });
+    /// let preview = group.spawn_task_with_handle(Priority::default(), async { "preview" });
+    ///
+    /// assert_eq!(block_on(manifest), Some("manifest"));
+    /// // nobody cares about this one specifically anymore
+    /// drop(preview);
+    /// let mut rest: Vec<&str> = block_on((&mut group).collect());
+    /// rest.sort();
+    /// assert_eq!(rest, ["preview", "thumbnail"]);
+    /// ```
+    pub fn spawn_task_with_handle<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> ChildHandle<ValueType>
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        match self.runtime.write_task_with_handle(priority, closure) {
+            Ok(handle) => {
+                self.increment_count();
+                handle
+            }
+            Err(_) => ChildHandle::refused(),
+        }
+    }
+
+    /// Runs a single future on the spawn group's threadpool and returns a
+    /// [`JoinHandle`](crate::JoinHandle) to await its output
+    ///
+    /// Unlike child tasks spawned with ``spawn_task``, its output doesn't go into the spawn group's
+    /// stream, but it's waited for and cancelled along with the spawn group. Dropping the
+    /// ``JoinHandle`` detaches the future. If the future can't be run, for example because the spawn group was
+    /// closed or is in exclusive batch mode, the returned ``JoinHandle`` finishes immediately without an output, and
+    /// [`try_run`](Self::try_run) tells why.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: the future to run
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// let handle = group.run(Priority::default(), async { String::from("done") });
+    /// assert_eq!(block_on(handle), Some(String::from("done")));
+    /// assert!(group.is_empty());
+    /// ```
+    pub fn run<Fut>(&mut self, priority: Priority, future: Fut) -> JoinHandle<Fut::Output>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime
+            .run_task(priority, future)
+            .unwrap_or_else(|_| finished())
+    }
+
+    /// Runs a single future on the spawn group's threadpool as [`run`](Self::run) does, unless it can't be run
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `future`: the future to run
+    ///
+    /// # Returns
+    /// - Ok: a [`JoinHandle`](crate::JoinHandle) to await the output of the future
+    /// - Err: ``SpawnError::BatchUnsupported`` if the spawn group is in exclusive batch mode, whose child tasks are
+    ///   the only ones it runs, or any error ``try_spawn_task`` returns other than ``SpawnError::QueueFull``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnError, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// let handle = group.try_run(Priority::default(), async { 1 }).unwrap();
+    /// assert_eq!(block_on(handle), Some(1));
+    ///
+    /// group.exclusive_batch(1);
+    /// assert!(matches!(
+    ///     group.try_run(Priority::default(), async {}),
+    ///     Err(SpawnError::BatchUnsupported)
+    /// ));
+    /// ```
+    pub fn try_run<Fut>(
+        &mut self,
+        priority: Priority,
+        future: Fut,
+    ) -> Result<JoinHandle<Fut::Output>, SpawnError>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime.run_task(priority, future)
+    }
+
+    /// Spawns a new task into the spawn group to retry the work of a result the consumer
+    /// found unsatisfactory
+    ///
+    /// This behaves exactly like ``spawn_task``
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn respawn<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.spawn_task(priority, closure);
+    }
+
+    /// Pushes a result back into the front of the spawn group's stream so that the next
+    /// consumer receives it before any other result
+    ///
+    /// This is safe to call while child tasks are still producing results. The result was let into the result buffer
+    /// once already, so it's buffered regardless of the capacity set with ``result_buffer`` and its overflow policy.
+    ///
+    /// # Parameters
+    ///
+    /// * `value`: the result to requeue
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<usize>::new(2);
+    /// for i in 0..3 {
+    ///     group.spawn_task(Priority::default(), async move { i });
+    /// }
+    ///
+    /// block_on(async move {
+    ///     let value = group.next().await.unwrap();
+    ///     // hand the result back to the next consumer
+    ///     group.requeue_result(value);
+    ///     assert_eq!(group.next().await, Some(value));
+    ///
+    ///     // the consumer decides the result should be retried
+    ///     group.respawn(Priority::default(), async move { value });
+    ///     assert_eq!(group.count().await, 3);
+    /// });
+    /// ```
+    pub fn requeue_result(&self, value: ValueType) {
+        let stream: AsyncStream<ValueType> = self.runtime.stream();
+        stream.requeue_item(stream.wave(), value);
+    }
+
+    /// Spawns a new child task only if the spawn group wasn't cancelled since it was created or last reset
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// - true: if the child task was spawned
+    /// - false: if the spawn group was cancelled or closed, in which case the child task is dropped without running
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// assert!(group.spawn_task_unless_cancelled(Priority::default(), async { 1 }));
+    /// group.cancel_all();
+    /// assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 2 }));
+    /// assert!(group.is_empty());
+    /// ```
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.add_task_unless_cancelled(priority, closure)
+    }
+
+    /// Spawn a new task only if the group is not cancelled yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
+    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        _ = self.spawn_task_unless_cancelled(priority, closure);
+    }
+
+    /// Cancels all running task in the spawn group
+    ///
+    /// Child tasks which haven't started running never run. A child task that a worker thread
+    /// was already polling finishes that poll, and delivers its result if it completed in it,
+    /// but it is never polled again. This method returns once those polls have finished.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::{
+    ///     collections::HashSet,
+    ///     sync::{Arc, Mutex},
+    ///     time::Duration,
+    /// };
+    ///
+    /// for attempt in 0..20 {
+    ///     let started = Arc::new(Mutex::new(HashSet::new()));
+    ///     let mut group = SpawnGroup::<usize>::new(2);
+    ///     for i in 0..100 {
+    ///         let started = started.clone();
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             started.lock().unwrap().insert(i);
+    ///             i
+    ///         });
+    ///     }
+    ///     std::thread::sleep(Duration::from_micros(attempt * 50));
+    ///     group.cancel_all();
+    ///
+    ///     let delivered: HashSet<usize> = block_on(group.collect());
+    ///     assert_eq!(delivered, *started.lock().unwrap());
+    /// }
+    /// ```
+    pub fn cancel_all(&mut self) {
+        self.cancel_all_tasks();
+    }
+
+    /// A Boolean value that indicates whether the spawn group was cancelled since it was created or last reset
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::SpawnGroup;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// assert!(!group.is_cancelled());
+    /// group.cancel_all();
+    /// assert!(group.is_cancelled());
+    /// ```
+    // the deprecated field of the same name only mirrors the state
+    #[allow(clippy::misnamed_getters)]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Cancels every child task below the given priority that hasn't started running yet, as a valve for shedding
+    /// load under memory pressure
+    ///
+    /// The running child tasks are left alone whatever their priority, so are the child tasks of an exclusive batch.
+    /// The child tasks shed never run, produce no result and count as cancelled, while the spawn group goes on
+    /// running the others and accepting new ones.
+    ///
+    /// # Parameters
+    ///
+    /// * `keep`: the lowest priority kept
+    ///
+    /// # Returns
+    /// The number of child tasks shed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<Priority>::new(1);
+    /// // keeps the only worker thread busy so the others stay queued
+    /// group.spawn_task(Priority::HIGH, async {
+    ///     std::thread::sleep(Duration::from_millis(100));
+    ///     Priority::HIGH
+    /// });
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// for priority in [Priority::BACKGROUND, Priority::LOW, Priority::MEDIUM] {
+    ///     group.spawn_task(priority, async move { priority });
+    /// }
+    /// assert_eq!(group.shed_load(Priority::MEDIUM), 2);
+    /// let mut results = block_on(group.collect::<Vec<_>>());
+    /// results.sort();
+    /// assert_eq!(results, [Priority::MEDIUM, Priority::HIGH]);
+    /// ```
+    pub fn shed_load(&mut self, keep: Priority) -> usize {
+        self.runtime.shed_load(keep)
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Closes the spawn group so it no longer accepts new child tasks
+    ///
+    /// Once closed, ``spawn_task`` does nothing and ``try_spawn_task`` returns ``SpawnError::Closed``,
+    /// which makes waiting for all child tasks final. Closing an already closed spawn group does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnError, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<usize>::new(2);
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// group.close();
+    /// group.close();
+    /// assert!(group.is_closed());
+    ///
+    /// // spawning after closing is rejected
+    /// group.spawn_task(Priority::default(), async { 2 });
+    /// assert_eq!(group.try_spawn_task(Priority::default(), async { 3 }), Err(SpawnError::Closed));
+    ///
+    /// let results: Vec<usize> = block_on(group.collect());
+    /// assert_eq!(results, vec![1]);
+    /// ```
+    pub fn close(&self) {
+        self.runtime.close();
+    }
+
+    /// A Boolean value that indicates whether the spawn group was closed
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Runs the given future on the spawn group's threadpool and closes the spawn group once it completes, however
+    /// it completes
+    ///
+    /// Meant for a slow producer spawning child tasks as it goes, through a [`GroupSpawner`](crate::GroupSpawner):
+    /// the consumers get the results as soon as they're produced, while the stream of the spawn group doesn't end
+    /// before the producer is done, even if every child task spawned so far finished in the meantime. Since closing
+    /// doesn't depend on the producer calling ``close`` on every path, an early error ends the stream all the same.
+    ///
+    /// Like a future started with [`run`](Self::run), it's waited for and cancelled along with the spawn group, and
+    /// a cancelled future closes the spawn group as well. If the future can't be run, for example because the spawn
+    /// group was already closed or is in exclusive batch mode, the spawn group is closed right away.
+    ///
+    /// # Parameters
+    ///
+    /// * `future`: the future whose completion closes the spawn group
+    ///
+    /// # Returns
+    /// A [`JoinHandle`](crate::JoinHandle) to await the output of the future
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// let spawner = group.spawner();
+    /// // reads a manifest slowly, spawning a child task for each of its entries along the way
+    /// let producer = group.close_when(async move {
+    ///     for entry in 0..5 {
+    ///         sleep(Duration::from_millis(5)).await;
+    ///         spawner.spawn_task(Priority::default(), async move { entry * 10 });
+    ///     }
+    ///     Ok::<usize, String>(5)
+    /// });
+    ///
+    /// let mut results: Vec<u64> = block_on((&mut group).collect());
+    /// results.sort();
+    /// assert_eq!(results, [0, 10, 20, 30, 40]);
+    /// assert_eq!(block_on(producer), Some(Ok(5)));
+    /// assert!(group.is_closed());
+    /// ```
+    pub fn close_when<Fut>(&mut self, future: Fut) -> JoinHandle<Fut::Output>
+    where
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        self.runtime
+            .close_when(future)
+            .unwrap_or_else(|_| finished())
+    }
+
+    /// Cancels every child task and starts a new generation, reopening the spawn group for reuse
+    ///
+    /// Buffered results are discarded, and so is any result of a child task spawned before the reset,
+    /// even one whose poll was already running, so no stale result crosses the generation boundary.
+    /// The spawn group is no longer cancelled nor closed afterwards.
+    ///
+    /// Child tasks still in flight are cancelled rather than waited for, so wait for all of them first to keep their
+    /// results. The worker threads are kept, so the next wave of child tasks runs on the same threadpool.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<usize>::new(2);
+    /// for attempt in 0..20 {
+    ///     let stale = group.generation();
+    ///     for i in 0..20 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_micros(i * 20)).await;
+    ///             stale
+    ///         });
+    ///     }
+    ///     std::thread::sleep(Duration::from_micros(attempt * 30));
+    ///     group.reset();
+    ///     assert_eq!(group.generation(), stale + 1);
+    ///
+    ///     let current = group.generation();
+    ///     for _ in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { current });
+    ///     }
+    ///     block_on(group.wait_for_all());
+    ///     let results: Vec<usize> = block_on((&mut group).collect());
+    ///     assert_eq!(results, [current; 5]);
+    /// }
+    /// ```
+    pub fn reset(&mut self) {
+        self.runtime.reset();
+        self.set_cancelled(false);
+        self.decrement_count_to_zero();
+    }
+
+    /// The generation of the spawn group, which starts at zero and increases with every ``reset()``
+    pub fn generation(&self) -> usize {
+        self.runtime.generation()
+    }
+
+    /// Returns a spawner that spawns child tasks into the spawn group from anywhere, including its own child tasks
+    ///
+    /// See [`GroupSpawner`](crate::GroupSpawner) for more.
+    pub fn spawner(&self) -> GroupSpawner<ValueType> {
+        GroupSpawner::new(self.runtime.clone(), self.count.clone())
+    }
+
+    pub(crate) fn closed_flag(&self) -> Arc<AtomicBool> {
+        self.runtime.closed_flag()
+    }
+
+    /// Whether the calling thread is one of the worker threads of the spawn group
+    pub(crate) fn is_worker_thread(&self) -> bool {
+        self.runtime.is_worker_thread()
+    }
+
+    pub(crate) fn try_spawn_tracked_task<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+        subset: SubsetGuard,
+    ) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        self.runtime
+            .write_tracked_task(priority, closure, Some(subset), None, None, None)?;
+        self.increment_count();
+        Ok(())
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Returns the first element of the stream, or None if it is empty.
+    ///
+    /// It waits for the next result if none is buffered yet, and is cancel-safe: a result is
+    /// only taken once the returned future completes, so dropping it early, for example when
+    /// it loses a race against a timeout, never loses a result.
+    ///
+    /// It may be awaited concurrently with other calls to ``first()`` or with consumers of the stream, each result
+    /// is then delivered to exactly one of them, in FIFO order but with no guarantee about which consumer gets which.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::FutureExt;
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// for value in 0..200 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_micros(value % 7 * 50)).await;
+    ///         value
+    ///     });
+    /// }
+    ///
+    /// let (mut consumed, mut sum) = (0, 0);
+    /// block_on(async {
+    ///     loop {
+    ///         let first = async { Some(group.first().await) };
+    ///         let timeout = async {
+    ///             sleep(Duration::from_micros(40)).await;
+    ///             None
+    ///         };
+    ///         match first.or(timeout).await {
+    ///             Some(Some(value)) => {
+    ///                 consumed += 1;
+    ///                 sum += value;
+    ///             }
+    ///             Some(None) => break,
+    ///             // timed out, the next attempt picks up where this one left off
+    ///             None => continue,
+    ///         }
+    ///     }
+    /// });
+    /// assert_eq!(consumed, 200);
+    /// assert_eq!(sum, (0..200).sum());
+    /// ```
+    pub async fn first(&self) -> Option<ValueType> {
+        self.runtime.stream().first().await
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Waits for all remaining child tasks for finish.
+    ///
+    /// The current task is suspended rather than the current thread blocked, so it may be awaited from any
+    /// executor. See [`wait_non_async`](Self::wait_non_async) for synchronous code.
+    pub async fn wait_for_all(&self) {
+        self.wait().await;
+    }
+
+    /// Waits for all remaining child tasks for finish, blocking the current thread, which polls them meanwhile
+    ///
+    /// Meant for synchronous code, asynchronous code awaits [`wait_for_all`](Self::wait_for_all) instead.
+    pub fn wait_non_async(&self) {
+        self.runtime.wait_for_all_tasks();
+        self.decrement_count_to_zero();
+    }
+
+    /// Waits for all remaining child tasks to finish for at most the given duration
+    ///
+    /// The child tasks still running once the duration elapses are left running, so the caller may wait again or
+    /// cancel them with ``cancel_all()``.
+    ///
+    /// # Parameters
+    ///
+    /// * `duration`: how long to wait for at most
+    ///
+    /// # Returns
+    /// Whether every child task finished within the duration
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// group.spawn_task(Priority::default(), async {
+    ///     sleep(Duration::from_secs(3600)).await;
+    ///     2
+    /// });
+    /// assert!(!block_on(group.wait_timeout(Duration::from_millis(50))));
+    /// // the straggler is given up on
+    /// group.cancel_all();
+    /// ```
+    pub async fn wait_timeout(&mut self, duration: Duration) -> bool {
+        let finished: bool = self.runtime.wait_until_idle_for(duration).await;
+        if finished {
+            self.decrement_count_to_zero();
+        }
+        finished
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Hands every result to the consumer as soon as it's delivered, until no child task remains and
+    /// no result is buffered
+    ///
+    /// Unlike calling ``wait_for_all()`` before consuming the stream, the results are consumed while the child tasks
+    /// are still running, so only the results the consumer hasn't caught up with yet are buffered at any time instead
+    /// of every result of the spawn group.
+    ///
+    /// The consumer gets the spawn group along with each result, so it may spawn more child tasks, which are drained
+    /// as well. Once the spawn group is cancelled, the results buffered by then are still handed to the consumer
+    /// before this returns.
+    ///
+    /// # Parameters
+    ///
+    /// * `consumer`: a closure that receives the spawn group and each result
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// for page in 0..100 {
+    ///     group.spawn_task(Priority::default(), async move { page });
+    /// }
+    ///
+    /// let mut total = 0;
+    /// block_on(group.drain_to_completion(|group, page| {
+    ///     total += page;
+    ///     // the last page links to an appendix that wasn't known upfront
+    ///     if page == 99 {
+    ///         group.spawn_task(Priority::default(), async { 1_000 });
+    ///     }
+    /// }));
+    /// assert_eq!(total, (0..100).sum::<u64>() + 1_000);
+    /// assert!(group.is_empty());
+    /// ```
+    pub async fn drain_to_completion<Consumer>(&mut self, mut consumer: Consumer)
+    where
+        Consumer: FnMut(&mut Self, ValueType),
+    {
+        // the stream ends once every child task has finished and its result was consumed, or once it was cancelled
+        // and the buffered results were consumed
+        while let Some(value) = self.next().await {
+            consumer(self, value);
+        }
+        self.decrement_count_to_zero();
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Waits for all remaining child tasks to finish and returns their results sorted by the given key function
+    ///
+    /// Results are pushed into a binary heap as soon as they arrive, so the ordering work is spread
+    /// over the run of the child tasks instead of happening all at once at the end.
+    /// Results with equal keys keep the order they arrived in.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: a closure that extracts the key to sort each result by
+    ///
+    /// # Returns
+    /// A vector of the results in ascending order of their keys
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::cmp::Reverse;
+    ///
+    /// let mut group = SpawnGroup::<(usize, String)>::new(2);
+    /// for shard in [3, 1, 4, 0, 2] {
+    ///     group.spawn_task(Priority::default(), async move { (shard, format!("shard {shard}")) });
+    /// }
+    /// let shards = block_on(group.collect_sorted_by_key(|(shard, _)| *shard));
+    /// assert_eq!(shards.iter().map(|(shard, _)| *shard).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    ///
+    /// for shard in [3, 1, 4, 0, 2] {
+    ///     group.spawn_task(Priority::default(), async move { (shard, format!("shard {shard}")) });
+    /// }
+    /// let shards = block_on(group.collect_sorted_by_key(|(shard, _)| Reverse(*shard)));
+    /// assert_eq!(shards[0].1, "shard 4");
+    /// ```
+    pub async fn collect_sorted_by_key<Key, KeyFn>(&mut self, mut key: KeyFn) -> Vec<ValueType>
+    where
+        Key: Ord,
+        KeyFn: FnMut(&ValueType) -> Key,
+    {
+        let mut heap: BinaryHeap<Keyed<Key, ValueType>> = BinaryHeap::new();
+        while let Some(value) = self.next().await {
+            heap.push(Keyed {
+                key: key(&value),
+                arrival: heap.len(),
+                value,
+            });
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|keyed: Keyed<Key, ValueType>| keyed.value)
+            .collect()
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order the child tasks were spawned
+    ///
+    /// Each child task is given an increasing index as it's spawned, which its result keeps however late it finishes.
+    /// A child task that was cancelled or panicked has no result, so after a cancellation only the results of the
+    /// child tasks that completed are returned, still in spawn order. Results handed back with ``requeue_result`` come
+    /// first.
+    ///
+    /// # Returns
+    /// A vector of the results in the order their child tasks were spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(4);
+    /// for delay in [30, 10, 20, 0] {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(delay)).await;
+    ///         delay
+    ///     });
+    /// }
+    /// assert_eq!(block_on(group.collect_ordered()), [30, 10, 20, 0]);
+    /// ```
+    pub async fn collect_ordered(mut self) -> Vec<ValueType>
+    where
+        ValueType: 'static,
+    {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        self.runtime.stream().collect_ordered().await
+    }
+
+    /// Waits for every child task to finish and returns their results, consuming the spawn group
+    ///
+    /// Unlike dropping a spawn group that waits for its child tasks, the current task is suspended rather than the
+    /// current thread blocked, so it's the way to end a spawn group from asynchronous code running on any executor.
+    /// Nothing is left to wait for once it returns, so dropping the spawn group afterwards never blocks, whatever its
+    /// drop policy.
+    ///
+    /// # Returns
+    /// A vector of the results, in the order their child tasks finished
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, GroupDropPolicy, Priority, SpawnGroup};
+    /// use std::{num::NonZeroUsize, time::Duration};
+    ///
+    /// let threads = NonZeroUsize::new(2).unwrap();
+    /// let mut group = SpawnGroup::<u64>::new_with(threads, GroupDropPolicy::WaitForAll);
+    /// for value in 1..=4 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(10)).await;
+    ///         value
+    ///     });
+    /// }
+    /// let results = block_on(group.finish());
+    /// assert_eq!(results.iter().sum::<u64>(), 10);
+    /// ```
+    pub async fn finish(mut self) -> Vec<ValueType>
+    where
+        ValueType: 'static,
+    {
+        self.wait().await;
+        self.drop_policy = GroupDropPolicy::CancelAll;
+        (&mut self).collect().await
+    }
+
+    /// Waits for every child task to finish and drops their results, consuming the spawn group
+    ///
+    /// See [`finish`](Self::finish).
+    pub async fn finish_discarding(mut self) {
+        self.wait().await;
+        // the results left are dropped along with the spawn group
+        self.drop_policy = GroupDropPolicy::CancelAll;
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    fn increment_count(&self) {
+        self.count.fetch_add(1, Ordering::Acquire);
+    }
+
+    fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    fn decrement_count_to_zero(&self) {
+        self.count.store(0, Ordering::Release);
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Captures the state of the spawn group without stopping its child tasks
+    ///
+    /// See [`GroupSnapshot`](crate::GroupSnapshot) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup, TaskState};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<()>::new(1);
+    /// group.spawn_task(Priority::HIGH, async {
+    ///     sleep(Duration::from_millis(200)).await;
+    /// });
+    /// group.spawn_task(Priority::LOW, async {});
+    /// group.cancel_all();
+    ///
+    /// let snapshot = group.snapshot();
+    /// assert_eq!(snapshot.spawned, 2);
+    /// assert!(snapshot.cancelled);
+    /// assert_eq!(snapshot.pending, 0);
+    /// assert!(snapshot.tasks.iter().all(|task| task.state == TaskState::Cancelled));
+    ///
+    /// let mut group = SpawnGroup::<()>::new(1);
+    /// group.spawn_task(Priority::HIGH, async {
+    ///     sleep(Duration::from_millis(200)).await;
+    /// });
+    /// let snapshot = group.snapshot();
+    /// assert_eq!(snapshot.pending, 1);
+    /// assert_eq!(snapshot.tasks[0].priority, Priority::HIGH);
+    /// # #[cfg(feature = "serde")]
+    /// # assert!(serde_json::to_string(&snapshot).unwrap().contains("\"pending\":1"));
+    /// block_on(group.wait_for_all());
+    /// assert!(group.snapshot().tasks.is_empty());
+    /// ```
+    pub fn snapshot(&self) -> GroupSnapshot {
+        self.runtime.snapshot(self.cancelled)
+    }
+
+    /// Makes the child tasks spawned from now on leave breadcrumbs of where they suspend, which
+    /// [`dump_state`](Self::dump_state) and [`snapshot`](Self::snapshot) report
+    ///
+    /// Each of them counts its polls and keeps the last label it went through with
+    /// [`trace_point!`](crate::trace_point), so a hanging child task shows where it waits, as in
+    /// "task 3 pending at 'db_query' for 42s". It's disabled by default since it takes a lock around each poll.
+    /// Disabling it leaves the child tasks already spawned traced.
+    pub fn trace_suspensions(&mut self, enabled: bool) {
+        self.runtime.trace_suspensions(enabled);
+    }
+
+    /// Returns the state of the spawn group as text, with a line for each child task that hasn't completed yet
+    ///
+    /// It formats [`snapshot`](Self::snapshot) for logs and debugging hangs, with where each traced child task is
+    /// suspended, see [`trace_suspensions`](Self::trace_suspensions).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, trace_point, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(1);
+    /// group.trace_suspensions(true);
+    /// group.spawn_task_named("fetch", Priority::default(), async {
+    ///     trace_point!("connect");
+    ///     sleep(Duration::from_millis(100)).await;
+    ///     trace_point!("db_query");
+    ///     sleep(Duration::from_secs(3600)).await;
+    ///     0
+    /// });
+    /// std::thread::sleep(Duration::from_millis(300));
+    /// let state = group.dump_state();
+    /// assert!(state.starts_with("spawned: 1, pending: 1"));
+    /// assert!(state.contains("task 0 (fetch) pending at 'db_query' for 0s"));
+    /// group.cancel_all();
+    /// ```
+    pub fn dump_state(&self) -> String {
+        self.snapshot().to_string()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Don't implicity wait for spawned child tasks to finish before being dropped
-    pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+    /// Returns the number of worker threads running the child tasks of the spawn group
+    pub fn num_threads(&self) -> usize {
+        self.runtime.num_threads()
     }
-}
 
-impl<ValueType: Send + 'static> SpawnGroup<ValueType> {
-    /// Spawns a new task into the spawn group
-    /// # Parameters
+    /// Returns the number of child tasks that are yet to complete, whether running or waiting for a worker thread
+    pub fn pending_tasks(&self) -> usize {
+        self.runtime.pending_tasks()
+    }
+
+    /// Returns the number of results produced by the child tasks that are yet to be consumed
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``ValueType``
-    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
-    where
-        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
-    {
-        self.add_task(priority, closure);
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(3);
+    /// assert_eq!(group.num_threads(), 3);
+    /// for value in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// assert_eq!(group.pending_tasks(), 0);
+    /// assert_eq!(group.buffered_results(), 5);
+    /// block_on(group.next());
+    /// assert_eq!(group.buffered_results(), 4);
+    /// ```
+    pub fn buffered_results(&self) -> usize {
+        self.runtime.buffered_results()
     }
 
-    /// Spawn a new task only if the group is not cancelled yet,
-    /// otherwise does nothing
+    /// Returns the number of child tasks spawned whose result is yet to be consumed from the spawn group
     ///
-    /// # Parameters
+    /// That's the pending child tasks along with the buffered results, apart from the child tasks cancelled with
+    /// ``cancel_all``, which will never produce one. Unlike [`is_empty`](Self::is_empty), which only looks at the
+    /// pending child tasks, it's only zero once every result was consumed as well.
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``ValueType``
-    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
-    where
-        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
-    {
-        self.add_task_unlessed_cancelled(priority, closure);
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// assert_eq!(group.len(), 0);
+    /// for value in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// assert_eq!(group.len(), 5);
+    /// block_on(group.wait_for_all());
+    /// assert_eq!((group.len(), group.completed_count()), (5, 5));
+    /// block_on(group.next());
+    /// assert_eq!((group.len(), group.completed_count()), (4, 4));
+    /// ```
+    pub fn len(&self) -> usize {
+        self.runtime.len()
     }
 
-    /// Cancels all running task in the spawn group
-    pub fn cancel_all(&mut self) {
-        self.cancel_all_tasks();
+    /// Returns the number of results the result buffer holds before it reallocates
+    ///
+    /// See [`with_capacity`](Self::with_capacity) for more.
+    pub fn result_capacity(&self) -> usize {
+        self.runtime.result_capacity()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Returns the first element of the stream, or None if it is empty.
-    pub async fn first(&self) -> Option<ValueType> {
-        self.runtime.stream().first().await
+    /// Returns the number of results ready to be consumed, the same as [`buffered_results`](Self::buffered_results)
+    pub fn completed_count(&self) -> usize {
+        self.runtime.buffered_results()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Waits for all remaining child tasks for finish.
-    pub async fn wait_for_all(&self) {
-        self.wait().await;
+    /// Returns the counters of the child tasks completed by the spawn group
+    ///
+    /// See [`Metrics`](crate::Metrics) for more.
+    pub fn metrics(&self) -> Metrics {
+        self.runtime.metrics()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    fn increment_count(&self) {
-        self.count.fetch_add(1, Ordering::Acquire);
+    /// Enables or disables recording the child tasks executed by each worker thread, disabled by default
+    ///
+    /// While disabled, recording costs a single atomic load per child task.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled`: whether to record the child tasks executed from now on
+    pub fn record_executions(&self, enabled: bool) {
+        self.runtime.record_executions(enabled);
     }
 
-    fn count(&self) -> usize {
-        self.count.load(Ordering::Acquire)
+    /// Returns the last child tasks executed by the spawn group, ordered by the instant they ended
+    ///
+    /// Each worker thread keeps its last 256 child tasks, which answers what ran recently on
+    /// each of them when the spawn group hangs. Nothing is kept unless ``record_executions`` was enabled.
+    ///
+    /// See [`ExecutionRecord`](crate::ExecutionRecord) for more.
+    pub fn recent_executions(&self) -> Vec<ExecutionRecord> {
+        self.runtime.recent_executions()
     }
 
-    fn decrement_count_to_zero(&self) {
-        self.count.store(0, Ordering::Release);
+    /// Returns the last wakes, suspensions and resumptions of the child tasks of the spawn group, along with the
+    /// last release of each of their wakers
+    ///
+    /// Hangs almost always come down to a lost or misrouted wake, which
+    /// [`WakeTrace::stalled_tasks`](crate::WakeTrace::stalled_tasks) looks for. Only available with the
+    /// `wake-trace` feature, without which nothing is recorded.
+    #[cfg(feature = "wake-trace")]
+    pub fn wake_trace(&self) -> crate::WakeTrace {
+        self.runtime.wake_trace()
     }
 }
 
@@ -140,9 +2293,636 @@ impl<ValueType: Send> SpawnGroup<ValueType> {
 }
 
 impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Returns an instance of the `Stream` trait.
-    pub fn stream(&self) -> impl Stream<Item = ValueType> {
-        self.runtime.stream()
+    /// Returns a handle spawning child tasks into the spawn group that can be waited for on their own
+    ///
+    /// See [`Subset`](crate::Subset) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<&str>::new(2);
+    /// group.spawn_task(Priority::BACKGROUND, async {
+    ///     sleep(Duration::from_millis(200)).await;
+    ///     "housekeeping"
+    /// });
+    ///
+    /// let mut phase = group.subset();
+    /// for _ in 0..3 {
+    ///     phase.spawn_task(Priority::default(), async { "phase 1" });
+    /// }
+    /// // only waits for the child tasks of the first phase
+    /// block_on(phase.wait());
+    /// assert_eq!(phase.remaining(), 0);
+    /// drop(phase);
+    ///
+    /// group.spawn_task(Priority::default(), async { "phase 2" });
+    /// block_on(group.wait_for_all());
+    /// ```
+    pub fn subset(&mut self) -> Subset<'_, ValueType> {
+        let state = self.runtime.subsets().create();
+        Subset::new(self, state)
+    }
+
+    /// Spawns a child task for every item of the input stream, with at most ``limit`` of them running at once
+    ///
+    /// Once ``limit`` of the child tasks it spawned haven't finished yet, it waits for one of them to finish before
+    /// pulling the next item. Only running child tasks count, so results left in the stream of the spawn group never
+    /// hold it back, and the stream may be consumed concurrently through a clone returned by
+    /// [`stream`](Self::stream), which doesn't end between the first poll of the returned future and its completion or
+    /// drop. Child tasks spawned otherwise don't count towards the limit either.
+    ///
+    /// It returns once the input stream is exhausted, without waiting for the child tasks it spawned, or as soon as the
+    /// spawn group refuses a child task or was cancelled, without pulling another item. Dropping the returned future
+    /// stops pulling from the input as well, leaving the child tasks already spawned to the spawn group.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use for every child task
+    /// * `limit`: the most child tasks running at once, at least 1
+    /// * `input`: the stream of items
+    /// * `spawner`: returns the child task for an item
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::{stream, StreamExt};
+    /// use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<String>::new(4);
+    /// let urls = stream::iter((0..20).map(|page| format!("https://example.com/{page}")));
+    /// block_on(group.spawn_from_stream(Priority::default(), 3, urls, |url| async move {
+    ///     sleep(Duration::from_millis(5)).await;
+    ///     format!("downloaded {url}")
+    /// }));
+    /// let pages: Vec<String> = block_on((&mut group).collect());
+    /// assert_eq!(pages.len(), 20);
+    /// ```
+    pub async fn spawn_from_stream<Input, Spawner, F>(
+        &mut self,
+        priority: Priority,
+        limit: usize,
+        input: Input,
+        spawner: Spawner,
+    ) where
+        Input: Stream,
+        Spawner: Fn(Input::Item) -> F,
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        let limit: usize = limit.max(1);
+        // the stream doesn't end for its consumers while there may be more child tasks to come
+        let _open = self.runtime.stream().hold_open();
+        let in_flight = self.runtime.subsets().create();
+        let mut input = pin!(input);
+        loop {
+            poll_fn(|cx| in_flight.poll_below(limit, cx)).await;
+            if in_flight.is_cancelled() {
+                return;
+            }
+            let Some(item) = input.next().await else {
+                return;
+            };
+            if self
+                .try_spawn_tracked_task(priority, spawner(item), in_flight.track())
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    /// Spawns a child task for every message received on the channel, from a bridge thread dedicated to it
+    ///
+    /// It's meant for work handed over by threads that don't run on any executor. The bridge thread keeps receiving
+    /// until every sender is dropped, or until the spawn group refuses a child task, was cancelled, reset or closed,
+    /// which it notices within a few milliseconds even while waiting for a message. The receiver is dropped along with
+    /// the bridge thread, so the senders fail from then on, and a message received after the cancellation is dropped.
+    ///
+    /// Once the pending child tasks fill the queue capacity set with [`queue_capacity`](Self::queue_capacity), the
+    /// bridge thread stops receiving until one of them finishes, which leaves the messages in the channel and blocks
+    /// the senders of a bounded channel. Without a queue capacity, every message becomes a child task as it arrives.
+    ///
+    /// The stream of the spawn group doesn't end while the bridge thread runs, but waiting for all of the child tasks
+    /// doesn't wait for the messages still to come, joining the returned handle does.
+    ///
+    /// # Parameters
+    ///
+    /// * `receiver`: the receiving end of the channel
+    /// * `priority`: priority to use for every child task
+    /// * `spawner`: returns the child task for a message
+    ///
+    /// # Returns
+    /// The handle of the bridge thread, which returns the number of child tasks it spawned
+    ///
+    /// # Panics
+    /// If the bridge thread can't be spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::{sync::mpsc, thread};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// let (sender, receiver) = mpsc::channel();
+    /// let bridge = group.spawn_from_channel(receiver, Priority::default(), |value: u64| async move {
+    ///     value * 2
+    /// });
+    /// thread::spawn(move || (1..=10).for_each(|value| sender.send(value).unwrap()));
+    /// // the stream ends once the sender is dropped and every message was handled
+    /// let total: u64 = block_on((&mut group).fold(0, |total, value| total + value));
+    /// assert_eq!(total, 110);
+    /// assert_eq!(bridge.join().unwrap(), 10);
+    /// ```
+    pub fn spawn_from_channel<Msg, Spawner, F>(
+        &mut self,
+        receiver: Receiver<Msg>,
+        priority: Priority,
+        spawner: Spawner,
+    ) -> thread::JoinHandle<usize>
+    where
+        ValueType: 'static,
+        Msg: Send + 'static,
+        Spawner: FnMut(Msg) -> F + Send + 'static,
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        channel_bridge::bridge(
+            self.runtime.clone(),
+            self.count.clone(),
+            receiver,
+            priority,
+            spawner,
+        )
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Returns a stream of the results of the spawn group
+    ///
+    /// Once the spawn group is cancelled or dropped, the returned stream ends after yielding the results
+    /// already buffered, waking any consumer still waiting on it. See [`ResultStream`](crate::ResultStream) for more.
+    pub fn stream(&self) -> ResultStream<ValueType> {
+        ResultStream::new(self.runtime.stream())
+    }
+
+    /// Returns a stream of the results of the spawn group that yields the newest buffered result first
+    ///
+    /// See [`RevStream`](crate::RevStream) for more.
+    pub fn rev_stream(&self) -> RevStream<ValueType> {
+        RevStream::new(self.runtime.stream())
+    }
+
+    /// Takes the next result if one is buffered, or returns None right away rather than waiting for one
+    ///
+    /// The result is consumed as if it was taken from the stream, so the stream still ends once every result was
+    /// consumed however they were taken.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// assert_eq!(group.try_next(), None);
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// group.spawn_task(Priority::default(), async { 2 });
+    /// block_on(group.wait_for_all());
+    /// let first = group.try_next().unwrap();
+    /// let second = block_on(group.next()).unwrap();
+    /// assert_eq!(first + second, 3);
+    /// assert_eq!(block_on(group.next()), None);
+    /// ```
+    pub fn try_next(&mut self) -> Option<ValueType> {
+        self.runtime.try_next()
+    }
+
+    /// Takes every result buffered at once, leaving the ones the pending child tasks are yet to produce to later calls
+    ///
+    /// The results are taken under a single lock of the buffer, so none of them goes to another consumer meanwhile,
+    /// and they're consumed as if they were taken from the stream.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// for value in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// block_on(group.wait_for_all());
+    /// let mut results = group.drain_ready();
+    /// results.sort();
+    /// assert_eq!(results, [0, 1, 2, 3, 4]);
+    /// assert!(group.drain_ready().is_empty());
+    /// ```
+    pub fn drain_ready(&mut self) -> Vec<ValueType> {
+        self.runtime.drain_ready()
+    }
+
+    /// Waits for up to `n` results and returns them as a vector
+    ///
+    /// Returns early with fewer results once the stream of the spawn group ends, which is when no child task is left
+    /// to produce one, either since every one of them completed or since they were cancelled. Unlike the deprecated
+    /// ``get_chunks``, it never panics, whatever `n` is compared to the number of child tasks spawned.
+    ///
+    /// # Parameters
+    ///
+    /// * `n`: the most results to wait for, an empty vector being returned right away for zero
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u8>::new(2);
+    /// for value in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// assert_eq!(block_on(group.next_chunk(2)).len(), 2);
+    /// // only three child tasks are left
+    /// assert_eq!(block_on(group.next_chunk(10)).len(), 3);
+    /// assert!(block_on(group.next_chunk(10)).is_empty());
+    /// ```
+    pub async fn next_chunk(&mut self, n: usize) -> Vec<ValueType> {
+        let mut results: Vec<ValueType> = Vec::with_capacity(n.min(self.len()));
+        while results.len() < n {
+            match self.next().await {
+                Some(result) => results.push(result),
+                None => break,
+            }
+        }
+        results
+    }
+
+    /// Waits for the newest result rather than the oldest one, or None once the stream of the spawn group ends
+    ///
+    /// It takes the result buffered last, waiting for the next one to come if none is buffered, and consumes it as if
+    /// it was taken from the stream, so it mixes freely with ``next``.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(1);
+    /// for reading in 0..3 {
+    ///     group.spawn_task(Priority::default(), async move { reading });
+    ///     block_on(group.wait_for_all());
+    /// }
+    /// assert_eq!(block_on(group.next_back()), Some(2));
+    /// assert_eq!(block_on(group.next()), Some(0));
+    /// assert_eq!(block_on(group.next_back()), Some(1));
+    /// assert_eq!(block_on(group.next_back()), None);
+    /// ```
+    pub async fn next_back(&mut self) -> Option<ValueType> {
+        self.runtime.next_back().await
+    }
+
+    /// Returns a view over the spawn group whose stream yields the results mapped by the given function
+    ///
+    /// See [`MappedGroup`](crate::MappedGroup) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `mapper`: a closure that maps each result of the spawn group
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, MappedGroup, Priority, SpawnGroup};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Celsius(i32);
+    ///
+    /// fn readings(group: &mut SpawnGroup<i32>) -> MappedGroup<'_, i32, Celsius> {
+    ///     for raw in [10, 20, 30] {
+    ///         group.spawn_task(Priority::default(), async move { raw });
+    ///     }
+    ///     group.map_view(Celsius)
+    /// }
+    ///
+    /// let mut group = SpawnGroup::new(2);
+    /// let mut view = readings(&mut group);
+    /// block_on(view.wait_for_all());
+    /// assert!(view.is_empty());
+    /// assert_eq!(view.snapshot().buffered, 3);
+    ///
+    /// let mut readings: Vec<Celsius> = block_on((&mut view).collect());
+    /// readings.sort_by_key(|reading| reading.0);
+    /// assert_eq!(readings, [Celsius(10), Celsius(20), Celsius(30)]);
+    ///
+    /// // cancelling the view cancels the spawn group itself
+    /// view.cancel_all();
+    /// assert!(view.is_cancelled());
+    /// drop(view);
+    /// assert!(group.is_cancelled());
+    /// ```
+    pub fn map_view<'a, MappedType, Mapper>(
+        &'a mut self,
+        mapper: Mapper,
+    ) -> MappedGroup<'a, ValueType, MappedType>
+    where
+        Mapper: FnMut(ValueType) -> MappedType + Send + 'a,
+    {
+        MappedGroup::new(self, mapper)
+    }
+
+    /// Returns an iterator blocking the current thread until the next result of the spawn group is available
+    ///
+    /// See [`BlockingIter`](crate::BlockingIter) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// for value in 0..10 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// let mut results: Vec<u64> = group.blocking_iter().collect();
+    /// results.sort();
+    /// assert_eq!(results, (0..10).collect::<Vec<_>>());
+    /// ```
+    pub fn blocking_iter(&mut self) -> BlockingIter<'_, ValueType> {
+        BlockingIter::new(self.runtime.stream(), 0)
+    }
+
+    /// Returns an iterator blocking the current thread until the next result of the spawn group is available,
+    /// prefetching up to the given number of results on a drain thread
+    ///
+    /// Prefetching pays off when the child tasks produce many small results, since most calls to ``next`` then
+    /// return a result without parking. A prefetch of zero is the same as [`blocking_iter`](Self::blocking_iter).
+    ///
+    /// Dropping the iterator hands the results it prefetched back to the spawn group, in their original order.
+    /// See [`BlockingIter`](crate::BlockingIter) for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `prefetch`: the maximum number of results taken off the stream ahead of the consumer
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// for value in 0..100 {
+    ///     group.spawn_task(Priority::default(), async move { value });
+    /// }
+    /// block_on(group.wait_for_all());
+    ///
+    /// let mut results = group.blocking_iter_with_prefetch(16);
+    /// let first: Vec<u64> = results.by_ref().take(10).collect();
+    /// drop(results);
+    ///
+    /// // the results prefetched but never consumed went back to the spawn group
+    /// let rest: Vec<u64> = group.blocking_iter().collect();
+    /// assert_eq!(first.len() + rest.len(), 100);
+    /// ```
+    pub fn blocking_iter_with_prefetch(&mut self, prefetch: usize) -> BlockingIter<'_, ValueType> {
+        BlockingIter::new(self.runtime.stream(), prefetch)
+    }
+
+    /// Turns the spawn group into an iterator blocking the current thread until its next result is available
+    ///
+    /// Unlike [`blocking_iter`](Self::blocking_iter), the iterator owns the spawn group, so it can be handed around
+    /// and returned from functions. Dropping it drops the spawn group, as its drop policy says.
+    ///
+    /// It must not be iterated from one of the worker threads of the spawn group, which panics rather than deadlocks.
+    /// See [`BlockingResults`](crate::BlockingResults) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{BlockingResults, Priority, SpawnGroup};
+    ///
+    /// fn squares(count: u64) -> BlockingResults<u64> {
+    ///     let mut group = SpawnGroup::new(2);
+    ///     for value in 0..count {
+    ///         group.spawn_task(Priority::default(), async move { value * value });
+    ///     }
+    ///     group.into_blocking_iter()
+    /// }
+    ///
+    /// let mut results: Vec<u64> = squares(5).collect();
+    /// results.sort();
+    /// assert_eq!(results, [0, 1, 4, 9, 16]);
+    /// ```
+    pub fn into_blocking_iter(self) -> BlockingResults<ValueType> {
+        BlockingResults::new(self)
+    }
+
+    /// Returns a stream of the results of the spawn group in ascending order of the sequence numbers their child tasks
+    /// were spawned with through [`spawn_task_sequenced`](Self::spawn_task_sequenced), starting at sequence number 0
+    ///
+    /// See [`SequencedStream`](crate::SequencedStream) for how missing sequence numbers are given up on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, sleep, Priority, Sequenced, SpawnGroup};
+    /// use std::time::Duration;
+    ///
+    /// let mut group = SpawnGroup::<&str>::new(2);
+    /// for (chunk, data) in [(2, "c"), (0, "a"), (1, "b")] {
+    ///     group.spawn_task_sequenced(chunk, Priority::default(), async move {
+    ///         sleep(Duration::from_millis(10 * (3 - chunk))).await;
+    ///         data
+    ///     });
+    /// }
+    /// // the chunk 3 never arrives
+    /// group.spawn_task_sequenced(4, Priority::default(), async { "e" });
+    ///
+    /// let chunks: Vec<Sequenced<&str>> = block_on(group.sequenced_stream().collect());
+    /// assert_eq!(
+    ///     chunks,
+    ///     [
+    ///         Sequenced::Item(0, "a"),
+    ///         Sequenced::Item(1, "b"),
+    ///         Sequenced::Item(2, "c"),
+    ///         Sequenced::Gap(3),
+    ///         Sequenced::Item(4, "e"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn sequenced_stream(&mut self) -> SequencedStream<'_, ValueType> {
+        SequencedStream::new(self.runtime.stream())
+    }
+
+    /// Places a marker after the results buffered so far, which [`marked_stream`](Self::marked_stream) delivers in
+    /// between them
+    ///
+    /// The marker goes through the same buffer as the results, so it keeps its place among the results buffered
+    /// before and after it, and it isn't counted as a result. The results of the child tasks still running come after
+    /// it, so a marker closing a phase of child tasks is usually inserted once the phase was waited for.
+    ///
+    /// # Parameters
+    ///
+    /// * `tag`: the tag the marker is delivered with
+    pub fn insert_marker(&self, tag: u64) {
+        self.runtime.insert_marker(tag);
+    }
+
+    /// Returns a stream of the results of the spawn group along with the markers inserted in between them with
+    /// [`insert_marker`](Self::insert_marker)
+    ///
+    /// See [`MarkedStream`](crate::MarkedStream) for more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Item, Priority, SpawnGroup};
+    ///
+    /// let mut group = SpawnGroup::<u64>::new(2);
+    /// for phase in 0..3 {
+    ///     for value in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move { phase * 10 + value });
+    ///     }
+    ///     block_on(group.wait_for_all());
+    ///     group.insert_marker(phase);
+    /// }
+    ///
+    /// let mut totals = vec![];
+    /// let mut total = 0;
+    /// block_on(async {
+    ///     let mut stream = group.marked_stream();
+    ///     while let Some(item) = stream.next().await {
+    ///         match item {
+    ///             Item::Value(value) => total += value,
+    ///             Item::Marker(_) => totals.push(std::mem::take(&mut total)),
+    ///         }
+    ///     }
+    /// });
+    /// assert_eq!(totals, [6, 46, 86]);
+    /// ```
+    pub fn marked_stream(&mut self) -> MarkedStream<'_, ValueType> {
+        MarkedStream::new(self.runtime.stream())
+    }
+
+    /// Puts the spawn group in ack mode, where a child task only starts once fewer than the given number of started
+    /// child tasks are either running or have their result left unacknowledged
+    ///
+    /// The results are acknowledged through [`acked_stream`](Self::acked_stream), which suits results standing for
+    /// side effects that must be confirmed before more of them happen. A result consumed any other way counts as
+    /// acknowledged as soon as it's consumed. Cancelling or resetting the spawn group lets the child tasks start
+    /// again, whether or not the results produced before were acknowledged.
+    ///
+    /// Waiting for every child task with results left unacknowledged never returns once the limit is reached, the
+    /// results must be consumed for the remaining child tasks to start.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_unacked`: the number of started child tasks whose results may be left unacknowledged
+    ///
+    /// # Panics
+    /// If `max_unacked` is zero, as no child task could ever start
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let started = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroup::<usize>::new(4);
+    /// group.ack_mode(2);
+    /// for id in 0..10 {
+    ///     let started = started.clone();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         started.fetch_add(1, Ordering::SeqCst);
+    ///         id
+    ///     });
+    /// }
+    /// let mut confirmed = vec![];
+    /// block_on(async {
+    ///     let mut stream = group.acked_stream();
+    ///     while let Some(result) = stream.next().await {
+    ///         // never more than two child tasks started ahead of the confirmed results
+    ///         assert!(started.load(Ordering::SeqCst) <= confirmed.len() + 2);
+    ///         confirmed.push(result.ack());
+    ///     }
+    /// });
+    /// confirmed.sort();
+    /// assert_eq!(confirmed, (0..10).collect::<Vec<_>>());
+    /// ```
+    pub fn ack_mode(&mut self, max_unacked: usize) {
+        assert!(max_unacked > 0, "ack mode needs room for at least one result");
+        self.runtime.ack_mode(max_unacked);
+    }
+
+    /// Returns a stream of the results wrapped in [`Acked`](crate::Acked), to acknowledge once they're done with in
+    /// ack mode
+    ///
+    /// See [`ack_mode`](Self::ack_mode).
+    pub fn acked_stream(&mut self) -> AckedStream<'_, ValueType> {
+        AckedStream::new(self.runtime.stream())
+    }
+}
+
+impl<Key: Send + 'static, ValueType: Send + 'static> SpawnGroup<(Key, ValueType)> {
+    /// Spawns a new task into the spawn group whose result is paired with the given key, does nothing if the spawn
+    /// group was closed or its priority policy refuses the priority
+    ///
+    /// The spawn group yields the result as a ``(key, value)`` pair, so fanning out over a map of inputs doesn't
+    /// need every closure to return its key along with its value.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: the key the result is paired with
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task_keyed<F>(&mut self, key: Key, priority: Priority, closure: F)
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        self.add_task(priority, async move { (key, closure.await) });
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results keyed by the key they were paired with
+    ///
+    /// When several results have the same key, the one arriving last wins.
+    ///
+    /// # Returns
+    /// A map of the results by their key
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{block_on, Priority, SpawnGroup};
+    /// use std::collections::HashMap;
+    ///
+    /// let inputs = HashMap::from([("one", 1), ("two", 2), ("three", 3)]);
+    /// let mut group = SpawnGroup::<(&str, u64)>::new(2);
+    /// for (name, input) in inputs {
+    ///     group.spawn_task_keyed(name, Priority::default(), async move { input * 10 });
+    /// }
+    /// let results: HashMap<&str, u64> = block_on(group.collect_map());
+    /// assert_eq!(results, HashMap::from([("one", 10), ("two", 20), ("three", 30)]));
+    /// ```
+    pub async fn collect_map(&mut self) -> HashMap<Key, ValueType>
+    where
+        Key: Eq + Hash,
+    {
+        let mut results: HashMap<Key, ValueType> = HashMap::new();
+        while let Some((key, value)) = self.next().await {
+            results.insert(key, value);
+        }
+        results
     }
 }
 
@@ -159,32 +2939,16 @@ impl<ValueType: Send> SpawnGroup<ValueType> {
     ///
     /// # Returns
     /// Returns a vector of length `of_count` elements from the spawn group instance
-    #[deprecated(since = "2.0.0", note = "Buggy")]
+    #[deprecated(since = "2.0.0", note = "Use next_chunk instead, which never panics")]
     pub async fn get_chunks(&self, of_count: usize) -> Vec<ValueType> {
-        if of_count == 0 {
-            return vec![];
-        }
-        let buffer_count = self.runtime.stream().buffer_count().await;
-        if buffer_count == of_count {
-            let mut count: usize = of_count;
-            let mut results: Vec<ValueType> = vec![];
-            while count != 0 {
-                if let Some(result) = self.runtime.stream().next().await {
-                    results.push(result);
-                    count -= 1;
-                }
-            }
-            return results;
-        }
         if of_count > self.count() {
             panic!("The argument supplied cannot be greater than the number of spawned child tasks")
         }
-        let mut count: usize = of_count;
-        let mut results: Vec<ValueType> = vec![];
-        while count != 0 {
-            if let Some(result) = self.runtime.stream().next().await {
-                results.push(result);
-                count -= 1;
+        let mut results: Vec<ValueType> = Vec::with_capacity(of_count);
+        while results.len() < of_count {
+            match self.runtime.stream().next().await {
+                Some(result) => results.push(result),
+                None => break,
             }
         }
         results
@@ -193,22 +2957,18 @@ impl<ValueType: Send> SpawnGroup<ValueType> {
 
 impl<ValueType: Send> Drop for SpawnGroup<ValueType> {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        match self.drop_policy {
+            GroupDropPolicy::WaitForAll => self.runtime.end_waiting(),
+            // cut off the same way as by cancel_all, without waiting for the polls in flight
+            GroupDropPolicy::CancelAll => self.runtime.end(false),
+            GroupDropPolicy::Detach => self.runtime.detach(),
         }
     }
 }
 
 impl<ValueType: Send> Initializible for SpawnGroup<ValueType> {
     fn init() -> Self {
-        SpawnGroup {
-            runtime: RuntimeEngine::init(),
-            is_cancelled: false,
-            count: Arc::new(AtomicUsize::new(0)),
-            wait_at_drop: true,
-        }
+        Self::from_runtime(RuntimeEngine::init(), GroupDropPolicy::WaitForAll)
     }
 }
 
@@ -219,23 +2979,34 @@ impl<ValueType: Send + 'static> Shared for SpawnGroup<ValueType> {
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        self.increment_count();
-        self.runtime.write_task(priority, closure);
+        if self.runtime.write_task(priority, closure).is_ok() {
+            self.increment_count();
+        }
     }
 
     fn cancel_all_tasks(&mut self) {
         self.runtime.cancel();
-        self.is_cancelled = true;
+        self.set_cancelled(true);
         self.decrement_count_to_zero();
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
+        if self.cancelled || self.runtime.write_task(priority, closure).is_err() {
+            return false;
         }
+        self.increment_count();
+        true
+    }
+}
+
+// lists the names of the child tasks in flight, which tells what a stuck spawn group waits on
+impl<ValueType: Send> fmt::Debug for SpawnGroup<ValueType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.runtime
+            .fmt_group(f, "SpawnGroup", self.cancelled, self.drop_policy)
     }
 }
 
@@ -245,12 +3016,26 @@ impl<ValueType: Send> Stream for SpawnGroup<ValueType> {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         self.runtime.stream().poll_next(cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.runtime.stream().size_hint()
+    }
+}
+
+impl<ValueType: Send> FusedStream for SpawnGroup<ValueType> {
+    /// Whether the spawn group has no result buffered and no child task left to produce one, either since every one
+    /// of them completed or since they were cancelled
+    ///
+    /// Spawning another child task makes the stream of the spawn group yield results again.
+    fn is_terminated(&self) -> bool {
+        self.runtime.stream().is_terminated()
+    }
 }
 
 #[async_trait]
 impl<ValueType: Send + 'static> Waitable for SpawnGroup<ValueType> {
     async fn wait(&self) {
-        self.runtime.wait_for_all_tasks();
+        self.runtime.wait_until_idle().await;
         self.decrement_count_to_zero();
     }
 }
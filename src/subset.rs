@@ -0,0 +1,109 @@
+use crate::{
+    shared::{
+        priority::Priority,
+        spawn_error::SpawnError,
+        subsets::{SubsetGuard, SubsetState},
+    },
+    spawn_group::SpawnGroup,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Subset
+///
+/// A handle spawning child tasks into a spawn group while keeping track of them on its own, as returned by
+/// [`SpawnGroup::subset`].
+///
+/// The child tasks spawned through the subset are ordinary child tasks of the spawn group: they count towards it and
+/// their results go into its stream. Waiting for the subset only waits for them though, so the subset works as a phase
+/// barrier within a single spawn group.
+///
+/// Once the spawn group is cancelled, reset or dropped, every wait for the subset completes even if some of its child
+/// tasks never finished.
+pub struct Subset<'a, ValueType: Send + 'static> {
+    group: &'a mut SpawnGroup<ValueType>,
+    state: Arc<SubsetState>,
+}
+
+impl<'a, ValueType: Send> Subset<'a, ValueType> {
+    pub(crate) fn new(group: &'a mut SpawnGroup<ValueType>, state: Arc<SubsetState>) -> Self {
+        Self { group, state }
+    }
+}
+
+impl<ValueType: Send> Subset<'_, ValueType> {
+    /// Spawns a new task into the spawn group and the subset, does nothing if the spawn group refuses it
+    ///
+    /// See [`SpawnGroup::spawn_task`] for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        _ = self.try_spawn_task(priority, closure);
+    }
+
+    /// Spawns a new task into the spawn group and the subset unless the spawn group refuses it
+    ///
+    /// See [`SpawnGroup::try_spawn_task`] for more.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn try_spawn_task<F>(&mut self, priority: Priority, closure: F) -> Result<(), SpawnError>
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+    {
+        // dropped along with the child task once its result is delivered, or once it's cancelled or refused
+        let guard: SubsetGuard = self.state.track();
+        self.group.try_spawn_tracked_task(priority, closure, guard)
+    }
+
+    /// Returns a future that completes once every child task spawned through the subset so far has finished
+    ///
+    /// See [`SubsetWait`](crate::SubsetWait) for more.
+    pub fn wait(&self) -> SubsetWait {
+        SubsetWait {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Number of child tasks spawned through the subset that haven't finished yet
+    pub fn remaining(&self) -> usize {
+        self.state.remaining()
+    }
+
+    /// Cancels all running child tasks of the spawn group, not just the ones of the subset
+    pub fn cancel_all(&mut self) {
+        self.group.cancel_all();
+    }
+}
+
+/// Subset Wait
+///
+/// Future completing once every child task spawned through a [`Subset`](crate::Subset) has finished and delivered its
+/// result to the spawn group, or once the spawn group was cancelled, reset or dropped.
+///
+/// It doesn't borrow the subset, so it may be awaited on another thread while the subset keeps spawning. A child task
+/// spawned through the subset before the future completes is waited for as well.
+#[derive(Clone)]
+pub struct SubsetWait {
+    state: Arc<SubsetState>,
+}
+
+impl Future for SubsetWait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.state.poll_done(cx)
+    }
+}
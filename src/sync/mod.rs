@@ -0,0 +1,6 @@
+//! Synchronization primitives shared by child tasks, which wait by parking their waker rather than blocking a worker
+//! thread
+
+mod once_cell;
+
+pub use self::once_cell::OnceCell;
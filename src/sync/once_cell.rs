@@ -0,0 +1,183 @@
+use parking_lot::Mutex;
+use std::{
+    convert::Infallible,
+    fmt::{self, Debug},
+    future::{poll_fn, Future},
+    sync::OnceLock,
+    task::{Poll, Waker},
+};
+
+#[derive(Default)]
+struct State {
+    initializing: bool,
+    waiters: Vec<Waker>,
+}
+
+/// OnceCell
+///
+/// A cell written to once by the first child task to initialize it, asynchronously
+///
+/// Child tasks racing to initialize it run a single initializer at a time, while the others wait for it by parking
+/// their waker rather than blocking the worker thread polling them, so a slow initializer never keeps the worker
+/// threads from polling other child tasks. If the initializer fails, or its future is dropped before it finishes,
+/// one of the waiting child tasks runs its own initializer instead.
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{block_on, sleep, sync::OnceCell, Priority, SpawnGroup};
+/// use std::{sync::Arc, time::Duration};
+///
+/// let config: Arc<OnceCell<String>> = Arc::new(OnceCell::new());
+/// let mut group = SpawnGroup::<usize>::new(2);
+/// for _ in 0..10 {
+///     let config = config.clone();
+///     group.spawn_task(Priority::default(), async move {
+///         let config = config
+///             .get_or_init(|| async {
+///                 sleep(Duration::from_millis(50)).await;
+///                 String::from("parsed")
+///             })
+///             .await;
+///         config.len()
+///     });
+/// }
+/// assert_eq!(block_on((&mut group).collect::<Vec<_>>()), vec![6; 10]);
+/// ```
+pub struct OnceCell<T> {
+    value: OnceLock<T>,
+    state: Mutex<State>,
+}
+
+impl<T> OnceCell<T> {
+    /// Creates an empty cell
+    pub const fn new() -> Self {
+        Self {
+            value: OnceLock::new(),
+            state: Mutex::new(State {
+                initializing: false,
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns the value of the cell if it was initialized, without waiting for an ongoing initialization
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// Returns the value of the cell, consuming it
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+
+    /// Returns the value of the cell, initializing it with the given initializer unless it was already initialized
+    ///
+    /// If another child task is initializing the cell, waits for it without blocking the worker thread and returns
+    /// the value it initialized the cell with.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: a closure returning the future of the value, only called if no other initializer completes first
+    pub async fn get_or_init<Init, Fut>(&self, init: Init) -> &T
+    where
+        Init: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let initialized: Result<&T, Infallible> =
+            self.get_or_try_init(|| async { Ok(init().await) }).await;
+        match initialized {
+            Ok(value) => value,
+        }
+    }
+
+    /// Returns the value of the cell, initializing it with the given fallible initializer unless it was already
+    /// initialized
+    ///
+    /// If another child task is initializing the cell, waits for it without blocking the worker thread. If that
+    /// initializer fails, the given one runs instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: a closure returning the future of the value, only called if no other initializer completes first
+    ///
+    /// # Returns
+    /// - Ok: the value of the cell
+    /// - Err: the error of the given initializer, which leaves the cell uninitialized
+    pub async fn get_or_try_init<Init, Fut, Error>(&self, init: Init) -> Result<&T, Error>
+    where
+        Init: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        loop {
+            if let Some(value) = self.value.get() {
+                return Ok(value);
+            }
+            if self.claim() {
+                break;
+            }
+            self.wait().await;
+        }
+        // hands the initialization over to a waiting child task if the initializer fails, panics or is dropped
+        let _release = Release(self);
+        let value: T = init().await?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Makes the current child task the one initializing the cell, unless another one already is
+    fn claim(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.initializing {
+            return false;
+        }
+        state.initializing = true;
+        true
+    }
+
+    /// Waits until the cell was initialized or nobody is initializing it anymore
+    async fn wait(&self) {
+        poll_fn(|cx| {
+            let mut state = self.state.lock();
+            // checked under the lock, so the initializer can't finish between the check and the registration
+            if self.value.get().is_some() || !state.initializing {
+                return Poll::Ready(());
+            }
+            if !state
+                .waiters
+                .iter()
+                .any(|registered| registered.will_wake(cx.waker()))
+            {
+                state.waiters.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Ends the initialization of the cell once dropped, whether it succeeded or not, and wakes every waiting child task
+struct Release<'a, T>(&'a OnceCell<T>);
+
+impl<T> Drop for Release<'_, T> {
+    fn drop(&mut self) {
+        let waiters: Vec<Waker> = {
+            let mut state = self.0.state.lock();
+            state.initializing = false;
+            std::mem::take(&mut state.waiters)
+        };
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Debug> Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceCell").field(&self.get()).finish()
+    }
+}
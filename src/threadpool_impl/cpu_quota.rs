@@ -0,0 +1,84 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+/// Longest burst the worker threads may run above the quota, which is also the longest debt they may owe
+const BURST: Duration = Duration::from_millis(50);
+
+/// Shortest park worth taking, smaller debts carry over so tiny jobs don't park the worker threads after each of them
+const MIN_PARK: Duration = Duration::from_millis(1);
+
+struct Bucket {
+    // seconds of CPU time the worker threads may still use, negative while they are in debt
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket capping the CPU time used collectively by the worker threads of a threadpool
+///
+/// The bucket refills at ``cores`` seconds of CPU time per second of wall-clock time, and each job
+/// run by a worker thread is charged for its duration. Workers park once the bucket is in debt,
+/// so the cap is approximate and only holds over windows of a few jobs.
+#[derive(Clone)]
+pub(crate) struct CpuQuota {
+    // bits of the number of cores, infinite when uncapped
+    cores: Arc<AtomicU64>,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl Default for CpuQuota {
+    fn default() -> Self {
+        Self {
+            cores: Arc::new(AtomicU64::new(f64::INFINITY.to_bits())),
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: 0.0,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl CpuQuota {
+    pub(crate) fn set(&self, cores: f64) {
+        let mut bucket = self.bucket.lock();
+        bucket.tokens = 0.0;
+        bucket.refilled_at = Instant::now();
+        self.cores.store(cores.to_bits(), Ordering::Release);
+    }
+
+    fn cores(&self) -> f64 {
+        f64::from_bits(self.cores.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn is_capped(&self) -> bool {
+        self.cores().is_finite()
+    }
+
+    /// Charges a job that kept a worker thread busy for the given duration
+    ///
+    /// # Returns
+    /// - Some: how long the worker thread should park to stay under the quota
+    /// - None: if the worker thread may go on right away
+    pub(crate) fn charge(&self, busy: Duration) -> Option<Duration> {
+        let cores: f64 = self.cores();
+        if !cores.is_finite() {
+            return None;
+        }
+        let burst: f64 = BURST.as_secs_f64() * cores;
+        let mut bucket = self.bucket.lock();
+        let now: Instant = Instant::now();
+        let refill: f64 = now.duration_since(bucket.refilled_at).as_secs_f64() * cores;
+        bucket.tokens = (bucket.tokens + refill).min(burst);
+        bucket.refilled_at = now;
+        // a job stretched by preemption can't put the bucket in debt for longer than a burst
+        bucket.tokens = (bucket.tokens - busy.as_secs_f64()).max(-burst);
+        let park: Duration = Duration::from_secs_f64(bucket.tokens.min(0.0).abs() / cores);
+        (park >= MIN_PARK).then_some(park)
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use parking_lot::Mutex;
+
+use crate::shared::priority::Priority;
+
+/// Number of records each worker thread keeps, older records are overwritten
+const CAPACITY: usize = 256;
+
+thread_local! {
+    /// The log the current worker thread belongs to and its index within its threadpool
+    static WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// How a child task ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// The child task ran to completion
+    Completed,
+    /// The child task panicked
+    Panicked,
+    /// The child task was cancelled after it was first polled
+    Cancelled,
+}
+
+/// Execution Record
+///
+/// A child task that was executed by a spawn group, as recorded by the worker thread that last polled it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionRecord {
+    /// Index of the worker thread, or ``None`` if it was a thread helping the threadpool
+    pub worker: Option<usize>,
+    /// Identifier of the child task, as in [`TaskSnapshot`](crate::TaskSnapshot)
+    pub task: u64,
+    /// The priority the child task was spawned with
+    pub priority: Priority,
+    /// The instant the child task was first polled
+    pub started_at: Instant,
+    /// The instant the child task ended
+    pub ended_at: Instant,
+    /// How the child task ended
+    pub outcome: ExecutionOutcome,
+}
+
+#[derive(Default)]
+struct Ring {
+    records: Vec<ExecutionRecord>,
+    next: usize,
+}
+
+impl Ring {
+    fn push(&mut self, record: ExecutionRecord) {
+        if self.records.len() < CAPACITY {
+            self.records.push(record);
+        } else {
+            self.records[self.next] = record;
+        }
+        self.next = (self.next + 1) % CAPACITY;
+    }
+}
+
+/// Rings of the last child tasks executed by each worker thread of a threadpool, disabled by default
+#[derive(Clone)]
+pub(crate) struct ExecutionLog {
+    enabled: Arc<AtomicBool>,
+    // one ring per worker thread, the last one is shared by the threads helping the threadpool
+    rings: Arc<[Mutex<Ring>]>,
+}
+
+impl ExecutionLog {
+    pub(crate) fn new(count: usize) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            rings: (0..=count).map(|_| Mutex::new(Ring::default())).collect(),
+        }
+    }
+
+    fn id(&self) -> usize {
+        Arc::as_ptr(&self.rings) as *const () as usize
+    }
+
+    /// Marks the calling thread as the given worker thread, called once by each worker thread as it starts
+    pub(crate) fn attach(&self, index: usize) {
+        WORKER.set(Some((self.id(), index)));
+    }
+
+    /// Whether the calling thread is one of the worker threads of the threadpool
+    pub(crate) fn is_current_worker(&self) -> bool {
+        WORKER.get().is_some_and(|(log, _)| log == self.id())
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Release);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Records a child task on the ring of the calling thread
+    pub(crate) fn record(
+        &self,
+        task: u64,
+        priority: Priority,
+        started_at: Instant,
+        outcome: ExecutionOutcome,
+    ) {
+        let worker: Option<usize> = WORKER
+            .get()
+            .filter(|(log, _)| *log == self.id())
+            .map(|(_, index)| index);
+        let ring: &Mutex<Ring> = &self.rings[worker.unwrap_or(self.rings.len() - 1)];
+        ring.lock().push(ExecutionRecord {
+            worker,
+            task,
+            priority,
+            started_at,
+            ended_at: Instant::now(),
+            outcome,
+        });
+    }
+
+    /// Merges the records of every ring, ordered by the instant the child tasks ended
+    pub(crate) fn records(&self) -> Vec<ExecutionRecord> {
+        let mut records: Vec<ExecutionRecord> = self
+            .rings
+            .iter()
+            .flat_map(|ring| ring.lock().records.clone())
+            .collect();
+        records.sort_by_key(|record| record.ended_at);
+        records
+    }
+}
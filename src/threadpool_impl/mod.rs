@@ -1,11 +1,28 @@
+mod cpu_quota;
+mod execution_log;
 mod iteratorimpl;
 mod queue;
 mod queueops;
-mod threadpool;
+mod rendezvous;
 mod thread;
+mod thread_factory;
+mod threadpool;
+mod worker_state;
+
+pub(crate) use cpu_quota::CpuQuota;
+pub(crate) use execution_log::ExecutionLog;
+pub use execution_log::{ExecutionOutcome, ExecutionRecord};
 
 pub(crate) type Func = dyn FnOnce() + Send;
 
+/// Run whenever a job is queued on a threadpool without worker threads, to let whoever runs its jobs know
+pub(crate) type QueuedHook = std::sync::Arc<dyn Fn() + Send + Sync>;
+
 pub(crate) use queue::ThreadSafeQueue;
 pub(crate) use queueops::QueueOperation;
-pub(crate) use threadpool::ThreadPool;
+pub(crate) use thread::UniqueThread;
+pub use thread_factory::ThreadSpec;
+pub(crate) use thread_factory::{default_factory, ThreadFactory};
+pub(crate) use threadpool::{default_count, ShutdownMode, ThreadPool};
+pub use worker_state::worker_state;
+pub(crate) use worker_state::WorkerStates;
@@ -9,15 +9,22 @@ pub(crate) struct ThreadSafeQueue<ItemType> {
 }
 
 impl<ItemType> ThreadSafeQueue<ItemType> {
-    pub fn enqueue(&self, value: ItemType) {
+    pub(crate) fn enqueue(&self, value: ItemType) {
         if let Ok(mut lock) = self.buffer.lock() {
             lock.push_back(value);
         }
     }
+
+    /// Enqueues every value under a single acquisition of the lock
+    pub(crate) fn enqueue_all(&self, values: impl IntoIterator<Item = ItemType>) {
+        if let Ok(mut lock) = self.buffer.lock() {
+            lock.extend(values);
+        }
+    }
 }
 
 impl<ItemType> ThreadSafeQueue<ItemType> {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             buffer: Arc::new(Mutex::new(VecDeque::new())),
         }
@@ -33,10 +40,37 @@ impl<ItemType> Clone for ThreadSafeQueue<ItemType> {
 }
 
 impl<ItemType> ThreadSafeQueue<ItemType> {
-    pub fn dequeue(&self) -> Option<ItemType> {
+    pub(crate) fn dequeue(&self) -> Option<ItemType> {
+        let Ok(mut buffer_lock) = self.buffer.lock() else {
+            return None;
+        };
+        buffer_lock.pop_front()
+    }
+
+    pub(crate) fn dequeue_if<Predicate>(&self, predicate: Predicate) -> Option<ItemType>
+    where
+        Predicate: FnOnce(&ItemType) -> bool,
+    {
         let Ok(mut buffer_lock) = self.buffer.lock() else {
             return None;
         };
+        if !predicate(buffer_lock.front()?) {
+            return None;
+        }
         buffer_lock.pop_front()
     }
+
+    /// Removes every item matching the predicate, returning them so they're dropped outside of the lock
+    pub(crate) fn remove_matching<Predicate>(&self, mut predicate: Predicate) -> Vec<ItemType>
+    where
+        Predicate: FnMut(&ItemType) -> bool,
+    {
+        let Ok(mut buffer_lock) = self.buffer.lock() else {
+            return vec![];
+        };
+        let (removed, kept): (VecDeque<ItemType>, VecDeque<ItemType>) =
+            buffer_lock.drain(..).partition(|item| predicate(item));
+        *buffer_lock = kept;
+        removed.into()
+    }
 }
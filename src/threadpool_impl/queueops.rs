@@ -2,4 +2,6 @@ pub(crate) enum QueueOperation<T: Send + ?Sized> {
     Ready(Box<T>),
     NotYet,
     Wait,
-}
\ No newline at end of file
+    /// Makes the worker thread dequeuing it exit, once every operation queued before it was dequeued
+    Shutdown,
+}
@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use parking_lot::{Condvar, Mutex};
+
+struct State {
+    arrived: usize,
+    // set once the caller stopped waiting, from then on the worker threads pass through without waiting
+    abandoned: bool,
+}
+
+/// Barrier between the worker threads of a threadpool and a caller waiting for them until a deadline
+///
+/// Unlike a plain barrier, the caller may give up waiting, which lets the worker threads arriving afterwards through
+/// rather than leaving them waiting for a caller that's gone.
+pub(crate) struct Rendezvous {
+    count: usize,
+    state: Mutex<State>,
+    cvar: Condvar,
+}
+
+impl Rendezvous {
+    /// Creates a rendezvous of the given number of worker threads and the caller
+    pub(crate) fn new(count: usize) -> Self {
+        Self {
+            count,
+            state: Mutex::new(State {
+                arrived: 0,
+                abandoned: false,
+            }),
+            cvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks the calling worker thread until every other one arrived or the caller gave up
+    pub(crate) fn arrive(&self) {
+        let mut state = self.state.lock();
+        state.arrived += 1;
+        self.cvar.notify_all();
+        while state.arrived < self.count && !state.abandoned {
+            self.cvar.wait(&mut state);
+        }
+    }
+
+    /// Blocks the caller until every worker thread arrived or the deadline passed, whichever comes first
+    ///
+    /// # Returns
+    /// Whether every worker thread arrived in time
+    pub(crate) fn wait_until(&self, deadline: Instant) -> bool {
+        let mut state = self.state.lock();
+        while state.arrived < self.count {
+            if self.cvar.wait_until(&mut state, deadline).timed_out() {
+                break;
+            }
+        }
+        state.abandoned = true;
+        self.cvar.notify_all();
+        state.arrived >= self.count
+    }
+}
@@ -1,23 +1,37 @@
-use std::thread;
+use std::{io, thread};
+
+use super::thread_factory::{ThreadFactory, ThreadSpec};
 
 pub(crate) struct UniqueThread {
     handle: thread::JoinHandle<()>,
 }
 
 impl UniqueThread {
-    pub(crate) fn new<Task: FnOnce() + Send + 'static>(name: String, task: Task) -> Self {
-        let handle = thread::Builder::new()
-            .name(name)
-            .spawn(move || {
-                task();
-            })
-            .unwrap();
-        UniqueThread { handle }
+    pub(crate) fn new<Task: FnOnce() + Send + 'static>(
+        factory: &ThreadFactory,
+        name: String,
+        task: Task,
+    ) -> io::Result<Self> {
+        let handle = factory(ThreadSpec {
+            name,
+            stack_size: None,
+            entry: Box::new(task),
+        })?;
+        Ok(UniqueThread { handle })
     }
 }
 
 impl UniqueThread {
+    /// Whether this is the handle of the calling thread
+    pub(crate) fn is_current(&self) -> bool {
+        self.handle.thread().id() == thread::current().id()
+    }
+
     pub(crate) fn join(self) {
+        // a worker thread dropping the last handle to its own threadpool stops on its own instead
+        if self.is_current() {
+            return;
+        }
         _ = self.handle.join();
     }
 }
@@ -0,0 +1,38 @@
+use std::{io, sync::Arc, thread};
+
+/// Creates the threads of a spawn group, spawning them with ``thread::Builder`` unless the caller provided a factory
+pub(crate) type ThreadFactory =
+    Arc<dyn Fn(ThreadSpec) -> io::Result<thread::JoinHandle<()>> + Send + Sync>;
+
+/// Thread Spec
+///
+/// Describes a thread a spawn group needs, as handed to the thread factory given to
+/// [`SpawnGroup::with_thread_factory`](crate::SpawnGroup::with_thread_factory) and the like.
+///
+/// The factory must run ``entry`` on the thread it creates and return the handle joining that thread.
+/// Besides that, it may create the thread however the environment requires, for example to apply a naming
+/// convention, enter a security context or record the thread for auditing.
+pub struct ThreadSpec {
+    /// Name of the thread
+    pub name: String,
+    /// Stack size of the thread in bytes, or None for the platform default
+    pub stack_size: Option<usize>,
+    /// What the thread runs
+    pub entry: Box<dyn FnOnce() + Send + 'static>,
+}
+
+impl ThreadSpec {
+    /// Spawns the thread the way spawn groups do without a thread factory
+    pub fn spawn(self) -> io::Result<thread::JoinHandle<()>> {
+        let mut builder: thread::Builder = thread::Builder::new().name(self.name);
+        if let Some(stack_size) = self.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+        builder.spawn(self.entry)
+    }
+}
+
+/// The thread factory used when the caller provided none
+pub(crate) fn default_factory() -> ThreadFactory {
+    Arc::new(ThreadSpec::spawn)
+}
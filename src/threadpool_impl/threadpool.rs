@@ -1,133 +1,385 @@
 use std::{
-    backtrace, panic,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Barrier,
-    },
+    backtrace, io, panic,
+    sync::{Arc, Barrier},
     thread,
+    time::Instant,
 };
 
-use super::{queueops::QueueOperation, thread::UniqueThread, Func, ThreadSafeQueue};
+use parking_lot::Mutex;
 
-pub struct ThreadPool {
-    handles: Vec<UniqueThread>,
+use crate::{
+    fault_injection::FaultInjector,
+    shared::{
+        panic_isolation::{self, PanicIsolation},
+        panic_output::{self, PanicOutput},
+    },
+};
+
+use super::{
+    cpu_quota::CpuQuota,
+    execution_log::ExecutionLog,
+    queueops::QueueOperation,
+    rendezvous::Rendezvous,
+    thread::UniqueThread,
+    thread_factory::{default_factory, ThreadFactory},
+    worker_state::WorkerStates,
+    Func, QueuedHook, ThreadSafeQueue,
+};
+
+pub(crate) struct ThreadPool {
+    handles: Arc<Mutex<Vec<UniqueThread>>>,
     count: usize,
     queue: ThreadSafeQueue<QueueOperation<Func>>,
     barrier: Arc<Barrier>,
-    stop_flag: Arc<AtomicBool>,
+    context: WorkerContext,
+    on_queued: Mutex<Option<QueuedHook>>,
+}
+
+/// What happens to the jobs still queued when a threadpool shuts down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ShutdownMode {
+    /// The worker threads run every queued job before exiting
+    Drain,
+    /// The queued jobs are dropped without running, the worker threads exit once done with their current one
+    Abandon,
+}
+
+/// What every worker thread of a threadpool shares besides its queue
+#[derive(Clone)]
+struct WorkerContext {
+    faults: FaultInjector,
+    states: WorkerStates,
+    log: ExecutionLog,
+    quota: CpuQuota,
+    isolation: Arc<Mutex<PanicIsolation>>,
+    // creates the replacement of a worker thread restarted after a panic
+    factory: ThreadFactory,
+    handles: Arc<Mutex<Vec<UniqueThread>>>,
 }
 
 impl Default for ThreadPool {
     fn default() -> Self {
-        panic_hook();
-        let queue = ThreadSafeQueue::new();
-        let count: usize;
-        if let Ok(thread_count) = thread::available_parallelism() {
-            count = thread_count.get();
-        } else {
-            count = 1;
-        }
-        let barrier = Arc::new(Barrier::new(count + 1));
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        let handles = (0..count)
-            .map(|index| start(index, queue.clone(), barrier.clone(), stop_flag.clone()))
-            .collect();
-        ThreadPool {
-            handles,
-            queue,
-            count,
-            barrier,
-            stop_flag,
-        }
+        Self::new(default_count())
     }
 }
 
+/// The number of worker threads of a default threadpool, the default amount of parallelism of the system
+pub(crate) fn default_count() -> usize {
+    let count: usize;
+    if let Ok(thread_count) = thread::available_parallelism() {
+        count = thread_count.get();
+    } else {
+        count = 1;
+    }
+    count
+}
+
 impl ThreadPool {
     pub(crate) fn new(count: usize) -> Self {
+        Self::with_factory(count, &default_factory()).unwrap()
+    }
+
+    /// Creates the worker threads through the given thread factory
+    ///
+    /// If the factory fails to create one of them, the ones already created are stopped before returning the error.
+    pub(crate) fn with_factory(count: usize, factory: &ThreadFactory) -> io::Result<Self> {
         panic_hook();
         let queue = ThreadSafeQueue::new();
         let barrier = Arc::new(Barrier::new(count + 1));
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        let handles = (0..count)
-            .map(|index| start(index, queue.clone(), barrier.clone(), stop_flag.clone()))
-            .collect();
-        ThreadPool {
-            handles,
+        let context = WorkerContext {
+            faults: FaultInjector::new(),
+            states: WorkerStates::default(),
+            log: ExecutionLog::new(count),
+            quota: CpuQuota::default(),
+            isolation: Arc::new(Mutex::new(PanicIsolation::default())),
+            factory: factory.clone(),
+            handles: Arc::new(Mutex::new(Vec::with_capacity(count))),
+        };
+        let mut handles: Vec<UniqueThread> = Vec::with_capacity(count);
+        for index in 0..count {
+            let handle: io::Result<UniqueThread> = start(
+                factory,
+                index,
+                queue.clone(),
+                barrier.clone(),
+                context.clone(),
+            );
+            match handle {
+                Ok(handle) => handles.push(handle),
+                Err(error) => {
+                    handles
+                        .iter()
+                        .for_each(|_| queue.enqueue(QueueOperation::Shutdown));
+                    handles.into_iter().for_each(UniqueThread::join);
+                    return Err(error);
+                }
+            }
+        }
+        *context.handles.lock() = handles;
+        Ok(ThreadPool {
+            handles: context.handles.clone(),
             queue,
             count,
             barrier,
-            stop_flag,
-        }
+            context,
+            on_queued: Mutex::new(None),
+        })
     }
 }
 
 impl ThreadPool {
-    pub fn submit<Task>(&self, task: Task)
+    pub(crate) fn submit<Task>(&self, task: Task)
     where
         Task: FnOnce() + 'static + Send,
     {
         self.queue.enqueue(QueueOperation::Ready(Box::new(task)));
+        self.queued();
+    }
+
+    /// Submits every job at once, so the worker threads contend for the queue once rather than once per job
+    pub(crate) fn submit_all<Task>(&self, tasks: impl IntoIterator<Item = Task>)
+    where
+        Task: FnOnce() + 'static + Send,
+    {
+        self.queue.enqueue_all(
+            tasks
+                .into_iter()
+                .map(|task| QueueOperation::Ready(Box::new(task) as Box<Func>)),
+        );
+        self.queued();
+    }
+
+    /// Sets the hook run whenever a job is queued, as long as the threadpool has no worker thread to run it
+    pub(crate) fn set_on_queued(&self, hook: Option<QueuedHook>) {
+        *self.on_queued.lock() = hook;
+    }
+
+    fn queued(&self) {
+        if self.count > 0 {
+            return;
+        }
+        let hook: Option<QueuedHook> = self.on_queued.lock().clone();
+        if let Some(hook) = hook {
+            hook();
+        }
     }
 }
 
 impl ThreadPool {
-    pub fn wait_for_all(&self) {
+    pub(crate) fn wait_for_all(&self) {
+        if self.count == 0 {
+            while self.run_pending() {}
+            return;
+        }
         for _ in 0..self.count {
             self.queue.enqueue(QueueOperation::Wait);
         }
         self.barrier.wait();
     }
+
+    /// Waits for the worker threads to be done with every job queued so far, giving up once the deadline passed
+    ///
+    /// The jobs still queued or running once it gives up are left to the worker threads.
+    ///
+    /// # Returns
+    /// Whether the worker threads were done with every job in time
+    pub(crate) fn wait_for_all_until(&self, deadline: Instant) -> bool {
+        if self.count == 0 {
+            while self.run_pending() {
+                if Instant::now() >= deadline {
+                    return false;
+                }
+            }
+            return true;
+        }
+        // every worker thread has to take one of the jobs, each of them holding it until the others took theirs
+        let rendezvous: Arc<Rendezvous> = Arc::new(Rendezvous::new(self.count));
+        self.submit_all((0..self.count).map(|_| {
+            let rendezvous: Arc<Rendezvous> = rendezvous.clone();
+            move || rendezvous.arrive()
+        }));
+        rendezvous.wait_until(deadline)
+    }
+}
+
+impl ThreadPool {
+    /// Runs at most one queued job on the calling thread
+    ///
+    /// Barrier operations are left in the queue for the worker threads
+    ///
+    /// # Returns
+    /// - true: if a job was run
+    /// - false: if there was no job ready to run
+    pub(crate) fn run_pending(&self) -> bool {
+        let Some(QueueOperation::Ready(work)) = self
+            .queue
+            .dequeue_if(|op| matches!(op, QueueOperation::Ready(_)))
+        else {
+            return false;
+        };
+        work();
+        // the calling thread isn't a worker thread, so it's never restarted over a panic
+        _ = panic_isolation::take_panicked();
+        true
+    }
+}
+
+impl ThreadPool {
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    pub(crate) fn faults(&self) -> &FaultInjector {
+        &self.context.faults
+    }
+
+    pub(crate) fn worker_states(&self) -> &WorkerStates {
+        &self.context.states
+    }
+
+    pub(crate) fn execution_log(&self) -> &ExecutionLog {
+        &self.context.log
+    }
+
+    pub(crate) fn cpu_quota(&self) -> &CpuQuota {
+        &self.context.quota
+    }
+
+    pub(crate) fn set_panic_isolation(&self, isolation: PanicIsolation) {
+        *self.context.isolation.lock() = isolation;
+    }
 }
 
 impl ThreadPool {
-    fn cancel_all(&self) {
-        self.stop_flag
-            .store(true, std::sync::atomic::Ordering::Release)
+    /// Makes every worker thread exit through a shutdown message queued behind the jobs, and waits for them
+    ///
+    /// Does nothing once the threadpool has already been shut down.
+    pub(crate) fn end(&self, mode: ShutdownMode) {
+        self.stop(mode).into_iter().for_each(UniqueThread::join);
+    }
+
+    /// Queues a shutdown message for every worker thread without waiting for them to exit
+    ///
+    /// # Returns
+    /// The handles of the worker threads, which exit on their own once they are dropped
+    pub(crate) fn stop(&self, mode: ShutdownMode) -> Vec<UniqueThread> {
+        let handles: Vec<UniqueThread> = std::mem::take(&mut *self.handles.lock());
+        if mode == ShutdownMode::Abandon {
+            // barrier operations stay, whoever waits on the barrier still gets through it
+            let abandoned: Vec<QueueOperation<Func>> = self
+                .queue
+                .remove_matching(|op| matches!(op, QueueOperation::Ready(_)));
+            drop(abandoned);
+        } else if self.count == 0 {
+            while self.run_pending() {}
+        }
+        handles
+            .iter()
+            .for_each(|_| self.queue.enqueue(QueueOperation::Shutdown));
+        handles
     }
 }
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        _ = panic::take_hook();
-        self.cancel_all();
-        while let Some(handle) = self.handles.pop() {
-            handle.join();
-        }
+        // the panic hook is left in place, since the other threadpools of the process still report through it
+        self.end(ShutdownMode::Abandon);
     }
 }
 
 fn start(
+    factory: &ThreadFactory,
     index: usize,
     queue: ThreadSafeQueue<QueueOperation<Func>>,
     barrier: Arc<Barrier>,
-    stop_flag: Arc<AtomicBool>,
-) -> UniqueThread {
-    UniqueThread::new(format!("ThreadPool #{}", index), move || {
-        for op in queue {
-            match (op, stop_flag.load(Ordering::Acquire)) {
-                (QueueOperation::NotYet, false) => continue,
-                (QueueOperation::Ready(work), false) => {
-                    work();
-                }
-                (QueueOperation::Wait, false) => _ = barrier.wait(),
-                _ => {
-                    return;
+    context: WorkerContext,
+) -> io::Result<UniqueThread> {
+    UniqueThread::new(factory, format!("ThreadPool #{}", index), move || {
+        let WorkerContext {
+            faults,
+            states,
+            log,
+            quota,
+            ..
+        } = context.clone();
+        states.attach();
+        log.attach(index);
+        for op in queue.clone() {
+            match op {
+                QueueOperation::NotYet => continue,
+                QueueOperation::Ready(work) => {
+                    faults.delay_worker(index);
+                    let started_at: Option<Instant> = quota.is_capped().then(Instant::now);
+                    // a panicking job must not take the worker thread down with it
+                    let panicked: bool =
+                        panic::catch_unwind(panic::AssertUnwindSafe(work)).is_err();
+                    // child tasks catch their own panics, they only note them for the worker thread
+                    let panicked: bool = panic_isolation::take_panicked() || panicked;
+                    if let Some(park) = started_at.and_then(|at| quota.charge(at.elapsed())) {
+                        thread::sleep(park);
+                    }
+                    if panicked
+                        && *context.isolation.lock() == PanicIsolation::RestartWorker
+                        && restart(index, &queue, &barrier, &context)
+                    {
+                        return;
+                    }
                 }
+                QueueOperation::Wait => _ = barrier.wait(),
+                QueueOperation::Shutdown => return,
             }
         }
     })
 }
 
+/// Starts a replacement for the calling worker thread, which exits right after
+///
+/// # Returns
+/// - true: if the replacement took over the place of the calling worker thread
+/// - false: if the threadpool is shutting down, or the thread factory failed, so the calling worker thread goes on
+fn restart(
+    index: usize,
+    queue: &ThreadSafeQueue<QueueOperation<Func>>,
+    barrier: &Arc<Barrier>,
+    context: &WorkerContext,
+) -> bool {
+    // held until the replacement is in place, so a shutdown either joins the replacement or sees no worker thread
+    let mut handles = context.handles.lock();
+    let Some(position) = handles.iter().position(UniqueThread::is_current) else {
+        return false;
+    };
+    let Ok(replacement) = start(
+        &context.factory,
+        index,
+        queue.clone(),
+        barrier.clone(),
+        context.clone(),
+    ) else {
+        return false;
+    };
+    // the handle of the calling worker thread is dropped, detaching it
+    handles[position] = replacement;
+    true
+}
+
 fn panic_hook() {
-    panic::set_hook(Box::new(move |info: &panic::PanicInfo<'_>| {
+    panic::set_hook(Box::new(move |info: &panic::PanicHookInfo<'_>| {
+        let output: PanicOutput = panic_output::current();
+        if matches!(output, PanicOutput::Silent) {
+            return;
+        }
+        // a child task spawned with a name is told apart from the others polled on the same worker thread
+        let task: String = panic_output::task_name()
+            .map(|name| format!(" in child task '{name}'"))
+            .unwrap_or_default();
         let msg = format!(
-            "{} panicked at location {} with {} \nBacktrace:\n{}",
-            thread::current().name().unwrap(),
+            "{}{} panicked at location {} with {} \nBacktrace:\n{}",
+            thread::current().name().unwrap_or("<unnamed>"),
+            task,
             info.location().unwrap(),
             info.to_string().split('\n').collect::<Vec<_>>()[1],
             backtrace::Backtrace::capture()
         );
-        eprintln!("{}", msg);
-        _ = panic::take_hook();
+        output.emit(&msg);
     }));
 }
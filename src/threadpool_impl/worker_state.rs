@@ -0,0 +1,113 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+type Initializer = Arc<dyn Fn() -> Box<dyn Any> + Send + Sync>;
+
+thread_local! {
+    static STATES: RefCell<Option<WorkerStates>> = const { RefCell::new(None) };
+    static VALUES: RefCell<HashMap<TypeId, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Initializers of the per worker thread states of a threadpool, keyed by the type of state
+#[derive(Clone, Default)]
+pub(crate) struct WorkerStates {
+    initializers: Arc<Mutex<HashMap<TypeId, Initializer>>>,
+}
+
+impl WorkerStates {
+    pub(crate) fn register<State, Init>(&self, init: Init)
+    where
+        State: 'static,
+        Init: Fn() -> State + Send + Sync + 'static,
+    {
+        self.initializers.lock().insert(
+            TypeId::of::<State>(),
+            Arc::new(move || Box::new(init()) as Box<dyn Any>),
+        );
+    }
+
+    /// Makes the states available to the calling thread, called once by each worker thread as it starts
+    pub(crate) fn attach(self) {
+        STATES.with_borrow_mut(|states| *states = Some(self));
+    }
+
+    fn initializer(&self, key: TypeId) -> Option<Initializer> {
+        self.initializers.lock().get(&key).cloned()
+    }
+}
+
+/// Returns the state of type ``State`` of the current worker thread
+///
+/// The state is created by the initializer registered with the ``worker_state`` method of the spawn group the first
+/// time a child task asks for it on each worker thread, and lives as long as the worker thread.
+///
+/// Since a child task may be polled by a different worker thread after each ``.await``, the returned ``Rc`` can't be
+/// held across an ``.await`` point, and the state of a type that isn't ``Send`` never leaves its worker thread.
+///
+/// # Returns
+/// - Some: the state of the current worker thread
+/// - None: if it isn't called from a worker thread, including the consumer helping the threadpool, or if no
+///   initializer was registered for ``State``
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, worker_state, DiscardingSpawnGroup, Priority};
+/// use std::{
+///     cell::Cell,
+///     marker::PhantomData,
+///     sync::{
+///         atomic::{AtomicUsize, Ordering},
+///         Arc,
+///     },
+/// };
+///
+/// // costly to create and not Send, like an FFI context
+/// struct Context {
+///     calls: Cell<usize>,
+///     _not_send: PhantomData<*const ()>,
+/// }
+///
+/// let created = Arc::new(AtomicUsize::new(0));
+/// let mut group = DiscardingSpawnGroup::new(2);
+/// group.worker_state({
+///     let created = created.clone();
+///     move || {
+///         created.fetch_add(1, Ordering::SeqCst);
+///         Context {
+///             calls: Cell::new(0),
+///             _not_send: PhantomData,
+///         }
+///     }
+/// });
+/// for _ in 0..100 {
+///     group.spawn_task(Priority::default(), async {
+///         let context = worker_state::<Context>().expect("registered above");
+///         context.calls.set(context.calls.get() + 1);
+///     });
+/// }
+/// block_on(group.wait_for_all());
+///
+/// // at most once per worker thread
+/// assert!(created.load(Ordering::SeqCst) <= 2);
+/// // this isn't a worker thread
+/// assert!(worker_state::<Context>().is_none());
+/// ```
+pub fn worker_state<State: 'static>() -> Option<Rc<State>> {
+    let key: TypeId = TypeId::of::<State>();
+    if let Some(value) = VALUES.with_borrow(|values| values.get(&key).cloned()) {
+        return value.downcast().ok();
+    }
+    let init: Initializer = STATES.with_borrow(|states| states.as_ref()?.initializer(key))?;
+    // no borrow is held while initializing, so an initializer may ask for another state
+    let value: Rc<dyn Any> = Rc::from(init());
+    VALUES.with_borrow_mut(|values| values.insert(key, value.clone()));
+    value.downcast().ok()
+}
@@ -0,0 +1,48 @@
+#[cfg(feature = "wake-trace")]
+mod tracer;
+
+#[cfg(feature = "wake-trace")]
+pub(crate) use tracer::{traced, WakeTracer};
+#[cfg(feature = "wake-trace")]
+pub use tracer::{WakeEvent, WakeEventKind, WakeTrace};
+
+#[cfg(not(feature = "wake-trace"))]
+use std::task::Waker;
+
+/// Stands in for the wake tracer when the `wake-trace` feature is disabled so every hook
+/// compiles down to nothing
+#[cfg(not(feature = "wake-trace"))]
+#[derive(Clone)]
+pub(crate) struct WakeTracer;
+
+#[cfg(not(feature = "wake-trace"))]
+impl WakeTracer {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub(crate) fn resumed(&self, _task: Option<u64>) {}
+
+    #[inline(always)]
+    pub(crate) fn suspended(&self, _task: Option<u64>) {}
+
+    #[inline(always)]
+    pub(crate) fn completed(&self, _task: Option<u64>) {}
+
+    #[inline(always)]
+    pub(crate) fn woken(&self, _task: Option<u64>) {}
+
+    #[inline(always)]
+    pub(crate) fn released(&self, _task: Option<u64>) {}
+
+    #[inline(always)]
+    pub(crate) fn clear(&self) {}
+}
+
+/// Hands the waker over as is when the `wake-trace` feature is disabled
+#[cfg(not(feature = "wake-trace"))]
+#[inline(always)]
+pub(crate) fn traced(waker: &Waker, _task: Option<u64>, _tracer: &WakeTracer) -> Waker {
+    waker.clone()
+}
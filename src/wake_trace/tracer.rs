@@ -0,0 +1,207 @@
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    task::{Wake, Waker},
+    thread::{self, ThreadId},
+    time::Instant,
+};
+
+/// Number of events a spawn group keeps, older events are dropped
+const CAPACITY: usize = 4096;
+
+/// What happened to a child task or to one of its wakers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeEventKind {
+    /// The child task is about to be polled
+    Resumed,
+    /// A poll of the child task returned pending
+    Suspended,
+    /// A poll of the child task returned ready
+    Completed,
+    /// A waker of the child task was woken, whether or not the wake was coalesced with an earlier one
+    Woken,
+    /// The last clone of a waker of the child task was dropped
+    ///
+    /// Clones of a waker can't be observed without unsafe code, which this crate forbids, so only the release of the
+    /// last one is recorded.
+    Released,
+}
+
+/// Wake Event
+///
+/// An event recorded by a spawn group built with the `wake-trace` feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WakeEvent {
+    /// Identifier of the child task, as in [`TaskSnapshot`](crate::TaskSnapshot), or ``None`` for a future run with
+    /// ``run``
+    pub task: Option<u64>,
+    /// The thread the event happened on
+    pub thread: ThreadId,
+    /// The instant the event happened
+    pub at: Instant,
+    /// What happened
+    pub kind: WakeEventKind,
+}
+
+/// Wake Trace
+///
+/// The last events of the child tasks of a spawn group and of their wakers, oldest first, as returned by the
+/// ``wake_trace`` method of the spawn group
+///
+/// Only the last 4096 events are kept, so the events of a child task may have been dropped from the start of the
+/// trace.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{block_on, Priority, SpawnGroup, WakeEventKind};
+///
+/// let mut group = SpawnGroup::<u8>::new(2);
+/// group.spawn_task(Priority::default(), async { 1 });
+/// block_on(group.wait_for_all());
+/// let trace = group.wake_trace();
+/// assert!(trace
+///     .events
+///     .iter()
+///     .any(|event| event.kind == WakeEventKind::Completed));
+/// assert!(trace.stalled_tasks().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WakeTrace {
+    /// The events, oldest first
+    pub events: Vec<WakeEvent>,
+}
+
+impl WakeTrace {
+    /// Finds the child tasks that look like the victims of a lost wakeup, which were suspended and never woken nor
+    /// completed afterwards
+    ///
+    /// # Returns
+    /// The last suspension of each of them, oldest first
+    pub fn stalled_tasks(&self) -> Vec<WakeEvent> {
+        // the last suspension of each child task not followed by a wake, and whether each child task was woken
+        // while it was being polled, which a suspension following it already accounts for
+        let mut suspended: HashMap<u64, WakeEvent> = HashMap::new();
+        let mut woken_while_polled: HashMap<u64, bool> = HashMap::new();
+        for event in &self.events {
+            let Some(task) = event.task else {
+                continue;
+            };
+            match event.kind {
+                WakeEventKind::Resumed => {
+                    suspended.remove(&task);
+                    woken_while_polled.insert(task, false);
+                }
+                WakeEventKind::Woken => {
+                    if suspended.remove(&task).is_none() {
+                        woken_while_polled.insert(task, true);
+                    }
+                }
+                WakeEventKind::Suspended => {
+                    if !woken_while_polled.remove(&task).unwrap_or_default() {
+                        suspended.insert(task, *event);
+                    }
+                }
+                WakeEventKind::Completed => {
+                    suspended.remove(&task);
+                    woken_while_polled.remove(&task);
+                }
+                WakeEventKind::Released => (),
+            }
+        }
+        let mut stalled: Vec<WakeEvent> = suspended.into_values().collect();
+        stalled.sort_by_key(|event| event.at);
+        stalled
+    }
+}
+
+/// The last events of the child tasks of a threadpool and of their wakers
+#[derive(Clone, Default)]
+pub(crate) struct WakeTracer {
+    events: Arc<Mutex<VecDeque<WakeEvent>>>,
+}
+
+impl WakeTracer {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, task: Option<u64>, kind: WakeEventKind) {
+        let event: WakeEvent = WakeEvent {
+            task,
+            thread: thread::current().id(),
+            at: Instant::now(),
+            kind,
+        };
+        let mut events = self.events.lock();
+        if events.len() == CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    pub(crate) fn resumed(&self, task: Option<u64>) {
+        self.record(task, WakeEventKind::Resumed);
+    }
+
+    pub(crate) fn suspended(&self, task: Option<u64>) {
+        self.record(task, WakeEventKind::Suspended);
+    }
+
+    pub(crate) fn completed(&self, task: Option<u64>) {
+        self.record(task, WakeEventKind::Completed);
+    }
+
+    pub(crate) fn woken(&self, task: Option<u64>) {
+        self.record(task, WakeEventKind::Woken);
+    }
+
+    pub(crate) fn released(&self, task: Option<u64>) {
+        self.record(task, WakeEventKind::Released);
+    }
+
+    /// Forgets every event, once the threadpool is handed to another spawn group
+    pub(crate) fn clear(&self) {
+        self.events.lock().clear();
+    }
+
+    pub(crate) fn trace(&self) -> WakeTrace {
+        WakeTrace {
+            events: self.events.lock().iter().copied().collect(),
+        }
+    }
+}
+
+/// Records the wakes of the child task it was made for before handing them to the waker it wraps
+struct TracedWaker {
+    waker: Waker,
+    task: Option<u64>,
+    tracer: WakeTracer,
+}
+
+impl Wake for TracedWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.tracer.woken(self.task);
+        self.waker.wake_by_ref();
+    }
+}
+
+impl Drop for TracedWaker {
+    fn drop(&mut self) {
+        self.tracer.released(self.task);
+    }
+}
+
+/// Wraps the waker shared by several child tasks so the wakes of the given one are recorded
+pub(crate) fn traced(waker: &Waker, task: Option<u64>, tracer: &WakeTracer) -> Waker {
+    Waker::from(Arc::new(TracedWaker {
+        waker: waker.clone(),
+        task,
+        tracer: tracer.clone(),
+    }))
+}
@@ -1,7 +1,7 @@
-use crate::yield_now::yielder::Yielder;
-
 mod yielder;
 
+pub use yielder::Yielder;
+
 /// Wakes the current task and returns [`std::task::Poll::Pending`] once.
 ///
 /// This function is useful when we want to cooperatively give time to the task executor. It is
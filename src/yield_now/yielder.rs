@@ -4,6 +4,7 @@ use std::{
     task::{Context, Poll},
 };
 
+/// Future returned by [`yield_now`](crate::yield_now), which is pending exactly once
 #[derive(Debug, Default)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Yielder {
@@ -0,0 +1,122 @@
+//! Holds back child tasks from starting while too many of their results are left unacknowledged
+
+mod common;
+
+use common::eventually;
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Acked, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+fn spawn_counted(
+    group: &mut SpawnGroup<u64>,
+    started: &Arc<AtomicUsize>,
+    values: std::ops::Range<u64>,
+) {
+    for value in values {
+        let started = started.clone();
+        group.spawn_task(Priority::default(), async move {
+            started.fetch_add(1, Ordering::SeqCst);
+            sleep(Duration::from_millis(1)).await;
+            value
+        });
+    }
+}
+
+#[test]
+fn no_more_child_tasks_start_than_the_unacknowledged_results_allow() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(4);
+    group.ack_mode(3);
+    spawn_counted(&mut group, &started, 0..40);
+
+    let mut acked = 0;
+    let mut values = vec![];
+    block_on(async {
+        let mut stream = group.acked_stream();
+        while let Some(result) = stream.next().await {
+            // the side effect of the result is confirmed a while after it's received
+            sleep(Duration::from_millis(2)).await;
+            assert!(started.load(Ordering::SeqCst) <= acked + 3);
+            values.push(result.ack());
+            acked += 1;
+        }
+    });
+    values.sort();
+    assert_eq!(values, (0..40).collect::<Vec<_>>());
+}
+
+#[test]
+fn held_results_keep_the_next_child_tasks_waiting() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.ack_mode(2);
+    spawn_counted(&mut group, &started, 0..5);
+
+    let held: Vec<Acked<u64>> = block_on(group.acked_stream().take(2).collect());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+
+    let mut held = held.into_iter();
+    assert!(*held.next().unwrap() < 5);
+    // dropped without an acknowledgement, which counts as one
+    assert!(eventually(|| started.load(Ordering::SeqCst) == 3));
+    held.next().unwrap().ack();
+    assert!(eventually(|| started.load(Ordering::SeqCst) == 4));
+
+    let rest: Vec<u64> = block_on(group.acked_stream().map(Acked::ack).collect());
+    assert_eq!(rest.len(), 3);
+}
+
+#[test]
+fn acknowledging_after_a_cancellation_leaves_the_limit_intact() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.ack_mode(2);
+    spawn_counted(&mut group, &started, 0..4);
+    let held: Vec<Acked<u64>> = block_on(group.acked_stream().take(2).collect());
+
+    // the cancellation lets child tasks start again, whatever is left unacknowledged
+    group.reset();
+    started.store(0, Ordering::SeqCst);
+    spawn_counted(&mut group, &started, 10..15);
+    assert!(eventually(|| started.load(Ordering::SeqCst) == 2));
+
+    // acknowledging the results from before the cancellation makes no room for another child task
+    held.into_iter().for_each(|result| _ = result.ack());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+
+    let mut values: Vec<u64> = block_on(group.acked_stream().map(Acked::ack).collect());
+    values.sort();
+    assert_eq!(values, (10..15).collect::<Vec<_>>());
+}
+
+#[test]
+fn results_consumed_without_the_acked_stream_count_as_acknowledged() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.ack_mode(1);
+    spawn_counted(&mut group, &started, 0..10);
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+    assert_eq!(started.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn acknowledging_outside_of_ack_mode_does_nothing() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..5 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    let mut values: Vec<u64> = block_on(group.acked_stream().map(Acked::ack).collect());
+    values.sort();
+    assert_eq!(values, [0, 1, 2, 3, 4]);
+}
@@ -0,0 +1,142 @@
+//! Adapts the number of child tasks running at once to how long they take, against synthetic latency curves
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, AdaptiveConfig, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A remote service answering within ``fast`` until more than ``capacity`` requests are in flight, and within ``slow``
+/// beyond, keeping track of the most requests it ever had in flight
+#[derive(Clone)]
+struct Service {
+    capacity: usize,
+    fast: Duration,
+    slow: Duration,
+    in_flight: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl Service {
+    fn new(capacity: usize, fast: Duration, slow: Duration) -> Self {
+        Self {
+            capacity,
+            fast,
+            slow,
+            in_flight: Arc::default(),
+            peak: Arc::default(),
+        }
+    }
+
+    async fn call(&self) {
+        let in_flight: usize = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_flight, Ordering::SeqCst);
+        if in_flight <= self.capacity {
+            sleep(self.fast).await;
+        } else {
+            sleep(self.slow).await;
+        }
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn the_window_widens_up_to_its_maximum_while_the_child_tasks_stay_within_the_target() {
+    let service = Service::new(usize::MAX, Duration::from_millis(1), Duration::ZERO);
+    let mut group = SpawnGroup::<()>::new(2);
+    group.adaptive_concurrency(AdaptiveConfig {
+        min: 1,
+        max: 6,
+        target_latency: Duration::from_millis(500),
+    });
+    for _ in 0..200 {
+        let service = service.clone();
+        group.spawn_task(Priority::default(), async move { service.call().await });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().concurrency_window(), Some(6));
+    assert!(service.peak() <= 6);
+}
+
+#[test]
+fn the_window_settles_around_the_capacity_of_an_overloaded_service() {
+    let service = Service::new(4, Duration::from_millis(2), Duration::from_millis(60));
+    let mut group = SpawnGroup::<()>::new(2);
+    group.adaptive_concurrency(AdaptiveConfig {
+        min: 1,
+        max: 64,
+        target_latency: Duration::from_millis(30),
+    });
+    for _ in 0..300 {
+        let service = service.clone();
+        group.spawn_task(Priority::default(), async move { service.call().await });
+    }
+    block_on(group.wait_for_all());
+    // past the capacity, a slow request halves the window before it grows far
+    assert!(group.metrics().concurrency_window() <= Some(8));
+    assert!(service.peak() < 16);
+}
+
+#[test]
+fn failing_child_tasks_narrow_the_window() {
+    let mut group = ErrSpawnGroup::<(), ()>::new(2);
+    group.adaptive_concurrency(AdaptiveConfig {
+        min: 2,
+        max: 8,
+        target_latency: Duration::from_millis(500),
+    });
+    for _ in 0..100 {
+        group.spawn_task(Priority::default(), async { Ok(()) });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().concurrency_window(), Some(8));
+
+    // a spike of errors halves the window once per round of child tasks, down to its minimum
+    for _ in 0..20 {
+        group.spawn_task(Priority::default(), async {
+            sleep(Duration::from_millis(1)).await;
+            Err(())
+        });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().concurrency_window(), Some(2));
+}
+
+#[test]
+fn resetting_frees_the_window_taken_by_the_cut_off_child_tasks() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.adaptive_concurrency(AdaptiveConfig {
+        min: 1,
+        max: 1,
+        target_latency: Duration::from_millis(500),
+    });
+    for value in 0..5 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_secs(3600)).await;
+            value
+        });
+    }
+    group.reset();
+    for value in 0..3 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    let mut values: Vec<u64> = block_on((&mut group).take(3).collect());
+    values.sort();
+    assert_eq!(values, [0, 1, 2]);
+}
+
+#[test]
+fn the_window_is_none_unless_the_concurrency_is_adaptive() {
+    let mut group = SpawnGroup::<()>::new(1);
+    group.spawn_task(Priority::default(), async {});
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().concurrency_window(), None);
+}
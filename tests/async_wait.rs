@@ -0,0 +1,119 @@
+//! Suspends the task awaiting ``wait_for_all`` rather than blocking its thread
+
+mod common;
+
+use common::CountingWaker;
+use futures_lite::{future, StreamExt};
+use spawn_groups::{block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn waiting_returns_pending_instead_of_blocking() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(200)).await;
+            value
+        });
+    }
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    {
+        let mut wait = pin!(group.wait_for_all());
+        let started = Instant::now();
+        assert!(wait.as_mut().poll(&mut cx).is_pending());
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        // woken by the last child task to finish, with no one polling meanwhile
+        while counter.0.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(wait.as_mut().poll(&mut cx).is_ready());
+    }
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, [0, 1, 2, 3]);
+}
+
+#[test]
+fn the_thread_awaiting_the_spawn_group_keeps_running_its_other_tasks() {
+    let mut group = ErrSpawnGroup::<u64, ()>::new(2);
+    let released = Arc::new(AtomicBool::new(false));
+    for value in 0..10 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            while !released.load(Ordering::SeqCst) {
+                sleep(Duration::from_millis(1)).await;
+            }
+            Ok(value)
+        });
+    }
+    // the child tasks only finish once the other task on the waiting thread releases them, which it couldn't if the
+    // wait blocked the thread
+    block_on(future::zip(group.wait_for_all(), async {
+        sleep(Duration::from_millis(20)).await;
+        released.store(true, Ordering::SeqCst);
+    }));
+    assert_eq!(group.len(), 10);
+}
+
+#[test]
+fn a_spawn_group_is_waited_for_from_a_child_task_of_another_one() {
+    let mut outer = SpawnGroup::<u64>::new(1);
+    outer.spawn_task(Priority::default(), async {
+        let mut inner = SpawnGroup::<u64>::new(1);
+        for value in 1..=10 {
+            inner.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(1)).await;
+                value
+            });
+        }
+        inner.wait_for_all().await;
+        let values: Vec<u64> = (&mut inner).collect().await;
+        values.into_iter().sum()
+    });
+    let sums: Vec<u64> = block_on(outer.collect());
+    assert_eq!(sums, [55]);
+}
+
+#[test]
+fn the_discarding_spawn_group_counts_every_child_task_once_waited_for() {
+    let mut group = DiscardingSpawnGroup::new(2);
+    for _ in 0..50 {
+        group.spawn_task(Priority::default(), async {
+            sleep(Duration::from_millis(1)).await;
+        });
+    }
+    let counts = block_on(group.wait_for_all());
+    assert_eq!(counts.completed, 50);
+}
+
+#[test]
+fn synchronous_code_waits_without_an_executor() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(1)).await;
+            value
+        });
+    }
+    group.wait_non_async();
+    assert_eq!(group.len(), 10);
+
+    let mut group = DiscardingSpawnGroup::new(2);
+    for _ in 0..10 {
+        group.spawn_task(Priority::default(), async {});
+    }
+    assert_eq!(group.wait_non_async().completed, 10);
+}
@@ -0,0 +1,82 @@
+//! Warns about the child tasks blocking their worker thread for longer than a threshold, or makes them panic
+
+use spawn_groups::{
+    block_on, sleep, CompletionCounts, DiscardingSpawnGroup, PanicOutput, Priority, SpawnGroup,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+static PANICS: Mutex<Vec<String>> = Mutex::new(vec![]);
+static UNBLOCKED: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn a_blocking_child_task_is_reported_while_it_blocks() {
+    let mut group = SpawnGroup::<u8>::new(2);
+    group.panic_output(PanicOutput::Handler(|report| {
+        WARNINGS.lock().unwrap().push(report.to_string());
+    }));
+    group.warn_on_blocking(Duration::from_millis(100));
+    // child tasks yielding at every await are never reported, however long they take
+    group.spawn_task(Priority::default(), async {
+        for _ in 0..10 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        0
+    });
+    group.spawn_task_named("parse", Priority::default(), async {
+        thread::sleep(Duration::from_millis(500));
+        UNBLOCKED.store(true, Ordering::Release);
+        1
+    });
+    while WARNINGS.lock().unwrap().is_empty() {
+        thread::yield_now();
+    }
+    assert!(!UNBLOCKED.load(Ordering::Acquire));
+
+    block_on(group.wait_for_all());
+    let warnings = WARNINGS.lock().unwrap();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(
+        warnings[0]
+            .starts_with("warning: child task 1 'parse' has been blocking the executor for "),
+        "{}",
+        warnings[0]
+    );
+    assert!(warnings[0].contains("without yielding, on thread '"));
+    assert_eq!(group.buffered_results(), 2);
+}
+
+#[test]
+fn panic_on_blocking_makes_the_child_task_panic() {
+    let mut group = DiscardingSpawnGroup::new(1);
+    group.panic_output(PanicOutput::Handler(|report| {
+        PANICS.lock().unwrap().push(report.to_string());
+    }));
+    group.panic_on_blocking(Duration::from_millis(100));
+    group.spawn_task_named("io", Priority::default(), async {
+        thread::sleep(Duration::from_millis(300));
+    });
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(50)).await;
+    });
+    assert_eq!(
+        block_on(group.wait_for_all()),
+        CompletionCounts {
+            completed: 1,
+            cancelled: 0,
+            panicked: 1
+        }
+    );
+    let reports = PANICS.lock().unwrap();
+    assert_eq!(reports.len(), 2, "{reports:?}");
+    assert!(reports[0].starts_with("warning: child task 0 'io' has been blocking the executor"));
+    assert!(reports[1].contains("in child task 'io'"), "{}", reports[1]);
+    assert!(reports[1].contains("blocked the executor for "));
+}
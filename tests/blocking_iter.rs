@@ -0,0 +1,80 @@
+//! Iterates over the results of a spawn group from a blocking thread, with and without prefetching
+
+use spawn_groups::{sleep, Priority, SpawnGroup};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+fn group_of(results: u64) -> SpawnGroup<u64> {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..results {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    group
+}
+
+#[test]
+fn prefetching_yields_every_result() {
+    for prefetch in [1, 4, 64] {
+        let mut group = group_of(500);
+        let mut results: Vec<u64> = group.blocking_iter_with_prefetch(prefetch).collect();
+        results.sort();
+        assert_eq!(results, (0..500).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn dropping_hands_prefetched_results_back_in_order() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    // each one goes to the front, so the buffer holds 0..100 in order
+    for value in (0..100).rev() {
+        group.requeue_result(value);
+    }
+
+    let mut results = group.blocking_iter_with_prefetch(32);
+    let consumed: Vec<u64> = results.by_ref().take(5).collect();
+    // gives the drain thread time to fill its prefetch
+    thread::sleep(Duration::from_millis(50));
+    drop(results);
+
+    let rest: Vec<u64> = group.blocking_iter().collect();
+    assert_eq!([consumed, rest].concat(), (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn dropping_stops_a_waiting_drain_thread() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_secs(3600)).await;
+            value
+        });
+    }
+    let results = group.blocking_iter_with_prefetch(8);
+    thread::sleep(Duration::from_millis(50));
+
+    let dropped_at = Instant::now();
+    drop(results);
+    assert!(dropped_at.elapsed() < Duration::from_secs(5));
+    assert_eq!(group.snapshot().buffered, 0);
+
+    group.cancel_all();
+    assert_eq!(group.blocking_iter_with_prefetch(8).count(), 0);
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn small_item_iteration() {
+    const RESULTS: u64 = 20_000;
+    for prefetch in [0, 1, 16, 256] {
+        let mut group = group_of(RESULTS);
+        let started_at = Instant::now();
+        let count = group.blocking_iter_with_prefetch(prefetch).count();
+        println!(
+            "prefetch {prefetch:>3}: {RESULTS} results in {:?}",
+            started_at.elapsed()
+        );
+        assert_eq!(count as u64, RESULTS);
+    }
+}
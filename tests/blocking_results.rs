@@ -0,0 +1,66 @@
+//! Consumes the results of a spawn group from synchronous code through the iterator owning it
+
+use spawn_groups::{sleep, BlockingResults, GroupDropPolicy, PanicOutput, Priority, SpawnGroup};
+use std::{
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::Duration,
+};
+
+#[test]
+fn dropping_the_iterator_early_follows_the_drop_policy_of_the_spawn_group() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let spawn = |policy: GroupDropPolicy| {
+        let mut group = SpawnGroup::<u64>::new_with(NonZeroUsize::new(2).unwrap(), policy);
+        for value in 0..6 {
+            let finished = finished.clone();
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(20 * value)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                value
+            });
+        }
+        group.into_blocking_iter()
+    };
+
+    let mut results = spawn(GroupDropPolicy::WaitForAll);
+    assert_eq!(results.size_hint(), (0, Some(6)));
+    assert_eq!(results.next(), Some(0));
+    drop(results);
+    assert_eq!(finished.swap(0, Ordering::SeqCst), 6);
+
+    let mut results = spawn(GroupDropPolicy::CancelAll);
+    assert_eq!(results.next(), Some(0));
+    drop(results);
+    // the child tasks left were cut off rather than waited for
+    std::thread::sleep(Duration::from_millis(150));
+    assert!(finished.load(Ordering::SeqCst) < 6);
+}
+
+#[test]
+fn iterating_on_a_worker_thread_of_the_spawn_group_panics_rather_than_deadlocks() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.panic_output(PanicOutput::Silent);
+    let spawner = group.spawner();
+    let slot: Arc<Mutex<Option<BlockingResults<u64>>>> = Arc::default();
+    *slot.lock().unwrap() = Some(group.into_blocking_iter());
+
+    let (sender, receiver) = mpsc::channel();
+    spawner.spawn_task(Priority::default(), {
+        let slot = slot.clone();
+        async move {
+            let mut results = slot.lock().unwrap().take().unwrap();
+            let waited = panic::catch_unwind(AssertUnwindSafe(|| results.next()));
+            // handed back, since dropping the spawn group on its own worker thread would wait for this child task
+            sender.send((waited.is_err(), results)).unwrap();
+            1
+        }
+    });
+    let (panicked, results) = receiver.recv().unwrap();
+    assert!(panicked);
+    assert_eq!(results.collect::<Vec<_>>(), [1]);
+}
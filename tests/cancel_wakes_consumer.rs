@@ -0,0 +1,63 @@
+//! Wakes the consumers waiting for a result once the spawn group is cancelled, ending their streams
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{sync::mpsc, thread, time::Duration};
+
+/// How long a woken consumer may take to return, far more than it needs
+const BOUND: Duration = Duration::from_secs(5);
+
+/// A child task that never completes unless cancelled
+async fn forever() {
+    sleep(Duration::from_secs(3600)).await;
+}
+
+#[test]
+fn a_consumer_waiting_in_next_returns_none_once_cancelled() {
+    let mut group = SpawnGroup::<u8>::new(1);
+    group.spawn_task(Priority::default(), async {
+        forever().await;
+        0
+    });
+    let (sender, receiver) = mpsc::channel();
+    let mut stream = group.stream();
+    let consumer = thread::spawn(move || sender.send(block_on(stream.next())).unwrap());
+
+    // the consumer is suspended in next since the only child task never completes
+    thread::sleep(Duration::from_millis(50));
+    assert!(receiver.try_recv().is_err());
+    let canceller = thread::spawn(move || {
+        group.cancel_all();
+        group
+    });
+    assert_eq!(receiver.recv_timeout(BOUND), Ok(None));
+    consumer.join().unwrap();
+    let mut group = canceller.join().unwrap();
+    assert_eq!(block_on(group.next()), None);
+}
+
+#[test]
+fn every_waiting_consumer_is_woken() {
+    let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    group.spawn_task(Priority::default(), async {
+        forever().await;
+        Ok(0)
+    });
+    let (sender, receiver) = mpsc::channel();
+    let consumers: Vec<_> = (0..3)
+        .map(|_| {
+            let mut stream = group.stream();
+            let sender = sender.clone();
+            thread::spawn(move || sender.send(block_on(stream.next())).unwrap())
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(50));
+    group.cancel_all();
+    for _ in 0..3 {
+        assert_eq!(receiver.recv_timeout(BOUND), Ok(None));
+    }
+    consumers
+        .into_iter()
+        .for_each(|consumer| consumer.join().unwrap());
+}
@@ -0,0 +1,122 @@
+//! Accounts for cancelled child tasks the same way in the metrics, the execution log and the snapshots
+
+use spawn_groups::{
+    sleep, yield_now, ExecutionOutcome, Metrics, PanicOutput, Priority, SpawnGroup, TaskState,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+fn ended(metrics: &Metrics) -> u64 {
+    metrics.completed() + metrics.panicked() + metrics.cancelled()
+}
+
+// the last polls cut off by a cancellation may still be running, and each child task counts once it's let go of
+fn wait_for_the_accounting(metrics: &Metrics, spawned: u64) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while ended(metrics) < spawned && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(5));
+    }
+    // a child task counted twice would show up by now
+    thread::sleep(Duration::from_millis(50));
+}
+
+#[test]
+fn the_metrics_add_up_to_the_spawned_child_tasks_after_a_cancel_heavy_workload() {
+    let mut group = SpawnGroup::<u64>::new(3);
+    group.panic_output(PanicOutput::Silent);
+    let metrics = group.metrics();
+    let mut spawned = 0;
+    for round in 0..12 {
+        let mut handles = Vec::new();
+        for value in 0..40u64 {
+            spawned += 1;
+            match value % 5 {
+                0 => group.spawn_task(Priority::HIGH, async move {
+                    yield_now().await;
+                    panic!("child task {value} failed");
+                }),
+                1 => group.spawn_task(Priority::LOW, async move { value }),
+                2 => group.spawn_task(Priority::default(), async move {
+                    sleep(Duration::from_secs(60)).await;
+                    value
+                }),
+                3 => handles.push(
+                    group.spawn_task_with_handle(Priority::default(), async move {
+                        sleep(Duration::from_millis(value)).await;
+                        value
+                    }),
+                ),
+                _ => group.spawn_task(Priority::default(), async move {
+                    for _ in 0..value {
+                        yield_now().await;
+                    }
+                    value
+                }),
+            }
+        }
+        // cancels a different mix of queued, running and finished child tasks each round
+        thread::sleep(Duration::from_millis(round));
+        handles.iter().step_by(2).for_each(|handle| handle.abort());
+        match round % 3 {
+            0 => group.cancel_all(),
+            1 => group.blocking_iter().take(5).for_each(drop),
+            _ => {}
+        }
+        group.reset();
+    }
+    wait_for_the_accounting(&metrics, spawned);
+    assert_eq!(metrics.spawned(), spawned);
+    assert_eq!(ended(&metrics), spawned);
+    assert_eq!(
+        metrics.cancelled(),
+        metrics.cancelled_queued() + metrics.cancelled_running()
+    );
+    assert!(metrics.cancelled_queued() > 0);
+    assert!(metrics.cancelled_running() > 0);
+    assert!(metrics.results_produced() <= metrics.completed());
+}
+
+#[test]
+fn cancelled_child_tasks_stay_listed_in_their_terminal_state() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.record_executions(true);
+    // the first child task holds the only slot, so the second one never starts
+    group.ack_mode(1);
+    let metrics = group.metrics();
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(60)).await;
+        0
+    });
+    group.spawn_task(Priority::default(), async { 1 });
+    while group.snapshot().tasks[0].state != TaskState::Running {
+        thread::sleep(Duration::from_millis(1));
+    }
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(group.snapshot().tasks[1].state, TaskState::Queued);
+
+    group.cancel_all();
+    let snapshot = group.snapshot();
+    assert_eq!(snapshot.pending, 0);
+    assert_eq!(
+        snapshot
+            .tasks
+            .iter()
+            .map(|task| (task.id, task.state))
+            .collect::<Vec<_>>(),
+        [(0, TaskState::Cancelled), (1, TaskState::Cancelled)]
+    );
+    wait_for_the_accounting(&metrics, 2);
+    assert_eq!(metrics.spawned(), 2);
+    assert_eq!(metrics.cancelled_running(), 1);
+    assert_eq!(metrics.cancelled_queued(), 1);
+    // only the child task that ran was executed
+    let executions = group.recent_executions();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].task, 0);
+    assert_eq!(executions[0].outcome, ExecutionOutcome::Cancelled);
+
+    group.reset();
+    assert!(group.snapshot().tasks.is_empty());
+}
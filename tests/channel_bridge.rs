@@ -0,0 +1,114 @@
+//! Turns the messages of a blocking channel into child tasks through a bridge thread
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn the_bridge_stops_once_every_sender_is_dropped() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let (sender, receiver) = mpsc::channel::<u64>();
+    let bridge = group.spawn_from_channel(receiver, Priority::default(), |value| async move {
+        sleep(Duration::from_millis(1)).await;
+        value
+    });
+    let producers: Vec<_> = (0..3)
+        .map(|producer| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for value in 0..5 {
+                    thread::sleep(Duration::from_millis(2));
+                    sender.send(producer * 10 + value).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(
+        results,
+        [0, 1, 2, 3, 4, 10, 11, 12, 13, 14, 20, 21, 22, 23, 24]
+    );
+    producers
+        .into_iter()
+        .for_each(|producer| producer.join().unwrap());
+    assert_eq!(bridge.join().unwrap(), 15);
+    assert_eq!(group.len(), 0);
+}
+
+#[test]
+fn a_cancellation_stops_the_bridge_waiting_for_a_message() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let (sender, receiver) = mpsc::channel::<u64>();
+    let bridge =
+        group.spawn_from_channel(receiver, Priority::default(), |value| async move { value });
+    sender.send(1).unwrap();
+    sender.send(2).unwrap();
+    assert_eq!(
+        block_on(group.next()).unwrap() + block_on(group.next()).unwrap(),
+        3
+    );
+
+    // the bridge is waiting for the next message when the spawn group is cancelled
+    let cancelled_at = Instant::now();
+    group.cancel_all();
+    assert_eq!(bridge.join().unwrap(), 2);
+    assert!(cancelled_at.elapsed() < Duration::from_secs(1));
+    // the receiver went away along with the bridge thread
+    assert!(sender.send(3).is_err());
+    assert_eq!(block_on(group.next()), None);
+}
+
+#[test]
+fn a_full_queue_pauses_the_bridge_until_a_child_task_finishes() {
+    const CAPACITY: usize = 2;
+    let mut group = SpawnGroup::<u64>::new(4);
+    group.queue_capacity(CAPACITY);
+    let running = Arc::new(AtomicUsize::new(0));
+    let most_running = Arc::new(AtomicUsize::new(0));
+    // a bounded channel, so the producer feels the pauses of the bridge
+    let (sender, receiver) = mpsc::sync_channel::<u64>(1);
+    let bridge = group.spawn_from_channel(receiver, Priority::default(), {
+        let (running, most_running) = (running.clone(), most_running.clone());
+        move |value| {
+            let (running, most_running) = (running.clone(), most_running.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most_running.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                value
+            }
+        }
+    });
+    // a fast producer
+    let started = Instant::now();
+    let producer = thread::spawn(move || {
+        for value in 0..40 {
+            sender.send(value).unwrap();
+        }
+        started.elapsed()
+    });
+
+    // a slow consumer, which doesn't hold back the child tasks but only takes their results
+    let mut results = Vec::new();
+    while let Some(value) = block_on(group.next()) {
+        thread::sleep(Duration::from_millis(1));
+        results.push(value);
+    }
+    results.sort();
+    assert_eq!(results, (0..40).collect::<Vec<_>>());
+    assert_eq!(bridge.join().unwrap(), 40);
+    assert!(most_running.load(Ordering::SeqCst) <= CAPACITY);
+    // at most CAPACITY child tasks of 5 milliseconds at a time, while the channel holds a single message
+    assert!(producer.join().unwrap() >= Duration::from_millis(5 * 38 / CAPACITY as u64));
+}
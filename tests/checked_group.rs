@@ -0,0 +1,46 @@
+//! Scopes a spawn group whose body only compiles once it finishes the spawn group explicitly
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, with_checked_spawn_group, Priority};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn finishing_returns_every_result_left() {
+    let (first, mut rest) = block_on(with_checked_spawn_group(|mut group| async move {
+        group.spawn_task(Priority::default(), async { 0 });
+        let first = group.next().await;
+        for value in 1..10 {
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(5)).await;
+                value
+            });
+        }
+        group.finish().await.map(|rest| (first, rest))
+    }));
+    rest.sort();
+    assert_eq!(first, Some(0));
+    assert_eq!(rest, (1..10).collect::<Vec<u64>>());
+}
+
+#[test]
+fn finishing_while_discarding_the_results_waits_for_every_child_task() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let counted = finished.clone();
+    block_on(with_checked_spawn_group(|mut group| async move {
+        for _ in 0..10 {
+            let counted = counted.clone();
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(5)).await;
+                counted.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        group.finish_discarding().await
+    }));
+    assert_eq!(finished.load(Ordering::SeqCst), 10);
+}
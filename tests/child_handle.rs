@@ -0,0 +1,129 @@
+//! Awaits the result of specific child tasks of a spawn group through their handles
+
+mod common;
+
+use common::{spawn_never_finishing, wait_until, PROMPTLY};
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn awaited_results_never_reach_the_stream() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let handles: Vec<_> = (0..20)
+        .map(|value| group.spawn_task_with_handle(Priority::default(), async move { value }))
+        .collect();
+    for value in 20..30 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+
+    let awaited: Vec<u64> = handles
+        .into_iter()
+        .map(|handle| block_on(handle).unwrap())
+        .collect();
+    assert_eq!(awaited, (0..20).collect::<Vec<_>>());
+    let mut streamed: Vec<u64> = block_on((&mut group).collect());
+    streamed.sort();
+    assert_eq!(streamed, (20..30).collect::<Vec<_>>());
+}
+
+#[test]
+fn dropped_handles_hand_the_results_to_the_stream() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    // dropped before the child task finished
+    drop(group.spawn_task_with_handle(Priority::default(), async {
+        sleep(Duration::from_millis(20)).await;
+        1
+    }));
+    // dropped once the child task finished
+    let finished = group.spawn_task_with_handle(Priority::default(), async { 2 });
+    let started_at = Instant::now();
+    while !finished.is_finished() {
+        assert!(started_at.elapsed() < PROMPTLY);
+        thread::sleep(Duration::from_millis(1));
+    }
+    drop(finished);
+
+    let mut streamed: Vec<u64> = block_on((&mut group).collect());
+    streamed.sort();
+    assert_eq!(streamed, [1, 2]);
+}
+
+#[test]
+fn aborting_stops_the_child_task_right_away() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let handle = spawn_never_finishing(&mut group);
+    group.spawn_task(Priority::default(), async { 1 });
+    thread::sleep(Duration::from_millis(20));
+    assert!(!handle.is_finished());
+
+    handle.abort();
+    assert!(handle.is_finished());
+    assert_eq!(block_on(handle), None);
+    // the aborted child task no longer keeps the stream going
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let streamed: Vec<u64> = block_on((&mut group).collect());
+        _ = sender.send(streamed);
+    });
+    assert_eq!(receiver.recv_timeout(PROMPTLY).unwrap(), [1]);
+}
+
+#[test]
+fn cancelled_and_refused_child_tasks_have_no_result() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let handle = spawn_never_finishing(&mut group);
+    thread::sleep(Duration::from_millis(20));
+    group.cancel_all();
+    assert_eq!(block_on(handle), None);
+
+    group.close();
+    let refused = group.spawn_task_with_handle(Priority::default(), async { 1 });
+    assert!(refused.is_finished());
+    assert_eq!(block_on(refused), None);
+}
+
+#[test]
+fn the_result_goes_to_the_handle_whether_it_is_awaited_before_or_after_it_finished() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    // awaited while the child task is still running
+    let pending = group.spawn_task_with_handle(Priority::default(), async {
+        sleep(Duration::from_millis(20)).await;
+        1
+    });
+    assert!(!pending.is_finished());
+    assert_eq!(block_on(pending), Some(1));
+    // awaited once the child task finished
+    let finished = group.spawn_task_with_handle(Priority::default(), async { 2 });
+    wait_until(|| finished.is_finished());
+    assert_eq!(block_on(finished), Some(2));
+
+    let streamed: Vec<u64> = block_on((&mut group).collect());
+    assert!(streamed.is_empty());
+}
+
+#[test]
+fn a_result_handed_back_by_a_dropped_handle_keeps_its_place_in_the_stream() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let first = group.spawn_task_with_handle(Priority::default(), async { 1 });
+    wait_until(|| first.is_finished());
+    for value in 2..=3 {
+        let handle = group.spawn_task_with_handle(Priority::default(), async move { value });
+        wait_until(|| handle.is_finished());
+        // handed back right away, yet behind the result still waiting in the first handle
+        drop(handle);
+    }
+    drop(group.spawn_task_with_handle(Priority::default(), async {
+        sleep(Duration::from_millis(20)).await;
+        4
+    }));
+    block_on(group.wait_for_all());
+    drop(first);
+
+    let streamed: Vec<u64> = block_on((&mut group).collect());
+    assert_eq!(streamed, [1, 2, 3, 4]);
+}
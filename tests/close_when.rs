@@ -0,0 +1,120 @@
+//! Closes a spawn group once the future of its producer completes, whether the producer finished or failed early
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, yield_now, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Reads the entries of a manifest slowly, failing on the given one if any
+async fn read_entry(entry: u64, failing: Option<u64>) -> Result<u64, String> {
+    sleep(Duration::from_millis(10)).await;
+    match failing {
+        Some(failing) if failing == entry => Err(format!("entry {entry} is corrupted")),
+        _ => Ok(entry),
+    }
+}
+
+#[test]
+fn the_results_stream_out_until_the_producer_finishes() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let spawner = group.spawner();
+    let read = Arc::new(AtomicBool::new(false));
+    let producer = group.close_when({
+        let read = read.clone();
+        async move {
+            for entry in 0..8 {
+                let entry = read_entry(entry, None).await?;
+                spawner.spawn_task(Priority::default(), async move { entry });
+            }
+            read.store(true, Ordering::SeqCst);
+            Ok::<u64, String>(8)
+        }
+    });
+
+    // the first result comes while the manifest is still being read
+    let mut results = vec![block_on(group.next()).unwrap()];
+    assert!(!read.load(Ordering::SeqCst));
+    assert!(!group.is_closed());
+    results.extend(block_on((&mut group).collect::<Vec<_>>()));
+    results.sort();
+    assert_eq!(results, (0..8).collect::<Vec<_>>());
+    assert!(read.load(Ordering::SeqCst));
+    assert_eq!(block_on(producer), Some(Ok(8)));
+    assert!(group.is_closed());
+}
+
+#[test]
+fn a_producer_failing_early_still_ends_the_stream() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let spawner = group.spawner();
+    let producer = group.close_when(async move {
+        for entry in 0..8 {
+            let entry = read_entry(entry, Some(3)).await?;
+            spawner.spawn_task(Priority::default(), async move { entry });
+        }
+        Ok(8)
+    });
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results, [0, 1, 2]);
+    assert_eq!(
+        block_on(producer),
+        Some(Err("entry 3 is corrupted".to_string()))
+    );
+    assert!(group.is_closed());
+    // a closed spawn group refuses any child task spawned afterwards
+    group.spawn_task(Priority::default(), async { 100 });
+    assert_eq!(block_on(group.next()), None);
+}
+
+#[test]
+fn a_fallible_spawn_group_stays_open_until_its_signal() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    let done = Arc::new(AtomicBool::new(false));
+    let signal = group.close_when({
+        let done = done.clone();
+        async move {
+            while !done.load(Ordering::SeqCst) {
+                yield_now().await;
+            }
+        }
+    });
+    group.spawn_task(Priority::default(), async { Ok(1) });
+    group.spawn_task(Priority::default(), async { Err("failed".to_string()) });
+    let mut results = block_on(group.next_chunk(2));
+    results.sort();
+    assert_eq!(results, [Ok(1), Err("failed".to_string())]);
+
+    // every child task finished, yet the stream waits for the signal
+    thread::sleep(Duration::from_millis(20));
+    assert!(group.try_next().is_none());
+    assert!(!group.is_closed());
+    group.spawn_task(Priority::default(), async { Ok(2) });
+    done.store(true, Ordering::SeqCst);
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [Ok(2)]);
+    assert_eq!(block_on(signal), Some(()));
+    assert!(group.is_closed());
+}
+
+#[test]
+fn cancelling_the_spawn_group_closes_it_without_waiting_for_the_producer() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let _producer = group.close_when(async {
+        sleep(Duration::from_secs(3600)).await;
+    });
+    group.cancel_all();
+    assert_eq!(block_on(group.next()), None);
+    assert!(group.is_closed());
+
+    // a reset reopens it, and the producer cut off before doesn't close it again
+    group.reset();
+    assert!(!group.is_closed());
+    group.spawn_task(Priority::default(), async { 1 });
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [1]);
+}
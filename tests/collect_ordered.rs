@@ -0,0 +1,93 @@
+//! Returns the results of a spawn group in the order their child tasks were spawned, whatever order they finish in
+
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long the child task spawned with the given index takes, so they finish wildly out of spawn order
+fn scrambled_delay(index: u64) -> Duration {
+    Duration::from_millis((index * 37) % 11 * 3)
+}
+
+#[test]
+fn results_come_back_in_spawn_order() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    for index in 0..100 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(scrambled_delay(index)).await;
+            index
+        });
+    }
+    assert_eq!(
+        block_on(group.collect_ordered()),
+        (0..100).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn results_handed_over_by_dropped_handles_keep_their_place() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    let mut handles = vec![];
+    for index in 0..40 {
+        let task = async move {
+            sleep(scrambled_delay(index)).await;
+            index
+        };
+        if index % 3 == 0 {
+            handles.push(group.spawn_task_with_handle(Priority::default(), task));
+        } else {
+            group.spawn_task(Priority::default(), task);
+        }
+    }
+    drop(handles);
+    assert_eq!(
+        block_on(group.collect_ordered()),
+        (0..40).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn cancellation_keeps_the_completed_results_in_order() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    for index in 0..20 {
+        group.spawn_task(Priority::default(), async move {
+            if index % 4 == 1 {
+                sleep(Duration::from_secs(3600)).await;
+            }
+            index
+        });
+    }
+    let started_at = Instant::now();
+    while group.buffered_results() < 15 {
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(1));
+    }
+    group.cancel_all();
+
+    let completed: Vec<u64> = (0..20).filter(|index| index % 4 != 1).collect();
+    assert_eq!(block_on(group.collect_ordered()), completed);
+}
+
+#[test]
+fn errors_keep_their_place_among_the_values() {
+    let mut group = ErrSpawnGroup::<u64, u64>::new(4);
+    for index in 0..30 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(scrambled_delay(index)).await;
+            if index % 5 == 0 {
+                return Err(index);
+            }
+            Ok(index)
+        });
+    }
+    let results = block_on(group.collect_ordered());
+    assert_eq!(results.len(), 30);
+    for (index, result) in (0..30).zip(results) {
+        match result {
+            Ok(value) => assert_eq!(value, index),
+            Err(error) => assert_eq!((error, index % 5), (index, 0)),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Helpers shared by the integration tests, each of which only uses some of them
+#![allow(dead_code)]
+
+use spawn_groups::{sleep, ChildHandle, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Wake,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long something expected to happen right away may take before the test fails
+pub const PROMPTLY: Duration = Duration::from_secs(5);
+
+/// Waits for the condition to hold, failing the test unless it does within ``PROMPTLY``
+pub fn wait_until(mut condition: impl FnMut() -> bool) {
+    let started_at = Instant::now();
+    while !condition() {
+        assert!(started_at.elapsed() < PROMPTLY);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Waits up to ten seconds for the condition to hold
+pub fn eventually(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        if Instant::now() > deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    true
+}
+
+/// A waker counting how many times it was woken
+#[derive(Default)]
+pub struct CountingWaker(pub AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Spawns a child task that doesn't finish within any test
+pub fn spawn_never_finishing(group: &mut SpawnGroup<u64>) -> ChildHandle<u64> {
+    group.spawn_task_with_handle(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    })
+}
@@ -0,0 +1,12 @@
+use spawn_groups::{block_on, with_checked_spawn_group, Priority};
+
+fn main() {
+    let sum: u64 = block_on(with_checked_spawn_group(|mut group| async move {
+        for i in 0..=10 {
+            group.spawn_task(Priority::default(), async move { i });
+        }
+        group.wait_for_all().await;
+        55
+    }));
+    assert_eq!(sum, 55);
+}
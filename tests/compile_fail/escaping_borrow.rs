@@ -0,0 +1,9 @@
+use spawn_groups::{block_on, with_spawn_group, Priority};
+
+fn main() {
+    let names = vec![String::from("spawn"), String::from("groups")];
+    let names = &names;
+    block_on(with_spawn_group(|mut group| async move {
+        group.spawn_task(Priority::default(), async move { names.len() });
+    }));
+}
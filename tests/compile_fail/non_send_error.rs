@@ -0,0 +1,6 @@
+use spawn_groups::ErrSpawnGroup;
+use std::rc::Rc;
+
+fn main() {
+    let _group = ErrSpawnGroup::<usize, Rc<String>>::new(1);
+}
@@ -0,0 +1,10 @@
+use spawn_groups::{block_on, with_discarding_spawn_group};
+use std::rc::Rc;
+
+fn main() {
+    block_on(with_discarding_spawn_group(|group| async move {
+        let shared = Rc::new(5);
+        group.wait_for_all().await;
+        drop(shared);
+    }));
+}
@@ -0,0 +1,6 @@
+use spawn_groups::SpawnGroup;
+use std::rc::Rc;
+
+fn main() {
+    let _group = SpawnGroup::<Rc<usize>>::new(1);
+}
@@ -0,0 +1,8 @@
+use spawn_groups::{block_on, ErrSpawnGroup, Priority};
+
+fn main() {
+    let mut group = ErrSpawnGroup::<usize, String>::new(1);
+    let parts = group.into_parts();
+    group.spawn_task(Priority::default(), async { Ok(1) });
+    block_on(parts);
+}
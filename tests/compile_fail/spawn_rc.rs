@@ -0,0 +1,8 @@
+use spawn_groups::{Priority, SpawnGroup};
+use std::rc::Rc;
+
+fn main() {
+    let mut group = SpawnGroup::<usize>::new(1);
+    let shared = Rc::new(5);
+    group.spawn_task(Priority::default(), async move { *shared });
+}
@@ -0,0 +1,11 @@
+use spawn_groups::{worker_state, yield_now, DiscardingSpawnGroup, Priority};
+
+fn main() {
+    let mut group = DiscardingSpawnGroup::new(1);
+    group.worker_state(|| 0usize);
+    group.spawn_task(Priority::default(), async {
+        let state = worker_state::<usize>();
+        yield_now().await;
+        drop(state);
+    });
+}
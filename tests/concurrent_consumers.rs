@@ -0,0 +1,118 @@
+//! Several consumers draining the same spawn group at once, some awaiting ``first()`` and some
+//! polling clones of its stream, must share its results without losing or duplicating any
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const RESULTS: u64 = 2_000;
+
+#[test]
+fn every_result_is_delivered_to_exactly_one_consumer() {
+    for _ in 0..10 {
+        let mut group = SpawnGroup::<u64>::new(4);
+        for i in 0..RESULTS {
+            group.spawn_task(Priority::default(), async move { i });
+        }
+
+        let mut delivered: Vec<u64> = thread::scope(|scope| {
+            let first_consumers: Vec<_> = (0..3)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut seen: Vec<u64> = vec![];
+                        while let Some(value) = block_on(group.first()) {
+                            seen.push(value);
+                        }
+                        seen
+                    })
+                })
+                .collect();
+            let stream_consumers: Vec<_> = (0..3)
+                .map(|_| {
+                    let stream = group.stream();
+                    scope.spawn(move || block_on(async { stream.collect::<Vec<u64>>().await }))
+                })
+                .collect();
+            first_consumers
+                .into_iter()
+                .chain(stream_consumers)
+                .flat_map(|consumer| consumer.join().unwrap())
+                .collect()
+        });
+
+        delivered.sort();
+        assert_eq!(delivered, (0..RESULTS).collect::<Vec<_>>());
+        assert_eq!(group.snapshot().buffered, 0);
+        // the last child tasks may still be winding down after delivering their results
+        block_on(group.wait_for_all());
+        assert_eq!(group.snapshot().pending, 0);
+    }
+}
+
+#[test]
+fn consumers_waiting_before_the_producers_are_all_woken() {
+    const PRODUCERS: u64 = 1_000;
+    let mut group = SpawnGroup::<u64>::new(4);
+    // keeps the stream from ending until every producer was spawned
+    let release = Arc::new(AtomicBool::new(false));
+    let gate = release.clone();
+    group.spawn_task(Priority::default(), async move {
+        while !gate.load(Ordering::Acquire) {
+            sleep(Duration::from_millis(1)).await;
+        }
+        PRODUCERS
+    });
+
+    // the consumers are all suspended in next, with no result buffered yet
+    let (sender, receiver) = mpsc::channel();
+    let consumers: Vec<_> = (0..4)
+        .map(|_| {
+            let mut stream = group.stream();
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let mut seen: Vec<u64> = vec![];
+                while let Some(value) = block_on(stream.next()) {
+                    seen.push(value);
+                }
+                sender.send(seen).unwrap();
+            })
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(20));
+
+    let producers: Vec<_> = (0..4)
+        .map(|thread_index| {
+            let spawner = group.spawner();
+            thread::spawn(move || {
+                for value in (thread_index..PRODUCERS).step_by(4) {
+                    spawner.spawn_task(Priority::default(), async move {
+                        sleep(Duration::from_micros(value % 7)).await;
+                        value
+                    });
+                }
+            })
+        })
+        .collect();
+    producers
+        .into_iter()
+        .for_each(|producer| producer.join().unwrap());
+    release.store(true, Ordering::Release);
+
+    // every consumer returns, and between them each result was consumed exactly once
+    let mut delivered: Vec<u64> = (0..4)
+        .flat_map(|_| receiver.recv_timeout(Duration::from_secs(30)).unwrap())
+        .collect();
+    consumers
+        .into_iter()
+        .for_each(|consumer| consumer.join().unwrap());
+    delivered.sort();
+    assert_eq!(delivered, (0..=PRODUCERS).collect::<Vec<_>>());
+    assert_eq!(group.metrics().results_consumed(), PRODUCERS + 1);
+}
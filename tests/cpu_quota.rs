@@ -0,0 +1,50 @@
+//! Throughput of a busy-loop workload under a CPU quota, which must roughly follow the quota
+//!
+//! The quotas alternate over several windows and the best window of each is compared, so another
+//! process briefly taking the CPU, like a build finishing right before the test, can't skew the ratio.
+
+use spawn_groups::{DiscardingSpawnGroup, Metrics, Priority};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const MEASURED_FOR: Duration = Duration::from_millis(500);
+
+const ROUNDS: usize = 3;
+
+/// Number of child tasks completed during the measurement window
+fn completed_during_window(metrics: &Metrics) -> u64 {
+    let before: u64 = metrics.completed();
+    thread::sleep(MEASURED_FOR);
+    metrics.completed() - before
+}
+
+#[test]
+fn halving_the_quota_roughly_halves_throughput() {
+    let mut group = DiscardingSpawnGroup::new(2);
+    let metrics = group.metrics();
+    for _ in 0..10_000 {
+        group.spawn_task(Priority::BACKGROUND, async {
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_millis(2) {}
+        });
+    }
+
+    let (mut full, mut half): (u64, u64) = (0, 0);
+    for _ in 0..ROUNDS {
+        // both quotas stay binding even on a single core shared with the rest of the test
+        group.cpu_quota(0.3);
+        full = full.max(completed_during_window(&metrics));
+        group.cpu_quota(0.15);
+        half = half.max(completed_during_window(&metrics));
+    }
+    group.cancel_all();
+
+    assert!(full > 0);
+    let ratio: f64 = half as f64 / full as f64;
+    assert!(
+        (0.3..=0.75).contains(&ratio),
+        "{half} child tasks with the halved quota against {full} with the full quota"
+    );
+}
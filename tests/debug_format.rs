@@ -0,0 +1,70 @@
+//! Formats the state of spawn groups for debugging, in a layout stable enough to grep
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, GroupDropPolicy, Priority, SpawnGroup,
+};
+use std::{collections::HashSet, num::NonZeroUsize, time::Duration};
+
+/// A result type that doesn't implement ``Debug``, which the spawn group never formats
+struct Opaque;
+
+#[test]
+fn a_new_spawn_group_formats_every_field() {
+    assert_eq!(
+        format!("{:?}", SpawnGroup::<Opaque>::new(2)),
+        "SpawnGroup { cancelled: false, closed: false, spawned: 0, completed: 0, pending: 0, buffered: 0, \
+         threads: 2, drop_policy: CancelAll, in_flight: [] }"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            ErrSpawnGroup::<Opaque, Opaque>::new_with(
+                NonZeroUsize::new(3).unwrap(),
+                GroupDropPolicy::Detach
+            )
+        ),
+        "ErrSpawnGroup { cancelled: false, closed: false, spawned: 0, completed: 0, pending: 0, buffered: 0, \
+         threads: 3, drop_policy: Detach, in_flight: [] }"
+    );
+    assert_eq!(
+        format!("{:?}", DiscardingSpawnGroup::new(1)),
+        "DiscardingSpawnGroup { cancelled: false, closed: false, spawned: 0, completed: 0, pending: 0, \
+         buffered: 0, threads: 1, drop_policy: CancelAll, in_flight: [] }"
+    );
+}
+
+#[test]
+fn the_counts_follow_the_child_tasks() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 1 });
+    group.spawn_task_named("slow", Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        2
+    });
+    while group.buffered_results() == 0 {
+        std::thread::yield_now();
+    }
+    let debug = format!("{group:?}");
+    assert!(
+        debug.contains("spawned: 2, completed: 1, pending: 1, buffered: 1"),
+        "{debug}"
+    );
+    assert!(debug.contains("in_flight: [\"slow\"]"), "{debug}");
+
+    group.cancel_all();
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [1]);
+    let debug = format!("{group:#?}");
+    assert!(debug.contains("cancelled: true,"), "{debug}");
+    assert!(debug.contains("buffered: 0,"), "{debug}");
+}
+
+#[test]
+fn priorities_are_hashable_and_formatted_by_name() {
+    let priorities: HashSet<Priority> = [Priority::HIGH, Priority::LOW, Priority::HIGH]
+        .into_iter()
+        .collect();
+    assert_eq!(priorities.len(), 2);
+    assert!(priorities.contains(&Priority::LOW));
+    assert_eq!(format!("{:?}", Priority::default()), "MEDIUM");
+}
@@ -0,0 +1,66 @@
+//! Spawns child tasks without naming a priority, with the default priority of their spawn group
+
+use parking_lot::Mutex;
+use spawn_groups::{
+    block_on, DiscardingSpawnGroup, ErrSpawnGroup, Priority, PriorityPolicy, QueueLatency,
+    SpawnGroup,
+};
+use std::sync::Arc;
+
+/// Records the priority of every child task once a worker thread picks it up
+fn recorder() -> (
+    Arc<Mutex<Vec<Priority>>>,
+    impl Fn(QueueLatency) + Send + Sync + 'static,
+) {
+    let priorities: Arc<Mutex<Vec<Priority>>> = Arc::default();
+    let recorded = priorities.clone();
+    (priorities, move |latency: QueueLatency| {
+        recorded.lock().push(latency.priority)
+    })
+}
+
+#[test]
+fn the_default_priority_is_the_default_one_until_changed() {
+    let (priorities, hook) = recorder();
+    let mut group = SpawnGroup::<u8>::new(1);
+    group.on_task_dequeued(hook);
+    group.spawn(async { 0 });
+    block_on(group.wait_for_all());
+    group.set_default_priority(Priority::USERINITIATED);
+    group.spawn(async { 1 });
+    block_on(group.wait_for_all());
+    assert_eq!(
+        *priorities.lock(),
+        [Priority::default(), Priority::USERINITIATED]
+    );
+}
+
+#[test]
+fn every_kind_of_spawn_group_spawns_with_its_default_priority() {
+    let (priorities, hook) = recorder();
+    let mut group = ErrSpawnGroup::<u8, String>::new(1);
+    group.on_task_dequeued(hook);
+    group.set_default_priority(Priority::LOW);
+    group.spawn(async { Ok(0) });
+    block_on(group.wait_for_all());
+    assert_eq!(*priorities.lock(), [Priority::LOW]);
+
+    let (priorities, hook) = recorder();
+    let mut group = DiscardingSpawnGroup::new(1);
+    group.on_task_dequeued(hook);
+    group.set_default_priority(Priority::BACKGROUND);
+    group.spawn(async {});
+    block_on(group.wait_for_all());
+    assert_eq!(*priorities.lock(), [Priority::BACKGROUND]);
+}
+
+#[test]
+fn the_default_priority_goes_through_the_priority_policy() {
+    let mut group = SpawnGroup::<u8>::new(1);
+    group.priority_policy(PriorityPolicy::Reject(Priority::BACKGROUND..=Priority::LOW));
+    group.spawn(async { 0 });
+    assert_eq!(group.len(), 0);
+    group.set_default_priority(Priority::LOW);
+    group.spawn(async { 1 });
+    assert_eq!(group.len(), 1);
+}
@@ -0,0 +1,86 @@
+//! Accounts for the results of a spawn group that are dropped without ever being consumed
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, Priority, SpawnGroup};
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+};
+
+fn group_with_buffered(results: u64) -> SpawnGroup<u64> {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..results {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    group
+}
+
+#[test]
+fn the_hook_runs_on_the_dropping_thread() {
+    let mut group = group_with_buffered(5);
+    let threads: Arc<Mutex<Vec<ThreadId>>> = Arc::default();
+    group.on_discarded({
+        let threads = threads.clone();
+        move |_| threads.lock().unwrap().push(thread::current().id())
+    });
+
+    thread::spawn(move || drop(group)).join().unwrap();
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 5);
+    assert!(threads.iter().all(|id| *id != thread::current().id()));
+    assert!(threads.windows(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn a_panicking_hook_still_receives_every_result() {
+    let mut group = group_with_buffered(6);
+    let metrics = group.metrics();
+    let received: Arc<Mutex<Vec<u64>>> = Arc::default();
+    group.on_discarded({
+        let received = received.clone();
+        move |value| {
+            received.lock().unwrap().push(value);
+            if value % 2 == 0 {
+                panic!("failed to clean up {value}");
+            }
+        }
+    });
+
+    drop(group);
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(received, (0..6).collect::<Vec<_>>());
+    assert_eq!(metrics.results_discarded(), 6);
+}
+
+#[test]
+fn resetting_discards_the_buffered_results() {
+    let mut group = group_with_buffered(4);
+    let metrics = group.metrics();
+    let received: Arc<Mutex<Vec<u64>>> = Arc::default();
+    group.on_discarded({
+        let received = received.clone();
+        move |value| received.lock().unwrap().push(value)
+    });
+
+    group.reset();
+    assert_eq!(received.lock().unwrap().len(), 4);
+    assert_eq!(metrics.results_produced(), 4);
+    assert_eq!(metrics.results_discarded(), 4);
+}
+
+#[test]
+fn without_a_hook_the_clones_of_the_stream_keep_the_results() {
+    let group = group_with_buffered(4);
+    let metrics = group.metrics();
+    let mut stream = group.stream();
+    drop(group);
+    assert_eq!(metrics.results_discarded(), 0);
+
+    assert!(block_on(stream.next()).is_some());
+    drop(stream);
+    assert_eq!(metrics.results_produced(), 4);
+    assert_eq!(metrics.results_consumed(), 1);
+    assert_eq!(metrics.results_discarded(), 3);
+}
@@ -0,0 +1,91 @@
+//! Consumes the results of a spawn group as they arrive until it's quiescent
+
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn results_are_consumed_while_the_child_tasks_run() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    // the results trickle in over a fifth of a second
+    for value in 0..200 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(value)).await;
+            value
+        });
+    }
+
+    let (mut consumed, mut most_buffered) = (0, 0);
+    block_on(group.drain_to_completion(|group, _| {
+        consumed += 1;
+        most_buffered = most_buffered.max(group.snapshot().buffered);
+    }));
+    assert_eq!(consumed, 200);
+    assert!(
+        most_buffered < 50,
+        "up to {most_buffered} results were buffered"
+    );
+    assert!(group.is_empty());
+}
+
+#[test]
+fn child_tasks_spawned_by_the_consumer_are_drained() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 0 });
+
+    let mut received = vec![];
+    block_on(group.drain_to_completion(|group, value| {
+        received.push(value);
+        if value < 50 {
+            group.spawn_task(Priority::default(), async move { value + 1 });
+        }
+    }));
+    assert_eq!(received, (0..=50).collect::<Vec<_>>());
+}
+
+#[test]
+fn cancelling_stops_after_the_buffered_results() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+    for value in 1..=10 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    let started_at = Instant::now();
+    while group.snapshot().buffered < 10 {
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let mut received = vec![];
+    block_on(group.drain_to_completion(|group, value| {
+        group.cancel_all();
+        received.push(value);
+    }));
+    received.sort();
+    assert_eq!(received, (1..=10).collect::<Vec<_>>());
+}
+
+#[test]
+fn errors_are_drained_along_with_values() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 0..20 {
+        group.spawn_task(Priority::default(), async move {
+            if value % 4 == 0 {
+                return Err(format!("{value} failed"));
+            }
+            Ok(value)
+        });
+    }
+
+    let (mut values, mut errors) = (0, 0);
+    block_on(group.drain_to_completion(|_, result| match result {
+        Ok(_) => values += 1,
+        Err(_) => errors += 1,
+    }));
+    assert_eq!((values, errors), (15, 5));
+}
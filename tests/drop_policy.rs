@@ -0,0 +1,187 @@
+//! Honors the drop policy given at construction, whatever the number of threads
+
+mod common;
+
+use common::eventually;
+use spawn_groups::{
+    sleep, DiscardingSpawnGroup, ErrSpawnGroup, GroupDropPolicy, Priority, SpawnGroup, ThreadSpec,
+};
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const THREAD_COUNTS: [usize; 3] = [1, 4, 16];
+
+fn threads(count: usize) -> NonZeroUsize {
+    NonZeroUsize::new(count).unwrap()
+}
+
+/// A child task taking a while before it counts itself as finished
+fn slow_task(
+    finished: &Arc<AtomicUsize>,
+    delay: Duration,
+) -> impl std::future::Future<Output = ()> {
+    let finished = finished.clone();
+    async move {
+        sleep(delay).await;
+        finished.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn waiting_groups_finish_every_child_task_before_dropping() {
+    for count in THREAD_COUNTS {
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        let mut group = SpawnGroup::<()>::new_with(threads(count), GroupDropPolicy::WaitForAll);
+        let mut err_group =
+            ErrSpawnGroup::<(), String>::new_with(threads(count), GroupDropPolicy::WaitForAll);
+        let mut discarding =
+            DiscardingSpawnGroup::new_with(threads(count), GroupDropPolicy::WaitForAll);
+        for _ in 0..5 {
+            let delay = Duration::from_millis(20);
+            group.spawn_task(Priority::default(), slow_task(&finished, delay));
+            let task = slow_task(&finished, delay);
+            err_group.spawn_task(Priority::default(), async move {
+                task.await;
+                Ok(())
+            });
+            discarding.spawn_task(Priority::default(), slow_task(&finished, delay));
+        }
+        drop(group);
+        drop(err_group);
+        drop(discarding);
+        assert_eq!(finished.load(Ordering::SeqCst), 15, "with {count} threads");
+    }
+}
+
+#[test]
+fn cancelling_groups_drop_without_waiting() {
+    for count in THREAD_COUNTS {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let delay = Duration::from_secs(3600);
+
+        let mut group = SpawnGroup::<()>::new_with(threads(count), GroupDropPolicy::CancelAll);
+        let mut err_group =
+            ErrSpawnGroup::<(), String>::new_with(threads(count), GroupDropPolicy::CancelAll);
+        let mut discarding =
+            DiscardingSpawnGroup::new_with(threads(count), GroupDropPolicy::CancelAll);
+        group.spawn_task(Priority::default(), slow_task(&finished, delay));
+        let task = slow_task(&finished, delay);
+        err_group.spawn_task(Priority::default(), async move {
+            task.await;
+            Ok(())
+        });
+        discarding.spawn_task(Priority::default(), slow_task(&finished, delay));
+
+        let started_at = Instant::now();
+        drop(group);
+        drop(err_group);
+        drop(discarding);
+        assert!(
+            started_at.elapsed() < Duration::from_secs(5),
+            "with {count} threads"
+        );
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+}
+
+#[test]
+fn the_drop_policy_is_changed_after_construction() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<()>::new(2);
+    group.set_drop_policy(GroupDropPolicy::WaitForAll);
+    for _ in 0..5 {
+        group.spawn_task(
+            Priority::default(),
+            slow_task(&finished, Duration::from_millis(20)),
+        );
+    }
+    drop(group);
+    assert_eq!(finished.load(Ordering::SeqCst), 5);
+
+    let mut group = DiscardingSpawnGroup::new_with(threads(2), GroupDropPolicy::WaitForAll);
+    group.set_drop_policy(GroupDropPolicy::CancelAll);
+    group.spawn_task(
+        Priority::default(),
+        slow_task(&finished, Duration::from_millis(50)),
+    );
+    drop(group);
+    // the cancelled child task never gets to finish, even once its delay elapsed
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(finished.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn detached_groups_finish_their_child_tasks_in_the_background() {
+    for count in THREAD_COUNTS {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let delay = Duration::from_millis(200);
+
+        let mut group = SpawnGroup::<()>::new_with(threads(count), GroupDropPolicy::Detach);
+        let mut err_group = ErrSpawnGroup::<(), String>::new(count);
+        err_group.set_drop_policy(GroupDropPolicy::Detach);
+        let mut discarding =
+            DiscardingSpawnGroup::new_with(threads(count), GroupDropPolicy::Detach);
+        for _ in 0..5 {
+            group.spawn_task(Priority::default(), slow_task(&finished, delay));
+            let task = slow_task(&finished, delay);
+            err_group.spawn_task(Priority::default(), async move {
+                task.await;
+                Ok(())
+            });
+            discarding.spawn_task(Priority::default(), slow_task(&finished, delay));
+        }
+
+        let started_at = Instant::now();
+        drop(group);
+        drop(err_group);
+        drop(discarding);
+        assert!(started_at.elapsed() < delay, "with {count} threads");
+        assert!(
+            eventually(|| finished.load(Ordering::SeqCst) == 15),
+            "with {count} threads"
+        );
+    }
+}
+
+#[test]
+fn a_detached_group_lets_go_of_its_threads_once_its_child_tasks_finished() {
+    let running = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<()>::with_thread_factory(2, {
+        let running = running.clone();
+        move |spec: ThreadSpec| {
+            let running = running.clone();
+            running.fetch_add(1, Ordering::SeqCst);
+            let entry = spec.entry;
+            thread::Builder::new().name(spec.name).spawn(move || {
+                entry();
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        }
+    })
+    .unwrap();
+    group.set_drop_policy(GroupDropPolicy::Detach);
+    let finished = Arc::new(AtomicUsize::new(0));
+    group.spawn_task(
+        Priority::default(),
+        slow_task(&finished, Duration::from_millis(100)),
+    );
+    let spawner = group.spawner();
+    drop(group);
+
+    // a spawner outliving the spawn group can't keep it around
+    spawner.spawn_task(
+        Priority::default(),
+        slow_task(&finished, Duration::from_millis(1)),
+    );
+    assert!(running.load(Ordering::SeqCst) > 0);
+    assert!(eventually(|| running.load(Ordering::SeqCst) == 0));
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,95 @@
+//! Bounds how long dropping a spawn group waits for its child tasks, cancelling and reporting the ones left behind
+
+use spawn_groups::{
+    sleep, DiscardingSpawnGroup, GroupDropPolicy, PanicOutput, Priority, SpawnGroup,
+};
+use std::{
+    future,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const CAP: Duration = Duration::from_millis(100);
+// how much longer than the cap dropping may take
+const SLACK: Duration = Duration::from_secs(2);
+
+fn threads(count: usize) -> NonZeroUsize {
+    NonZeroUsize::new(count).unwrap()
+}
+
+static STUCK_REPORTS: Mutex<Vec<String>> = Mutex::new(vec![]);
+static BLOCKING_REPORTS: Mutex<Vec<String>> = Mutex::new(vec![]);
+static FINISHED_REPORTS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+#[test]
+fn dropping_gives_up_on_a_stuck_child_task_and_reports_it() {
+    let mut group = SpawnGroup::<u64>::new_with(threads(2), GroupDropPolicy::WaitForAll);
+    group.drop_wait_cap(CAP);
+    group.panic_output(PanicOutput::Handler(|report| {
+        STUCK_REPORTS.lock().unwrap().push(report.to_string())
+    }));
+    for value in 0..5 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    group.spawn_task(Priority::default(), future::pending());
+
+    let started = Instant::now();
+    drop(group);
+    let took = started.elapsed();
+    assert!(took >= CAP, "dropped after {took:?}");
+    assert!(took < CAP + SLACK, "dropped after {took:?}");
+
+    let reports = STUCK_REPORTS.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(
+        reports[0].contains("1 child tasks still pending"),
+        "{}",
+        reports[0]
+    );
+    assert!(reports[0].contains("ids [5]"), "{}", reports[0]);
+}
+
+#[test]
+fn dropping_leaves_a_child_task_blocking_its_worker_thread_behind() {
+    let mut group = DiscardingSpawnGroup::new_with(threads(1), GroupDropPolicy::WaitForAll);
+    group.drop_wait_cap(CAP);
+    group.panic_output(PanicOutput::Handler(|report| {
+        BLOCKING_REPORTS.lock().unwrap().push(report.to_string())
+    }));
+    group.spawn_task(Priority::default(), async {
+        thread::sleep(Duration::from_secs(5));
+    });
+    // queued behind the blocking child task, it never gets to run
+    group.spawn_task(Priority::default(), async {});
+    thread::sleep(Duration::from_millis(10));
+
+    let started = Instant::now();
+    drop(group);
+    assert!(started.elapsed() < CAP + SLACK);
+    assert_eq!(BLOCKING_REPORTS.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn child_tasks_finishing_within_the_cap_are_waited_for() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<()>::new_with(threads(2), GroupDropPolicy::WaitForAll);
+    group.drop_wait_cap(Duration::from_secs(10));
+    group.panic_output(PanicOutput::Handler(|report| {
+        FINISHED_REPORTS.lock().unwrap().push(report.to_string())
+    }));
+    for _ in 0..10 {
+        let finished = finished.clone();
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(20)).await;
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    drop(group);
+    assert_eq!(finished.load(Ordering::SeqCst), 10);
+    assert!(FINISHED_REPORTS.lock().unwrap().is_empty());
+}
@@ -0,0 +1,128 @@
+//! Spawns a batch of child tasks declared upfront, skipping the bookkeeping of the dynamic features
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, CompletionCounts, DiscardingSpawnGroup, Priority, SpawnError, SpawnGroup,
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn the_batch_delivers_every_result() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    group.exclusive_batch(10_000);
+    for value in 0..10_000 {
+        group.spawn_task(Priority::default(), async move { value * 2 });
+    }
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(
+        results,
+        (0..10_000).map(|value| value * 2).collect::<Vec<_>>()
+    );
+    assert_eq!(group.snapshot().spawned, 10_000);
+}
+
+#[test]
+fn spawns_beyond_the_batch_are_refused() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.exclusive_batch(3);
+    group.spawn_all(
+        Priority::default(),
+        (0..2).map(|value| async move { value }),
+    );
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { 2 }),
+        Ok(())
+    );
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { 3 }),
+        Err(SpawnError::BatchComplete(3))
+    );
+    // spawn_task silently drops it
+    group.spawn_task(Priority::default(), async { 4 });
+    assert_eq!(block_on((&mut group).count()), 3);
+}
+
+#[test]
+fn dynamic_features_are_refused() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.exclusive_batch(4);
+    assert_eq!(
+        block_on(group.spawn_task_with_handle(Priority::default(), async { 0 })),
+        None
+    );
+    assert_eq!(block_on(group.run(Priority::default(), async { 1 })), None);
+    assert!(matches!(
+        group.try_run(Priority::default(), async { 1 }),
+        Err(SpawnError::BatchUnsupported)
+    ));
+    group.spawn_task_sequenced(0, Priority::default(), async { 2 });
+    assert!(group.is_empty());
+    // a future that can't be run closes the spawn group at once
+    assert_eq!(block_on(group.close_when(async { 3 })), None);
+    assert!(group.is_closed());
+}
+
+#[test]
+fn results_come_back_in_spawn_order() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    group.exclusive_batch(100);
+    group.spawn_all(
+        Priority::default(),
+        (0..100).map(|value| async move { value }),
+    );
+    assert_eq!(
+        block_on(group.collect_ordered()),
+        (0..100).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn cancelled_child_tasks_are_counted() {
+    let mut group = DiscardingSpawnGroup::new(2);
+    group.exclusive_batch(4);
+    for _ in 0..4 {
+        group.spawn_task(Priority::default(), async {
+            spawn_groups::sleep(Duration::from_secs(3600)).await;
+        });
+    }
+    group.cancel_all();
+    assert_eq!(
+        block_on(group.wait_for_all()),
+        CompletionCounts {
+            completed: 0,
+            cancelled: 4,
+            panicked: 0,
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "exclusive batch mode must be declared before spawning any child task")]
+fn the_batch_is_declared_before_spawning() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 0 });
+    group.exclusive_batch(1);
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn a_million_trivial_child_tasks() {
+    const TASKS: u64 = 1_000_000;
+    let run = |batch: bool| {
+        let mut group = SpawnGroup::<u64>::new(4);
+        if batch {
+            group.exclusive_batch(TASKS as usize);
+        }
+        let started_at = Instant::now();
+        group.spawn_all(
+            Priority::default(),
+            (0..TASKS).map(|value| async move { value }),
+        );
+        let count = block_on((&mut group).count());
+        assert_eq!(count as u64, TASKS);
+        started_at.elapsed()
+    };
+    println!("{TASKS} child tasks: {:?}", run(false));
+    println!("{TASKS} child tasks in an exclusive batch: {:?}", run(true));
+}
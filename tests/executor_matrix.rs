@@ -0,0 +1,111 @@
+//! Drives the same workload through the stream of a spawn group from several outer executors,
+//! each with its own waker and polling behavior
+
+use futures_lite::StreamExt;
+use spawn_groups::{sleep, Priority, SpawnGroup};
+use std::{future::Future, sync::mpsc, thread, time::Duration};
+
+const DEADLINE: Duration = Duration::from_secs(60);
+
+async fn workload() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    for i in 0..100 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_micros((i % 5) * 200)).await;
+            i
+        });
+    }
+
+    let mut consumed = 0;
+    while consumed < 25 {
+        assert!(group.next().await.is_some());
+        consumed += 1;
+    }
+
+    group.cancel_all();
+    let delivered: Vec<u64> = (&mut group).collect().await;
+    assert!(delivered.len() <= 75);
+
+    for i in 100..150 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_micros((i % 3) * 100)).await;
+            i
+        });
+    }
+    group.wait_for_all().await;
+    let mut results: Vec<u64> = group.collect().await;
+    results.sort();
+    assert_eq!(results, (100..150).collect::<Vec<_>>());
+}
+
+/// Fails the test instead of hanging the whole suite when the workload never finishes
+fn within_deadline<Run>(run: Run)
+where
+    Run: FnOnce() + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        run();
+        _ = sender.send(());
+    });
+    if let Err(mpsc::RecvTimeoutError::Timeout) = receiver.recv_timeout(DEADLINE) {
+        panic!("the workload didn't finish within {:?}", DEADLINE);
+    }
+}
+
+fn run_matrix<Exec>(executor: Exec)
+where
+    Exec: Fn(std::pin::Pin<Box<dyn Future<Output = ()> + Send>>) + Send + 'static,
+{
+    within_deadline(move || {
+        for _ in 0..5 {
+            executor(Box::pin(workload()));
+        }
+    });
+}
+
+#[test]
+fn spawn_groups_block_on() {
+    run_matrix(spawn_groups::block_on);
+}
+
+#[test]
+fn tokio_current_thread() {
+    run_matrix(|future| {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    });
+}
+
+#[test]
+fn tokio_multi_thread() {
+    run_matrix(|future| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .build()
+            .unwrap()
+            .block_on(future)
+    });
+}
+
+#[test]
+fn tokio_spawned_task() {
+    run_matrix(|future| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(tokio::spawn(future)).unwrap();
+    });
+}
+
+#[test]
+fn async_std() {
+    run_matrix(async_std::task::block_on);
+}
+
+#[test]
+fn smol() {
+    run_matrix(smol::block_on);
+}
@@ -0,0 +1,100 @@
+//! Resumes the child tasks woken from threads outside of the threadpool ahead of the background work
+
+use futures_lite::StreamExt;
+use parking_lot::Mutex;
+use spawn_groups::{block_on, yield_now, Priority, SpawnGroup};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Completed from a plain thread, like a signal handler or a callback from foreign code would
+#[derive(Clone, Default)]
+struct Signal(Arc<Mutex<(Option<Instant>, Option<Waker>)>>);
+
+impl Signal {
+    fn is_awaited(&self) -> bool {
+        self.0.lock().1.is_some()
+    }
+
+    fn fire(&self) {
+        let waker: Option<Waker> = {
+            let mut state = self.0.lock();
+            state.0 = Some(Instant::now());
+            state.1.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Future for Signal {
+    type Output = Instant;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock();
+        match state.0 {
+            Some(fired_at) => Poll::Ready(fired_at),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Keeps a worker thread busy for about a millisecond between yields until told to stop
+async fn background_work(stop: Arc<AtomicBool>) -> Duration {
+    while !stop.load(Ordering::Acquire) {
+        let started_at = Instant::now();
+        while started_at.elapsed() < Duration::from_millis(1) {
+            std::hint::spin_loop();
+        }
+        yield_now().await;
+    }
+    Duration::ZERO
+}
+
+#[test]
+fn externally_woken_child_tasks_resume_before_the_background_work() {
+    let mut group = SpawnGroup::<Duration>::new(2);
+    let stop = Arc::new(AtomicBool::new(false));
+    // polling each of them once takes a worker thread about 300 milliseconds in total
+    for _ in 0..300 {
+        group.spawn_task(Priority::BACKGROUND, background_work(stop.clone()));
+    }
+    let signal = Signal::default();
+    let awaited = signal.clone();
+    group.spawn_task(Priority::HIGH, async move {
+        let fired_at: Instant = awaited.await;
+        fired_at.elapsed()
+    });
+
+    // fired once the child task is waiting for it amid the background work
+    let started_at = Instant::now();
+    while !signal.is_awaited() {
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(1));
+    }
+    thread::sleep(Duration::from_millis(50));
+    thread::spawn(move || signal.fire()).join().unwrap();
+    thread::sleep(Duration::from_millis(100));
+    stop.store(true, Ordering::Release);
+
+    let latency: Duration = block_on((&mut group).collect::<Vec<_>>())
+        .into_iter()
+        .max()
+        .unwrap();
+    assert!(
+        latency < Duration::from_millis(60),
+        "resumed {latency:?} after the wake"
+    );
+}
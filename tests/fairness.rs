@@ -0,0 +1,42 @@
+//! A body alternating between spawning child tasks and consuming their results must keep up with them
+//! even while a long-running child task keeps the wake-up watermark from being reached
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::time::{Duration, Instant};
+
+const LONG_RUNNING: Duration = Duration::from_secs(5);
+
+#[test]
+fn consumer_is_not_starved_by_the_watermark() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.wake_after_n(16);
+    // keeps the remaining child tasks above the buffered results, so only the
+    // watermark could wake the consumer without the fairness rule
+    group.spawn_task(Priority::HIGH, async {
+        sleep(LONG_RUNNING).await;
+        u64::MAX
+    });
+
+    let started = Instant::now();
+    let consumed: Vec<u64> = block_on(async {
+        let mut consumed: Vec<u64> = vec![];
+        for i in 0..50 {
+            group.spawn_task(Priority::LOW, async move {
+                sleep(Duration::from_micros(200)).await;
+                i
+            });
+            consumed.push(group.next().await.expect("child task was spawned"));
+        }
+        consumed
+    });
+    let elapsed: Duration = started.elapsed();
+    group.cancel_all();
+
+    assert_eq!(consumed.len(), 50);
+    assert!(!consumed.contains(&u64::MAX));
+    assert!(
+        elapsed < LONG_RUNNING / 2,
+        "the consumer lagged behind for {elapsed:?}"
+    );
+}
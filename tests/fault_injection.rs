@@ -0,0 +1,49 @@
+//! Drives the child tasks whose wakeups the fault injector dropped once the spawn group is waited for
+#![cfg(feature = "fault-injection")]
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, yield_now, DiscardingSpawnGroup, FaultPlan, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn waiting_drives_the_child_tasks_stalled_before_the_wait() {
+    let mut group = SpawnGroup::<usize>::new(2);
+    group.inject(FaultPlan::new().drop_wakeups(1.0));
+    for i in 0..5 {
+        group.spawn_task(Priority::default(), async move {
+            yield_now().await;
+            i
+        });
+    }
+    // every child task suspends and has its wakeup dropped well before the wait
+    thread::sleep(Duration::from_millis(50));
+    block_on(group.wait_for_all());
+    let mut results: Vec<usize> = block_on(group.collect());
+    results.sort();
+    assert_eq!(results, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn waiting_drives_the_child_tasks_through_repeated_dropped_wakeups() {
+    let mut group = DiscardingSpawnGroup::new(4);
+    group.inject(FaultPlan::new().drop_wakeups(0.5).seed(7));
+    let finished: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    for _ in 0..20 {
+        let finished: Arc<AtomicUsize> = finished.clone();
+        group.spawn_task(Priority::default(), async move {
+            for _ in 0..10 {
+                yield_now().await;
+            }
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(finished.load(Ordering::SeqCst), 20);
+}
@@ -0,0 +1,100 @@
+//! Ends a spawn group from asynchronous code by awaiting ``finish`` rather than blocking in its drop
+
+use futures_lite::future;
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, GroupDropPolicy, Priority, SpawnGroup,
+};
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+fn threads(count: usize) -> NonZeroUsize {
+    NonZeroUsize::new(count).unwrap()
+}
+
+// a child task that only finishes once released
+async fn gated(released: Arc<AtomicBool>) {
+    while !released.load(Ordering::SeqCst) {
+        sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test]
+fn finishing_keeps_the_other_tasks_of_the_thread_running() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = SpawnGroup::<u64>::new_with(threads(2), GroupDropPolicy::WaitForAll);
+    for value in 0..10 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            gated(released).await;
+            value
+        });
+    }
+    // the child tasks only finish once the other task on the finishing thread releases them, which it couldn't if
+    // the spawn group blocked the thread
+    let (mut values, ()) = block_on(future::zip(group.finish(), async {
+        sleep(Duration::from_millis(20)).await;
+        released.store(true, Ordering::SeqCst);
+    }));
+    values.sort();
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_spawn_group_is_finished_from_a_child_task_of_another_one() {
+    let mut outer = ErrSpawnGroup::<u64, String>::new(1);
+    outer.spawn_task(Priority::default(), async {
+        let mut inner =
+            ErrSpawnGroup::<u64, String>::new_with(threads(1), GroupDropPolicy::WaitForAll);
+        for value in 1..=10 {
+            inner.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(1)).await;
+                match value % 5 {
+                    0 => Err(format!("{value} failed")),
+                    _ => Ok(value),
+                }
+            });
+        }
+        let results = inner.finish().await;
+        assert_eq!(results.iter().filter(|result| result.is_err()).count(), 2);
+        Ok(results.into_iter().filter_map(Result::ok).sum())
+    });
+    let sums = block_on(outer.finish());
+    assert_eq!(sums, [Ok(40)]);
+}
+
+#[test]
+fn finishing_while_discarding_the_results_waits_for_every_child_task() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<Vec<u8>>::new(2);
+    let mut err_group = ErrSpawnGroup::<(), ()>::new(2);
+    let mut discarding = DiscardingSpawnGroup::new(2);
+    for _ in 0..10 {
+        let counted = finished.clone();
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(5)).await;
+            counted.fetch_add(1, Ordering::SeqCst);
+            vec![0; 64]
+        });
+        let counted = finished.clone();
+        err_group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(5)).await;
+            counted.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        let counted = finished.clone();
+        discarding.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(5)).await;
+            counted.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    block_on(group.finish_discarding());
+    block_on(err_group.finish_discarding());
+    assert_eq!(block_on(discarding.finish()).completed, 10);
+    assert_eq!(finished.load(Ordering::SeqCst), 30);
+}
@@ -0,0 +1,103 @@
+//! Reports the end of the stream of a spawn group and how many results it may still yield
+
+use futures::{stream::FusedStream, Stream, StreamExt};
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// a child task that only finishes once released
+async fn gated(released: Arc<AtomicBool>) {
+    while !released.load(Ordering::SeqCst) {
+        sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test]
+fn the_size_hint_counts_the_buffered_results_and_the_pending_child_tasks() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..4 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            gated(released).await;
+            value
+        });
+    }
+    assert_eq!(group.size_hint(), (0, Some(4)));
+
+    released.store(true, Ordering::SeqCst);
+    block_on(group.wait_for_all());
+    assert_eq!(group.size_hint(), (4, Some(4)));
+    assert!(block_on(group.next()).is_some());
+    assert_eq!(group.size_hint(), (3, Some(3)));
+    assert_eq!(group.stream().size_hint(), (3, Some(3)));
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()).len(), 3);
+    assert_eq!(group.size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn a_terminated_stream_keeps_ending() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 0..3 {
+        group.spawn_task(Priority::default(), async move { Ok(value) });
+    }
+    assert!(!group.is_terminated());
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()).len(), 3);
+    assert!(group.is_terminated());
+    let mut stream = group.stream();
+    assert!(stream.is_terminated());
+    for _ in 0..3 {
+        assert_eq!(block_on(group.next()), None);
+        assert_eq!(block_on(stream.next()), None);
+    }
+
+    // another child task revives the stream
+    group.spawn_task(Priority::default(), async { Ok(3) });
+    assert!(!group.is_terminated());
+    assert_eq!(block_on(group.next()), Some(Ok(3)));
+}
+
+#[test]
+fn cancelling_terminates_the_stream() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..4 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            gated(released).await;
+            value
+        });
+    }
+    assert!(!group.is_terminated());
+    group.cancel_all();
+    assert!(group.is_terminated());
+    assert_eq!(group.size_hint(), (0, Some(0)));
+    assert_eq!(block_on(group.next()), None);
+    assert_eq!(block_on(group.next()), None);
+}
+
+#[test]
+fn spawn_groups_are_selected_over_until_both_are_done() {
+    let mut numbers = SpawnGroup::<u64>::new(2);
+    let mut fallible = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 1..=5 {
+        numbers.spawn_task(Priority::default(), async move { value });
+        fallible.spawn_task(Priority::default(), async move { Ok(value * 10) });
+    }
+    let mut total = 0;
+    block_on(async {
+        loop {
+            futures::select! {
+                value = numbers.select_next_some() => total += value,
+                result = fallible.select_next_some() => total += result.unwrap(),
+                complete => break,
+            }
+        }
+    });
+    assert_eq!(total, 165);
+}
@@ -0,0 +1,134 @@
+//! Spawns child tasks into their own spawn group through a cloneable spawner
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, GroupSpawner, Priority, SpawnGroup};
+use std::{thread, time::Duration};
+
+// every page links to three pages one level deeper, down to the third level
+fn crawl(spawner: GroupSpawner<String>, url: String, depth: usize) {
+    let links = spawner.clone();
+    spawner.spawn_task(Priority::default(), async move {
+        sleep(Duration::from_millis(2)).await;
+        if depth < 3 {
+            for link in 0..3 {
+                crawl(links.clone(), format!("{url}/{link}"), depth + 1);
+            }
+        }
+        url
+    });
+}
+
+fn expected_pages() -> Vec<String> {
+    let mut pages = vec!["root".to_string()];
+    let mut level = pages.clone();
+    for _ in 0..3 {
+        level = level
+            .iter()
+            .flat_map(|url| (0..3).map(move |link| format!("{url}/{link}")))
+            .collect();
+        pages.extend(level.iter().cloned());
+    }
+    pages.sort();
+    pages
+}
+
+#[test]
+fn child_tasks_spawned_recursively_three_levels_deep_all_deliver() {
+    let mut group = SpawnGroup::<String>::new(4);
+    crawl(group.spawner(), "root".to_string(), 0);
+    block_on(group.wait_for_all());
+    let mut pages: Vec<String> = block_on((&mut group).collect());
+    pages.sort();
+    assert_eq!(pages.len(), 40);
+    assert_eq!(pages, expected_pages());
+}
+
+#[test]
+fn the_stream_only_ends_once_the_recursion_is_done() {
+    let mut group = SpawnGroup::<String>::new(4);
+    crawl(group.spawner(), "root".to_string(), 0);
+    let mut pages: Vec<String> = block_on((&mut group).collect());
+    pages.sort();
+    assert_eq!(pages, expected_pages());
+}
+
+#[test]
+fn child_tasks_spawned_from_another_thread_while_waiting_are_awaited() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(50)).await;
+        0
+    });
+    let spawner = group.spawner();
+    let handle = thread::spawn(move || {
+        for value in 1..=10 {
+            spawner.spawn_task(Priority::default(), async move { value });
+        }
+    });
+    block_on(group.wait_for_all());
+    handle.join().unwrap();
+    block_on(group.wait_for_all());
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, (0..=10).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_spawner_does_nothing_once_the_spawn_group_is_cancelled() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let spawner = group.spawner();
+    assert!(!spawner.is_cancelled());
+    group.cancel_all();
+    assert!(spawner.is_cancelled());
+    spawner.spawn_task(Priority::default(), async { 1 });
+    assert!(group.is_empty());
+
+    // a spawner taken once the spawn group is reset spawns as usual, unlike the one taken before
+    group.reset();
+    spawner.spawn_task(Priority::default(), async { 1 });
+    group.spawner().spawn_task(Priority::default(), async { 2 });
+    let values: Vec<u64> = block_on((&mut group).collect());
+    assert_eq!(values, [2]);
+}
+
+#[test]
+fn spawners_racing_a_cancellation_never_spawn_past_it() {
+    for _ in 0..20 {
+        let mut group = SpawnGroup::<u64>::new(2);
+        let spawners: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let spawner = group.spawner();
+                thread::spawn(move || {
+                    while !spawner.is_cancelled() {
+                        spawner.spawn_task(Priority::default(), async { 1 });
+                    }
+                })
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(2));
+        group.cancel_all();
+        spawners
+            .into_iter()
+            .for_each(|spawner| spawner.join().unwrap());
+        // none of the child tasks spawned while cancelling is counted or delivered once the spawn group is reset
+        assert!(group.is_empty());
+        group.reset();
+        group.spawn_task(Priority::default(), async { 2 });
+        let values: Vec<u64> = block_on((&mut group).collect());
+        assert_eq!(values, [2]);
+    }
+}
+
+#[test]
+fn a_spawner_outliving_its_spawn_group_is_refused() {
+    let group = SpawnGroup::<u64>::new(2);
+    let spawner = group.spawner();
+    drop(group);
+    spawner.spawn_task(Priority::default(), async { 1 });
+}
+
+#[test]
+fn a_spawner_is_cloneable_and_sendable() {
+    fn assert_spawner<T: Clone + Send + 'static>() {}
+    assert_spawner::<GroupSpawner<String>>();
+}
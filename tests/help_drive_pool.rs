@@ -0,0 +1,58 @@
+//! Lets the consumer of a spawn group run its queued jobs, waking it once a job is queued rather than spinning
+
+mod common;
+
+use common::CountingWaker;
+use futures_lite::{Stream, StreamExt};
+use spawn_groups::{block_on, yield_now, Priority, SpawnGroup};
+use std::{
+    pin::Pin,
+    sync::{atomic::Ordering, Arc},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn a_consumer_finding_no_job_to_run_does_not_wake_itself() {
+    let mut group = SpawnGroup::<usize>::new(1);
+    group.help_drive_pool(true);
+    // holds the only worker thread, leaving no other job queued
+    group.spawn_task(Priority::default(), async {
+        thread::sleep(Duration::from_millis(300));
+        1
+    });
+    thread::sleep(Duration::from_millis(50));
+    let counter: Arc<CountingWaker> = Arc::new(CountingWaker::default());
+    let waker: Waker = Waker::from(counter.clone());
+    let mut cx: Context<'_> = Context::from_waker(&waker);
+    for _ in 0..3 {
+        assert_eq!(Pin::new(&mut group).poll_next(&mut cx), Poll::Pending);
+    }
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    let results: Vec<usize> = block_on(group.collect());
+    assert_eq!(results, vec![1]);
+}
+
+#[test]
+fn a_consumer_is_woken_once_a_job_is_queued_without_worker_threads() {
+    let mut group = SpawnGroup::<usize>::new(0);
+    group.help_drive_pool(true);
+    for i in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            // every yield hands the child task back to the executor, which queues it again meanwhile
+            for _ in 0..5 {
+                yield_now().await;
+            }
+            i
+        });
+    }
+    let sum: usize = block_on(async move {
+        let mut sum: usize = 0;
+        while let Some(value) = group.next().await {
+            sum += value;
+        }
+        sum
+    });
+    assert_eq!(sum, 45);
+}
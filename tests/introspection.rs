@@ -0,0 +1,114 @@
+//! Tracks the worker threads, pending child tasks and buffered results of spawn groups as they change
+
+mod common;
+
+use common::wait_until;
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup};
+use std::time::Duration;
+
+#[test]
+fn the_number_of_worker_threads_is_the_requested_one() {
+    assert_eq!(SpawnGroup::<u8>::new(3).num_threads(), 3);
+    assert_eq!(ErrSpawnGroup::<u8, u8>::new(1).num_threads(), 1);
+    assert_eq!(DiscardingSpawnGroup::new(4).num_threads(), 4);
+}
+
+#[test]
+fn counts_follow_spawning_and_consuming() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    assert_eq!((group.pending_tasks(), group.buffered_results()), (0, 0));
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(50)).await;
+            value
+        });
+    }
+    assert_eq!(group.pending_tasks(), 10);
+    assert_eq!(group.buffered_results(), 0);
+
+    wait_until(|| group.pending_tasks() == 0);
+    assert_eq!(group.buffered_results(), 10);
+    for consumed in 1..=10 {
+        assert!(block_on(group.next()).is_some());
+        assert_eq!(group.buffered_results(), 10 - consumed);
+    }
+    assert_eq!(block_on(group.next()), None);
+}
+
+#[test]
+fn consumed_results_stop_counting_while_others_are_pending() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        Ok(0)
+    });
+    group.spawn_task(Priority::default(), async { Err("failed".to_string()) });
+    group.spawn_task(Priority::default(), async { Ok(2) });
+
+    wait_until(|| group.buffered_results() == 2);
+    assert_eq!(group.pending_tasks(), 1);
+    block_on(group.next());
+    assert_eq!((group.pending_tasks(), group.buffered_results()), (1, 1));
+    group.cancel_all();
+    assert_eq!(group.pending_tasks(), 0);
+}
+
+#[test]
+fn discarding_spawn_groups_only_have_pending_tasks() {
+    let mut group = DiscardingSpawnGroup::new(2);
+    for _ in 0..5 {
+        group.spawn_task(Priority::default(), async {
+            sleep(Duration::from_millis(20)).await;
+        });
+    }
+    assert_eq!(group.pending_tasks(), 5);
+    wait_until(|| group.pending_tasks() == 0);
+}
+
+#[test]
+fn len_and_completed_count_follow_the_life_of_the_group() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    assert_eq!((group.len(), group.completed_count()), (0, 0));
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    for _ in 0..3 {
+        group.spawn_task(Priority::default(), async {
+            sleep(Duration::from_secs(3600)).await;
+            0
+        });
+    }
+
+    // mid-flight, the finished ones are ready while the others are still counted
+    wait_until(|| group.completed_count() == 4);
+    assert_eq!(group.len(), 7);
+    assert_eq!(group.pending_tasks(), 3);
+    block_on(group.next());
+    assert_eq!((group.len(), group.completed_count()), (6, 3));
+
+    // the cancelled child tasks never produce a result, so they're no longer counted
+    group.cancel_all();
+    assert_eq!((group.len(), group.completed_count()), (3, 3));
+    assert_eq!(block_on((&mut group).count()), 3);
+    assert_eq!((group.len(), group.completed_count()), (0, 0));
+}
+
+#[test]
+fn len_and_completed_count_agree_once_every_child_task_finished() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(value)).await;
+            if value % 3 == 0 {
+                return Err(format!("{value} failed"));
+            }
+            Ok(value)
+        });
+    }
+    assert_eq!(group.len(), 10);
+    block_on(group.wait_for_all());
+    assert_eq!((group.len(), group.completed_count()), (10, 10));
+    block_on(group.next());
+    assert_eq!((group.len(), group.completed_count()), (9, 9));
+}
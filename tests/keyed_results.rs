@@ -0,0 +1,47 @@
+//! Pairs the results of the child tasks with a key given at spawn time
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{collections::HashMap, time::Duration};
+
+#[test]
+fn results_come_paired_with_their_key() {
+    let mut group = SpawnGroup::<(String, usize)>::new(4);
+    for word in ["spawn", "group", "keyed", "results"] {
+        group.spawn_task_keyed(word.to_string(), Priority::default(), async move {
+            sleep(Duration::from_millis(word.len() as u64)).await;
+            word.len()
+        });
+    }
+    let mut pairs: Vec<(String, usize)> = block_on((&mut group).collect());
+    pairs.sort();
+    assert_eq!(
+        pairs,
+        [
+            ("group".to_string(), 5),
+            ("keyed".to_string(), 5),
+            ("results".to_string(), 7),
+            ("spawn".to_string(), 5),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_keys_are_all_streamed_but_the_last_one_wins_in_the_map() {
+    let mut group = SpawnGroup::<(u8, &str)>::new(1);
+    for (key, value) in [(1, "first"), (2, "only"), (1, "second")] {
+        group.spawn_task_keyed(key, Priority::default(), async move { value });
+        // each result arrives before the next child task is spawned
+        block_on(group.wait_for_all());
+    }
+    assert_eq!(group.buffered_results(), 3);
+    assert_eq!(
+        block_on(group.collect_map()),
+        HashMap::from([(1, "second"), (2, "only")])
+    );
+
+    // keyed and unkeyed child tasks share the stream
+    group.spawn_task_keyed(3, Priority::default(), async { "keyed" });
+    group.spawn_task(Priority::default(), async { (4, "unkeyed") });
+    assert_eq!(block_on(group.collect_map()).len(), 2);
+}
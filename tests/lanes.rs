@@ -0,0 +1,170 @@
+//! Polls the child tasks of a spawn group on separate IO and compute lanes, each with its own worker threads
+
+use futures_lite::StreamExt;
+use parking_lot::Mutex;
+use spawn_groups::{
+    block_on, move_to, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Lane, Priority, SpawnGroup,
+};
+use std::{
+    collections::HashSet,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, ThreadId},
+    time::Duration,
+};
+
+/// The worker thread counts of the IO and compute lanes
+fn lanes(io: usize, compute: usize) -> (NonZeroUsize, NonZeroUsize) {
+    (
+        NonZeroUsize::new(io).unwrap(),
+        NonZeroUsize::new(compute).unwrap(),
+    )
+}
+
+#[derive(Default)]
+struct Threads {
+    io: Mutex<HashSet<ThreadId>>,
+    compute: Mutex<HashSet<ThreadId>>,
+}
+
+impl Threads {
+    fn record(&self, lane: Lane) {
+        let threads = match lane {
+            Lane::Io => &self.io,
+            Lane::Compute => &self.compute,
+        };
+        threads.lock().insert(thread::current().id());
+    }
+}
+
+#[test]
+fn child_tasks_hop_between_the_worker_threads_of_each_lane() {
+    let threads = Arc::new(Threads::default());
+    let (io, compute) = lanes(3, 2);
+    let mut group = SpawnGroup::<u64>::with_lanes(io, compute).unwrap();
+    assert_eq!(group.num_threads(), 5);
+    for value in 0..40 {
+        let threads = threads.clone();
+        group.spawn_task(Priority::default(), async move {
+            threads.record(Lane::Io);
+            sleep(Duration::from_millis(1)).await;
+            threads.record(Lane::Io);
+            move_to(Lane::Compute).await;
+            threads.record(Lane::Compute);
+            let square = value * value;
+            move_to(Lane::Io).await;
+            threads.record(Lane::Io);
+            square
+        });
+    }
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(
+        values,
+        (0..40).map(|value| value * value).collect::<Vec<_>>()
+    );
+
+    let (io, compute) = (threads.io.lock(), threads.compute.lock());
+    assert!(!io.is_empty() && io.len() <= 3, "{io:?}");
+    assert!(!compute.is_empty() && compute.len() <= 2, "{compute:?}");
+    assert!(io.is_disjoint(&compute));
+}
+
+#[test]
+fn a_child_task_spawned_on_a_lane_starts_there() {
+    let threads = Arc::new(Threads::default());
+    let (io, compute) = lanes(1, 1);
+    let mut group = ErrSpawnGroup::<u64, ()>::with_lanes(io, compute).unwrap();
+    for value in 0..10 {
+        let (computing, waiting) = (threads.clone(), threads.clone());
+        group.spawn_task_on(Lane::Compute, Priority::default(), async move {
+            computing.record(Lane::Compute);
+            Ok(value)
+        });
+        group.spawn_task(Priority::default(), async move {
+            waiting.record(Lane::Io);
+            Ok(value)
+        });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.len(), 20);
+    let (io, compute) = (threads.io.lock(), threads.compute.lock());
+    assert_eq!((io.len(), compute.len()), (1, 1));
+    assert!(io.is_disjoint(&compute));
+}
+
+#[test]
+fn a_busy_compute_lane_never_holds_up_the_io_lane() {
+    let released = Arc::new(AtomicBool::new(false));
+    let (io, compute) = lanes(1, 1);
+    let mut group = SpawnGroup::<u64>::with_lanes(io, compute).unwrap();
+    // hogs the only worker thread of the compute lane until the IO lane got its work done
+    group.spawn_task_on(Lane::Compute, Priority::default(), {
+        let released = released.clone();
+        async move {
+            while !released.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            0
+        }
+    });
+    for value in 1..=10 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            if value == 10 {
+                released.store(true, Ordering::SeqCst);
+            }
+            value
+        });
+    }
+    let total: u64 = block_on((&mut group).collect::<Vec<u64>>())
+        .into_iter()
+        .sum();
+    assert_eq!(total, 55);
+}
+
+#[test]
+fn cancelling_reaches_the_child_tasks_on_either_lane() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let (io, compute) = lanes(2, 2);
+    let mut group = DiscardingSpawnGroup::with_lanes(io, compute).unwrap();
+    for index in 0..10 {
+        let finished = finished.clone();
+        let lane = if index % 2 == 0 {
+            Lane::Io
+        } else {
+            Lane::Compute
+        };
+        group.spawn_task_on(lane, Priority::default(), async move {
+            sleep(Duration::from_secs(10)).await;
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    group.cancel_all();
+    assert!(block_on(group.wait_timeout(Duration::from_secs(5))));
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn moving_is_a_no_op_without_separate_lanes() {
+    let threads = Arc::new(Threads::default());
+    let mut group = SpawnGroup::<u64>::new(1);
+    for value in 0..5 {
+        let threads = threads.clone();
+        group.spawn_task_on(Lane::Compute, Priority::default(), async move {
+            threads.record(Lane::Compute);
+            move_to(Lane::Io).await;
+            threads.record(Lane::Io);
+            value
+        });
+    }
+    let values: Vec<u64> = block_on((&mut group).collect());
+    assert_eq!(values.len(), 5);
+    assert_eq!(*threads.io.lock(), *threads.compute.lock());
+
+    // outside of a child task as well
+    block_on(move_to(Lane::Compute));
+}
@@ -0,0 +1,154 @@
+//! Delivers markers inserted in between the results of a spawn group, in their place among the results
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Item, Priority, SpawnGroup};
+use std::time::Duration;
+
+// splits the items into the values of each phase, closed by the marker ending it
+fn phases(items: Vec<Item<u64>>) -> Vec<(u64, Vec<u64>)> {
+    let mut phases = vec![];
+    let mut values = vec![];
+    for item in items {
+        match item {
+            Item::Value(value) => values.push(value),
+            Item::Marker(tag) => {
+                values.sort();
+                phases.push((tag, std::mem::take(&mut values)));
+            }
+        }
+    }
+    assert!(
+        values.is_empty(),
+        "values after the last marker: {values:?}"
+    );
+    phases
+}
+
+#[test]
+fn markers_close_each_phase_of_concurrent_child_tasks() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    for phase in 0..5 {
+        for value in 0..20 {
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_micros((value * 7919 + phase * 31) % 2000)).await;
+                phase * 100 + value
+            });
+        }
+        block_on(group.wait_for_all());
+        group.insert_marker(phase);
+    }
+    let items: Vec<Item<u64>> = block_on(group.marked_stream().collect());
+    let expected: Vec<(u64, Vec<u64>)> = (0..5)
+        .map(|phase| (phase, (0..20).map(|value| phase * 100 + value).collect()))
+        .collect();
+    assert_eq!(phases(items), expected);
+}
+
+#[test]
+fn results_buffered_after_the_marker_come_after_it() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 1 });
+    block_on(group.wait_for_all());
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(50)).await;
+        2
+    });
+    group.insert_marker(7);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(100)).await;
+        3
+    });
+    let items: Vec<Item<u64>> = block_on(group.marked_stream().collect());
+    assert_eq!(
+        items,
+        [
+            Item::Value(1),
+            Item::Marker(7),
+            Item::Value(2),
+            Item::Value(3)
+        ]
+    );
+}
+
+#[test]
+fn consecutive_markers_and_a_marker_after_every_result_are_delivered() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.insert_marker(0);
+    group.spawn_task(Priority::default(), async { 1 });
+    block_on(group.wait_for_all());
+    group.insert_marker(1);
+    group.insert_marker(2);
+    let items: Vec<Item<u64>> = block_on(group.marked_stream().collect());
+    assert_eq!(
+        items,
+        [
+            Item::Marker(0),
+            Item::Value(1),
+            Item::Marker(1),
+            Item::Marker(2)
+        ]
+    );
+}
+
+#[test]
+fn markers_never_count_as_results() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    group.insert_marker(1);
+    assert_eq!(group.len(), 10);
+    assert_eq!(group.buffered_results(), 10);
+    assert_eq!(group.metrics().results_produced(), 10);
+
+    // the plain stream skips the markers
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+    assert_eq!(group.metrics().results_consumed(), 10);
+    assert!(group.is_empty());
+}
+
+#[test]
+fn a_marked_consumer_keeps_up_with_phases_spawned_while_it_consumes() {
+    fn spawn_phase(group: &mut SpawnGroup<u64>, phase: u64) {
+        for value in 0..10 {
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_micros(value * 100)).await;
+                phase * 10 + value
+            });
+        }
+    }
+
+    let mut group = SpawnGroup::<u64>::new(4);
+    let mut totals = vec![];
+    spawn_phase(&mut group, 0);
+    block_on(group.wait_for_all());
+    group.insert_marker(0);
+    for phase in 1..=10 {
+        // the next phase completes while the previous one is consumed up to its marker
+        if phase < 10 {
+            spawn_phase(&mut group, phase);
+        }
+        let mut total = 0;
+        block_on(async {
+            let mut stream = group.marked_stream();
+            while let Some(item) = stream.next().await {
+                match item {
+                    Item::Value(value) => total += value,
+                    Item::Marker(tag) => {
+                        totals.push((tag, total));
+                        break;
+                    }
+                }
+            }
+        });
+        if phase < 10 {
+            block_on(group.wait_for_all());
+            group.insert_marker(phase);
+        }
+    }
+    let expected: Vec<(u64, u64)> = (0..10).map(|phase| (phase, phase * 100 + 45)).collect();
+    assert_eq!(totals, expected);
+}
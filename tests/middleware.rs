@@ -0,0 +1,81 @@
+//! Middlewares that swallow or extend child tasks must leave the counters of the spawn group consistent
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, BoxFuture, DiscardingSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Completes without running every other child task
+fn swallow_every_other() -> impl Fn(BoxFuture<'static, ()>) -> BoxFuture<'static, ()> {
+    let seen = AtomicUsize::new(0);
+    move |task: BoxFuture<'static, ()>| -> BoxFuture<'static, ()> {
+        if seen.fetch_add(1, Ordering::SeqCst).is_multiple_of(2) {
+            task
+        } else {
+            Box::pin(async {})
+        }
+    }
+}
+
+#[test]
+fn swallowed_child_tasks_produce_no_result() {
+    let mut group = SpawnGroup::<u32>::new(2);
+    group.task_middleware(swallow_every_other());
+    for i in 0..100 {
+        group.spawn_task(Priority::default(), async move { i });
+    }
+    block_on(group.wait_for_all());
+
+    let snapshot = group.snapshot();
+    assert_eq!(snapshot.pending, 0);
+    assert_eq!(snapshot.buffered, 50);
+    assert!(group.is_empty());
+    // the stream terminates instead of waiting for the swallowed results
+    let results: Vec<u32> = block_on((&mut group).collect());
+    assert_eq!(results.len(), 50);
+    assert_eq!(group.metrics().completed(), 50);
+}
+
+#[test]
+fn extended_child_tasks_are_waited_for() {
+    let extended = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u32>::new(2);
+    group.task_middleware({
+        let extended = extended.clone();
+        move |task: BoxFuture<'static, ()>| -> BoxFuture<'static, ()> {
+            let extended = extended.clone();
+            Box::pin(async move {
+                task.await;
+                sleep(Duration::from_millis(5)).await;
+                extended.fetch_add(1, Ordering::SeqCst);
+            })
+        }
+    });
+    for i in 0..20 {
+        group.spawn_task(Priority::default(), async move { i });
+    }
+    block_on(group.wait_for_all());
+
+    assert_eq!(extended.load(Ordering::SeqCst), 20);
+    let mut results: Vec<u32> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn swallowed_child_tasks_count_as_cancelled() {
+    let mut group = DiscardingSpawnGroup::new(2);
+    group.task_middleware(swallow_every_other());
+    for _ in 0..10 {
+        group.spawn_task(Priority::default(), async {});
+    }
+    let counts = block_on(group.wait_for_all());
+    assert_eq!(counts.completed, 5);
+    assert_eq!(counts.cancelled, 5);
+    assert_eq!(counts.panicked, 0);
+}
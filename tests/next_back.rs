@@ -0,0 +1,84 @@
+//! Takes the newest results first with ``next_back`` and ``rev_stream``, or keeps only the newest with ``latest_only``
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{thread, time::Duration};
+
+/// Spawns child tasks finishing in the order of their values, each one waited for before spawning the next
+fn in_arrival_order(group: &mut SpawnGroup<u64>, values: std::ops::Range<u64>) {
+    for value in values {
+        group.spawn_task(Priority::default(), async move { value });
+        block_on(group.wait_for_all());
+    }
+}
+
+#[test]
+fn next_and_next_back_take_from_either_end() {
+    let mut group = SpawnGroup::<u64>::new(1);
+    in_arrival_order(&mut group, 0..6);
+    assert_eq!(block_on(group.next_back()), Some(5));
+    assert_eq!(block_on(group.next()), Some(0));
+    assert_eq!(block_on(group.next_back()), Some(4));
+    assert_eq!(group.len(), 3);
+    assert_eq!(block_on(group.rev_stream().collect::<Vec<_>>()), [3, 2, 1]);
+    assert_eq!(group.len(), 0);
+    assert_eq!(block_on(group.next_back()), None);
+    assert_eq!(group.metrics().results_consumed(), 6);
+
+    // waits for the next result when none is buffered
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(20)).await;
+        10
+    });
+    assert_eq!(block_on(group.next_back()), Some(10));
+    assert_eq!(block_on(group.next()), None);
+
+    let mut group = ErrSpawnGroup::<u64, String>::new(1);
+    group.spawn_task(Priority::default(), async { Ok(1) });
+    block_on(group.wait_for_all());
+    group.spawn_task(Priority::default(), async { Err("newest".to_string()) });
+    block_on(group.wait_for_all());
+    assert_eq!(block_on(group.next_back()), Some(Err("newest".to_string())));
+    assert_eq!(block_on(group.rev_stream().next()), Some(Ok(1)));
+}
+
+#[test]
+fn latest_only_keeps_a_single_result_under_rapid_production() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    group.latest_only(true);
+    for value in 0..1000 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.len(), 1);
+    assert_eq!(group.buffered_results(), 1);
+    let metrics = group.metrics();
+    assert_eq!(metrics.results_overflowed(), 999);
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()).len(), 1);
+
+    // a consumer takes whatever was newest each time, and the stream still ends
+    let producer = {
+        let spawner = group.spawner();
+        thread::spawn(move || {
+            for value in 0..200 {
+                spawner.spawn_task(Priority::default(), async move { value });
+            }
+        })
+    };
+    producer.join().unwrap();
+    let mut taken: Vec<u64> = vec![];
+    while let Some(value) = block_on(group.next_back()) {
+        taken.push(value);
+    }
+    assert!(!taken.is_empty());
+    assert_eq!(group.len(), 0);
+    assert_eq!(
+        metrics.results_consumed() + metrics.results_overflowed(),
+        1200
+    );
+
+    // disabled, every result is kept again
+    group.latest_only(false);
+    in_arrival_order(&mut group, 0..3);
+    assert_eq!(block_on(group.rev_stream().collect::<Vec<_>>()), [2, 1, 0]);
+}
@@ -0,0 +1,73 @@
+//! Waits for up to a number of results with ``next_chunk``, returning fewer once the spawn group runs out of them
+
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn a_chunk_larger_than_the_child_tasks_returns_every_result() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    assert!(block_on(group.next_chunk(0)).is_empty());
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(value)).await;
+            value
+        });
+    }
+    // zero waits for nothing and takes nothing
+    assert!(block_on(group.next_chunk(0)).is_empty());
+    assert_eq!(group.len(), 4);
+
+    let mut results = block_on(group.next_chunk(3));
+    assert_eq!(results.len(), 3);
+    results.extend(block_on(group.next_chunk(100)));
+    results.sort();
+    assert_eq!(results, [0, 1, 2, 3]);
+    assert!(block_on(group.next_chunk(100)).is_empty());
+
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.spawn_task(Priority::default(), async { Ok(1) });
+    group.spawn_task(Priority::default(), async { Err("failed".to_string()) });
+    let mut results = block_on(group.next_chunk(5));
+    results.sort();
+    assert_eq!(results, [Ok(1), Err("failed".to_string())]);
+}
+
+#[test]
+fn a_cancellation_mid_chunk_returns_the_results_so_far() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..2 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            group.spawn_task_with_handle(Priority::default(), async {
+                sleep(Duration::from_secs(60)).await;
+                u64::MAX
+            })
+        })
+        .collect();
+    // cancels the child tasks left while the chunk is still waiting on them
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handles.iter().for_each(|handle| handle.abort());
+    });
+
+    let started = Instant::now();
+    let mut results = block_on(group.next_chunk(4));
+    results.sort();
+    assert_eq!(results, [0, 1]);
+    assert!(started.elapsed() < Duration::from_secs(10));
+    canceller.join().unwrap();
+    assert_eq!(group.len(), 0);
+
+    // and so does a chunk waited for after the spawn group was cancelled
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(60)).await;
+        0
+    });
+    group.cancel_all();
+    assert!(block_on(group.next_chunk(1)).is_empty());
+}
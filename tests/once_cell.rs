@@ -0,0 +1,99 @@
+//! Initializes a value shared by child tasks racing for it exactly once, without blocking the worker threads
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, sync::OnceCell, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn racing_child_tasks_run_the_initializer_once() {
+    let cell: Arc<OnceCell<u64>> = Arc::new(OnceCell::new());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(4);
+    for _ in 0..100 {
+        let (cell, runs) = (cell.clone(), runs.clone());
+        group.spawn_task(Priority::default(), async move {
+            *cell
+                .get_or_init(|| async {
+                    runs.fetch_add(1, Ordering::AcqRel);
+                    sleep(Duration::from_millis(20)).await;
+                    42
+                })
+                .await
+        });
+    }
+    let results: Vec<u64> = block_on((&mut group).collect());
+    assert_eq!(results, vec![42; 100]);
+    assert_eq!(runs.load(Ordering::Acquire), 1);
+    assert_eq!(cell.get(), Some(&42));
+}
+
+#[test]
+fn waiting_child_tasks_leave_the_worker_thread_free() {
+    let cell: Arc<OnceCell<u64>> = Arc::new(OnceCell::new());
+    // a single worker thread would deadlock if a waiting child task blocked it
+    let mut group = SpawnGroup::<u64>::new(1);
+    for value in 0..10 {
+        let cell = cell.clone();
+        group.spawn_task(Priority::default(), async move {
+            *cell
+                .get_or_init(|| async move {
+                    sleep(Duration::from_millis(20)).await;
+                    value
+                })
+                .await
+        });
+    }
+    let results: Vec<u64> = block_on((&mut group).collect());
+    let first = results[0];
+    assert!(results.iter().all(|&result| result == first));
+}
+
+#[test]
+fn a_failed_initializer_hands_over_to_the_next_one() {
+    let cell: Arc<OnceCell<u64>> = Arc::new(OnceCell::new());
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<Result<u64, String>>::new(4);
+    for _ in 0..20 {
+        let (cell, attempts) = (cell.clone(), attempts.clone());
+        group.spawn_task(Priority::default(), async move {
+            cell.get_or_try_init(|| async {
+                sleep(Duration::from_millis(10)).await;
+                match attempts.fetch_add(1, Ordering::AcqRel) {
+                    0 => Err(String::from("connection refused")),
+                    _ => Ok(7),
+                }
+            })
+            .await
+            .copied()
+        });
+    }
+    let mut results: Vec<Result<u64, String>> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results[0], Ok(7));
+    assert_eq!(results[19], Err(String::from("connection refused")));
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+    assert_eq!(attempts.load(Ordering::Acquire), 2);
+}
+
+#[test]
+fn a_dropped_initializer_hands_over_to_the_next_one() {
+    let cell: OnceCell<u64> = OnceCell::new();
+    block_on(async {
+        let mut abandoned = Box::pin(cell.get_or_init(|| async {
+            sleep(Duration::from_secs(3600)).await;
+            0
+        }));
+        assert!(futures_lite::future::poll_once(&mut abandoned)
+            .await
+            .is_none());
+        drop(abandoned);
+        assert_eq!(*cell.get_or_init(|| async { 1 }).await, 1);
+    });
+    assert_eq!(cell.into_inner(), Some(1));
+}
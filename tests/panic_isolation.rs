@@ -0,0 +1,98 @@
+//! Keeps the worker threads running child tasks, or restarts them, once a child task panicked on them
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, PanicIsolation, PanicOutput, Priority, SpawnGroup};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+};
+
+/// The id of the calling thread if it's a worker thread, instead of the consumer helping out with the queued jobs
+fn worker_thread() -> Option<ThreadId> {
+    let current = thread::current();
+    current
+        .name()
+        .is_some_and(|name| name.starts_with("ThreadPool"))
+        .then(|| current.id())
+}
+
+fn interleaved_with_panics(isolation: PanicIsolation) {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.panic_output(PanicOutput::Silent);
+    group.panic_isolation(isolation);
+    for value in 0..200 {
+        group.spawn_task(Priority::default(), async move {
+            if value % 3 == 0 {
+                panic!("child task {value} failed");
+            }
+            value
+        });
+    }
+    block_on(group.wait_for_all());
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(
+        values,
+        (0..200).filter(|value| value % 3 != 0).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn every_other_child_task_completes_when_the_worker_threads_go_on() {
+    interleaved_with_panics(PanicIsolation::Task);
+}
+
+#[test]
+fn every_other_child_task_completes_when_the_worker_threads_restart() {
+    interleaved_with_panics(PanicIsolation::RestartWorker);
+}
+
+#[test]
+fn worker_threads_go_on_after_a_panic_by_default() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.panic_output(PanicOutput::Silent);
+    let workers: Arc<Mutex<HashSet<ThreadId>>> = Arc::default();
+    for value in 0..50 {
+        let workers = workers.clone();
+        group.spawn_task(Priority::default(), async move {
+            workers.lock().unwrap().extend(worker_thread());
+            if value % 2 == 0 {
+                panic!("child task {value} failed");
+            }
+            value
+        });
+    }
+    block_on(group.wait_for_all());
+    assert!(workers.lock().unwrap().len() <= 2);
+}
+
+#[test]
+fn no_child_task_runs_on_a_worker_thread_restarted_after_a_panic() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.panic_output(PanicOutput::Silent);
+    group.panic_isolation(PanicIsolation::RestartWorker);
+    let panicked_on: Arc<Mutex<HashSet<ThreadId>>> = Arc::default();
+    let ran_on: Arc<Mutex<Vec<ThreadId>>> = Arc::default();
+    for value in 0..10 {
+        let noted = panicked_on.clone();
+        group.spawn_task(Priority::default(), async move {
+            noted.lock().unwrap().extend(worker_thread());
+            panic!("child task {value} failed");
+        });
+        // the worker thread that panicked has been replaced once every worker thread reached the barrier
+        block_on(group.wait_for_all());
+        for value in 0..4 {
+            let noted = ran_on.clone();
+            group.spawn_task(Priority::default(), async move {
+                noted.lock().unwrap().extend(worker_thread());
+                value
+            });
+        }
+        block_on(group.wait_for_all());
+        let panicked_on = panicked_on.lock().unwrap();
+        let ran_on: Vec<ThreadId> = std::mem::take(&mut *ran_on.lock().unwrap());
+        assert!(ran_on.iter().all(|id| !panicked_on.contains(id)));
+    }
+    assert_eq!(block_on((&mut group).count()), 40);
+}
@@ -0,0 +1,71 @@
+//! Reports the panics of child tasks to the configured output, checked on the standard error of a child process
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, PanicOutput, Priority, SpawnGroup};
+use std::{env, process::Command};
+
+const OUTPUT: &str = "PANIC_OUTPUT_UNDER_TEST";
+
+/// Reports panics of child tasks to stdout, so the tests can tell it apart from the standard error
+fn to_stdout(report: &str) {
+    println!("handled: {report}");
+}
+
+/// Runs the child task panicking in a child process with the given output, returning its stdout and stderr
+fn run_with(output: &str) -> (String, String) {
+    let child = Command::new(env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "panicking_child_task",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(OUTPUT, output)
+        .output()
+        .unwrap();
+    assert!(child.status.success());
+    (
+        String::from_utf8_lossy(&child.stdout).into_owned(),
+        String::from_utf8_lossy(&child.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn panicking_child_task() {
+    let output = match env::var(OUTPUT).as_deref() {
+        Ok("silent") => PanicOutput::Silent,
+        Ok("stderr") => PanicOutput::Stderr,
+        Ok("handler") => PanicOutput::Handler(to_stdout),
+        // only run as the child process of the other tests
+        _ => return,
+    };
+    let mut group = SpawnGroup::<u8>::new(2);
+    group.panic_output(output);
+    group.spawn_task(Priority::default(), async { panic!("the disk is full") });
+    group.spawn_task(Priority::default(), async { 1 });
+    block_on(group.wait_for_all());
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [1]);
+}
+
+#[test]
+fn stderr_is_the_default() {
+    let (stdout, stderr) = run_with("stderr");
+    assert!(stderr.contains("the disk is full"));
+    assert!(stderr.contains("Backtrace"));
+    assert!(!stdout.contains("handled"));
+}
+
+#[test]
+fn silent_leaves_stderr_untouched() {
+    let (stdout, stderr) = run_with("silent");
+    assert!(!stderr.contains("the disk is full"), "{stderr}");
+    assert!(!stdout.contains("the disk is full"));
+}
+
+#[test]
+fn the_handler_receives_the_report() {
+    let (stdout, stderr) = run_with("handler");
+    assert!(!stderr.contains("the disk is full"), "{stderr}");
+    assert!(stdout.contains("handled: "));
+    assert!(stdout.contains("the disk is full"));
+}
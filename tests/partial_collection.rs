@@ -0,0 +1,68 @@
+//! Collects the results of an ``ErrSpawnGroup`` keeping the values along with the errors, or failing fast on the first error
+
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority};
+use std::time::Duration;
+
+/// Spawns child tasks finishing in the order of the results, each one waited for before spawning the next
+fn in_arrival_order(group: &mut ErrSpawnGroup<u8, String>, results: &[Result<u8, &str>]) {
+    for result in results {
+        let result: Result<u8, String> = result.map_err(String::from);
+        group.spawn_task(Priority::default(), async move { result });
+        block_on(group.wait_for_all());
+    }
+}
+
+#[test]
+fn partial_collection_never_stops_at_an_error() {
+    let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    in_arrival_order(&mut group, &[Ok(1), Err("a"), Ok(2), Err("b"), Ok(3)]);
+    assert_eq!(
+        block_on(group.try_collect_partial()),
+        (vec![1, 2, 3], vec!["a".to_string(), "b".to_string()])
+    );
+    assert!(group.is_empty());
+    assert!(!group.is_cancelled());
+
+    // the spawn group is left usable
+    in_arrival_order(&mut group, &[Err("c"), Ok(4)]);
+    assert_eq!(
+        block_on(group.try_collect_partial()),
+        (vec![4], vec!["c".to_string()])
+    );
+    assert_eq!(block_on(group.try_collect_partial()), (vec![], vec![]));
+}
+
+#[test]
+fn the_first_error_cancels_keeping_the_values_gathered() {
+    let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    in_arrival_order(&mut group, &[Ok(1), Err("a"), Ok(2), Err("b")]);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        Ok(3)
+    });
+    // the values buffered behind the error are kept, while the later error is dropped
+    assert_eq!(
+        block_on(group.try_collect_or_cancel()),
+        Err(("a".to_string(), vec![1, 2]))
+    );
+    assert!(group.is_cancelled());
+    assert!(group.is_empty());
+    assert_eq!(group.buffered_results(), 0);
+    assert_eq!(group.pending_tasks(), 0);
+}
+
+#[test]
+fn without_errors_every_value_is_collected() {
+    let mut group = ErrSpawnGroup::<u8, String>::new(2);
+    for value in 0..20 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(u64::from(value % 4))).await;
+            Ok(value)
+        });
+    }
+    let mut values = block_on(group.try_collect_or_cancel()).unwrap();
+    values.sort();
+    assert_eq!(values, (0..20).collect::<Vec<_>>());
+    assert!(!group.is_cancelled());
+    assert!(group.is_empty());
+}
@@ -0,0 +1,122 @@
+//! Polls futures by hand with ``pin_future!``, ``poll_once`` and ``now_or_never``
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, now_or_never, pin_future, poll_once, sleep, yield_now, Priority, SpawnGroup,
+};
+use std::{
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+// pending until completed from elsewhere, keeping only the waker of its first poll like a future that registers its
+// interest once
+#[derive(Default)]
+struct Signal {
+    state: Mutex<(bool, Option<Waker>)>,
+}
+
+impl Signal {
+    fn complete(&self) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            state.0 = true;
+            state.1.take()
+        };
+        waker.into_iter().for_each(Waker::wake);
+    }
+}
+
+struct Wait(Arc<Signal>);
+
+impl Future for Wait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.0.state.lock().unwrap();
+        if state.0 {
+            return Poll::Ready(());
+        }
+        state.1.get_or_insert_with(|| cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[test]
+fn a_pinned_future_is_polled_by_hand_and_awaited_afterwards() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let signal = Arc::new(Signal::default());
+    let wait = Wait(signal.clone());
+    group.spawn_task(Priority::default(), async move {
+        wait.await;
+        5
+    });
+    let mut stream = group.stream();
+    let next = stream.next();
+    let also_next = async { 0 };
+    pin_future!(next, also_next);
+    assert_eq!(now_or_never(next.as_mut()), None);
+    assert_eq!(now_or_never(also_next), Some(0));
+
+    signal.complete();
+    // the future kept its progress, and whoever polls it next is the one woken
+    assert_eq!(block_on(next), Some(5));
+}
+
+#[test]
+fn poll_once_wakes_the_calling_task_with_the_wakes_of_the_future() {
+    let signal = Arc::new(Signal::default());
+    let completer = {
+        let signal = signal.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            signal.complete();
+        })
+    };
+    block_on(async {
+        let wait = Wait(signal.clone());
+        pin_future!(wait);
+        assert_eq!(poll_once(wait.as_mut()).await, Poll::Pending);
+        // pending once without registering anything, so only the wake the signal registered through poll_once can
+        // get the task polled again
+        let mut suspended = false;
+        poll_fn(|_| {
+            if std::mem::replace(&mut suspended, true) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        assert_eq!(poll_once(wait).await, Poll::Ready(()));
+    });
+    completer.join().unwrap();
+}
+
+#[test]
+fn a_wake_kept_by_the_future_never_cuts_a_later_block_on_short() {
+    let signal = Arc::new(Signal::default());
+    assert_eq!(now_or_never(Wait(signal.clone())), None);
+    // the future woke itself while being polled
+    assert_eq!(now_or_never(yield_now()), None);
+    signal.complete();
+
+    let started = Instant::now();
+    block_on(sleep(Duration::from_millis(20)));
+    assert!(started.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn a_future_woken_after_now_or_never_is_not_lost() {
+    let signal = Arc::new(Signal::default());
+    let wait = Wait(signal.clone());
+    pin_future!(wait);
+    assert_eq!(now_or_never(wait.as_mut()), None);
+    // completed before it's polled again, which the next poll notices whatever became of the first waker
+    signal.complete();
+    assert_eq!(now_or_never(wait), Some(()));
+}
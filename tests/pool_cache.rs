@@ -0,0 +1,229 @@
+//! Reuses the threadpool of a scoped spawn group for the next one started on the same thread
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, flush_cached_pools, sleep, with_err_spawn_group, with_spawn_group,
+    with_spawn_group_with_threads, Priority, SpawnGroup,
+};
+use std::{
+    collections::HashSet,
+    num::NonZeroUsize,
+    sync::{Arc, Barrier},
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+/// Returns every worker thread of the spawn group, each of them running one of its child tasks at the same time
+async fn every_worker_thread(mut group: SpawnGroup<ThreadId>) -> HashSet<ThreadId> {
+    running_worker_threads(&mut group).await
+}
+
+/// Returns every worker thread of the spawn group as ``every_worker_thread`` does, leaving the spawn group alive
+async fn running_worker_threads(group: &mut SpawnGroup<ThreadId>) -> HashSet<ThreadId> {
+    let count: usize = group.num_threads();
+    let all_running = Arc::new(Barrier::new(count));
+    for _ in 0..count {
+        let all_running = all_running.clone();
+        group.spawn_task(Priority::default(), async move {
+            all_running.wait();
+            thread::current().id()
+        });
+    }
+    group.take(count).collect().await
+}
+
+/// Returns the worker threads of a scoped spawn group
+fn worker_threads() -> HashSet<ThreadId> {
+    block_on(with_spawn_group(every_worker_thread))
+}
+
+/// Returns the worker threads of a scoped spawn group with the given number of them
+fn worker_threads_of(count: usize) -> HashSet<ThreadId> {
+    let threads = NonZeroUsize::new(count).unwrap();
+    let workers = block_on(with_spawn_group_with_threads(threads, every_worker_thread));
+    assert_eq!(workers.len(), count);
+    workers
+}
+
+#[test]
+fn sequential_scopes_share_their_worker_threads() {
+    let first = worker_threads();
+    assert_eq!(first, worker_threads());
+    assert_eq!(first, worker_threads());
+
+    flush_cached_pools();
+    assert!(first.is_disjoint(&worker_threads()));
+}
+
+#[test]
+fn threadpools_are_reused_for_the_same_number_of_threads() {
+    let two = worker_threads_of(2);
+    let three = worker_threads_of(3);
+    assert!(two.is_disjoint(&three));
+    assert_eq!(two, worker_threads_of(2));
+    assert_eq!(three, worker_threads_of(3));
+}
+
+#[test]
+fn threadpools_stay_with_their_thread() {
+    let first = worker_threads();
+    let elsewhere = thread::spawn(worker_threads).join().unwrap();
+    assert!(first.is_disjoint(&elsewhere));
+}
+
+#[test]
+fn customized_threadpools_are_not_reused() {
+    let first: HashSet<ThreadId> = block_on(with_spawn_group(|group| async move {
+        group.record_executions(true);
+        every_worker_thread(group).await
+    }));
+    assert!(first.is_disjoint(&worker_threads()));
+}
+
+#[test]
+fn results_never_leak_into_the_next_scope() {
+    for round in 0..50u64 {
+        // the first half of the child tasks is left unconsumed when the scope ends
+        let consumed: Vec<u64> = block_on(with_spawn_group(move |mut group| async move {
+            for value in 0..10 {
+                group.spawn_task(Priority::default(), async move { round * 100 + value });
+            }
+            let mut consumed: Vec<u64> = (&mut group).take(5).collect().await;
+            consumed.sort();
+            consumed
+        }));
+        assert_eq!(consumed.len(), 5);
+        assert!(consumed.iter().all(|value| value / 100 == round));
+    }
+}
+
+#[test]
+fn cancellations_never_leak_into_the_next_scope() {
+    let started_at = Instant::now();
+    block_on(with_spawn_group(|mut group| async move {
+        for _ in 0..4 {
+            group.spawn_task(Priority::default(), async {
+                sleep(Duration::from_secs(3600)).await;
+            });
+        }
+        group.cancel_all();
+    }));
+    // neither the cancelled child tasks nor the cancellation carry over
+    let results: Vec<Result<u64, String>> =
+        block_on(with_err_spawn_group(|mut group| async move {
+            for value in 0..10 {
+                group.spawn_task(Priority::default(), async move {
+                    sleep(Duration::from_millis(5)).await;
+                    Ok(value)
+                });
+            }
+            group.collect().await
+        }));
+    assert_eq!(results.len(), 10);
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn live_scopes_never_share_a_threadpool() {
+    let two = NonZeroUsize::new(2).unwrap();
+    // three threadpools left behind on this thread, so every scope below takes one from the cache
+    block_on(with_spawn_group_with_threads(
+        two,
+        move |_: SpawnGroup<ThreadId>| async move {
+            with_spawn_group_with_threads(two, move |_: SpawnGroup<ThreadId>| async move {
+                with_spawn_group_with_threads(two, |_: SpawnGroup<ThreadId>| async {}).await
+            })
+            .await
+        },
+    ));
+
+    let [outer, middle, inner] = block_on(with_spawn_group_with_threads(
+        two,
+        move |mut outer: SpawnGroup<ThreadId>| async move {
+            let outer: HashSet<ThreadId> = running_worker_threads(&mut outer).await;
+            with_spawn_group_with_threads(two, move |mut middle: SpawnGroup<ThreadId>| async move {
+                let middle: HashSet<ThreadId> = running_worker_threads(&mut middle).await;
+                with_spawn_group_with_threads(
+                    two,
+                    move |mut inner: SpawnGroup<ThreadId>| async move {
+                        [outer, middle, running_worker_threads(&mut inner).await]
+                    },
+                )
+                .await
+            })
+            .await
+        },
+    ));
+    assert!(outer.is_disjoint(&middle));
+    assert!(outer.is_disjoint(&inner));
+    assert!(middle.is_disjoint(&inner));
+    // the threadpools went back to the cache once their scopes ended
+    assert!([outer, middle, inner].contains(&worker_threads_of(2)));
+}
+
+#[test]
+fn cancelling_a_scope_leaves_the_scope_nested_in_it_alone() {
+    let two = NonZeroUsize::new(2).unwrap();
+    // two threadpools left behind on this thread, so both scopes below take theirs from the cache
+    block_on(with_spawn_group_with_threads(
+        two,
+        move |_: SpawnGroup<()>| async move {
+            with_spawn_group_with_threads(two, |_: SpawnGroup<()>| async {}).await
+        },
+    ));
+
+    let (outer, nested): (Vec<u64>, Vec<u64>) = block_on(with_spawn_group_with_threads(
+        two,
+        move |mut outer: SpawnGroup<u64>| async move {
+            for _ in 0..4 {
+                outer.spawn_task(Priority::default(), async {
+                    sleep(Duration::from_secs(3600)).await;
+                    0
+                });
+            }
+            with_spawn_group_with_threads(two, move |mut nested: SpawnGroup<u64>| async move {
+                // two child tasks running and the rest queued behind them once the outer scope is cancelled
+                for value in 0..8 {
+                    nested.spawn_task(Priority::default(), async move {
+                        thread::sleep(Duration::from_millis(20));
+                        value
+                    });
+                }
+                outer.cancel_all();
+                let mut nested_results: Vec<u64> = (&mut nested).collect().await;
+                nested_results.sort();
+                assert!(!nested.is_cancelled());
+                (outer.collect().await, nested_results)
+            })
+            .await
+        },
+    ));
+    assert!(outer.is_empty());
+    assert_eq!(nested, (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn many_sequential_small_scopes() {
+    const SCOPES: usize = 10_000;
+    let run = |flushing: bool| {
+        let started_at = Instant::now();
+        for value in 0..SCOPES {
+            let sum: usize = block_on(with_spawn_group(move |mut group| async move {
+                group.spawn_task(Priority::default(), async move { value });
+                group.spawn_task(Priority::default(), async { 1 });
+                group.fold(0, |acc, value| acc + value).await
+            }));
+            assert_eq!(sum, value + 1);
+            if flushing {
+                flush_cached_pools();
+            }
+        }
+        started_at.elapsed()
+    };
+    println!(
+        "{SCOPES} scopes with a new threadpool each: {:?}",
+        run(true)
+    );
+    println!("{SCOPES} scopes reusing their threadpool: {:?}", run(false));
+}
@@ -0,0 +1,87 @@
+//! Shuts the threadpool of a spawn group down as it's dropped, draining or abandoning a deep queue of jobs
+
+mod common;
+
+use common::PROMPTLY;
+use spawn_groups::{worker_state, GroupDropPolicy, Priority, SpawnGroup};
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A worker state counting the worker threads that are still alive, as it's dropped when its worker thread exits
+struct Alive(Arc<AtomicUsize>);
+
+impl Alive {
+    fn new(alive: &Arc<AtomicUsize>) -> Self {
+        alive.fetch_add(1, Ordering::AcqRel);
+        Self(alive.clone())
+    }
+}
+
+impl Drop for Alive {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn group_tracking(policy: GroupDropPolicy, alive: &Arc<AtomicUsize>) -> SpawnGroup<()> {
+    let mut group = SpawnGroup::<()>::new_with(NonZeroUsize::new(1).unwrap(), policy);
+    let alive = alive.clone();
+    group.worker_state(move || Alive::new(&alive));
+    group
+}
+
+fn spawn_counted(group: &mut SpawnGroup<()>, ran: &Arc<AtomicUsize>, count: usize) {
+    for _ in 0..count {
+        let ran = ran.clone();
+        group.spawn_task(Priority::default(), async move {
+            _ = worker_state::<Alive>();
+            ran.fetch_add(1, Ordering::AcqRel);
+        });
+    }
+}
+
+#[test]
+fn draining_runs_the_queue_before_the_worker_threads_exit() {
+    let (alive, ran) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let mut group = group_tracking(GroupDropPolicy::WaitForAll, &alive);
+    spawn_counted(&mut group, &ran, 1_000);
+
+    drop(group);
+    assert_eq!(ran.load(Ordering::Acquire), 1_000);
+    // the worker thread had exited by the time the drop returned
+    assert_eq!(alive.load(Ordering::Acquire), 0);
+}
+
+#[test]
+fn abandoning_drops_the_queue_without_waiting_for_the_worker_threads() {
+    let (alive, ran) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let mut group = group_tracking(GroupDropPolicy::CancelAll, &alive);
+    // keeps the only worker thread busy while the queue fills up behind it
+    group.spawn_task(Priority::default(), async {
+        _ = worker_state::<Alive>();
+        thread::sleep(Duration::from_secs(1));
+    });
+    let started_at = Instant::now();
+    while alive.load(Ordering::Acquire) == 0 {
+        assert!(started_at.elapsed() < PROMPTLY);
+        thread::sleep(Duration::from_millis(1));
+    }
+    spawn_counted(&mut group, &ran, 1_000);
+
+    let dropped_at = Instant::now();
+    drop(group);
+    assert!(dropped_at.elapsed() < Duration::from_millis(500));
+    // the worker thread exits once done with the job it was running, without running the queue
+    while alive.load(Ordering::Acquire) > 0 {
+        assert!(dropped_at.elapsed() < PROMPTLY);
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(ran.load(Ordering::Acquire), 0);
+}
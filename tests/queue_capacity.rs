@@ -0,0 +1,79 @@
+//! Bounds the pending child tasks spawned with try_spawn_task, reporting a full queue instead of growing it
+
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnError, SpawnGroup,
+};
+use std::time::Duration;
+
+async fn slow(value: u64) -> u64 {
+    sleep(Duration::from_millis(200)).await;
+    value
+}
+
+#[test]
+fn a_full_queue_refuses_the_child_task() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.queue_capacity(4);
+    for value in 0..4 {
+        assert_eq!(
+            group.try_spawn_task(Priority::default(), slow(value)),
+            Ok(())
+        );
+    }
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), slow(4)),
+        Err(SpawnError::QueueFull(4))
+    );
+    assert_eq!(group.pending_tasks(), 4);
+
+    // the queue has room again once the pending child tasks finished
+    block_on(group.wait_for_all());
+    assert_eq!(group.try_spawn_task(Priority::default(), slow(5)), Ok(()));
+}
+
+#[test]
+fn spawn_task_stays_unbounded() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.queue_capacity(4);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), slow(value));
+    }
+    assert_eq!(group.pending_tasks(), 10);
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), slow(10)),
+        Err(SpawnError::QueueFull(4))
+    );
+    group.cancel_all();
+}
+
+#[test]
+fn every_kind_of_spawn_group_has_a_queue_capacity() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.queue_capacity(1);
+    assert!(group
+        .try_spawn_task(Priority::default(), async { Ok(slow(0).await) })
+        .is_ok());
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { Ok(1) }),
+        Err(SpawnError::QueueFull(1))
+    );
+    group.cancel_all();
+
+    let mut group = DiscardingSpawnGroup::new(2);
+    group.queue_capacity(0);
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async {}),
+        Err(SpawnError::QueueFull(0))
+    );
+}
+
+#[test]
+fn a_closed_spawn_group_reports_being_closed() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.queue_capacity(0);
+    group.close();
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), slow(0)),
+        Err(SpawnError::Closed)
+    );
+}
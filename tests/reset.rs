@@ -0,0 +1,106 @@
+//! Reuses a cancelled spawn group for another wave of child tasks by resetting it
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn a_reset_spawn_group_runs_a_new_wave_of_child_tasks() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(5)).await;
+            value
+        });
+    }
+    block_on(group.wait_for_all());
+    group.cancel_all();
+    assert!(group.is_cancelled());
+    // a cancelled spawn group refuses the child tasks spawned unless it's cancelled
+    assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 100 }));
+
+    group.reset();
+    assert!(!group.is_cancelled());
+    for value in 10..20 {
+        group.spawn_task_unless_cancelled(Priority::default(), async move { value });
+    }
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, (10..20).collect::<Vec<_>>());
+    assert_eq!(group.num_threads(), 2);
+}
+
+#[test]
+fn resetting_cancels_the_child_tasks_in_flight() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_secs(3600)).await;
+            Ok(value)
+        });
+    }
+    group.reset();
+    assert!(group.is_empty());
+
+    for value in 10..20 {
+        group.spawn_task(Priority::default(), async move {
+            match value % 2 {
+                0 => Ok(value),
+                _ => Err(format!("{value} is odd")),
+            }
+        });
+    }
+    let results: Vec<Result<u64, String>> = block_on((&mut group).collect());
+    assert_eq!(results.len(), 10);
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 5);
+}
+
+#[test]
+fn a_reset_discarding_spawn_group_counts_only_its_new_wave() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut group = DiscardingSpawnGroup::new(2);
+    for _ in 0..5 {
+        group.spawn_task(Priority::default(), async {
+            sleep(Duration::from_secs(3600)).await;
+        });
+    }
+    group.cancel_all();
+    group.reset();
+    for _ in 0..10 {
+        let finished = finished.clone();
+        group.spawn_task_unless_cancelled(Priority::default(), async move {
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    let counts = block_on(group.wait_for_all());
+    assert_eq!(counts.completed, 10);
+    assert_eq!(counts.cancelled, 0);
+    assert_eq!(finished.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+#[allow(deprecated)]
+fn the_deprecated_cancelled_field_follows_the_accessor() {
+    let mut group = SpawnGroup::<u64>::new(1);
+    let mut err_group = ErrSpawnGroup::<u64, String>::new(1);
+    let mut discarding_group = DiscardingSpawnGroup::new(1);
+    group.cancel_all();
+    err_group.cancel_all();
+    discarding_group.cancel_all();
+    assert!(group.is_cancelled() && group.is_cancelled);
+    assert!(err_group.is_cancelled() && err_group.is_cancelled);
+    assert!(discarding_group.is_cancelled() && discarding_group.is_cancelled);
+
+    group.reset();
+    err_group.reset();
+    discarding_group.reset();
+    assert!(!group.is_cancelled() && !group.is_cancelled);
+    assert!(!err_group.is_cancelled() && !err_group.is_cancelled);
+    assert!(!discarding_group.is_cancelled() && !discarding_group.is_cancelled);
+}
@@ -0,0 +1,121 @@
+//! Bounds the results a spawn group buffers, dropping or holding back the ones beyond its result buffer
+
+mod common;
+
+use common::eventually;
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, ErrSpawnGroup, Metrics, OverflowPolicy, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+// spawns the child tasks one after the other, each of them only once the previous one finished
+fn spawn_in_turn(group: &mut SpawnGroup<u64>, values: std::ops::Range<u64>) {
+    for value in values {
+        group.spawn_task(Priority::default(), async move { value });
+        block_on(group.wait_for_all());
+    }
+}
+
+#[test]
+fn dropping_the_oldest_results_keeps_the_newest_ones() {
+    let dropped = Arc::new(Mutex::new(vec![]));
+    let mut group = SpawnGroup::<u64>::new(1);
+    group.result_buffer(3, OverflowPolicy::DropOldest);
+    group.on_discarded({
+        let dropped = dropped.clone();
+        move |value| dropped.lock().unwrap().push(value)
+    });
+    spawn_in_turn(&mut group, 0..10);
+
+    let metrics = group.metrics();
+    assert_eq!(metrics.results_overflowed(), 7);
+    assert_eq!(metrics.results_discarded(), 7);
+    assert_eq!(*dropped.lock().unwrap(), (0..7).collect::<Vec<_>>());
+    assert_eq!(block_on(group.finish()), [7, 8, 9]);
+}
+
+#[test]
+fn dropping_the_newest_results_keeps_the_oldest_ones() {
+    let mut group = SpawnGroup::<u64>::new(1);
+    group.result_buffer(3, OverflowPolicy::DropNewest);
+    spawn_in_turn(&mut group, 0..10);
+
+    assert_eq!(group.metrics().results_overflowed(), 7);
+    assert_eq!(block_on(group.finish()), [0, 1, 2]);
+}
+
+#[test]
+fn a_requeued_result_is_buffered_regardless_of_the_capacity() {
+    for policy in [
+        OverflowPolicy::DropOldest,
+        OverflowPolicy::DropNewest,
+        OverflowPolicy::Block,
+    ] {
+        let mut group = SpawnGroup::<u64>::new(1);
+        group.result_buffer(1, policy);
+        spawn_in_turn(&mut group, 0..1);
+        let taken: u64 = block_on(group.next()).unwrap();
+        spawn_in_turn(&mut group, 1..2);
+        // the buffer is full again, yet the result taken out goes back ahead of the buffered one
+        group.requeue_result(taken);
+        assert_eq!(group.buffered_results(), 2);
+        assert_eq!(group.metrics().results_overflowed(), 0);
+        assert_eq!(block_on(group.finish()), [0, 1]);
+    }
+}
+
+#[test]
+fn blocked_child_tasks_deliver_their_results_once_there_is_room() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.result_buffer(2, OverflowPolicy::Block);
+    for value in 0..20 {
+        let finished = finished.clone();
+        group.spawn_task(Priority::default(), async move {
+            finished.fetch_add(1, Ordering::SeqCst);
+            Ok(value)
+        });
+    }
+    assert!(eventually(|| finished.load(Ordering::SeqCst) == 20));
+    // the child tasks ran, but only as many of them as the buffer holds could hand over their result
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(group.metrics().results_produced(), 2);
+
+    let mut values: Vec<u64> = block_on((&mut group).map(Result::unwrap).collect());
+    values.sort();
+    assert_eq!(values, (0..20).collect::<Vec<_>>());
+    assert_eq!(group.metrics().results_overflowed(), 0);
+}
+
+#[test]
+fn cancelling_releases_the_blocked_child_tasks() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.result_buffer(1, OverflowPolicy::Block);
+    group.on_discarded({
+        let dropped = dropped.clone();
+        move |_| _ = dropped.fetch_add(1, Ordering::SeqCst)
+    });
+    for value in 0..5 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    assert!(eventually(|| group.metrics().results_produced() == 1));
+
+    // the blocked child tasks are cancelled rather than waiting for room that will never be made, only the ones
+    // being polled by one of the two worker threads at that point give up and drop their result
+    group.cancel_all();
+    block_on(group.wait_for_all());
+    let metrics: Metrics = group.metrics();
+    drop(group);
+    // a poll in flight may still drop its result once the spawn group is gone
+    assert!(eventually(
+        || dropped.load(Ordering::SeqCst) as u64 == metrics.results_produced()
+    ));
+    assert!((1..=3).contains(&metrics.results_produced()));
+}
@@ -0,0 +1,59 @@
+//! Preallocates the result buffer of spawn groups created with ``with_capacity``, which otherwise behave the same
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, ErrSpawnGroup, Priority, SpawnGroup};
+
+const RESULTS: u64 = 20_000;
+
+/// Produces every result before consuming any of them, returning them sorted
+fn produce_all(mut group: SpawnGroup<u64>) -> Vec<u64> {
+    for value in 0..RESULTS {
+        group.spawn_task(Priority::default(), async move { value * 3 });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.buffered_results(), RESULTS as usize);
+    let mut results: Vec<u64> = block_on(group.collect());
+    results.sort();
+    results
+}
+
+#[test]
+fn the_capacity_reaches_the_result_buffer() {
+    assert_eq!(SpawnGroup::<u8>::new(1).result_capacity(), 0);
+    let mut group = SpawnGroup::<u8>::with_capacity(1, 500);
+    let capacity = group.result_capacity();
+    assert!(capacity >= 500);
+    assert_eq!(group.num_threads(), 1);
+
+    // taking the results out, even all at once, or resetting keeps the buffer preallocated
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.drain_ready().len(), 10);
+    group.spawn_task(Priority::default(), async { 10 });
+    block_on(group.wait_for_all());
+    group.reset();
+    assert_eq!(group.result_capacity(), capacity);
+
+    let group = ErrSpawnGroup::<u8, String>::with_capacity(2, 64);
+    assert!(group.result_capacity() >= 64);
+    assert_eq!(group.num_threads(), 2);
+}
+
+#[test]
+fn a_preallocated_buffer_changes_nothing_but_the_reallocations() {
+    let group = SpawnGroup::<u64>::with_capacity(4, RESULTS as usize);
+    let capacity = group.result_capacity();
+    let preallocated = produce_all(group);
+    assert_eq!(preallocated, produce_all(SpawnGroup::new(4)));
+    assert_eq!(preallocated.len(), RESULTS as usize);
+
+    // the buffer never reallocated while holding every result at once
+    let mut group = SpawnGroup::<u64>::with_capacity(4, RESULTS as usize);
+    for value in 0..RESULTS {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.result_capacity(), capacity);
+}
@@ -0,0 +1,113 @@
+//! Counts the bytes of the buffered results, per spawn group and across the process
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, total_buffered_bytes, ErrSpawnGroup, OverflowPolicy, Priority, ResultSize, SpawnGroup,
+};
+use std::sync::{Arc, Mutex};
+
+// the process-wide total is shared by every test of the file
+static SERIAL: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, PartialEq)]
+struct Blob(usize);
+
+impl ResultSize for Blob {
+    fn approx_size(&self) -> usize {
+        self.0
+    }
+}
+
+fn spawn_blobs(group: &mut SpawnGroup<Blob>, sizes: &[usize]) {
+    for &size in sizes {
+        group.spawn_task(Priority::default(), async move { Blob(size) });
+        // one at a time, so the results are buffered in order
+        block_on(group.wait_for_all());
+    }
+}
+
+#[test]
+fn the_counters_follow_the_results_in_and_out_of_the_buffer() {
+    let _serial = SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let before = total_buffered_bytes();
+    let mut group = SpawnGroup::<Blob>::new(2);
+    group.measure_results();
+    let metrics = group.metrics();
+    spawn_blobs(&mut group, &[10, 20, 30, 40]);
+    assert_eq!(metrics.buffered_bytes(), 100);
+    assert_eq!(total_buffered_bytes(), before + 100);
+
+    assert_eq!(block_on(group.next()), Some(Blob(10)));
+    assert_eq!(group.try_next(), Some(Blob(20)));
+    assert_eq!(metrics.buffered_bytes(), 70);
+    // a result handed back is counted again
+    group.requeue_result(Blob(5));
+    assert_eq!(metrics.buffered_bytes(), 75);
+    assert_eq!(total_buffered_bytes(), before + 75);
+
+    // the results discarded with the spawn group are let go of as well
+    drop(group);
+    assert_eq!(metrics.buffered_bytes(), 0);
+    assert_eq!(total_buffered_bytes(), before);
+}
+
+#[test]
+fn results_are_counted_by_their_type_unless_measured() {
+    let _serial = SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let before = total_buffered_bytes();
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..3 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().buffered_bytes(), 24);
+    assert_eq!(total_buffered_bytes(), before + 24);
+    group.reset();
+    assert_eq!(group.metrics().buffered_bytes(), 0);
+
+    let mut group = ErrSpawnGroup::<String, String>::new(2);
+    group.measure_results();
+    group.spawn_task(Priority::default(), async { Ok("x".repeat(100)) });
+    group.spawn_task(Priority::default(), async {
+        Err(String::with_capacity(50))
+    });
+    block_on(group.wait_for_all());
+    let string = std::mem::size_of::<String>();
+    assert_eq!(group.metrics().buffered_bytes(), 2 * string + 150);
+    assert_eq!(block_on((&mut group).count()), 2);
+    assert_eq!(group.metrics().buffered_bytes(), 0);
+}
+
+#[test]
+fn a_bound_in_bytes_drops_as_many_of_the_oldest_results_as_needed() {
+    let _serial = SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let before = total_buffered_bytes();
+    let dropped: Arc<Mutex<Vec<usize>>> = Arc::default();
+    let mut group = SpawnGroup::<Blob>::new(1);
+    group.measure_results();
+    group.result_buffer_bytes(100, OverflowPolicy::DropOldest);
+    group.on_discarded({
+        let dropped = dropped.clone();
+        move |blob: Blob| dropped.lock().unwrap().push(blob.0)
+    });
+    let metrics = group.metrics();
+    spawn_blobs(&mut group, &[30, 40, 20]);
+    assert_eq!(metrics.buffered_bytes(), 90);
+    spawn_blobs(&mut group, &[70]);
+    assert_eq!(*dropped.lock().unwrap(), [30, 40]);
+    assert_eq!(metrics.results_overflowed(), 2);
+    assert_eq!(metrics.buffered_bytes(), 90);
+    // a result bigger than the bound still fits into an empty buffer
+    spawn_blobs(&mut group, &[500]);
+    assert_eq!(metrics.buffered_bytes(), 500);
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [Blob(500)]);
+    assert_eq!(metrics.buffered_bytes(), 0);
+    drop(group);
+    assert_eq!(total_buffered_bytes(), before);
+}
@@ -0,0 +1,61 @@
+//! Hands out the results of a spawn group through a stream type that can be named, stored and cloned
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, ErrSpawnGroup, Priority, ResultStream, SpawnGroup};
+
+// a consumer holding the stream of a spawn group it doesn't borrow
+struct Collector {
+    results: ResultStream<Result<u64, String>>,
+}
+
+impl Collector {
+    fn failures(&mut self) -> Vec<String> {
+        block_on((&mut self.results).filter_map(Result::err).collect())
+    }
+}
+
+#[test]
+fn the_stream_can_be_stored_in_a_struct() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    let mut collector = Collector {
+        results: group.stream(),
+    };
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            match value % 3 {
+                0 => Err(format!("{value} failed")),
+                _ => Ok(value),
+            }
+        });
+    }
+    let mut failures = collector.failures();
+    failures.sort();
+    assert_eq!(failures, ["0 failed", "3 failed", "6 failed", "9 failed"]);
+}
+
+#[test]
+fn the_stream_reports_its_buffered_results_and_its_end() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let mut stream = group.stream();
+    assert!(stream.is_empty());
+    assert!(stream.is_terminated());
+
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+    assert_eq!(stream.len(), 4);
+    assert!(!stream.is_terminated());
+    assert_eq!(
+        format!("{stream:?}"),
+        "ResultStream { len: 4, is_terminated: false }"
+    );
+
+    // the clones share the results
+    let mut clone = stream.clone();
+    assert!(block_on(clone.next()).is_some());
+    assert_eq!(stream.len(), 3);
+    assert_eq!(block_on((&mut stream).collect::<Vec<_>>()).len(), 3);
+    assert!(stream.is_terminated());
+    assert_eq!(block_on(clone.next()), None);
+}
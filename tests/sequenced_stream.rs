@@ -0,0 +1,90 @@
+//! Delivers the results of child tasks in the order of the sequence numbers they were spawned with
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, Sequenced, SpawnGroup};
+use std::time::{Duration, Instant};
+
+fn spawn_stuck(group: &mut SpawnGroup<u64>, sequence: u64) {
+    group.spawn_task_sequenced(sequence, Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+}
+
+#[test]
+fn results_arriving_out_of_order_are_delivered_in_sequence() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    // the later chunks finish first
+    for sequence in (0..100).rev() {
+        group.spawn_task_sequenced(sequence, Priority::default(), async move {
+            sleep(Duration::from_millis(100 - sequence)).await;
+            sequence * 10
+        });
+    }
+    let chunks: Vec<Sequenced<u64>> = block_on(group.sequenced_stream().collect());
+    assert_eq!(
+        chunks,
+        (0..100)
+            .map(|sequence| Sequenced::Item(sequence, sequence * 10))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn a_stalled_sequence_number_is_given_up_on_after_the_gap_timeout() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    spawn_stuck(&mut group, 1);
+    for sequence in [0, 2, 3] {
+        group.spawn_task_sequenced(sequence, Priority::default(), async move { sequence });
+    }
+    let started_at = Instant::now();
+    let chunks: Vec<Sequenced<u64>> = block_on(
+        group
+            .sequenced_stream()
+            .gap_timeout(Duration::from_millis(50))
+            .take(4)
+            .collect(),
+    );
+    assert!(started_at.elapsed() >= Duration::from_millis(50));
+    assert_eq!(
+        chunks,
+        [
+            Sequenced::Item(0, 0),
+            Sequenced::Gap(1),
+            Sequenced::Item(2, 2),
+            Sequenced::Item(3, 3)
+        ]
+    );
+    group.cancel_all();
+}
+
+#[test]
+fn a_stalled_sequence_number_is_given_up_on_once_the_buffer_is_full() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    spawn_stuck(&mut group, 1);
+    for sequence in [0, 2, 3, 4, 5, 6] {
+        group.spawn_task_sequenced(sequence, Priority::default(), async move { sequence });
+    }
+    let chunks: Vec<Sequenced<u64>> =
+        block_on(group.sequenced_stream().max_buffered(3).take(7).collect());
+    let mut expected = vec![Sequenced::Item(0, 0), Sequenced::Gap(1)];
+    expected.extend((2..=6).map(|sequence| Sequenced::Item(sequence, sequence)));
+    assert_eq!(chunks, expected);
+    group.cancel_all();
+}
+
+#[test]
+fn results_out_of_sequence_go_back_to_the_spawn_group() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task_sequenced(0, Priority::default(), async { 1 });
+    group.spawn_task(Priority::default(), async { 2 });
+    block_on(group.wait_for_all());
+    // a duplicate of the sequence number already delivered
+    group.spawn_task_sequenced(0, Priority::default(), async { 3 });
+
+    let chunks: Vec<Sequenced<u64>> = block_on(group.sequenced_stream().collect());
+    assert_eq!(chunks, [Sequenced::Item(0, 1)]);
+    let mut rest: Vec<u64> = block_on((&mut group).collect());
+    rest.sort();
+    assert_eq!(rest, [2, 3]);
+}
@@ -0,0 +1,130 @@
+//! Sheds the child tasks below a priority that haven't started yet, leaving the running ones and the others alone
+
+mod common;
+
+use common::wait_until;
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, GroupSnapshot, Priority, SpawnGroup,
+    TaskState,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const BACKLOG: [Priority; 4] = [
+    Priority::BACKGROUND,
+    Priority::LOW,
+    Priority::UTILITY,
+    Priority::MEDIUM,
+];
+
+/// Blocks the worker thread polling it until released, so the child tasks spawned meanwhile stay queued
+async fn block_until(released: Arc<AtomicBool>) {
+    while !released.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn is_running(snapshot: &GroupSnapshot, priority: Priority) -> bool {
+    snapshot
+        .tasks
+        .iter()
+        .any(|task| task.priority == priority && task.state == TaskState::Running)
+}
+
+#[test]
+fn only_the_queued_child_tasks_below_the_priority_are_shed() {
+    let mut group = SpawnGroup::<(Priority, u64)>::new(1);
+    // already running, so kept despite its priority
+    group.spawn_task(Priority::BACKGROUND, async {
+        sleep(Duration::from_millis(100)).await;
+        (Priority::BACKGROUND, 100)
+    });
+    wait_until(|| is_running(&group.snapshot(), Priority::BACKGROUND));
+    let released = Arc::new(AtomicBool::new(false));
+    group.spawn_task(Priority::HIGH, {
+        let released = released.clone();
+        async move {
+            block_until(released).await;
+            (Priority::HIGH, 101)
+        }
+    });
+    wait_until(|| is_running(&group.snapshot(), Priority::HIGH));
+    for index in 0..12 {
+        let priority = BACKLOG[index as usize % BACKLOG.len()];
+        group.spawn_task(priority, async move { (priority, index) });
+    }
+    assert_eq!(group.len(), 14);
+
+    assert_eq!(group.shed_load(Priority::UTILITY), 6);
+    // uncounted right away, without waiting for a worker thread to drop them
+    assert_eq!(group.len(), 8);
+    let shed: Vec<_> = group
+        .snapshot()
+        .tasks
+        .into_iter()
+        .filter(|task| task.state == TaskState::Cancelled)
+        .map(|task| task.priority)
+        .collect();
+    assert_eq!(shed.len(), 6);
+    assert!(shed.iter().all(|&priority| priority < Priority::UTILITY));
+    // nothing is left to shed
+    assert_eq!(group.shed_load(Priority::UTILITY), 0);
+
+    released.store(true, Ordering::SeqCst);
+    let mut results: Vec<_> = block_on((&mut group).collect());
+    results.sort_by_key(|&(_, index)| index);
+    let mut expected: Vec<_> = (0..12)
+        .map(|index| (BACKLOG[index as usize % BACKLOG.len()], index))
+        .filter(|&(priority, _)| priority >= Priority::UTILITY)
+        .collect();
+    expected.extend([(Priority::BACKGROUND, 100), (Priority::HIGH, 101)]);
+    assert_eq!(results, expected);
+    block_on(group.wait_for_all());
+    assert_eq!(group.metrics().cancelled(), 6);
+    assert_eq!(group.metrics().completed(), 8);
+    assert!(!group.is_cancelled());
+
+    // the spawn group goes on accepting child tasks
+    group.spawn_task(Priority::BACKGROUND, async { (Priority::BACKGROUND, 200) });
+    assert_eq!(block_on(group.next()), Some((Priority::BACKGROUND, 200)));
+}
+
+#[test]
+fn the_fallible_and_discarding_spawn_groups_shed_load_too() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(1);
+    let released = Arc::new(AtomicBool::new(false));
+    group.spawn_task(Priority::HIGH, {
+        let released = released.clone();
+        async move {
+            block_until(released).await;
+            Ok(0)
+        }
+    });
+    wait_until(|| is_running(&group.snapshot(), Priority::HIGH));
+    group.spawn_task(Priority::LOW, async { Err("shed".to_string()) });
+    group.spawn_task(Priority::MEDIUM, async { Ok(1) });
+    assert_eq!(group.shed_load(Priority::MEDIUM), 1);
+    released.store(true, Ordering::SeqCst);
+    let mut results: Vec<_> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results, [Ok(0), Ok(1)]);
+
+    let mut group = DiscardingSpawnGroup::new(1);
+    let released = Arc::new(AtomicBool::new(false));
+    group.spawn_task(Priority::HIGH, block_until(released.clone()));
+    wait_until(|| is_running(&group.snapshot(), Priority::HIGH));
+    for priority in BACKLOG {
+        group.spawn_task(priority, async {});
+    }
+    assert_eq!(group.shed_load(Priority::USERINITIATED), 4);
+    released.store(true, Ordering::SeqCst);
+    let counts = block_on(group.wait_for_all());
+    assert_eq!((counts.completed, counts.cancelled), (1, 4));
+}
@@ -0,0 +1,81 @@
+//! Spawns many child tasks in one call, with the same outcome as spawning them one by one
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, DiscardingSpawnGroup, ErrSpawnGroup, Priority, PriorityPolicy, SpawnGroup,
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+fn results(mut group: SpawnGroup<u64>) -> Vec<u64> {
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    results
+}
+
+#[test]
+fn batched_results_match_the_ones_spawned_in_a_loop() {
+    let mut looped = SpawnGroup::<u64>::new(2);
+    for value in 0..10_000 {
+        looped.spawn_task(Priority::default(), async move { value * value });
+    }
+    let mut batched = SpawnGroup::<u64>::new(2);
+    batched.spawn_all(
+        Priority::default(),
+        (0..10_000).map(|value| async move { value * value }),
+    );
+    assert_eq!(results(batched), results(looped));
+}
+
+#[test]
+fn batched_errors_match_the_ones_spawned_in_a_loop() {
+    let task = |value: u64| async move {
+        if value.is_multiple_of(7) {
+            return Err(value);
+        }
+        Ok(value)
+    };
+    let mut looped = ErrSpawnGroup::<u64, u64>::new(2);
+    for value in 0..1000 {
+        looped.spawn_task(Priority::default(), task(value));
+    }
+    let mut batched = ErrSpawnGroup::<u64, u64>::new(2);
+    batched.spawn_all(Priority::default(), (0..1000).map(task));
+
+    let mut looped: Vec<Result<u64, u64>> = block_on((&mut looped).collect());
+    let mut batched: Vec<Result<u64, u64>> = block_on((&mut batched).collect());
+    looped.sort();
+    batched.sort();
+    assert_eq!(batched, looped);
+}
+
+#[test]
+fn every_batched_child_task_of_a_discarding_spawn_group_runs() {
+    let ran: Arc<AtomicU64> = Arc::default();
+    let mut group = DiscardingSpawnGroup::new(2);
+    group.spawn_all(
+        Priority::default(),
+        (0..1000).map(|value| {
+            let ran = ran.clone();
+            async move {
+                ran.fetch_add(value, Ordering::SeqCst);
+            }
+        }),
+    );
+    let counts = block_on(group.wait_for_all());
+    assert_eq!(counts.completed, 1000);
+    assert_eq!(ran.load(Ordering::SeqCst), (0..1000).sum());
+}
+
+#[test]
+fn nothing_is_spawned_once_closed_or_refused() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.priority_policy(PriorityPolicy::Reject(Priority::HIGH..=Priority::HIGH));
+    group.spawn_all(Priority::LOW, (0..10).map(|value| async move { value }));
+    group.spawn_all(Priority::HIGH, (0..10).map(|value| async move { value }));
+    group.close();
+    group.spawn_all(Priority::HIGH, (10..20).map(|value| async move { value }));
+    assert_eq!(results(group), (0..10).collect::<Vec<_>>());
+}
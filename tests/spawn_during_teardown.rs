@@ -0,0 +1,76 @@
+//! Refuses the child tasks spawned by the destructors of the results discarded while a spawn group is torn down
+
+use futures_lite::StreamExt;
+use parking_lot::Mutex;
+use spawn_groups::{block_on, DiscardingSpawnGroup, Priority, SpawnError, SpawnGroup};
+use std::sync::Arc;
+
+type Outcomes = Arc<Mutex<Vec<Result<(), SpawnError>>>>;
+
+/// A result whose destructor spawns a cleanup child task into another spawn group
+struct Cleanup {
+    into: Arc<Mutex<DiscardingSpawnGroup>>,
+    outcomes: Outcomes,
+}
+
+impl Drop for Cleanup {
+    fn drop(&mut self) {
+        let outcome = self
+            .into
+            .lock()
+            .try_spawn_task(Priority::default(), async {});
+        self.outcomes.lock().push(outcome);
+    }
+}
+
+/// A spawn group with the given number of results buffered, each spawning into the returned spawn group once dropped
+fn buffered_cleanups(
+    count: usize,
+) -> (
+    SpawnGroup<Cleanup>,
+    Arc<Mutex<DiscardingSpawnGroup>>,
+    Outcomes,
+) {
+    let into = Arc::new(Mutex::new(DiscardingSpawnGroup::new(1)));
+    let outcomes: Outcomes = Arc::default();
+    let mut group = SpawnGroup::<Cleanup>::new(2);
+    for _ in 0..count {
+        let (into, outcomes) = (into.clone(), outcomes.clone());
+        group.spawn_task(
+            Priority::default(),
+            async move { Cleanup { into, outcomes } },
+        );
+    }
+    block_on(group.wait_for_all());
+    (group, into, outcomes)
+}
+
+#[test]
+fn destructors_of_discarded_results_fail_to_spawn() {
+    let (group, into, outcomes) = buffered_cleanups(5);
+    drop(group);
+    assert_eq!(*outcomes.lock(), [Err(SpawnError::ShuttingDown); 5]);
+    // the refusal only lasts as long as the teardown
+    assert_eq!(
+        into.lock().try_spawn_task(Priority::default(), async {}),
+        Ok(())
+    );
+}
+
+#[test]
+fn results_handed_to_the_discard_hook_fail_to_spawn_as_well() {
+    let (mut group, _into, outcomes) = buffered_cleanups(3);
+    group.on_discarded(drop);
+    drop(group);
+    assert_eq!(*outcomes.lock(), [Err(SpawnError::ShuttingDown); 3]);
+}
+
+#[test]
+fn consumed_results_spawn_from_their_destructors() {
+    let (mut group, into, outcomes) = buffered_cleanups(2);
+    let consumed: Vec<Cleanup> = block_on((&mut group).collect());
+    drop(consumed);
+    assert_eq!(*outcomes.lock(), [Ok(()), Ok(())]);
+    drop(group);
+    block_on(into.lock().wait_for_all());
+}
@@ -0,0 +1,89 @@
+//! Spawns a child task for every item of a stream with a bounded number of them running at once
+
+use futures_lite::{future, stream, StreamExt};
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn at_most_the_limit_of_child_tasks_run_at_once() {
+    let running: Arc<AtomicUsize> = Arc::default();
+    let most_running: Arc<AtomicUsize> = Arc::default();
+    let mut group = SpawnGroup::<u64>::new(4);
+    block_on(
+        group.spawn_from_stream(Priority::default(), 3, stream::iter(0..30), |value| {
+            let (running, most_running) = (running.clone(), most_running.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most_running.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(2)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                value
+            }
+        }),
+    );
+    // the results were left in the stream all along
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results, (0..30).collect::<Vec<_>>());
+    assert!(most_running.load(Ordering::SeqCst) <= 3);
+}
+
+#[test]
+fn results_are_consumed_while_spawning() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let results = group.stream();
+    // the spawner is polled first, so the stream doesn't end before the first child task is spawned
+    let ((), mut results): ((), Vec<u64>) = block_on(future::zip(
+        group.spawn_from_stream(
+            Priority::default(),
+            2,
+            stream::iter(0..50),
+            |value| async move { value },
+        ),
+        results.collect(),
+    ));
+    results.sort();
+    assert_eq!(results, (0..50).collect::<Vec<_>>());
+}
+
+#[test]
+fn cancelling_stops_pulling_from_the_input() {
+    let pulled: Arc<AtomicUsize> = Arc::default();
+    let mut group = SpawnGroup::<u64>::new(2);
+    let input = {
+        let pulled = pulled.clone();
+        stream::iter(0..).inspect(move |_| _ = pulled.fetch_add(1, Ordering::SeqCst))
+    };
+    let spawning = group.spawn_from_stream(Priority::default(), 2, input, |value| async move {
+        sleep(Duration::from_secs(3600)).await;
+        value
+    });
+    // both child tasks never finish, so the spawner waits until it's dropped
+    block_on(future::or(spawning, sleep(Duration::from_millis(50))));
+    group.cancel_all();
+    assert_eq!(pulled.load(Ordering::SeqCst), 2);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(pulled.load(Ordering::SeqCst), 2);
+    assert_eq!(block_on((&mut group).count()), 0);
+}
+
+#[test]
+fn a_refused_child_task_stops_the_spawner() {
+    let pulled: Arc<AtomicUsize> = Arc::default();
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.close();
+    let input = {
+        let pulled = pulled.clone();
+        stream::iter(0..10).inspect(move |_| _ = pulled.fetch_add(1, Ordering::SeqCst))
+    };
+    block_on(group.spawn_from_stream(Priority::default(), 2, input, |value| async move { value }));
+    assert_eq!(pulled.load(Ordering::SeqCst), 1);
+    assert!(group.is_empty());
+}
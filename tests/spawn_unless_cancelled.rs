@@ -0,0 +1,57 @@
+//! Tells whether ``spawn_task_unless_cancelled`` spawned the child task, keeping the counters of every spawn group in step
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup};
+
+#[test]
+fn the_guarded_spawn_reports_the_child_tasks_it_refused() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    assert!(group.spawn_task_unless_cancelled(Priority::default(), async { 1 }));
+    assert!(group.spawn_task_unless_cancelled(Priority::default(), async { 2 }));
+    assert_eq!(group.len(), 2);
+    assert!(!group.is_empty());
+
+    group.cancel_all();
+    assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 3 }));
+    assert!(group.is_empty());
+    assert_eq!(group.len(), 0);
+    assert_eq!(group.metrics().spawned(), 2);
+
+    group.reset();
+    assert!(group.spawn_task_unless_cancelled(Priority::default(), async { 4 }));
+    assert_eq!(group.len(), 1);
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [4]);
+
+    // a closed spawn group refuses it as well
+    group.close();
+    assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 5 }));
+    assert_eq!(group.metrics().spawned(), 3);
+}
+
+#[test]
+fn the_fallible_and_discarding_spawn_groups_count_the_guarded_spawns_too() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    assert!(group.spawn_task_unless_cancelled(Priority::default(), async { Ok(1) }));
+    assert!(
+        group.spawn_task_unless_cancelled(Priority::default(), async { Err("failed".to_string()) })
+    );
+    // counted like the child tasks of spawn_task
+    group.spawn_task(Priority::default(), async { Ok(2) });
+    assert_eq!(group.len(), 3);
+    assert!(!group.is_empty());
+    let mut results = block_on(group.next_chunk(3));
+    results.sort();
+    assert_eq!(results, [Ok(1), Ok(2), Err("failed".to_string())]);
+
+    group.cancel_all();
+    assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { Ok(3) }));
+    assert!(group.is_empty());
+    assert_eq!(group.metrics().spawned(), 3);
+
+    let mut group = DiscardingSpawnGroup::new(2);
+    assert!(group.spawn_task_unless_cancelled(Priority::default(), async {}));
+    assert_eq!(block_on(group.wait_for_all()).completed, 1);
+    group.cancel_all();
+    assert!(!group.spawn_task_unless_cancelled(Priority::default(), async {}));
+    assert_eq!(group.metrics().spawned(), 1);
+}
@@ -0,0 +1,89 @@
+//! Suspends the spawner until the pending child tasks leave room under the queue capacity
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnError, SpawnGroup,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn running_child_tasks_never_exceed_the_capacity() {
+    let running = Arc::new(AtomicUsize::new(0));
+    let most_running = Arc::new(AtomicUsize::new(0));
+    let mut group = DiscardingSpawnGroup::new(4);
+    group.queue_capacity(3);
+    block_on(async {
+        for _ in 0..30 {
+            let (running, most_running) = (running.clone(), most_running.clone());
+            let spawned = group
+                .spawn_task_when_ready(Priority::default(), async move {
+                    let now = running.fetch_add(1, Ordering::AcqRel) + 1;
+                    most_running.fetch_max(now, Ordering::AcqRel);
+                    sleep(Duration::from_millis(5)).await;
+                    running.fetch_sub(1, Ordering::AcqRel);
+                })
+                .await;
+            assert_eq!(spawned, Ok(()));
+        }
+    });
+    let counts = block_on(group.wait_for_all());
+    assert_eq!(counts.completed, 30);
+    assert!(most_running.load(Ordering::Acquire) <= 3);
+}
+
+#[test]
+fn without_a_capacity_the_child_task_is_spawned_right_away() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    block_on(async {
+        for value in 0..10 {
+            group
+                .spawn_task_when_ready(Priority::default(), async move {
+                    sleep(Duration::from_secs(3600)).await;
+                    value
+                })
+                .await
+                .unwrap();
+        }
+    });
+    assert_eq!(group.pending_tasks(), 10);
+    group.cancel_all();
+}
+
+#[test]
+fn every_result_is_delivered() {
+    let mut group = ErrSpawnGroup::<u64, u64>::new(2);
+    group.queue_capacity(1);
+    block_on(async {
+        for value in 0..20 {
+            group
+                .spawn_task_when_ready(Priority::default(), async move {
+                    if value % 2 == 0 {
+                        return Err(value);
+                    }
+                    Ok(value)
+                })
+                .await
+                .unwrap();
+        }
+    });
+    let results: Vec<Result<u64, u64>> = block_on((&mut group).collect());
+    assert_eq!(results.len(), 20);
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 10);
+}
+
+#[test]
+fn a_closed_spawn_group_reports_being_closed() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.queue_capacity(0);
+    group.close();
+    assert_eq!(
+        block_on(group.spawn_task_when_ready(Priority::default(), async { 0 })),
+        Err(SpawnError::Closed)
+    );
+}
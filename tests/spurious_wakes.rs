@@ -0,0 +1,111 @@
+//! Survives futures waking their waker after they completed
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, Priority, SpawnGroup};
+use std::{
+    future::poll_fn,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Completes right away, keeping its waker to wake it afterwards
+async fn complete_keeping_waker(kept: &Mutex<Option<Waker>>) {
+    poll_fn(|cx| {
+        *kept.lock().unwrap() = Some(cx.waker().clone());
+        Poll::Ready(())
+    })
+    .await
+}
+
+/// Stays pending until another thread wakes it after the delay, counting its polls
+fn woken_after(delay: Duration, polls: &AtomicUsize) {
+    let mut waking: Option<thread::JoinHandle<()>> = None;
+    let started_at = Instant::now();
+    block_on(poll_fn(|cx| {
+        polls.fetch_add(1, Ordering::SeqCst);
+        if started_at.elapsed() >= delay {
+            return Poll::Ready(());
+        }
+        if waking.is_none() {
+            let waker = cx.waker().clone();
+            waking = Some(thread::spawn(move || {
+                thread::sleep(delay);
+                waker.wake();
+            }));
+        }
+        Poll::Pending
+    }));
+    waking.unwrap().join().unwrap();
+}
+
+#[test]
+fn a_late_wake_doesnt_cut_the_next_suspension_short() {
+    let kept: Mutex<Option<Waker>> = Mutex::new(None);
+    block_on(complete_keeping_waker(&kept));
+    // the future has completed, so this wake is meant for nobody
+    kept.lock().unwrap().take().unwrap().wake();
+
+    let polls = AtomicUsize::new(0);
+    woken_after(Duration::from_millis(50), &polls);
+    assert_eq!(polls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn repeated_late_wakes_dont_busy_loop() {
+    let kept: Mutex<Option<Waker>> = Mutex::new(None);
+    block_on(complete_keeping_waker(&kept));
+    let waker = kept.lock().unwrap().take().unwrap();
+    let waking = thread::spawn(move || {
+        for _ in 0..10_000 {
+            waker.wake_by_ref();
+        }
+    });
+
+    let polls = AtomicUsize::new(0);
+    woken_after(Duration::from_millis(50), &polls);
+    waking.join().unwrap();
+    assert_eq!(polls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn child_tasks_waking_after_completion_run_once() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let wakers: Arc<Mutex<Vec<Waker>>> = Arc::default();
+    let mut group = SpawnGroup::<usize>::new(2);
+    let metrics = group.metrics();
+    for value in 0..100 {
+        let runs = runs.clone();
+        let wakers = wakers.clone();
+        group.spawn_task(Priority::default(), async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            poll_fn(|cx| {
+                wakers.lock().unwrap().push(cx.waker().clone());
+                Poll::Ready(())
+            })
+            .await;
+            value
+        });
+    }
+    let waking = {
+        let wakers = wakers.clone();
+        thread::spawn(move || {
+            for _ in 0..20 {
+                wakers.lock().unwrap().iter().for_each(Waker::wake_by_ref);
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+
+    let mut results: Vec<usize> = block_on((&mut group).collect());
+    waking.join().unwrap();
+    results.sort();
+    assert_eq!(results, (0..100).collect::<Vec<_>>());
+    assert_eq!(runs.load(Ordering::SeqCst), 100);
+    assert_eq!(metrics.results_produced(), 100);
+    assert_eq!(metrics.results_consumed(), 100);
+}
@@ -0,0 +1,71 @@
+//! Passes reference counted results from many child tasks to a single consumer
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+type Page = Arc<[u8; 4096]>;
+
+fn group_sharing(page: &Page, threads: usize, results: usize) -> SpawnGroup<Page> {
+    let mut group = SpawnGroup::<Page>::new(threads);
+    for _ in 0..results {
+        let page = page.clone();
+        group.spawn_task(Priority::default(), async move { page });
+    }
+    group
+}
+
+#[test]
+fn every_result_is_handed_over_without_a_copy() {
+    let page: Page = Arc::new([7; 4096]);
+    let mut group = group_sharing(&page, 4, 1_000);
+    let results: Vec<Page> = block_on((&mut group).collect());
+    assert_eq!(results.len(), 1_000);
+    assert!(results.iter().all(|result| Arc::ptr_eq(result, &page)));
+    drop(results);
+    drop(group);
+    assert_eq!(Arc::strong_count(&page), 1);
+}
+
+#[test]
+fn results_outliving_a_reset_are_released() {
+    let page: Page = Arc::new([7; 4096]);
+    let mut group = SpawnGroup::<Page>::new(4);
+    for delay in 0..200 {
+        let page = page.clone();
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_micros(delay * 10)).await;
+            page
+        });
+    }
+    thread::sleep(Duration::from_millis(1));
+    group.reset();
+
+    let started_at = Instant::now();
+    while Arc::strong_count(&page) > 1 && started_at.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(Arc::strong_count(&page), 1);
+    assert_eq!(block_on(group.count()), 0);
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn many_producers_one_consumer() {
+    const RESULTS: usize = 100_000;
+    let page: Page = Arc::new([7; 4096]);
+    for threads in [1, 4, 16] {
+        let mut group = group_sharing(&page, threads, RESULTS);
+        let started_at = Instant::now();
+        let count = block_on((&mut group).count());
+        println!(
+            "{threads:>2} producers: {RESULTS} results in {:?}",
+            started_at.elapsed()
+        );
+        assert_eq!(count, RESULTS);
+    }
+}
@@ -0,0 +1,53 @@
+//! Consumes the stream of a spawn group faster than its child tasks produce results, which must never end it early
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::time::Duration;
+
+#[test]
+fn eager_consumer_receives_every_staggered_result() {
+    let mut group = SpawnGroup::<u64>::new(4);
+    for value in 0..50 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(value * 2)).await;
+            value
+        });
+    }
+
+    let mut received: Vec<u64> = block_on(async {
+        let mut received = vec![];
+        while let Some(value) = group.next().await {
+            received.push(value);
+        }
+        received
+    });
+    received.sort();
+    assert_eq!(received, (0..50).collect::<Vec<_>>());
+}
+
+#[test]
+fn the_buffer_running_dry_between_bursts_never_ends_the_stream() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for burst in 0..3 {
+        for value in 0..5 {
+            group.spawn_task(Priority::default(), async move {
+                sleep(Duration::from_millis(burst * 100)).await;
+                match value {
+                    0 => Err(format!("burst {burst} failed")),
+                    _ => Ok(burst * 10 + value),
+                }
+            });
+        }
+    }
+
+    let (mut values, mut errors) = (0, 0);
+    block_on(async {
+        while let Some(result) = group.next().await {
+            match result {
+                Ok(_) => values += 1,
+                Err(_) => errors += 1,
+            }
+        }
+    });
+    assert_eq!((values, errors), (12, 3));
+}
@@ -0,0 +1,93 @@
+//! Waits for the child tasks spawned through a subset of a spawn group, while the rest of the spawn group goes on
+
+mod common;
+
+use common::{spawn_never_finishing, PROMPTLY};
+use spawn_groups::{block_on, sleep, Priority, SpawnError, SpawnGroup, SubsetWait};
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Awaits the future on another thread and returns a receiver signalled once it completed
+fn waited_elsewhere(wait: SubsetWait) -> mpsc::Receiver<()> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        block_on(wait);
+        _ = sender.send(());
+    });
+    receiver
+}
+
+#[test]
+fn waits_only_for_the_subset() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    _ = spawn_never_finishing(&mut group);
+
+    let mut subset = group.subset();
+    for value in 1..=10 {
+        subset.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(value)).await;
+            value
+        });
+    }
+    let started_at = Instant::now();
+    block_on(subset.wait());
+    assert!(started_at.elapsed() < PROMPTLY);
+    assert_eq!(subset.remaining(), 0);
+    drop(subset);
+
+    // the results of the subset went into the spawn group, which still runs its other child task
+    assert_eq!(group.snapshot().buffered, 10);
+    assert!(!group.is_empty());
+    group.cancel_all();
+}
+
+#[test]
+fn cancelling_completes_the_waits() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let mut subset = group.subset();
+    subset.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+    let waits: Vec<_> = (0..3).map(|_| waited_elsewhere(subset.wait())).collect();
+    thread::sleep(Duration::from_millis(50));
+
+    subset.cancel_all();
+    for wait in waits {
+        wait.recv_timeout(PROMPTLY)
+            .expect("the wait never completed");
+    }
+}
+
+#[test]
+fn dropping_the_group_completes_the_waits() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    let mut subset = group.subset();
+    subset.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+    let wait = waited_elsewhere(subset.wait());
+    drop(subset);
+    thread::sleep(Duration::from_millis(50));
+
+    drop(group);
+    wait.recv_timeout(PROMPTLY)
+        .expect("the wait never completed");
+}
+
+#[test]
+fn refused_child_tasks_are_not_waited_for() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.close();
+    let mut subset = group.subset();
+    assert_eq!(
+        subset.try_spawn_task(Priority::default(), async { 1 }),
+        Err(SpawnError::Closed)
+    );
+    assert_eq!(subset.remaining(), 0);
+    block_on(subset.wait());
+}
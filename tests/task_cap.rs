@@ -0,0 +1,81 @@
+//! Caps the number of child tasks a spawn group spawns over its lifetime
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, ErrSpawnGroup, Priority, SpawnError, SpawnGroup, TaskCapPolicy};
+use std::panic::{self, AssertUnwindSafe};
+
+#[test]
+fn every_way_of_spawning_counts_towards_the_cap() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 0 });
+    group.set_max_tasks(5, TaskCapPolicy::Refuse);
+    group.spawn_all(
+        Priority::default(),
+        (1..3).map(|value| async move { value }),
+    );
+    let handle = group.spawn_task_with_handle(Priority::default(), async { 3 });
+    assert!(group
+        .try_spawn_task(Priority::default(), async { 4 })
+        .is_ok());
+
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { 5 }),
+        Err(SpawnError::TaskCapReached(5))
+    );
+    let refused = group.spawn_task_with_handle(Priority::default(), async { 6 });
+    assert!(refused.is_finished());
+    assert_eq!(block_on(refused), None);
+    assert_eq!(block_on(handle), Some(3));
+
+    let mut results: Vec<u64> = block_on((&mut group).collect());
+    results.sort();
+    assert_eq!(results, [0, 1, 2, 4]);
+    assert_eq!(group.metrics().spawns_over_cap(), 2);
+}
+
+#[test]
+fn a_batch_going_beyond_the_cap_is_refused_as_a_whole() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.set_max_tasks(10, TaskCapPolicy::Refuse);
+    group.spawn_all(
+        Priority::default(),
+        (0..8).map(|value| async move { value }),
+    );
+    group.spawn_all(
+        Priority::default(),
+        (8..11).map(|value| async move { value }),
+    );
+    group.spawn_all(
+        Priority::default(),
+        (11..13).map(|value| async move { value }),
+    );
+
+    assert_eq!(block_on((&mut group).count()), 10);
+    assert_eq!(group.metrics().spawns_over_cap(), 3);
+}
+
+#[test]
+fn resetting_never_lifts_the_cap() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.set_max_tasks(2, TaskCapPolicy::Refuse);
+    group.spawn_task(Priority::default(), async { Ok(0) });
+    group.reset();
+    group.spawn_task(Priority::default(), async { Ok(1) });
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { Ok(2) }),
+        Err(SpawnError::TaskCapReached(2))
+    );
+    block_on(group.wait_for_all());
+}
+
+#[test]
+fn spawning_beyond_the_cap_panics_when_told_to() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.set_max_tasks(1, TaskCapPolicy::Panic);
+    group.spawn_task(Priority::default(), async { 0 });
+    let spawned = panic::catch_unwind(AssertUnwindSafe(|| {
+        group.spawn_task(Priority::default(), async { 1 });
+    }));
+    assert!(spawned.is_err());
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [0]);
+}
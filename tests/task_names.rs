@@ -0,0 +1,95 @@
+//! Tells the child tasks spawned with a name apart in the snapshots, the debug output and the panic reports
+
+use futures_lite::StreamExt;
+use spawn_groups::{
+    block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, PanicOutput, Priority, SpawnGroup,
+};
+use std::{sync::Mutex, thread, time::Duration};
+
+static REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(report: &str) {
+    REPORTS.lock().unwrap().push(report.to_string());
+}
+
+#[test]
+fn the_names_of_the_child_tasks_in_flight_are_listed() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task_named("quick", Priority::default(), async { 0 });
+    group.spawn_task_named("resize thumbnails", Priority::HIGH, async {
+        sleep(Duration::from_secs(3600)).await;
+        1
+    });
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        2
+    });
+    assert_eq!(block_on(group.next()), Some(0));
+    // the completed child task is let go of right after delivering its result
+    while group.snapshot().tasks.len() > 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let snapshot = group.snapshot();
+    let names: Vec<_> = snapshot
+        .tasks
+        .iter()
+        .map(|task| task.name.as_deref())
+        .collect();
+    assert_eq!(names, [Some("resize thumbnails"), None]);
+    assert_eq!(snapshot.tasks[0].priority, Priority::HIGH);
+    let debug = format!("{group:?}");
+    assert!(
+        debug.contains("in_flight: [\"resize thumbnails\"]"),
+        "{debug}"
+    );
+    assert!(!debug.contains("quick"));
+
+    // the cancelled child tasks are no longer in flight
+    group.cancel_all();
+    assert!(format!("{group:?}").contains("in_flight: []"));
+    assert_eq!(
+        group.snapshot().tasks[0].name.as_deref(),
+        Some("resize thumbnails")
+    );
+
+    // the child tasks of an exclusive batch are never listed, so a named one is refused
+    group.reset();
+    group.exclusive_batch(1);
+    group.spawn_task_named("batched", Priority::default(), async { 3 });
+    assert_eq!(group.len(), 0);
+    group.spawn_task(Priority::default(), async { 4 });
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [4]);
+}
+
+#[test]
+fn a_panic_report_names_the_child_task() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    group.panic_output(PanicOutput::Handler(record));
+    group.spawn_task_named("parse manifest", Priority::default(), async {
+        panic!("unexpected end of file")
+    });
+    group.spawn_task(Priority::default(), async { panic!("unnamed failure") });
+    group.spawn_task_named("read header", Priority::default(), async { Ok(1) });
+    assert_eq!(block_on((&mut group).collect::<Vec<_>>()), [Ok(1)]);
+
+    let mut group = DiscardingSpawnGroup::new(1);
+    group.panic_output(PanicOutput::Handler(record));
+    group.spawn_task_named("flush cache", Priority::default(), async {
+        panic!("the disk is full")
+    });
+    block_on(group.wait_for_all());
+
+    let reports = REPORTS.lock().unwrap();
+    assert_eq!(reports.len(), 3);
+    let report_of = |message: &str| {
+        reports
+            .iter()
+            .find(|report| report.contains(message))
+            .unwrap()
+            .clone()
+    };
+    assert!(report_of("unexpected end of file").contains("in child task 'parse manifest'"));
+    assert!(report_of("the disk is full").contains("in child task 'flush cache'"));
+    assert!(!report_of("unnamed failure").contains("in child task"));
+}
@@ -0,0 +1,106 @@
+//! Tears a spawn group down while a consumer on another thread is blocked waiting on its stream,
+//! which must always end the consumer's wait with a terminal None
+
+mod common;
+
+use common::PROMPTLY;
+use futures_lite::{Stream, StreamExt};
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup};
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Spawns a consumer blocked on the stream and returns the results it saw once its wait ended
+fn blocked_consumer(
+    mut stream: impl Stream<Item = u64> + Send + Unpin + 'static,
+) -> mpsc::Receiver<Vec<u64>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let results: Vec<u64> = block_on(async {
+            let mut results: Vec<u64> = vec![];
+            while let Some(value) = stream.next().await {
+                results.push(value);
+            }
+            results
+        });
+        _ = sender.send(results);
+    });
+    // gives the consumer time to register its waker
+    thread::sleep(Duration::from_millis(50));
+    receiver
+}
+
+fn never_finishing_group() -> SpawnGroup<u64> {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for i in 0..4 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_secs(3600)).await;
+            i
+        });
+    }
+    group
+}
+
+#[test]
+fn drop_without_waiting_unblocks_consumer() {
+    let mut group = never_finishing_group();
+    group.dont_wait_at_drop();
+    let consumer = blocked_consumer(group.stream());
+
+    let dropped_at = Instant::now();
+    drop(group);
+    let results: Vec<u64> = consumer
+        .recv_timeout(PROMPTLY)
+        .expect("consumer stayed blocked");
+    assert!(results.is_empty());
+    assert!(dropped_at.elapsed() < PROMPTLY);
+}
+
+#[test]
+fn drop_after_waiting_unblocks_consumer() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for i in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(100)).await;
+            i
+        });
+    }
+    let consumer = blocked_consumer(group.stream());
+
+    block_on(group.wait_for_all());
+    drop(group);
+    let mut results: Vec<u64> = consumer
+        .recv_timeout(PROMPTLY)
+        .expect("consumer stayed blocked");
+    results.sort();
+    assert_eq!(results, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn cancel_unblocks_consumer() {
+    let mut group = never_finishing_group();
+    let consumer = blocked_consumer(group.stream());
+
+    group.cancel_all();
+    let results: Vec<u64> = consumer
+        .recv_timeout(PROMPTLY)
+        .expect("consumer stayed blocked");
+    assert!(results.is_empty());
+}
+
+#[test]
+fn every_consumer_is_unblocked() {
+    let mut group = never_finishing_group();
+    group.dont_wait_at_drop();
+    let consumers: Vec<_> = (0..4).map(|_| blocked_consumer(group.stream())).collect();
+
+    drop(group);
+    for consumer in consumers {
+        assert!(consumer
+            .recv_timeout(PROMPTLY)
+            .expect("consumer stayed blocked")
+            .is_empty());
+    }
+}
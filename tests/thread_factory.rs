@@ -0,0 +1,115 @@
+//! Creates spawn groups over a thread factory, which must create every thread they need
+//! and whose errors must surface from the fallible constructors
+
+use spawn_groups::{block_on, Priority, SpawnError, SpawnGroup, ThreadSpec};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// A factory that records the threads it creates, fails once it created `limit` of them
+/// and counts the threads still running
+#[derive(Clone, Default)]
+struct CountingFactory {
+    names: Arc<Mutex<Vec<String>>>,
+    running: Arc<AtomicUsize>,
+    limit: Option<usize>,
+}
+
+impl CountingFactory {
+    fn failing_after(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    fn create(&self, spec: ThreadSpec) -> io::Result<thread::JoinHandle<()>> {
+        let mut names = self.names.lock().unwrap();
+        if self.limit.is_some_and(|limit| names.len() == limit) {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        }
+        names.push(spec.name.clone());
+        let running: Arc<AtomicUsize> = self.running.clone();
+        running.fetch_add(1, Ordering::AcqRel);
+        let entry = spec.entry;
+        ThreadSpec {
+            entry: Box::new(move || {
+                entry();
+                running.fetch_sub(1, Ordering::AcqRel);
+            }),
+            ..spec
+        }
+        .spawn()
+    }
+
+    fn into_fn(self) -> impl Fn(ThreadSpec) -> io::Result<thread::JoinHandle<()>> + Send + Sync {
+        move |spec| self.create(spec)
+    }
+
+    fn created(&self) -> Vec<String> {
+        self.names.lock().unwrap().clone()
+    }
+}
+
+#[test]
+fn every_thread_goes_through_the_factory() {
+    let factory = CountingFactory::default();
+    let mut group = SpawnGroup::<u64>::with_thread_factory(3, factory.clone().into_fn()).unwrap();
+    for value in 0..20 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    block_on(group.wait_for_all());
+
+    let mut created: Vec<String> = factory.created();
+    created.sort();
+    assert_eq!(
+        created,
+        [
+            "Executor",
+            "ThreadPool #0",
+            "ThreadPool #1",
+            "ThreadPool #2"
+        ]
+    );
+
+    // the thread running the child tasks is created again after a cancellation
+    group.cancel_all();
+    group.spawn_task(Priority::default(), async { 20 });
+    assert_eq!(factory.created().len(), 5);
+    assert_eq!(factory.created()[4], "Executor");
+}
+
+#[test]
+fn factory_errors_surface_from_the_constructor() {
+    for limit in [0, 2, 4] {
+        let factory = CountingFactory::failing_after(limit);
+        let Err(error) = SpawnGroup::<u64>::with_thread_factory(4, factory.clone().into_fn())
+        else {
+            panic!("the factory failed after {limit} threads");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        // the worker threads created before the error were stopped
+        assert_eq!(factory.running.load(Ordering::Acquire), 0);
+    }
+}
+
+#[test]
+fn factory_errors_surface_when_spawning_after_a_cancellation() {
+    let factory = CountingFactory::failing_after(3);
+    let mut group = SpawnGroup::<u64>::with_thread_factory(2, factory.into_fn()).unwrap();
+    group.spawn_task(Priority::default(), async { 1 });
+    assert_eq!(block_on(group.first()), Some(1));
+
+    group.cancel_all();
+    assert_eq!(
+        group.try_spawn_task(Priority::default(), async { 2 }),
+        Err(SpawnError::ThreadUnavailable(
+            io::ErrorKind::PermissionDenied
+        ))
+    );
+}
@@ -0,0 +1,122 @@
+//! Reports where traced child tasks are suspended, from the last ``trace_point!`` they went through
+
+use spawn_groups::{
+    block_on, sleep, trace_point, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup,
+    TaskSnapshot,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Waits until the only child task listed in the spawn group is suspended after the given label
+fn suspended_at(group: &SpawnGroup<u8>, label: &str) -> TaskSnapshot {
+    loop {
+        let snapshot = group.snapshot();
+        let task = snapshot.tasks[0].clone();
+        if task.trace_point == Some(label) && task.suspended_for.is_some() {
+            return task;
+        }
+        thread::yield_now();
+    }
+}
+
+#[test]
+fn the_dump_follows_the_trace_points() {
+    let mut group = SpawnGroup::<u8>::new(1);
+    group.trace_suspensions(true);
+    let proceed = Arc::new(AtomicBool::new(false));
+    let flag = proceed.clone();
+    group.spawn_task_named("fetch", Priority::default(), async move {
+        trace_point!("connect");
+        while !flag.load(Ordering::Acquire) {
+            sleep(Duration::from_millis(5)).await;
+        }
+        trace_point!("db_query");
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+
+    let task = suspended_at(&group, "connect");
+    assert!(task.polls >= 1);
+    let state = group.dump_state();
+    assert!(
+        state.starts_with("spawned: 1, pending: 1, buffered: 0"),
+        "{state}"
+    );
+    assert!(
+        state.contains("\ntask 0 (fetch) pending at 'connect' for 0s"),
+        "{state}"
+    );
+
+    // the label moves along with the child task, and the polls keep being counted
+    proceed.store(true, Ordering::Release);
+    let moved = suspended_at(&group, "db_query");
+    assert!(moved.polls > task.polls);
+    let state = group.dump_state();
+    assert!(state.contains("pending at 'db_query'"), "{state}");
+    assert!(!state.contains("connect"), "{state}");
+
+    group.cancel_all();
+    assert!(group.dump_state().ends_with("task 0 (fetch) cancelled"));
+}
+
+#[test]
+fn nothing_is_recorded_unless_traced() {
+    let mut group = SpawnGroup::<u8>::new(1);
+    group.spawn_task(Priority::default(), async {
+        trace_point!("untraced");
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+    group.trace_suspensions(true);
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_secs(3600)).await;
+        0
+    });
+    while group.snapshot().tasks[1].suspended_for.is_none() {
+        thread::yield_now();
+    }
+    let snapshot = group.snapshot();
+    assert_eq!(snapshot.tasks[0].trace_point, None);
+    assert_eq!(snapshot.tasks[0].polls, 0);
+    assert_eq!(snapshot.tasks[0].suspended_for, None);
+    assert!(snapshot.tasks[0]
+        .to_string()
+        .starts_with("task 0 running for "));
+    // a traced child task without any trace point still tells how long it's been suspended
+    assert!(snapshot.tasks[1].polls >= 1);
+    assert!(snapshot.tasks[1]
+        .to_string()
+        .starts_with("task 1 pending for 0s after "));
+    group.cancel_all();
+
+    // trace points outside of child tasks are ignored
+    trace_point!("outside");
+
+    let mut group = ErrSpawnGroup::<u8, String>::new(1);
+    group.trace_suspensions(true);
+    group.spawn_task(Priority::default(), async {
+        trace_point!("err");
+        sleep(Duration::from_secs(3600)).await;
+        Ok(0)
+    });
+    while !group.dump_state().contains("pending at 'err'") {
+        thread::yield_now();
+    }
+    group.cancel_all();
+
+    let mut group = DiscardingSpawnGroup::new(1);
+    group.trace_suspensions(true);
+    group.spawn_task(Priority::default(), async {
+        trace_point!("discarded");
+    });
+    block_on(group.wait_for_all());
+    let state = group.dump_state();
+    assert!(state.starts_with("spawned: 1, pending: 0"), "{state}");
+    assert!(!state.contains("task"), "{state}");
+}
@@ -0,0 +1,72 @@
+//! Takes the results already buffered without waiting with ``try_next`` and ``drain_ready``
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn the_stream_still_ends_after_results_taken_without_waiting() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..6 {
+        group.spawn_task(Priority::default(), async move { value });
+    }
+    let slow = released.clone();
+    group.spawn_task(Priority::default(), async move {
+        while !slow.load(Ordering::SeqCst) {
+            sleep(Duration::from_millis(1)).await;
+        }
+        6
+    });
+    while group.buffered_results() < 6 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let mut results = vec![group.try_next().unwrap()];
+    results.extend(group.drain_ready());
+    // the slow child task is still pending
+    assert_eq!(group.try_next(), None);
+    assert!(group.drain_ready().is_empty());
+    assert_eq!(group.len(), 1);
+
+    released.store(true, Ordering::SeqCst);
+    results.push(block_on(group.next()).unwrap());
+    assert_eq!(block_on(group.next()), None);
+    results.sort();
+    assert_eq!(results, [0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn a_fallible_spawn_group_hands_out_its_results_without_waiting() {
+    let mut group = ErrSpawnGroup::<u64, String>::new(2);
+    for value in 0..4 {
+        group.spawn_task(Priority::default(), async move {
+            match value % 2 {
+                0 => Ok(value),
+                _ => Err(format!("{value} failed")),
+            }
+        });
+    }
+    block_on(group.wait_for_all());
+    let first = group.try_next().unwrap();
+    let mut rest = group.drain_ready();
+    rest.push(first);
+    rest.sort();
+    assert_eq!(
+        rest,
+        [
+            Ok(0),
+            Ok(2),
+            Err("1 failed".to_string()),
+            Err("3 failed".to_string())
+        ]
+    );
+    assert_eq!(group.try_next(), None);
+    assert_eq!(block_on(group.next()), None);
+}
@@ -0,0 +1,92 @@
+//! Waits for the child tasks for at most a given duration, leaving the stragglers running
+
+use futures_lite::StreamExt;
+use spawn_groups::{block_on, sleep, DiscardingSpawnGroup, ErrSpawnGroup, Priority, SpawnGroup};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+// a child task that only finishes once released
+async fn straggler(released: Arc<AtomicBool>) {
+    while !released.load(Ordering::SeqCst) {
+        sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test]
+fn every_child_task_finishing_in_time_resolves_true() {
+    let mut group = SpawnGroup::<u64>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            sleep(Duration::from_millis(10)).await;
+            value
+        });
+    }
+    assert!(block_on(group.wait_timeout(Duration::from_secs(10))));
+    let mut values: Vec<u64> = block_on((&mut group).collect());
+    values.sort();
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn an_empty_spawn_group_resolves_true_right_away() {
+    let mut group = DiscardingSpawnGroup::new(1);
+    assert!(block_on(group.wait_timeout(Duration::ZERO)));
+}
+
+#[test]
+fn a_straggler_times_the_wait_out_and_keeps_running() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = SpawnGroup::<u64>::new(2);
+    group.spawn_task(Priority::default(), async { 1 });
+    let gate = released.clone();
+    group.spawn_task(Priority::default(), async move {
+        straggler(gate).await;
+        2
+    });
+
+    assert_eq!(block_on(group.first()), Some(1));
+
+    let started = Instant::now();
+    assert!(!block_on(group.wait_timeout(Duration::from_millis(50))));
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert_eq!(group.snapshot().pending, 1);
+
+    // the straggler wasn't cancelled, so waiting again once it's released sees it through
+    released.store(true, Ordering::SeqCst);
+    assert!(block_on(group.wait_timeout(Duration::from_secs(10))));
+    let values: Vec<u64> = block_on((&mut group).collect());
+    assert_eq!(values, [2]);
+}
+
+#[test]
+fn the_caller_cancels_the_stragglers_once_the_wait_timed_out() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = ErrSpawnGroup::<u64, ()>::new(2);
+    for value in 0..4 {
+        let released = released.clone();
+        group.spawn_task(Priority::default(), async move {
+            straggler(released).await;
+            Ok(value)
+        });
+    }
+    assert!(!block_on(group.wait_timeout(Duration::from_millis(20))));
+    group.cancel_all();
+    assert!(block_on(group.wait_timeout(Duration::from_secs(10))));
+    assert!(group.is_empty());
+}
+
+#[test]
+fn the_discarding_spawn_group_times_out_as_well() {
+    let released = Arc::new(AtomicBool::new(false));
+    let mut group = DiscardingSpawnGroup::new(2);
+    group.spawn_task(Priority::default(), straggler(released.clone()));
+    assert!(!block_on(group.wait_timeout(Duration::from_millis(20))));
+    released.store(true, Ordering::SeqCst);
+    assert!(block_on(group.wait_timeout(Duration::from_secs(10))));
+    assert_eq!(block_on(group.wait_for_all()).completed, 1);
+}
@@ -0,0 +1,83 @@
+//! Records the wakes of child tasks and finds the ones that were never woken after suspending
+#![cfg(feature = "wake-trace")]
+
+use spawn_groups::{block_on, sleep, Priority, SpawnGroup, WakeEventKind};
+use std::{
+    future::{pending, poll_fn},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn a_child_task_never_woken_is_reported() {
+    let mut group = SpawnGroup::<u8>::new(2);
+    // suspends without handing its waker to anyone
+    group.spawn_task(Priority::default(), pending());
+    group.spawn_task(Priority::default(), async {
+        sleep(Duration::from_millis(10)).await;
+        1
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let trace = group.wake_trace();
+    let stalled = trace.stalled_tasks();
+    assert_eq!(stalled.len(), 1);
+    assert_eq!(stalled[0].kind, WakeEventKind::Suspended);
+    let task = stalled[0].task;
+    assert!(!trace
+        .events
+        .iter()
+        .any(|event| event.task == task && event.kind == WakeEventKind::Woken));
+    group.cancel_all();
+}
+
+#[test]
+fn woken_child_tasks_are_not_reported() {
+    let mut group = SpawnGroup::<u8>::new(2);
+    for value in 0..10 {
+        group.spawn_task(Priority::default(), async move {
+            let ready = Arc::new(AtomicBool::new(false));
+            let waker_slot: Arc<Mutex<Option<Waker>>> = Arc::default();
+            let mut waiting = false;
+            poll_fn(|cx| {
+                if ready.load(Ordering::Acquire) {
+                    return Poll::Ready(value);
+                }
+                *waker_slot.lock().unwrap() = Some(cx.waker().clone());
+                if !waiting {
+                    waiting = true;
+                    let (waker_slot, ready) = (waker_slot.clone(), ready.clone());
+                    thread::spawn(move || {
+                        thread::sleep(Duration::from_millis(5));
+                        // only ready once woken, so the wake is always recorded before the completion
+                        let wake = || waker_slot.lock().unwrap().take().map(Waker::wake);
+                        wake();
+                        ready.store(true, Ordering::Release);
+                        wake();
+                    });
+                }
+                Poll::Pending
+            })
+            .await
+        });
+    }
+    block_on(group.wait_for_all());
+
+    let trace = group.wake_trace();
+    assert!(trace.stalled_tasks().is_empty());
+    let count = |kind: WakeEventKind| {
+        trace
+            .events
+            .iter()
+            .filter(|event| event.kind == kind)
+            .count()
+    };
+    assert_eq!(count(WakeEventKind::Completed), 10);
+    assert!(count(WakeEventKind::Woken) >= 10);
+    assert!(count(WakeEventKind::Suspended) >= 10);
+}
@@ -0,0 +1,90 @@
+//! Suggests a number of threads across combinations of workload hints
+
+use spawn_groups::{
+    recommended_threads, DiscardingSpawnGroup, ErrSpawnGroup, SpawnGroup, WorkloadHint,
+};
+
+fn hint(
+    io_bound_fraction: f64,
+    expected_task_count: Option<usize>,
+    blocking: bool,
+) -> WorkloadHint {
+    WorkloadHint {
+        io_bound_fraction,
+        expected_task_count,
+        blocking,
+    }
+}
+
+#[test]
+fn cpu_bound_workloads_use_one_thread_per_core() {
+    assert_eq!(WorkloadHint::default().threads_for(8), 8);
+    assert_eq!(hint(0.0, None, true).threads_for(8), 8);
+}
+
+#[test]
+fn awaiting_workloads_use_one_thread_per_core_however_io_bound() {
+    for io_bound_fraction in [0.0, 0.5, 0.9, 1.0] {
+        assert_eq!(hint(io_bound_fraction, None, false).threads_for(8), 8);
+    }
+}
+
+#[test]
+fn blocking_workloads_scale_with_the_time_spent_waiting() {
+    assert_eq!(hint(0.5, None, true).threads_for(8), 16);
+    assert_eq!(hint(0.75, None, true).threads_for(8), 32);
+    assert_eq!(hint(0.9, None, true).threads_for(8), 80);
+    // waiting all the time is capped at ten threads per core
+    assert_eq!(hint(1.0, None, true).threads_for(8), 80);
+    assert_eq!(hint(f64::INFINITY, None, true).threads_for(8), 80);
+}
+
+#[test]
+fn nonsensical_fractions_are_treated_as_cpu_bound() {
+    assert_eq!(hint(-0.5, None, true).threads_for(8), 8);
+    assert_eq!(hint(f64::NAN, None, true).threads_for(8), 8);
+}
+
+#[test]
+fn the_expected_task_count_caps_the_suggestion() {
+    assert_eq!(hint(0.9, Some(12), true).threads_for(8), 12);
+    assert_eq!(hint(0.0, Some(3), false).threads_for(8), 3);
+    assert_eq!(hint(0.0, Some(100), false).threads_for(8), 8);
+}
+
+#[test]
+fn the_suggestion_is_never_below_one_thread() {
+    assert_eq!(hint(0.0, Some(0), false).threads_for(8), 1);
+    assert_eq!(hint(0.5, None, true).threads_for(0), 2);
+    assert_eq!(WorkloadHint::default().threads_for(0), 1);
+}
+
+#[test]
+fn this_machine_gets_a_suggestion_within_bounds() {
+    let cores: usize = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    assert_eq!(recommended_threads(WorkloadHint::default()), cores);
+    assert_eq!(recommended_threads(hint(0.9, None, true)), cores * 10);
+}
+
+#[test]
+fn every_spawn_group_can_be_sized_by_a_workload_hint() {
+    for workload in [
+        WorkloadHint::default(),
+        hint(0.75, None, true),
+        hint(0.9, Some(3), true),
+    ] {
+        let threads: usize = recommended_threads(workload);
+        assert_eq!(
+            SpawnGroup::<u8>::with_workload(workload).num_threads(),
+            threads
+        );
+        assert_eq!(
+            ErrSpawnGroup::<u8, u8>::with_workload(workload).num_threads(),
+            threads
+        );
+        assert_eq!(
+            DiscardingSpawnGroup::with_workload(workload).num_threads(),
+            threads
+        );
+    }
+}